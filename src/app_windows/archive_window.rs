@@ -0,0 +1,315 @@
+use crate::accessibility::{handle_accessibility, to_wide};
+use crate::archive::{ArchiveEntry, list_archive_entries};
+use crate::i18n;
+use crate::with_state;
+use std::path::{Path, PathBuf};
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{COLOR_WINDOW, HBRUSH, HFONT};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Controls::{WC_BUTTON, WC_LISTBOXW};
+use windows::Win32::UI::Input::KeyboardAndMouse::{EnableWindow, GetFocus, SetFocus, VK_RETURN};
+use windows::Win32::UI::WindowsAndMessaging::{
+    BS_DEFPUSHBUTTON, CREATESTRUCTW, CW_USEDEFAULT, CreateWindowExW, DefWindowProcW, DestroyWindow,
+    GWLP_USERDATA, GetWindowLongPtrW, HMENU, IDC_ARROW, IDCANCEL, LB_ADDSTRING, LB_GETCURSEL,
+    LB_SETCURSEL, LBN_DBLCLK, LBS_HASSTRINGS, LBS_NOTIFY, LoadCursorW, MSG, RegisterClassW,
+    SendMessageW, SetForegroundWindow, SetWindowLongPtrW, WINDOW_STYLE, WM_CLOSE, WM_COMMAND,
+    WM_CREATE, WM_DESTROY, WM_KEYDOWN, WM_NCDESTROY, WM_SETFONT, WNDCLASSW, WS_CAPTION, WS_CHILD,
+    WS_EX_CLIENTEDGE, WS_EX_CONTROLPARENT, WS_EX_DLGMODALFRAME, WS_SYSMENU, WS_TABSTOP, WS_VISIBLE,
+    WS_VSCROLL,
+};
+use windows::core::PCWSTR;
+
+const ARCHIVE_CLASS_NAME: &str = "NovapadArchive";
+const ARCHIVE_ID_LIST: usize = 9301;
+const ARCHIVE_ID_OPEN: usize = 9302;
+const ARCHIVE_ID_CLOSE: usize = 9303;
+
+struct ArchiveWindowInit {
+    parent: HWND,
+    archive: PathBuf,
+    entries: Vec<ArchiveEntry>,
+}
+
+struct ArchiveWindowState {
+    parent: HWND,
+    archive: PathBuf,
+    entries: Vec<ArchiveEntry>,
+    hwnd_list: HWND,
+    hwnd_open: HWND,
+}
+
+pub unsafe fn handle_navigation(hwnd: HWND, msg: &MSG) -> bool {
+    if msg.message == WM_KEYDOWN && msg.wParam.0 as u32 == VK_RETURN.0 as u32 {
+        let focus = GetFocus();
+        let (list, btn) =
+            with_archive_state(hwnd, |s| (s.hwnd_list, s.hwnd_open)).unwrap_or((HWND(0), HWND(0)));
+        if focus == list || focus == btn {
+            open_selected(hwnd);
+            return true;
+        }
+    }
+    handle_accessibility(hwnd, msg)
+}
+
+/// Shows the entries of a ZIP or 7z archive so one of them can be opened.
+pub unsafe fn open(parent: HWND, archive: &Path) {
+    let existing = with_state(parent, |state| state.archive_window).unwrap_or(HWND(0));
+    if existing.0 != 0 {
+        crate::log_if_err!(DestroyWindow(existing));
+    }
+
+    let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+    let entries = match list_archive_entries(archive, language) {
+        Ok(entries) => entries,
+        Err(message) => {
+            crate::show_error(parent, language, &message);
+            return;
+        }
+    };
+    if entries.is_empty() {
+        crate::show_info(parent, language, &i18n::tr(language, "archive.empty"));
+        return;
+    }
+
+    let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+    let class_name = to_wide(ARCHIVE_CLASS_NAME);
+    let wc = WNDCLASSW {
+        hCursor: windows::Win32::UI::WindowsAndMessaging::HCURSOR(
+            LoadCursorW(None, IDC_ARROW).unwrap_or_default().0,
+        ),
+        hInstance: hinstance,
+        lpszClassName: PCWSTR(class_name.as_ptr()),
+        lpfnWndProc: Some(archive_wndproc),
+        hbrBackground: HBRUSH((COLOR_WINDOW.0 + 1) as isize),
+        ..Default::default()
+    };
+    RegisterClassW(&wc);
+
+    let archive_name = archive
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let title = to_wide(&i18n::tr_f(
+        language,
+        "archive.window_title",
+        &[("name", archive_name)],
+    ));
+    let init = Box::new(ArchiveWindowInit {
+        parent,
+        archive: archive.to_path_buf(),
+        entries,
+    });
+    let init_ptr = Box::into_raw(init);
+
+    let window = CreateWindowExW(
+        WS_EX_CONTROLPARENT | WS_EX_DLGMODALFRAME,
+        PCWSTR(class_name.as_ptr()),
+        PCWSTR(title.as_ptr()),
+        WS_CAPTION | WS_SYSMENU | WS_VISIBLE,
+        CW_USEDEFAULT,
+        CW_USEDEFAULT,
+        500,
+        450,
+        parent,
+        None,
+        hinstance,
+        Some(init_ptr as *const std::ffi::c_void),
+    );
+
+    if window.0 == 0 {
+        drop(Box::from_raw(init_ptr));
+        return;
+    }
+    if with_state(parent, |state| {
+        state.archive_window = window;
+    })
+    .is_none()
+    {
+        crate::log_debug("Failed to access archive window state");
+    }
+    EnableWindow(parent, false);
+    SetForegroundWindow(window);
+}
+
+unsafe extern "system" fn archive_wndproc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_CREATE => {
+            let create_struct = lparam.0 as *const CREATESTRUCTW;
+            let init = Box::from_raw((*create_struct).lpCreateParams as *mut ArchiveWindowInit);
+            let parent = init.parent;
+            let hfont = with_state(parent, |state| state.hfont).unwrap_or(HFONT(0));
+            let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+
+            let hwnd_list = CreateWindowExW(
+                WS_EX_CLIENTEDGE,
+                WC_LISTBOXW,
+                PCWSTR::null(),
+                WS_CHILD
+                    | WS_VISIBLE
+                    | WS_VSCROLL
+                    | WS_TABSTOP
+                    | WINDOW_STYLE((LBS_NOTIFY | LBS_HASSTRINGS) as u32),
+                10,
+                10,
+                460,
+                340,
+                hwnd,
+                HMENU(ARCHIVE_ID_LIST as isize),
+                HINSTANCE(0),
+                None,
+            );
+
+            let open_text = i18n::tr(language, "archive.open");
+            let hwnd_open = CreateWindowExW(
+                Default::default(),
+                WC_BUTTON,
+                PCWSTR(to_wide(&open_text).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_DEFPUSHBUTTON as u32),
+                250,
+                360,
+                110,
+                30,
+                hwnd,
+                HMENU(ARCHIVE_ID_OPEN as isize),
+                HINSTANCE(0),
+                None,
+            );
+
+            let close_text = i18n::tr(language, "archive.close");
+            let hwnd_close = CreateWindowExW(
+                Default::default(),
+                WC_BUTTON,
+                PCWSTR(to_wide(&close_text).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP,
+                360,
+                360,
+                110,
+                30,
+                hwnd,
+                HMENU(ARCHIVE_ID_CLOSE as isize),
+                HINSTANCE(0),
+                None,
+            );
+
+            for ctrl in [hwnd_list, hwnd_open, hwnd_close] {
+                if ctrl.0 != 0 && hfont.0 != 0 {
+                    SendMessageW(ctrl, WM_SETFONT, WPARAM(hfont.0 as usize), LPARAM(1));
+                }
+            }
+
+            for entry in &init.entries {
+                let size_kb = entry.size.div_ceil(1024).to_string();
+                let text = i18n::tr_f(
+                    language,
+                    "archive.entry_label",
+                    &[("name", &entry.name), ("size", &size_kb)],
+                );
+                let wide = to_wide(&text);
+                SendMessageW(
+                    hwnd_list,
+                    LB_ADDSTRING,
+                    WPARAM(0),
+                    LPARAM(wide.as_ptr() as isize),
+                );
+            }
+            SendMessageW(hwnd_list, LB_SETCURSEL, WPARAM(0), LPARAM(0));
+
+            let init = *init;
+            let state = Box::new(ArchiveWindowState {
+                parent,
+                archive: init.archive,
+                entries: init.entries,
+                hwnd_list,
+                hwnd_open,
+            });
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, Box::into_raw(state) as isize);
+            SetFocus(hwnd_list);
+            LRESULT(0)
+        }
+        WM_COMMAND => {
+            let cmd_id = wparam.0 & 0xffff;
+            let notify = (wparam.0 >> 16) as u16;
+            match cmd_id {
+                ARCHIVE_ID_OPEN => {
+                    open_selected(hwnd);
+                    LRESULT(0)
+                }
+                ARCHIVE_ID_LIST if notify == LBN_DBLCLK as u16 => {
+                    open_selected(hwnd);
+                    LRESULT(0)
+                }
+                ARCHIVE_ID_CLOSE => {
+                    crate::log_if_err!(DestroyWindow(hwnd));
+                    LRESULT(0)
+                }
+                cmd if cmd == IDCANCEL.0 as usize => {
+                    crate::log_if_err!(DestroyWindow(hwnd));
+                    LRESULT(0)
+                }
+                _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+            }
+        }
+        WM_CLOSE => {
+            crate::log_if_err!(DestroyWindow(hwnd));
+            LRESULT(0)
+        }
+        WM_DESTROY => {
+            let parent = with_archive_state(hwnd, |s| s.parent).unwrap_or(HWND(0));
+            if parent.0 != 0 {
+                EnableWindow(parent, true);
+                SetForegroundWindow(parent);
+                if with_state(parent, |state| {
+                    state.archive_window = HWND(0);
+                })
+                .is_none()
+                {
+                    crate::log_debug("Failed to access archive window state");
+                }
+            }
+            LRESULT(0)
+        }
+        WM_NCDESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut ArchiveWindowState;
+            if !ptr.is_null() {
+                drop(Box::from_raw(ptr));
+            }
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+unsafe fn with_archive_state<F, R>(hwnd: HWND, f: F) -> Option<R>
+where
+    F: FnOnce(&mut ArchiveWindowState) -> R,
+{
+    let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut ArchiveWindowState;
+    if ptr.is_null() {
+        None
+    } else {
+        Some(f(&mut *ptr))
+    }
+}
+
+unsafe fn open_selected(hwnd: HWND) {
+    let selected = with_archive_state(hwnd, |s| {
+        let sel = SendMessageW(s.hwnd_list, LB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+        if sel < 0 {
+            return None;
+        }
+        s.entries
+            .get(sel as usize)
+            .map(|entry| (s.parent, s.archive.clone(), entry.name.clone()))
+    })
+    .flatten();
+    let Some((parent, archive, entry)) = selected else {
+        return;
+    };
+    crate::log_if_err!(DestroyWindow(hwnd));
+    crate::editor_manager::open_archive_entry(parent, &archive, &entry);
+}
@@ -0,0 +1,668 @@
+use crate::accessibility::{handle_accessibility, nvda_speak, to_wide};
+use crate::autocorrect::{AutocorrectEntry, MAX_WORD_CHARS, save_autocorrect};
+use crate::i18n;
+use crate::settings::save_settings;
+use crate::with_state;
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{COLOR_WINDOW, HBRUSH, HFONT};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Controls::{BST_CHECKED, WC_BUTTON, WC_LISTBOXW};
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    EnableWindow, GetFocus, SetFocus, VK_ESCAPE, VK_RETURN,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    BM_GETCHECK, BM_SETCHECK, BS_AUTOCHECKBOX, BS_DEFPUSHBUTTON, CREATESTRUCTW, CW_USEDEFAULT,
+    CreateWindowExW, DefWindowProcW, DestroyWindow, ES_AUTOHSCROLL, GWLP_USERDATA, GetDlgItem,
+    GetWindowLongPtrW, HMENU, IDC_ARROW, LB_ADDSTRING, LB_GETCURSEL, LB_RESETCONTENT, LB_SETCURSEL,
+    LBN_SELCHANGE, LBS_HASSTRINGS, LBS_NOTIFY, LoadCursorW, MSG, RegisterClassW, SendMessageW,
+    SetForegroundWindow, SetWindowLongPtrW, SetWindowTextW, WINDOW_STYLE, WM_CLOSE, WM_COMMAND,
+    WM_CREATE, WM_DESTROY, WM_GETTEXT, WM_GETTEXTLENGTH, WM_KEYDOWN, WM_NCDESTROY, WM_SETFONT,
+    WNDCLASSW, WS_CAPTION, WS_CHILD, WS_EX_CLIENTEDGE, WS_EX_CONTROLPARENT, WS_EX_DLGMODALFRAME,
+    WS_SYSMENU, WS_TABSTOP, WS_VISIBLE, WS_VSCROLL,
+};
+use windows::core::{PCWSTR, w};
+
+const AUTOCORRECT_CLASS_NAME: &str = "NovapadAutocorrect";
+const AUTOCORRECT_ID_ENABLED: usize = 1916;
+const AUTOCORRECT_ID_LIST: usize = 1917;
+const AUTOCORRECT_ID_FROM: usize = 1918;
+const AUTOCORRECT_ID_TO: usize = 1919;
+const AUTOCORRECT_ID_STATUS: usize = 1920;
+const AUTOCORRECT_ID_NEW: usize = 1921;
+const AUTOCORRECT_ID_SAVE: usize = 1922;
+const AUTOCORRECT_ID_DELETE: usize = 1923;
+const AUTOCORRECT_ID_CLOSE: usize = 1924;
+const AUTOCORRECT_ID_EXCEPTIONS: usize = 1925;
+const AUTOCORRECT_ID_SAVE_EXCEPTIONS: usize = 1926;
+
+struct AutocorrectWindowState {
+    parent: HWND,
+    hwnd_enabled: HWND,
+    hwnd_list: HWND,
+    hwnd_from: HWND,
+    hwnd_to: HWND,
+    hwnd_exceptions: HWND,
+}
+
+pub unsafe fn handle_navigation(hwnd: HWND, msg: &MSG) -> bool {
+    if msg.message == WM_KEYDOWN && msg.wParam.0 as u32 == VK_RETURN.0 as u32 {
+        let focus = GetFocus();
+        let Some((list, from, to, exceptions)) = with_autocorrect_state(hwnd, |s| {
+            (s.hwnd_list, s.hwnd_from, s.hwnd_to, s.hwnd_exceptions)
+        }) else {
+            return false;
+        };
+        if focus == list {
+            SetFocus(from);
+            return true;
+        }
+        if focus == from || focus == to {
+            save_entry(hwnd);
+            return true;
+        }
+        if focus == exceptions {
+            save_exceptions(hwnd);
+            return true;
+        }
+    }
+    handle_accessibility(hwnd, msg)
+}
+
+pub unsafe fn open(parent: HWND) {
+    let existing = with_state(parent, |state| state.autocorrect_window).unwrap_or(HWND(0));
+    if existing.0 != 0 {
+        SetForegroundWindow(existing);
+        return;
+    }
+    let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+
+    let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+    let class_name = to_wide(AUTOCORRECT_CLASS_NAME);
+    let wc = WNDCLASSW {
+        hCursor: windows::Win32::UI::WindowsAndMessaging::HCURSOR(
+            LoadCursorW(None, IDC_ARROW).unwrap_or_default().0,
+        ),
+        hInstance: hinstance,
+        lpszClassName: PCWSTR(class_name.as_ptr()),
+        lpfnWndProc: Some(autocorrect_wndproc),
+        hbrBackground: HBRUSH((COLOR_WINDOW.0 + 1) as isize),
+        ..Default::default()
+    };
+    RegisterClassW(&wc);
+
+    let title = to_wide(&i18n::tr(language, "autocorrect.title"));
+    let state = Box::new(AutocorrectWindowState {
+        parent,
+        hwnd_enabled: HWND(0),
+        hwnd_list: HWND(0),
+        hwnd_from: HWND(0),
+        hwnd_to: HWND(0),
+        hwnd_exceptions: HWND(0),
+    });
+    let state_ptr = Box::into_raw(state);
+    let window = CreateWindowExW(
+        WS_EX_CONTROLPARENT | WS_EX_DLGMODALFRAME,
+        PCWSTR(class_name.as_ptr()),
+        PCWSTR(title.as_ptr()),
+        WS_CAPTION | WS_SYSMENU | WS_VISIBLE,
+        CW_USEDEFAULT,
+        CW_USEDEFAULT,
+        560,
+        620,
+        parent,
+        None,
+        hinstance,
+        Some(state_ptr as *const _),
+    );
+    if window.0 == 0 {
+        drop(Box::from_raw(state_ptr));
+        return;
+    }
+
+    let Some(hwnd_enabled) = with_autocorrect_state(window, |s| s.hwnd_enabled) else {
+        return;
+    };
+    with_state(parent, |state| state.autocorrect_window = window);
+    EnableWindow(parent, false);
+    SetForegroundWindow(window);
+    refresh_list(window, Some(0));
+    SetFocus(hwnd_enabled);
+}
+
+unsafe extern "system" fn autocorrect_wndproc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_CREATE => {
+            let create_struct = lparam.0 as *const CREATESTRUCTW;
+            let state_ptr = (*create_struct).lpCreateParams as *mut AutocorrectWindowState;
+            if state_ptr.is_null() {
+                return LRESULT(0);
+            }
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, state_ptr as isize);
+            let parent = (*state_ptr).parent;
+            let hfont = with_state(parent, |state| state.hfont).unwrap_or(HFONT(0));
+            let (language, enabled, exceptions) = with_state(parent, |state| {
+                (
+                    state.settings.language,
+                    state.settings.autocorrect,
+                    state.autocorrect.exceptions.join(" "),
+                )
+            })
+            .unwrap_or_default();
+            let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+
+            let hwnd_enabled = CreateWindowExW(
+                Default::default(),
+                WC_BUTTON,
+                PCWSTR(to_wide(&i18n::tr(language, "autocorrect.enabled")).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_AUTOCHECKBOX as u32),
+                10,
+                10,
+                520,
+                24,
+                hwnd,
+                HMENU(AUTOCORRECT_ID_ENABLED as isize),
+                hinstance,
+                None,
+            );
+            if enabled {
+                SendMessageW(
+                    hwnd_enabled,
+                    BM_SETCHECK,
+                    WPARAM(BST_CHECKED.0 as usize),
+                    LPARAM(0),
+                );
+            }
+            let label_list = create_static(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "autocorrect.list"),
+                44,
+                18,
+                0,
+            );
+            let hwnd_list = CreateWindowExW(
+                WS_EX_CLIENTEDGE,
+                WC_LISTBOXW,
+                PCWSTR::null(),
+                WS_CHILD
+                    | WS_VISIBLE
+                    | WS_VSCROLL
+                    | WS_TABSTOP
+                    | WINDOW_STYLE((LBS_NOTIFY | LBS_HASSTRINGS) as u32),
+                10,
+                64,
+                520,
+                120,
+                hwnd,
+                HMENU(AUTOCORRECT_ID_LIST as isize),
+                hinstance,
+                None,
+            );
+            let label_from = create_static(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "autocorrect.from"),
+                194,
+                18,
+                0,
+            );
+            let hwnd_from = create_input(hwnd, hinstance, 214, AUTOCORRECT_ID_FROM);
+            let label_to = create_static(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "autocorrect.to"),
+                248,
+                18,
+                0,
+            );
+            let hwnd_to = create_input(hwnd, hinstance, 268, AUTOCORRECT_ID_TO);
+            let entry_buttons = [
+                ("autocorrect.new", AUTOCORRECT_ID_NEW, 10, 302),
+                ("autocorrect.save", AUTOCORRECT_ID_SAVE, 140, 302),
+                ("autocorrect.delete", AUTOCORRECT_ID_DELETE, 270, 302),
+            ]
+            .map(|(key, id, x, y)| {
+                create_button(hwnd, hinstance, &i18n::tr(language, key), x, y, id)
+            });
+            let label_exceptions = create_static(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "autocorrect.exceptions"),
+                342,
+                18,
+                0,
+            );
+            let hwnd_exceptions = create_input(hwnd, hinstance, 362, AUTOCORRECT_ID_EXCEPTIONS);
+            let wide = to_wide(&exceptions);
+            crate::log_if_err!(SetWindowTextW(hwnd_exceptions, PCWSTR(wide.as_ptr())));
+            let help = create_static(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "autocorrect.help"),
+                436,
+                72,
+                0,
+            );
+            let status = create_static(hwnd, hinstance, "", 512, 18, AUTOCORRECT_ID_STATUS);
+            let buttons = [
+                (
+                    "autocorrect.save_exceptions",
+                    AUTOCORRECT_ID_SAVE_EXCEPTIONS,
+                    10,
+                    396,
+                ),
+                ("autocorrect.close", AUTOCORRECT_ID_CLOSE, 400, 540),
+            ]
+            .map(|(key, id, x, y)| {
+                create_button(hwnd, hinstance, &i18n::tr(language, key), x, y, id)
+            });
+
+            for ctrl in [
+                hwnd_enabled,
+                label_list,
+                hwnd_list,
+                label_from,
+                hwnd_from,
+                label_to,
+                hwnd_to,
+                label_exceptions,
+                hwnd_exceptions,
+                help,
+                status,
+            ]
+            .into_iter()
+            .chain(entry_buttons)
+            .chain(buttons)
+            {
+                if ctrl.0 != 0 && hfont.0 != 0 {
+                    SendMessageW(ctrl, WM_SETFONT, WPARAM(hfont.0 as usize), LPARAM(1));
+                }
+            }
+
+            (*state_ptr).hwnd_enabled = hwnd_enabled;
+            (*state_ptr).hwnd_list = hwnd_list;
+            (*state_ptr).hwnd_from = hwnd_from;
+            (*state_ptr).hwnd_to = hwnd_to;
+            (*state_ptr).hwnd_exceptions = hwnd_exceptions;
+            LRESULT(0)
+        }
+        WM_KEYDOWN => {
+            if wparam.0 as u32 == VK_ESCAPE.0 as u32 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_COMMAND => {
+            let cmd_id = wparam.0 & 0xffff;
+            let notify = (wparam.0 >> 16) as u16;
+            match cmd_id {
+                AUTOCORRECT_ID_ENABLED => {
+                    store_enabled(hwnd);
+                    LRESULT(0)
+                }
+                AUTOCORRECT_ID_LIST if notify == LBN_SELCHANGE as u16 => {
+                    fill_fields(hwnd);
+                    LRESULT(0)
+                }
+                AUTOCORRECT_ID_NEW => {
+                    new_entry(hwnd);
+                    LRESULT(0)
+                }
+                AUTOCORRECT_ID_SAVE => {
+                    save_entry(hwnd);
+                    LRESULT(0)
+                }
+                AUTOCORRECT_ID_DELETE => {
+                    delete_selected(hwnd);
+                    LRESULT(0)
+                }
+                AUTOCORRECT_ID_SAVE_EXCEPTIONS => {
+                    save_exceptions(hwnd);
+                    LRESULT(0)
+                }
+                cmd if cmd == AUTOCORRECT_ID_CLOSE || cmd == 2 => {
+                    crate::log_if_err!(DestroyWindow(hwnd));
+                    LRESULT(0)
+                }
+                _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+            }
+        }
+        WM_CLOSE => {
+            crate::log_if_err!(DestroyWindow(hwnd));
+            LRESULT(0)
+        }
+        WM_DESTROY => {
+            let parent = with_autocorrect_state(hwnd, |s| s.parent).unwrap_or(HWND(0));
+            if parent.0 != 0 {
+                EnableWindow(parent, true);
+                SetForegroundWindow(parent);
+                with_state(parent, |state| state.autocorrect_window = HWND(0));
+                if let Some(hwnd_edit) = crate::get_active_edit(parent) {
+                    SetFocus(hwnd_edit);
+                }
+            }
+            LRESULT(0)
+        }
+        WM_NCDESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut AutocorrectWindowState;
+            if !ptr.is_null() {
+                drop(Box::from_raw(ptr));
+            }
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+unsafe fn create_static(
+    hwnd: HWND,
+    hinstance: HINSTANCE,
+    text: &str,
+    y: i32,
+    height: i32,
+    id: usize,
+) -> HWND {
+    CreateWindowExW(
+        Default::default(),
+        w!("STATIC"),
+        PCWSTR(to_wide(text).as_ptr()),
+        WS_CHILD | WS_VISIBLE,
+        10,
+        y,
+        520,
+        height,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    )
+}
+
+unsafe fn create_input(hwnd: HWND, hinstance: HINSTANCE, y: i32, id: usize) -> HWND {
+    CreateWindowExW(
+        WS_EX_CLIENTEDGE,
+        w!("EDIT"),
+        PCWSTR::null(),
+        WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(ES_AUTOHSCROLL as u32),
+        10,
+        y,
+        520,
+        24,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    )
+}
+
+unsafe fn create_button(
+    hwnd: HWND,
+    hinstance: HINSTANCE,
+    text: &str,
+    x: i32,
+    y: i32,
+    id: usize,
+) -> HWND {
+    let mut style = WS_CHILD | WS_VISIBLE | WS_TABSTOP;
+    if id == AUTOCORRECT_ID_SAVE {
+        style |= WINDOW_STYLE(BS_DEFPUSHBUTTON as u32);
+    }
+    CreateWindowExW(
+        Default::default(),
+        WC_BUTTON,
+        PCWSTR(to_wide(text).as_ptr()),
+        style,
+        x,
+        y,
+        120,
+        30,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    )
+}
+
+unsafe fn with_autocorrect_state<F, R>(hwnd: HWND, f: F) -> Option<R>
+where
+    F: FnOnce(&mut AutocorrectWindowState) -> R,
+{
+    let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut AutocorrectWindowState;
+    if ptr.is_null() {
+        None
+    } else {
+        Some(f(&mut *ptr))
+    }
+}
+
+/// Saves the "correct while typing" checkbox as soon as it changes.
+unsafe fn store_enabled(hwnd: HWND) {
+    let Some((parent, hwnd_enabled)) = with_autocorrect_state(hwnd, |s| (s.parent, s.hwnd_enabled))
+    else {
+        return;
+    };
+    let enabled =
+        SendMessageW(hwnd_enabled, BM_GETCHECK, WPARAM(0), LPARAM(0)).0 == BST_CHECKED.0 as isize;
+    with_state(parent, |state| {
+        state.settings.autocorrect = enabled;
+        if !enabled {
+            state.last_autocorrection = None;
+        }
+        save_settings(state.settings.clone());
+    });
+}
+
+/// Fills the list from the user's replacements and selects the entry at `select`.
+unsafe fn refresh_list(hwnd: HWND, select: Option<usize>) {
+    let Some((parent, hwnd_list)) = with_autocorrect_state(hwnd, |s| (s.parent, s.hwnd_list))
+    else {
+        return;
+    };
+    let items = with_state(parent, |state| {
+        let language = state.settings.language;
+        state
+            .autocorrect
+            .entries
+            .iter()
+            .map(|entry| {
+                i18n::tr_f(
+                    language,
+                    "autocorrect.item",
+                    &[("from", &entry.from), ("to", &entry.to)],
+                )
+            })
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_default();
+    SendMessageW(hwnd_list, LB_RESETCONTENT, WPARAM(0), LPARAM(0));
+    for item in &items {
+        let wide = to_wide(item);
+        SendMessageW(
+            hwnd_list,
+            LB_ADDSTRING,
+            WPARAM(0),
+            LPARAM(wide.as_ptr() as isize),
+        );
+    }
+    if let Some(select) = select.filter(|_| !items.is_empty()) {
+        let index = select.min(items.len() - 1);
+        SendMessageW(hwnd_list, LB_SETCURSEL, WPARAM(index), LPARAM(0));
+    }
+    fill_fields(hwnd);
+}
+
+/// Shows the selected replacement in the edit fields.
+unsafe fn fill_fields(hwnd: HWND) {
+    let Some((parent, hwnd_from, hwnd_to)) =
+        with_autocorrect_state(hwnd, |s| (s.parent, s.hwnd_from, s.hwnd_to))
+    else {
+        return;
+    };
+    let selected = selected_index(hwnd).and_then(|index| {
+        with_state(parent, |state| {
+            state.autocorrect.entries.get(index).cloned()
+        })
+        .flatten()
+    });
+    let (from, to) = selected
+        .map(|entry| (entry.from, entry.to))
+        .unwrap_or_default();
+    let wide = to_wide(&from);
+    crate::log_if_err!(SetWindowTextW(hwnd_from, PCWSTR(wide.as_ptr())));
+    let wide = to_wide(&to);
+    crate::log_if_err!(SetWindowTextW(hwnd_to, PCWSTR(wide.as_ptr())));
+}
+
+unsafe fn selected_index(hwnd: HWND) -> Option<usize> {
+    let hwnd_list = with_autocorrect_state(hwnd, |s| s.hwnd_list)?;
+    let selected = SendMessageW(hwnd_list, LB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+    usize::try_from(selected).ok()
+}
+
+unsafe fn read_text(hwnd_edit: HWND) -> String {
+    let len = SendMessageW(hwnd_edit, WM_GETTEXTLENGTH, WPARAM(0), LPARAM(0)).0;
+    let mut buf = vec![0u16; len as usize + 1];
+    SendMessageW(
+        hwnd_edit,
+        WM_GETTEXT,
+        WPARAM(buf.len()),
+        LPARAM(buf.as_mut_ptr() as isize),
+    );
+    String::from_utf16_lossy(&buf[..len as usize])
+}
+
+unsafe fn show_status(hwnd: HWND, message: &str) {
+    let status = GetDlgItem(hwnd, AUTOCORRECT_ID_STATUS as i32);
+    let wide = to_wide(message);
+    crate::log_if_err!(SetWindowTextW(status, PCWSTR(wide.as_ptr())));
+    nvda_speak(message);
+}
+
+/// Clears the selection and the fields so that Save adds a new replacement.
+unsafe fn new_entry(hwnd: HWND) {
+    let Some((hwnd_list, hwnd_from)) = with_autocorrect_state(hwnd, |s| (s.hwnd_list, s.hwnd_from))
+    else {
+        return;
+    };
+    SendMessageW(hwnd_list, LB_SETCURSEL, WPARAM(usize::MAX), LPARAM(0));
+    fill_fields(hwnd);
+    SetFocus(hwnd_from);
+}
+
+/// Stores the edited replacement over the selected one, or adds it when nothing is
+/// selected. A word can only have one replacement, so any other entry for the same
+/// word is removed.
+unsafe fn save_entry(hwnd: HWND) {
+    let Some((parent, hwnd_from, hwnd_to)) =
+        with_autocorrect_state(hwnd, |s| (s.parent, s.hwnd_from, s.hwnd_to))
+    else {
+        return;
+    };
+    let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+    let from = read_text(hwnd_from).trim().to_string();
+    if from.is_empty()
+        || from.chars().any(char::is_whitespace)
+        || from.chars().count() > MAX_WORD_CHARS
+    {
+        let max = MAX_WORD_CHARS.to_string();
+        show_status(
+            hwnd,
+            &i18n::tr_f(language, "autocorrect.invalid_word", &[("max", &max)]),
+        );
+        SetFocus(hwnd_from);
+        return;
+    }
+    let to = read_text(hwnd_to).trim().to_string();
+    if to.is_empty() {
+        show_status(hwnd, &i18n::tr(language, "autocorrect.empty_replacement"));
+        SetFocus(hwnd_to);
+        return;
+    }
+    let selected = selected_index(hwnd);
+    let Some(index) = with_state(parent, |state| {
+        let entries = &mut state.autocorrect.entries;
+        let entry = AutocorrectEntry {
+            from: from.clone(),
+            to,
+        };
+        let mut index = match selected.filter(|&index| index < entries.len()) {
+            Some(index) => {
+                entries[index] = entry;
+                index
+            }
+            None => {
+                entries.push(entry);
+                entries.len() - 1
+            }
+        };
+        if let Some(duplicate) = (0..entries.len()).find(|&i| i != index && entries[i].from == from)
+        {
+            entries.remove(duplicate);
+            if duplicate < index {
+                index -= 1;
+            }
+        }
+        save_autocorrect(&state.autocorrect);
+        index
+    }) else {
+        return;
+    };
+    refresh_list(hwnd, Some(index));
+    show_status(
+        hwnd,
+        &i18n::tr_f(language, "autocorrect.saved", &[("word", &from)]),
+    );
+}
+
+unsafe fn delete_selected(hwnd: HWND) {
+    let Some(index) = selected_index(hwnd) else {
+        return;
+    };
+    let Some((parent, hwnd_list)) = with_autocorrect_state(hwnd, |s| (s.parent, s.hwnd_list))
+    else {
+        return;
+    };
+    let Some((language, removed)) = with_state(parent, |state| {
+        let removed = state.autocorrect.entries.remove(index);
+        save_autocorrect(&state.autocorrect);
+        (state.settings.language, removed)
+    }) else {
+        return;
+    };
+    refresh_list(hwnd, Some(index));
+    SetFocus(hwnd_list);
+    show_status(
+        hwnd,
+        &i18n::tr_f(language, "autocorrect.deleted", &[("word", &removed.from)]),
+    );
+}
+
+/// Stores the words of the exceptions field, separated by spaces, as the words the
+/// double capitals fix leaves alone.
+unsafe fn save_exceptions(hwnd: HWND) {
+    let Some((parent, hwnd_exceptions)) =
+        with_autocorrect_state(hwnd, |s| (s.parent, s.hwnd_exceptions))
+    else {
+        return;
+    };
+    let mut exceptions: Vec<String> = Vec::new();
+    for word in read_text(hwnd_exceptions).split_whitespace() {
+        if !exceptions.iter().any(|existing| existing == word) {
+            exceptions.push(word.to_string());
+        }
+    }
+    let Some(language) = with_state(parent, |state| {
+        state.autocorrect.exceptions = exceptions;
+        save_autocorrect(&state.autocorrect);
+        state.settings.language
+    }) else {
+        return;
+    };
+    show_status(hwnd, &i18n::tr(language, "autocorrect.exceptions_saved"));
+}
@@ -34,9 +34,10 @@ use windows::core::{PCWSTR, PWSTR, w};
 use crate::accessibility::{EM_REPLACESEL, ES_READONLY, to_wide};
 use crate::app_windows::find_in_files_window::browse_for_folder;
 use crate::file_handler::{
-    decode_text, is_doc_path, is_docx_path, is_epub_path, is_html_path, is_mp3_path, is_pdf_path,
-    is_ppt_path, is_pptx_path, is_spreadsheet_path, read_doc_text, read_docx_text, read_epub_text,
-    read_html_text, read_pdf_text, read_ppt_text, read_spreadsheet_text,
+    decode_text, is_doc_path, is_docx_path, is_epub_path, is_html_path, is_mobi_path, is_mp3_path,
+    is_pdf_path, is_ppt_path, is_pptx_path, is_spreadsheet_path, read_doc_text, read_docx_text,
+    read_epub_text, read_html_text, read_mobi_text, read_pdf_text, read_ppt_text,
+    read_spreadsheet_text,
 };
 use crate::i18n;
 use crate::settings::{DictionaryEntry, Language, TtsEngine};
@@ -63,6 +64,7 @@ const BATCH_ID_CANCEL: usize = 9412;
 const BATCH_ID_CLOSE: usize = 9413;
 const BATCH_ID_LOG: usize = 9414;
 const BATCH_ID_PROGRESS: usize = 9415;
+const BATCH_ID_ASCII_NAMES: usize = 9416;
 
 const WM_BATCH_EVENT: u32 = WM_APP + 120;
 const BATCH_TIMER_ID: usize = 1;
@@ -111,6 +113,7 @@ struct BatchLabels {
     format_wav: String,
     option_subfolder: String,
     option_avoid_overwrite: String,
+    option_ascii_names: String,
     start: String,
     cancel: String,
     close: String,
@@ -142,6 +145,7 @@ struct BatchState {
     format_combo: HWND,
     checkbox_subfolder: HWND,
     checkbox_avoid_overwrite: HWND,
+    checkbox_ascii_names: HWND,
     start_button: HWND,
     cancel_button: HWND,
     close_button: HWND,
@@ -188,6 +192,8 @@ struct BatchSettings {
     format: AudioFormat,
     create_subfolder: bool,
     avoid_overwrite: bool,
+    /// Output names without accents, other alphabets or typographic punctuation.
+    ascii_names: bool,
 }
 
 struct TtsSettings {
@@ -229,6 +235,7 @@ fn labels(language: Language) -> BatchLabels {
         format_wav: i18n::tr(language, "batch_audiobooks.format.wav"),
         option_subfolder: i18n::tr(language, "batch_audiobooks.option_subfolder"),
         option_avoid_overwrite: i18n::tr(language, "batch_audiobooks.option_avoid_overwrite"),
+        option_ascii_names: i18n::tr(language, "batch_audiobooks.option_ascii_names"),
         start: i18n::tr(language, "batch_audiobooks.start"),
         cancel: i18n::tr(language, "batch_audiobooks.cancel"),
         close: i18n::tr(language, "batch_audiobooks.close"),
@@ -544,6 +551,20 @@ unsafe extern "system" fn batch_wndproc(
                 WPARAM(BST_CHECKED.0 as usize),
                 LPARAM(0),
             );
+            let checkbox_ascii_names = CreateWindowExW(
+                Default::default(),
+                WC_BUTTON,
+                PCWSTR(to_wide(&labels.option_ascii_names).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_AUTOCHECKBOX as u32),
+                360,
+                328,
+                376,
+                22,
+                hwnd,
+                HMENU(BATCH_ID_ASCII_NAMES as isize),
+                HINSTANCE(0),
+                None,
+            );
 
             let progress_label = CreateWindowExW(
                 Default::default(),
@@ -666,6 +687,7 @@ unsafe extern "system" fn batch_wndproc(
                 format_combo,
                 checkbox_subfolder,
                 checkbox_avoid_overwrite,
+                checkbox_ascii_names,
                 progress_label,
                 progress_bar,
                 start_button,
@@ -703,6 +725,7 @@ unsafe extern "system" fn batch_wndproc(
                 format_combo,
                 checkbox_subfolder,
                 checkbox_avoid_overwrite,
+                checkbox_ascii_names,
                 start_button,
                 cancel_button,
                 close_button,
@@ -1120,6 +1143,7 @@ fn start_batch(state: &mut BatchState) {
 
     let create_subfolder = is_checked(state.checkbox_subfolder);
     let avoid_overwrite = is_checked(state.checkbox_avoid_overwrite);
+    let ascii_names = is_checked(state.checkbox_ascii_names);
 
     let tts_settings = load_tts_settings(state.parent, tts_voice, state.language);
     let batch_settings = BatchSettings {
@@ -1127,6 +1151,7 @@ fn start_batch(state: &mut BatchState) {
         format,
         create_subfolder,
         avoid_overwrite,
+        ascii_names,
     };
 
     set_running(state, true);
@@ -1179,6 +1204,7 @@ fn set_running(state: &mut BatchState, running: bool) {
         EnableWindow(state.format_combo, !running);
         EnableWindow(state.checkbox_subfolder, !running);
         EnableWindow(state.checkbox_avoid_overwrite, !running);
+        EnableWindow(state.checkbox_ascii_names, !running);
         EnableWindow(state.start_button, !running);
         EnableWindow(state.cancel_button, running);
         EnableWindow(state.close_button, !running);
@@ -1355,7 +1381,7 @@ fn append_log(state: &mut BatchState, line: &str) {
     }
 }
 
-fn open_files_dialog(hwnd: HWND, language: Language) -> Option<Vec<PathBuf>> {
+pub(crate) fn open_files_dialog(hwnd: HWND, language: Language) -> Option<Vec<PathBuf>> {
     let filter_raw = i18n::tr(language, "dialog.open_filter");
     let filter = to_wide(&filter_raw.replace("\\0", "\0"));
     let mut buffer = vec![0u16; 4096];
@@ -1763,7 +1789,11 @@ fn build_output_paths(
         .file_stem()
         .and_then(|s| s.to_str())
         .unwrap_or("audiobook");
-    let mut base_name = sanitize_filename(base);
+    let mut base_name = if settings.ascii_names {
+        sanitize_filename(&crate::transliterate::to_ascii(base, true))
+    } else {
+        sanitize_filename(base)
+    };
     if base_name.is_empty() {
         base_name = "audiobook".to_string();
     }
@@ -1889,7 +1919,7 @@ fn export_parts(
                         volume: tts.tts_volume,
                         cancel: cancel.clone(),
                     },
-                    |_chunk_idx| {
+                    |_chunk_idx, _audio_ms| {
                         progress += 1;
                     },
                 )?;
@@ -1912,6 +1942,9 @@ fn read_text_for_audiobook(path: &Path, language: Language) -> Result<String, St
     if is_epub_path(path) {
         return read_epub_text(path, language);
     }
+    if is_mobi_path(path) {
+        return read_mobi_text(path, language);
+    }
     if is_html_path(path) {
         return read_html_text(path, language).map(|(text, _)| text);
     }
@@ -0,0 +1,484 @@
+use crate::accessibility::{handle_accessibility, nvda_speak, to_wide};
+use crate::braille::{self, BrfLayout};
+use crate::editor_manager::get_edit_text;
+use crate::i18n;
+use crate::settings::save_settings;
+use crate::{get_active_edit, save_braille_dialog, show_error, show_info, with_state};
+use std::path::Path;
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{COLOR_WINDOW, HBRUSH};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Controls::{BST_CHECKED, WC_BUTTON, WC_COMBOBOXW};
+use windows::Win32::UI::Input::KeyboardAndMouse::{EnableWindow, SetFocus};
+use windows::Win32::UI::WindowsAndMessaging::{
+    BM_GETCHECK, BM_SETCHECK, BS_AUTOCHECKBOX, BS_DEFPUSHBUTTON, CB_ADDSTRING, CB_GETCURSEL,
+    CB_SETCURSEL, CBS_DROPDOWNLIST, CREATESTRUCTW, CW_USEDEFAULT, CreateWindowExW, DefWindowProcW,
+    DestroyWindow, GWLP_USERDATA, GetDlgItem, GetWindowLongPtrW, HMENU, IDC_ARROW, LoadCursorW,
+    RegisterClassW, SendMessageW, SetForegroundWindow, SetWindowLongPtrW, SetWindowTextW,
+    WINDOW_STYLE, WM_COMMAND, WM_CREATE, WM_DESTROY, WM_GETTEXT, WM_GETTEXTLENGTH, WM_KEYDOWN,
+    WM_NCDESTROY, WNDCLASSW, WS_CAPTION, WS_CHILD, WS_EX_CLIENTEDGE, WS_EX_DLGMODALFRAME, WS_POPUP,
+    WS_TABSTOP, WS_VISIBLE,
+};
+use windows::core::{PCWSTR, w};
+
+const BRAILLE_EXPORT_CLASS: &str = "NovapadBrailleExport";
+const BRAILLE_EXPORT_TABLE_ID: usize = 1831;
+const BRAILLE_EXPORT_CELLS_ID: usize = 1832;
+const BRAILLE_EXPORT_LINES_ID: usize = 1833;
+const BRAILLE_EXPORT_NUMBERS_ID: usize = 1834;
+const BRAILLE_EXPORT_OK_ID: usize = 1835;
+const BRAILLE_EXPORT_CANCEL_ID: usize = 1836;
+const BRAILLE_EXPORT_STATUS_ID: usize = 1837;
+
+const CELLS_RANGE: std::ops::RangeInclusive<usize> = 10..=100;
+const LINES_RANGE: std::ops::RangeInclusive<usize> = 5..=100;
+
+struct BrailleExportState {
+    parent: HWND,
+}
+
+pub unsafe fn open(parent: HWND) {
+    let existing = with_state(parent, |state| state.braille_export_dialog).unwrap_or(HWND(0));
+    if existing.0 != 0 {
+        SetForegroundWindow(existing);
+        return;
+    }
+
+    let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+    let class_name = to_wide(BRAILLE_EXPORT_CLASS);
+    let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+    let title_w = to_wide(&i18n::tr(language, "braille_export.title"));
+
+    let wc = WNDCLASSW {
+        hCursor: windows::Win32::UI::WindowsAndMessaging::HCURSOR(
+            LoadCursorW(None, IDC_ARROW).unwrap_or_default().0,
+        ),
+        hInstance: hinstance,
+        lpszClassName: PCWSTR(class_name.as_ptr()),
+        lpfnWndProc: Some(braille_export_wndproc),
+        hbrBackground: HBRUSH((COLOR_WINDOW.0 + 1) as isize),
+        ..Default::default()
+    };
+    RegisterClassW(&wc);
+
+    let state = Box::new(BrailleExportState { parent });
+    let state_ptr = Box::into_raw(state);
+    let hwnd = CreateWindowExW(
+        WS_EX_DLGMODALFRAME,
+        PCWSTR(class_name.as_ptr()),
+        PCWSTR(title_w.as_ptr()),
+        WS_POPUP | WS_CAPTION | WS_VISIBLE,
+        CW_USEDEFAULT,
+        CW_USEDEFAULT,
+        420,
+        300,
+        parent,
+        HMENU(0),
+        hinstance,
+        Some(state_ptr as *const _),
+    );
+    if hwnd.0 == 0 {
+        drop(Box::from_raw(state_ptr));
+        return;
+    }
+    EnableWindow(parent, false);
+    with_state(parent, |state| state.braille_export_dialog = hwnd);
+}
+
+unsafe fn create_label(hwnd: HWND, hinstance: HINSTANCE, text: &str, y: i32, id: isize) {
+    CreateWindowExW(
+        Default::default(),
+        w!("STATIC"),
+        PCWSTR(to_wide(text).as_ptr()),
+        WS_CHILD | WS_VISIBLE,
+        10,
+        y,
+        390,
+        16,
+        hwnd,
+        HMENU(id),
+        hinstance,
+        None,
+    );
+}
+
+unsafe fn create_input(hwnd: HWND, hinstance: HINSTANCE, text: &str, y: i32, id: usize) {
+    CreateWindowExW(
+        WS_EX_CLIENTEDGE,
+        w!("EDIT"),
+        PCWSTR(to_wide(text).as_ptr()),
+        WS_CHILD | WS_VISIBLE | WS_TABSTOP,
+        10,
+        y,
+        120,
+        24,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    );
+}
+
+unsafe fn read_input(hwnd: HWND, id: usize) -> String {
+    let input = GetDlgItem(hwnd, id as i32);
+    let len = SendMessageW(input, WM_GETTEXTLENGTH, WPARAM(0), LPARAM(0)).0;
+    let mut buf = vec![0u16; len as usize + 1];
+    SendMessageW(
+        input,
+        WM_GETTEXT,
+        WPARAM(buf.len()),
+        LPARAM(buf.as_mut_ptr() as isize),
+    );
+    String::from_utf16_lossy(&buf[..len as usize])
+}
+
+unsafe fn show_status(hwnd: HWND, message: &str, focus_id: usize) {
+    let status = GetDlgItem(hwnd, BRAILLE_EXPORT_STATUS_ID as i32);
+    let wide = to_wide(message);
+    crate::log_if_err!(SetWindowTextW(status, PCWSTR(wide.as_ptr())));
+    nvda_speak(message);
+    SetFocus(GetDlgItem(hwnd, focus_id as i32));
+}
+
+fn parse_in_range(text: &str, range: std::ops::RangeInclusive<usize>) -> Option<usize> {
+    text.trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|value| range.contains(value))
+}
+
+unsafe extern "system" fn braille_export_wndproc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_CREATE => {
+            let cs = lparam.0 as *const CREATESTRUCTW;
+            let init_ptr = (*cs).lpCreateParams as *mut BrailleExportState;
+            if init_ptr.is_null() {
+                return LRESULT(0);
+            }
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, init_ptr as isize);
+            let parent = (*init_ptr).parent;
+            let (language, table_id, cells, lines, page_numbers) = with_state(parent, |state| {
+                (
+                    state.settings.language,
+                    state.settings.braille_table.clone(),
+                    state.settings.braille_cells_per_line,
+                    state.settings.braille_lines_per_page,
+                    state.settings.braille_page_numbers,
+                )
+            })
+            .unwrap_or_default();
+            let table_id = if braille::table_ids().any(|id| id == table_id) {
+                table_id
+            } else {
+                braille::default_table_id(language).to_string()
+            };
+
+            let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+            create_label(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "braille_export.table"),
+                12,
+                1,
+            );
+            let combo = CreateWindowExW(
+                WS_EX_CLIENTEDGE,
+                WC_COMBOBOXW,
+                PCWSTR::null(),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(CBS_DROPDOWNLIST as u32),
+                10,
+                30,
+                390,
+                160,
+                hwnd,
+                HMENU(BRAILLE_EXPORT_TABLE_ID as isize),
+                hinstance,
+                None,
+            );
+            for (idx, id) in braille::table_ids().enumerate() {
+                let name = braille::load_table(id)
+                    .map(|table| table.name)
+                    .unwrap_or_else(|_| id.to_string());
+                let name_w = to_wide(&name);
+                SendMessageW(
+                    combo,
+                    CB_ADDSTRING,
+                    WPARAM(0),
+                    LPARAM(name_w.as_ptr() as isize),
+                );
+                if id == table_id {
+                    SendMessageW(combo, CB_SETCURSEL, WPARAM(idx), LPARAM(0));
+                }
+            }
+            create_label(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "braille_export.cells_per_line"),
+                66,
+                2,
+            );
+            create_input(
+                hwnd,
+                hinstance,
+                &cells.to_string(),
+                84,
+                BRAILLE_EXPORT_CELLS_ID,
+            );
+            create_label(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "braille_export.lines_per_page"),
+                116,
+                3,
+            );
+            create_input(
+                hwnd,
+                hinstance,
+                &lines.to_string(),
+                134,
+                BRAILLE_EXPORT_LINES_ID,
+            );
+            let numbers = CreateWindowExW(
+                Default::default(),
+                WC_BUTTON,
+                PCWSTR(to_wide(&i18n::tr(language, "braille_export.page_numbers")).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_AUTOCHECKBOX as u32),
+                10,
+                166,
+                390,
+                22,
+                hwnd,
+                HMENU(BRAILLE_EXPORT_NUMBERS_ID as isize),
+                hinstance,
+                None,
+            );
+            if page_numbers {
+                SendMessageW(
+                    numbers,
+                    BM_SETCHECK,
+                    WPARAM(BST_CHECKED.0 as usize),
+                    LPARAM(0),
+                );
+            }
+            create_label(hwnd, hinstance, "", 196, BRAILLE_EXPORT_STATUS_ID as isize);
+            CreateWindowExW(
+                Default::default(),
+                w!("BUTTON"),
+                PCWSTR(to_wide(&i18n::tr(language, "braille_export.ok")).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_DEFPUSHBUTTON as u32),
+                230,
+                222,
+                80,
+                26,
+                hwnd,
+                HMENU(BRAILLE_EXPORT_OK_ID as isize),
+                hinstance,
+                None,
+            );
+            CreateWindowExW(
+                Default::default(),
+                w!("BUTTON"),
+                PCWSTR(to_wide(&i18n::tr(language, "braille_export.cancel")).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP,
+                320,
+                222,
+                80,
+                26,
+                hwnd,
+                HMENU(BRAILLE_EXPORT_CANCEL_ID as isize),
+                hinstance,
+                None,
+            );
+
+            SetFocus(combo);
+            LRESULT(0)
+        }
+        WM_KEYDOWN => {
+            if wparam.0 as u32 == windows::Win32::UI::Input::KeyboardAndMouse::VK_ESCAPE.0 as u32 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_COMMAND => {
+            let id = wparam.0 & 0xffff;
+            if id == BRAILLE_EXPORT_CANCEL_ID || id == 2 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            if id == BRAILLE_EXPORT_OK_ID || id == 1 {
+                let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const BrailleExportState;
+                if ptr.is_null() {
+                    return LRESULT(0);
+                }
+                let parent = (*ptr).parent;
+                let language =
+                    with_state(parent, |state| state.settings.language).unwrap_or_default();
+                let Some(cells) =
+                    parse_in_range(&read_input(hwnd, BRAILLE_EXPORT_CELLS_ID), CELLS_RANGE)
+                else {
+                    let msg = i18n::tr_f(
+                        language,
+                        "braille_export.invalid_cells",
+                        &[
+                            ("min", &CELLS_RANGE.start().to_string()),
+                            ("max", &CELLS_RANGE.end().to_string()),
+                        ],
+                    );
+                    show_status(hwnd, &msg, BRAILLE_EXPORT_CELLS_ID);
+                    return LRESULT(0);
+                };
+                let Some(lines) =
+                    parse_in_range(&read_input(hwnd, BRAILLE_EXPORT_LINES_ID), LINES_RANGE)
+                else {
+                    let msg = i18n::tr_f(
+                        language,
+                        "braille_export.invalid_lines",
+                        &[
+                            ("min", &LINES_RANGE.start().to_string()),
+                            ("max", &LINES_RANGE.end().to_string()),
+                        ],
+                    );
+                    show_status(hwnd, &msg, BRAILLE_EXPORT_LINES_ID);
+                    return LRESULT(0);
+                };
+                let combo = GetDlgItem(hwnd, BRAILLE_EXPORT_TABLE_ID as i32);
+                let selected = SendMessageW(combo, CB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+                let table_id = usize::try_from(selected)
+                    .ok()
+                    .and_then(|idx| braille::table_ids().nth(idx))
+                    .unwrap_or_else(|| braille::default_table_id(language));
+                let numbers = GetDlgItem(hwnd, BRAILLE_EXPORT_NUMBERS_ID as i32);
+                let page_numbers = SendMessageW(numbers, BM_GETCHECK, WPARAM(0), LPARAM(0)).0
+                    == BST_CHECKED.0 as isize;
+                let settings = with_state(parent, |state| {
+                    state.settings.braille_table = table_id.to_string();
+                    state.settings.braille_cells_per_line = cells as u32;
+                    state.settings.braille_lines_per_page = lines as u32;
+                    state.settings.braille_page_numbers = page_numbers;
+                    state.settings.clone()
+                });
+                if let Some(settings) = settings {
+                    save_settings(settings);
+                }
+                crate::log_if_err!(DestroyWindow(hwnd));
+                export_document(
+                    parent,
+                    table_id,
+                    BrfLayout {
+                        cells_per_line: cells,
+                        lines_per_page: lines,
+                        page_numbers,
+                    },
+                );
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_DESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const BrailleExportState;
+            if !ptr.is_null() {
+                let parent = (*ptr).parent;
+                EnableWindow(parent, true);
+                SetForegroundWindow(parent);
+                with_state(parent, |s| s.braille_export_dialog = HWND(0));
+                if let Some(hwnd_edit) = get_active_edit(parent) {
+                    SetFocus(hwnd_edit);
+                }
+            }
+            LRESULT(0)
+        }
+        WM_NCDESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut BrailleExportState;
+            if !ptr.is_null() {
+                drop(Box::from_raw(ptr));
+            }
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+/// Translates the current document and writes it to a BRF file chosen by the user.
+unsafe fn export_document(parent: HWND, table_id: &str, layout: BrfLayout) {
+    let Some(hwnd_edit) = get_active_edit(parent) else {
+        return;
+    };
+    let (language, title) = with_state(parent, |state| {
+        (
+            state.settings.language,
+            state
+                .docs
+                .get(state.current)
+                .map(|doc| doc.title.clone())
+                .unwrap_or_default(),
+        )
+    })
+    .unwrap_or_default();
+    let text = get_edit_text(hwnd_edit);
+    if text.trim().is_empty() {
+        show_error(
+            parent,
+            language,
+            &i18n::tr(language, "braille_export.no_text"),
+        );
+        return;
+    }
+    let table = match braille::load_table(table_id) {
+        Ok(table) => table,
+        Err(err) => {
+            show_error(
+                parent,
+                language,
+                &i18n::tr_f(language, "braille_export.table_error", &[("err", &err)]),
+            );
+            return;
+        }
+    };
+    let stem = Path::new(&title)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .map(str::to_string);
+    let Some(path) = save_braille_dialog(parent, stem.as_deref()) else {
+        return;
+    };
+    let brf = table.to_brf(&text, layout);
+    if let Err(err) = std::fs::write(&path, brf.text.as_bytes()) {
+        show_error(
+            parent,
+            language,
+            &i18n::tr_f(
+                language,
+                "braille_export.write_error",
+                &[("err", &err.to_string())],
+            ),
+        );
+        return;
+    }
+    let mut message = i18n::tr_f(
+        language,
+        "braille_export.saved",
+        &[("pages", &brf.pages.to_string())],
+    );
+    if brf.untranslated > 0 {
+        message.push('\n');
+        message.push_str(&i18n::tr_f(
+            language,
+            "braille_export.untranslated",
+            &[("count", &brf.untranslated.to_string())],
+        ));
+    }
+    show_info(parent, language, &message);
+}
+
+pub unsafe fn handle_navigation(
+    hwnd: HWND,
+    msg: &windows::Win32::UI::WindowsAndMessaging::MSG,
+) -> bool {
+    handle_accessibility(hwnd, msg)
+}
@@ -0,0 +1,449 @@
+use super::sort_lines_window::{display_delimiter, parse_delimiter};
+use crate::accessibility::{handle_accessibility, nvda_speak, to_wide};
+use crate::i18n;
+use crate::settings::{ColumnAction, Language, save_settings};
+use crate::with_state;
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{COLOR_WINDOW, HBRUSH};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Controls::WC_COMBOBOXW;
+use windows::Win32::UI::Input::KeyboardAndMouse::{EnableWindow, SetFocus};
+use windows::Win32::UI::WindowsAndMessaging::{
+    BS_DEFPUSHBUTTON, CB_ADDSTRING, CB_GETCURSEL, CB_SETCURSEL, CBS_DROPDOWNLIST, CREATESTRUCTW,
+    CW_USEDEFAULT, CreateWindowExW, DefWindowProcW, DestroyWindow, GWLP_USERDATA, GetDlgItem,
+    GetWindowLongPtrW, HMENU, IDC_ARROW, LoadCursorW, RegisterClassW, SendMessageW,
+    SetForegroundWindow, SetWindowLongPtrW, SetWindowTextW, WINDOW_STYLE, WM_COMMAND, WM_CREATE,
+    WM_DESTROY, WM_GETTEXT, WM_GETTEXTLENGTH, WM_KEYDOWN, WM_NCDESTROY, WNDCLASSW, WS_CAPTION,
+    WS_CHILD, WS_EX_CLIENTEDGE, WS_EX_DLGMODALFRAME, WS_POPUP, WS_TABSTOP, WS_VISIBLE,
+};
+use windows::core::{PCWSTR, w};
+
+const COLUMNS_CLASS: &str = "NovapadColumns";
+const COLUMNS_ACTION_ID: usize = 1909;
+const COLUMNS_FIELDS_ID: usize = 1910;
+const COLUMNS_DELIMITER_ID: usize = 1911;
+const COLUMNS_CUSTOM_ID: usize = 1912;
+const COLUMNS_OK_ID: usize = 1913;
+const COLUMNS_CANCEL_ID: usize = 1914;
+const COLUMNS_STATUS_ID: usize = 1915;
+
+const COLUMN_ACTIONS: [(ColumnAction, &str); 2] = [
+    (ColumnAction::Extract, "columns.action_extract"),
+    (ColumnAction::Delete, "columns.action_delete"),
+];
+
+/// Separators offered by the dialog; an empty one splits on runs of whitespace. Any
+/// other separator is typed in the custom box.
+const DELIMITERS: [(&str, &str); 4] = [
+    ("\t", "columns.delimiter_tab"),
+    (",", "columns.delimiter_comma"),
+    (";", "columns.delimiter_semicolon"),
+    ("", "columns.delimiter_spaces"),
+];
+
+struct ColumnsState {
+    parent: HWND,
+}
+
+/// Asks which fields of the selected lines, or of every line, to keep or delete.
+pub unsafe fn open(parent: HWND) {
+    let existing = with_state(parent, |state| state.columns_dialog).unwrap_or(HWND(0));
+    if existing.0 != 0 {
+        SetForegroundWindow(existing);
+        return;
+    }
+
+    let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+    let class_name = to_wide(COLUMNS_CLASS);
+    let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+    let title_w = to_wide(&i18n::tr(language, "columns.title"));
+
+    let wc = WNDCLASSW {
+        hCursor: windows::Win32::UI::WindowsAndMessaging::HCURSOR(
+            LoadCursorW(None, IDC_ARROW).unwrap_or_default().0,
+        ),
+        hInstance: hinstance,
+        lpszClassName: PCWSTR(class_name.as_ptr()),
+        lpfnWndProc: Some(columns_wndproc),
+        hbrBackground: HBRUSH((COLOR_WINDOW.0 + 1) as isize),
+        ..Default::default()
+    };
+    RegisterClassW(&wc);
+
+    let state = Box::new(ColumnsState { parent });
+    let state_ptr = Box::into_raw(state);
+    let hwnd = CreateWindowExW(
+        WS_EX_DLGMODALFRAME,
+        PCWSTR(class_name.as_ptr()),
+        PCWSTR(title_w.as_ptr()),
+        WS_POPUP | WS_CAPTION | WS_VISIBLE,
+        CW_USEDEFAULT,
+        CW_USEDEFAULT,
+        420,
+        340,
+        parent,
+        HMENU(0),
+        hinstance,
+        Some(state_ptr as *const _),
+    );
+    if hwnd.0 == 0 {
+        drop(Box::from_raw(state_ptr));
+        return;
+    }
+    EnableWindow(parent, false);
+    with_state(parent, |state| state.columns_dialog = hwnd);
+}
+
+unsafe fn create_label(hwnd: HWND, hinstance: HINSTANCE, text: &str, y: i32, id: isize) {
+    CreateWindowExW(
+        Default::default(),
+        w!("STATIC"),
+        PCWSTR(to_wide(text).as_ptr()),
+        WS_CHILD | WS_VISIBLE,
+        10,
+        y,
+        390,
+        16,
+        hwnd,
+        HMENU(id),
+        hinstance,
+        None,
+    );
+}
+
+unsafe fn create_input(hwnd: HWND, hinstance: HINSTANCE, text: &str, y: i32, id: usize) {
+    CreateWindowExW(
+        WS_EX_CLIENTEDGE,
+        w!("EDIT"),
+        PCWSTR(to_wide(text).as_ptr()),
+        WS_CHILD | WS_VISIBLE | WS_TABSTOP,
+        10,
+        y,
+        120,
+        24,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    );
+}
+
+unsafe fn create_combo(
+    hwnd: HWND,
+    hinstance: HINSTANCE,
+    labels: &[String],
+    selected: usize,
+    y: i32,
+    id: usize,
+) -> HWND {
+    let combo = CreateWindowExW(
+        WS_EX_CLIENTEDGE,
+        WC_COMBOBOXW,
+        PCWSTR::null(),
+        WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(CBS_DROPDOWNLIST as u32),
+        10,
+        y,
+        390,
+        120,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    );
+    for label in labels {
+        let label_w = to_wide(label);
+        SendMessageW(
+            combo,
+            CB_ADDSTRING,
+            WPARAM(0),
+            LPARAM(label_w.as_ptr() as isize),
+        );
+    }
+    SendMessageW(combo, CB_SETCURSEL, WPARAM(selected), LPARAM(0));
+    combo
+}
+
+unsafe fn selected_index(hwnd: HWND, id: usize) -> Option<usize> {
+    let combo = GetDlgItem(hwnd, id as i32);
+    usize::try_from(SendMessageW(combo, CB_GETCURSEL, WPARAM(0), LPARAM(0)).0).ok()
+}
+
+unsafe fn read_input(hwnd: HWND, id: usize) -> String {
+    let input = GetDlgItem(hwnd, id as i32);
+    let len = SendMessageW(input, WM_GETTEXTLENGTH, WPARAM(0), LPARAM(0)).0;
+    let mut buf = vec![0u16; len as usize + 1];
+    SendMessageW(
+        input,
+        WM_GETTEXT,
+        WPARAM(buf.len()),
+        LPARAM(buf.as_mut_ptr() as isize),
+    );
+    String::from_utf16_lossy(&buf[..len as usize])
+}
+
+unsafe fn show_status(hwnd: HWND, message: &str, focus_id: usize) {
+    let status = GetDlgItem(hwnd, COLUMNS_STATUS_ID as i32);
+    let wide = to_wide(message);
+    crate::log_if_err!(SetWindowTextW(status, PCWSTR(wide.as_ptr())));
+    nvda_speak(message);
+    SetFocus(GetDlgItem(hwnd, focus_id as i32));
+}
+
+/// A field range, 1-based and inclusive: "3", "2-4", "3-" up to the last field or "-2"
+/// from the first.
+fn parse_field_range(text: &str) -> Option<(usize, usize)> {
+    let text = text.trim();
+    let parse = |part: &str| part.trim().parse::<usize>().ok().filter(|field| *field > 0);
+    let (first, last) = match text.split_once('-') {
+        Some((first, last)) => (
+            if first.trim().is_empty() {
+                1
+            } else {
+                parse(first)?
+            },
+            if last.trim().is_empty() {
+                usize::MAX
+            } else {
+                parse(last)?
+            },
+        ),
+        None => {
+            let field = parse(text)?;
+            (field, field)
+        }
+    };
+    (first <= last).then_some((first, last))
+}
+
+fn delimiter_labels(language: Language) -> Vec<String> {
+    DELIMITERS
+        .iter()
+        .map(|(_, key)| i18n::tr(language, key))
+        .chain(std::iter::once(i18n::tr(
+            language,
+            "columns.delimiter_custom",
+        )))
+        .collect()
+}
+
+unsafe extern "system" fn columns_wndproc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_CREATE => {
+            let cs = lparam.0 as *const CREATESTRUCTW;
+            let init_ptr = (*cs).lpCreateParams as *mut ColumnsState;
+            if init_ptr.is_null() {
+                return LRESULT(0);
+            }
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, init_ptr as isize);
+            let parent = (*init_ptr).parent;
+            let Some(settings) = with_state(parent, |state| state.settings.clone()) else {
+                return LRESULT(0);
+            };
+            let language = settings.language;
+
+            let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+            create_label(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "columns.action"),
+                12,
+                1,
+            );
+            let action_labels: Vec<String> = COLUMN_ACTIONS
+                .iter()
+                .map(|(_, key)| i18n::tr(language, key))
+                .collect();
+            let action = COLUMN_ACTIONS
+                .iter()
+                .position(|(action, _)| *action == settings.column_action)
+                .unwrap_or(0);
+            let combo = create_combo(
+                hwnd,
+                hinstance,
+                &action_labels,
+                action,
+                30,
+                COLUMNS_ACTION_ID,
+            );
+            create_label(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "columns.fields"),
+                66,
+                2,
+            );
+            create_input(
+                hwnd,
+                hinstance,
+                &settings.column_fields,
+                84,
+                COLUMNS_FIELDS_ID,
+            );
+            create_label(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "columns.delimiter"),
+                116,
+                3,
+            );
+            // A separator that is not one of the presets selects "Custom".
+            let delimiter = DELIMITERS
+                .iter()
+                .position(|(delimiter, _)| *delimiter == settings.column_delimiter);
+            let custom = if delimiter.is_some() {
+                String::new()
+            } else {
+                display_delimiter(&settings.column_delimiter)
+            };
+            create_combo(
+                hwnd,
+                hinstance,
+                &delimiter_labels(language),
+                delimiter.unwrap_or(DELIMITERS.len()),
+                134,
+                COLUMNS_DELIMITER_ID,
+            );
+            create_label(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "columns.custom"),
+                170,
+                4,
+            );
+            create_input(hwnd, hinstance, &custom, 188, COLUMNS_CUSTOM_ID);
+            create_label(hwnd, hinstance, "", 222, COLUMNS_STATUS_ID as isize);
+            CreateWindowExW(
+                Default::default(),
+                w!("BUTTON"),
+                PCWSTR(to_wide(&i18n::tr(language, "columns.ok")).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_DEFPUSHBUTTON as u32),
+                230,
+                250,
+                80,
+                26,
+                hwnd,
+                HMENU(COLUMNS_OK_ID as isize),
+                hinstance,
+                None,
+            );
+            CreateWindowExW(
+                Default::default(),
+                w!("BUTTON"),
+                PCWSTR(to_wide(&i18n::tr(language, "columns.cancel")).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP,
+                320,
+                250,
+                80,
+                26,
+                hwnd,
+                HMENU(COLUMNS_CANCEL_ID as isize),
+                hinstance,
+                None,
+            );
+
+            SetFocus(combo);
+            LRESULT(0)
+        }
+        WM_KEYDOWN => {
+            if wparam.0 as u32 == windows::Win32::UI::Input::KeyboardAndMouse::VK_ESCAPE.0 as u32 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_COMMAND => {
+            let id = wparam.0 & 0xffff;
+            if id == COLUMNS_CANCEL_ID || id == 2 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            if id == COLUMNS_OK_ID || id == 1 {
+                let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const ColumnsState;
+                if ptr.is_null() {
+                    return LRESULT(0);
+                }
+                let parent = (*ptr).parent;
+                let language =
+                    with_state(parent, |state| state.settings.language).unwrap_or_default();
+                let fields_text = read_input(hwnd, COLUMNS_FIELDS_ID).trim().to_string();
+                let Some(fields) = parse_field_range(&fields_text) else {
+                    let msg = i18n::tr(language, "columns.invalid_fields");
+                    show_status(hwnd, &msg, COLUMNS_FIELDS_ID);
+                    return LRESULT(0);
+                };
+                let delimiter = match selected_index(hwnd, COLUMNS_DELIMITER_ID)
+                    .and_then(|idx| DELIMITERS.get(idx))
+                {
+                    Some((delimiter, _)) => delimiter.to_string(),
+                    None => {
+                        let custom = parse_delimiter(&read_input(hwnd, COLUMNS_CUSTOM_ID));
+                        if custom.is_empty() {
+                            let msg = i18n::tr(language, "columns.empty_custom");
+                            show_status(hwnd, &msg, COLUMNS_CUSTOM_ID);
+                            return LRESULT(0);
+                        }
+                        custom
+                    }
+                };
+                let action = selected_index(hwnd, COLUMNS_ACTION_ID)
+                    .and_then(|idx| COLUMN_ACTIONS.get(idx))
+                    .map(|(action, _)| *action)
+                    .unwrap_or_default();
+                let settings = with_state(parent, |state| {
+                    state.settings.column_action = action;
+                    state.settings.column_fields = fields_text.clone();
+                    state.settings.column_delimiter = delimiter.clone();
+                    state.settings.clone()
+                });
+                if let Some(settings) = settings {
+                    save_settings(settings);
+                }
+                crate::log_if_err!(DestroyWindow(hwnd));
+                let keep = action == ColumnAction::Extract;
+                let key = if crate::editor_manager::select_fields_active_edit(
+                    parent, &delimiter, fields, keep,
+                ) {
+                    "columns.done"
+                } else {
+                    "columns.unchanged"
+                };
+                nvda_speak(&i18n::tr(language, key));
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_DESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const ColumnsState;
+            if !ptr.is_null() {
+                let parent = (*ptr).parent;
+                EnableWindow(parent, true);
+                SetForegroundWindow(parent);
+                with_state(parent, |s| s.columns_dialog = HWND(0));
+                if let Some(hwnd_edit) = crate::get_active_edit(parent) {
+                    SetFocus(hwnd_edit);
+                }
+            }
+            LRESULT(0)
+        }
+        WM_NCDESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut ColumnsState;
+            if !ptr.is_null() {
+                drop(Box::from_raw(ptr));
+            }
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+pub unsafe fn handle_navigation(
+    hwnd: HWND,
+    msg: &windows::Win32::UI::WindowsAndMessaging::MSG,
+) -> bool {
+    handle_accessibility(hwnd, msg)
+}
@@ -0,0 +1,692 @@
+use crate::accessibility::{handle_accessibility, nvda_speak, to_wide};
+use crate::diff::{Hunk, HunkKind, WordChange, diff_lines, diff_words, split_lines};
+use crate::editor_manager::{get_edit_text, replace_edit_lines, select_edit_lines};
+use crate::i18n;
+use crate::settings::{FileFormat, Language};
+use crate::with_state;
+use std::path::PathBuf;
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{COLOR_WINDOW, HBRUSH, HFONT};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Controls::{WC_BUTTON, WC_COMBOBOXW, WC_LISTBOXW};
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    EnableWindow, GetFocus, SetFocus, VK_ESCAPE, VK_RETURN,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    BS_DEFPUSHBUTTON, CB_ADDSTRING, CB_GETCURSEL, CB_SETCURSEL, CBN_SELCHANGE, CBS_DROPDOWNLIST,
+    CREATESTRUCTW, CW_USEDEFAULT, CreateWindowExW, DefWindowProcW, DestroyWindow, GWLP_USERDATA,
+    GetDlgItem, GetWindowLongPtrW, HMENU, IDC_ARROW, LB_ADDSTRING, LB_GETCURSEL, LB_RESETCONTENT,
+    LB_SETCURSEL, LBN_DBLCLK, LBS_HASSTRINGS, LBS_NOTIFY, LoadCursorW, MSG, RegisterClassW,
+    SendMessageW, SetForegroundWindow, SetWindowLongPtrW, SetWindowTextW, WINDOW_STYLE, WM_CLOSE,
+    WM_COMMAND, WM_CREATE, WM_DESTROY, WM_KEYDOWN, WM_NCDESTROY, WM_SETFONT, WNDCLASSW, WS_CAPTION,
+    WS_CHILD, WS_EX_CLIENTEDGE, WS_EX_CONTROLPARENT, WS_EX_DLGMODALFRAME, WS_SYSMENU, WS_TABSTOP,
+    WS_VISIBLE, WS_VSCROLL,
+};
+use windows::core::{PCWSTR, w};
+
+const COMPARE_CLASS_NAME: &str = "NovapadCompare";
+const COMPARE_ID_SOURCE: usize = 1861;
+const COMPARE_ID_LIST: usize = 1862;
+const COMPARE_ID_GOTO: usize = 1863;
+const COMPARE_ID_TAKE_OTHER: usize = 1864;
+const COMPARE_ID_SEND_OTHER: usize = 1865;
+const COMPARE_ID_CLOSE: usize = 1866;
+const COMPARE_ID_STATUS: usize = 1867;
+
+/// Longest excerpt of a line or word change quoted in a difference description.
+const EXCERPT_CHARS: usize = 60;
+/// Word changes spelled out for a changed hunk before summarizing the rest.
+const MAX_WORD_CHANGES: usize = 3;
+
+/// What the current document is compared against: another tab or its saved file.
+enum CompareSource {
+    Tab(HWND),
+    Saved(PathBuf),
+}
+
+struct CompareWindowState {
+    parent: HWND,
+    /// Editor of the document being reviewed; differences are reported against its lines.
+    hwnd_edit: HWND,
+    hwnd_source: HWND,
+    hwnd_list: HWND,
+    hwnd_goto: HWND,
+    hwnd_send_other: HWND,
+    sources: Vec<CompareSource>,
+    hunks: Vec<Hunk>,
+}
+
+pub unsafe fn handle_navigation(hwnd: HWND, msg: &MSG) -> bool {
+    if msg.message == WM_KEYDOWN && msg.wParam.0 as u32 == VK_RETURN.0 as u32 {
+        let focus = GetFocus();
+        let (list, btn) =
+            with_compare_state(hwnd, |s| (s.hwnd_list, s.hwnd_goto)).unwrap_or((HWND(0), HWND(0)));
+        if focus == list || focus == btn {
+            goto_selected(hwnd);
+            return true;
+        }
+    }
+    handle_accessibility(hwnd, msg)
+}
+
+pub unsafe fn open(parent: HWND) {
+    let existing = with_state(parent, |state| state.compare_window).unwrap_or(HWND(0));
+    if existing.0 != 0 {
+        SetForegroundWindow(existing);
+        return;
+    }
+
+    let Some((language, hwnd_edit, sources, labels)) = with_state(parent, |state| {
+        let language = state.settings.language;
+        let current = state.docs.get(state.current)?;
+        let mut sources = Vec::new();
+        let mut labels = Vec::new();
+        if let Some(path) = &current.path
+            && current.archive_source.is_none()
+            && current.large_file.is_none()
+            && !matches!(current.format, FileFormat::Audiobook)
+            && !crate::encryption::is_encrypted_file(path)
+        {
+            sources.push(CompareSource::Saved(path.clone()));
+            labels.push(i18n::tr(language, "compare.saved_version"));
+        }
+        for (index, doc) in state.docs.iter().enumerate() {
+            if index == state.current
+                || doc.large_file.is_some()
+                || matches!(doc.format, FileFormat::Audiobook)
+            {
+                continue;
+            }
+            sources.push(CompareSource::Tab(doc.hwnd_edit));
+            labels.push(i18n::tr_f(
+                language,
+                "compare.tab",
+                &[("number", &(index + 1).to_string()), ("title", &doc.title)],
+            ));
+        }
+        Some((language, current.hwnd_edit, sources, labels))
+    })
+    .flatten() else {
+        return;
+    };
+    if sources.is_empty() {
+        crate::show_info(parent, language, &i18n::tr(language, "compare.no_source"));
+        return;
+    }
+
+    let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+    let class_name = to_wide(COMPARE_CLASS_NAME);
+    let wc = WNDCLASSW {
+        hCursor: windows::Win32::UI::WindowsAndMessaging::HCURSOR(
+            LoadCursorW(None, IDC_ARROW).unwrap_or_default().0,
+        ),
+        hInstance: hinstance,
+        lpszClassName: PCWSTR(class_name.as_ptr()),
+        lpfnWndProc: Some(compare_wndproc),
+        hbrBackground: HBRUSH((COLOR_WINDOW.0 + 1) as isize),
+        ..Default::default()
+    };
+    RegisterClassW(&wc);
+
+    let title = to_wide(&i18n::tr(language, "compare.title"));
+    let state = Box::new(CompareWindowState {
+        parent,
+        hwnd_edit,
+        hwnd_source: HWND(0),
+        hwnd_list: HWND(0),
+        hwnd_goto: HWND(0),
+        hwnd_send_other: HWND(0),
+        sources,
+        hunks: Vec::new(),
+    });
+    let state_ptr = Box::into_raw(state);
+    let window = CreateWindowExW(
+        WS_EX_CONTROLPARENT | WS_EX_DLGMODALFRAME,
+        PCWSTR(class_name.as_ptr()),
+        PCWSTR(title.as_ptr()),
+        WS_CAPTION | WS_SYSMENU | WS_VISIBLE,
+        CW_USEDEFAULT,
+        CW_USEDEFAULT,
+        560,
+        470,
+        parent,
+        None,
+        hinstance,
+        Some(state_ptr as *const _),
+    );
+    if window.0 == 0 {
+        drop(Box::from_raw(state_ptr));
+        return;
+    }
+
+    let hwnd_source = with_compare_state(window, |s| s.hwnd_source).unwrap_or(HWND(0));
+    for label in &labels {
+        let wide = to_wide(label);
+        SendMessageW(
+            hwnd_source,
+            CB_ADDSTRING,
+            WPARAM(0),
+            LPARAM(wide.as_ptr() as isize),
+        );
+    }
+    SendMessageW(hwnd_source, CB_SETCURSEL, WPARAM(0), LPARAM(0));
+    with_state(parent, |state| state.compare_window = window);
+    EnableWindow(parent, false);
+    SetForegroundWindow(window);
+    refresh(window, 0, false);
+}
+
+unsafe extern "system" fn compare_wndproc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_CREATE => {
+            let create_struct = lparam.0 as *const CREATESTRUCTW;
+            let state_ptr = (*create_struct).lpCreateParams as *mut CompareWindowState;
+            if state_ptr.is_null() {
+                return LRESULT(0);
+            }
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, state_ptr as isize);
+            let parent = (*state_ptr).parent;
+            let hfont = with_state(parent, |state| state.hfont).unwrap_or(HFONT(0));
+            let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+            let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+
+            let label_source = create_static(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "compare.source"),
+                10,
+                0,
+            );
+            let hwnd_source = CreateWindowExW(
+                WS_EX_CLIENTEDGE,
+                WC_COMBOBOXW,
+                PCWSTR::null(),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(CBS_DROPDOWNLIST as u32),
+                10,
+                30,
+                520,
+                200,
+                hwnd,
+                HMENU(COMPARE_ID_SOURCE as isize),
+                hinstance,
+                None,
+            );
+            let label_list = create_static(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "compare.differences"),
+                66,
+                0,
+            );
+            let hwnd_list = CreateWindowExW(
+                WS_EX_CLIENTEDGE,
+                WC_LISTBOXW,
+                PCWSTR::null(),
+                WS_CHILD
+                    | WS_VISIBLE
+                    | WS_VSCROLL
+                    | WS_TABSTOP
+                    | WINDOW_STYLE((LBS_NOTIFY | LBS_HASSTRINGS) as u32),
+                10,
+                86,
+                520,
+                240,
+                hwnd,
+                HMENU(COMPARE_ID_LIST as isize),
+                hinstance,
+                None,
+            );
+            let status = create_static(hwnd, hinstance, "", 334, COMPARE_ID_STATUS);
+            let hwnd_goto = create_button(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "compare.goto"),
+                10,
+                COMPARE_ID_GOTO,
+                true,
+            );
+            let hwnd_take_other = create_button(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "compare.take_other"),
+                140,
+                COMPARE_ID_TAKE_OTHER,
+                false,
+            );
+            let hwnd_send_other = create_button(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "compare.send_other"),
+                270,
+                COMPARE_ID_SEND_OTHER,
+                false,
+            );
+            let hwnd_close = create_button(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "compare.close"),
+                400,
+                COMPARE_ID_CLOSE,
+                false,
+            );
+
+            for ctrl in [
+                label_source,
+                hwnd_source,
+                label_list,
+                hwnd_list,
+                status,
+                hwnd_goto,
+                hwnd_take_other,
+                hwnd_send_other,
+                hwnd_close,
+            ] {
+                if ctrl.0 != 0 && hfont.0 != 0 {
+                    SendMessageW(ctrl, WM_SETFONT, WPARAM(hfont.0 as usize), LPARAM(1));
+                }
+            }
+
+            (*state_ptr).hwnd_source = hwnd_source;
+            (*state_ptr).hwnd_list = hwnd_list;
+            (*state_ptr).hwnd_goto = hwnd_goto;
+            (*state_ptr).hwnd_send_other = hwnd_send_other;
+            SetFocus(hwnd_list);
+            LRESULT(0)
+        }
+        WM_KEYDOWN => {
+            if wparam.0 as u32 == VK_ESCAPE.0 as u32 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_COMMAND => {
+            let cmd_id = wparam.0 & 0xffff;
+            let notify = (wparam.0 >> 16) as u16;
+            match cmd_id {
+                COMPARE_ID_SOURCE if notify == CBN_SELCHANGE as u16 => {
+                    refresh(hwnd, 0, false);
+                    LRESULT(0)
+                }
+                COMPARE_ID_GOTO => {
+                    goto_selected(hwnd);
+                    LRESULT(0)
+                }
+                COMPARE_ID_LIST if notify == LBN_DBLCLK as u16 => {
+                    goto_selected(hwnd);
+                    LRESULT(0)
+                }
+                COMPARE_ID_TAKE_OTHER => {
+                    merge_selected(hwnd, false);
+                    LRESULT(0)
+                }
+                COMPARE_ID_SEND_OTHER => {
+                    merge_selected(hwnd, true);
+                    LRESULT(0)
+                }
+                cmd if cmd == COMPARE_ID_CLOSE || cmd == 2 => {
+                    crate::log_if_err!(DestroyWindow(hwnd));
+                    LRESULT(0)
+                }
+                _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+            }
+        }
+        WM_CLOSE => {
+            crate::log_if_err!(DestroyWindow(hwnd));
+            LRESULT(0)
+        }
+        WM_DESTROY => {
+            let parent = with_compare_state(hwnd, |s| s.parent).unwrap_or(HWND(0));
+            if parent.0 != 0 {
+                EnableWindow(parent, true);
+                SetForegroundWindow(parent);
+                with_state(parent, |state| state.compare_window = HWND(0));
+                if let Some(hwnd_edit) = crate::get_active_edit(parent) {
+                    SetFocus(hwnd_edit);
+                }
+            }
+            LRESULT(0)
+        }
+        WM_NCDESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut CompareWindowState;
+            if !ptr.is_null() {
+                drop(Box::from_raw(ptr));
+            }
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+unsafe fn create_static(hwnd: HWND, hinstance: HINSTANCE, text: &str, y: i32, id: usize) -> HWND {
+    CreateWindowExW(
+        Default::default(),
+        w!("STATIC"),
+        PCWSTR(to_wide(text).as_ptr()),
+        WS_CHILD | WS_VISIBLE,
+        10,
+        y,
+        520,
+        18,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    )
+}
+
+unsafe fn create_button(
+    hwnd: HWND,
+    hinstance: HINSTANCE,
+    text: &str,
+    x: i32,
+    id: usize,
+    default: bool,
+) -> HWND {
+    let mut style = WS_CHILD | WS_VISIBLE | WS_TABSTOP;
+    if default {
+        style |= WINDOW_STYLE(BS_DEFPUSHBUTTON as u32);
+    }
+    CreateWindowExW(
+        Default::default(),
+        WC_BUTTON,
+        PCWSTR(to_wide(text).as_ptr()),
+        style,
+        x,
+        370,
+        120,
+        30,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    )
+}
+
+unsafe fn with_compare_state<F, R>(hwnd: HWND, f: F) -> Option<R>
+where
+    F: FnOnce(&mut CompareWindowState) -> R,
+{
+    let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut CompareWindowState;
+    if ptr.is_null() {
+        None
+    } else {
+        Some(f(&mut *ptr))
+    }
+}
+
+/// Text of the selected comparison source, read again every time so merges and
+/// edits made meanwhile are taken into account.
+unsafe fn other_text(hwnd: HWND, language: Language) -> Option<String> {
+    let (hwnd_source, edit_or_path) = with_compare_state(hwnd, |s| {
+        let selected = SendMessageW(s.hwnd_source, CB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+        let source = usize::try_from(selected)
+            .ok()
+            .and_then(|idx| s.sources.get(idx))
+            .map(|source| match source {
+                CompareSource::Tab(hwnd_edit) => Ok(*hwnd_edit),
+                CompareSource::Saved(path) => Err(path.clone()),
+            });
+        (s.hwnd_source, source)
+    })?;
+    match edit_or_path? {
+        Ok(hwnd_edit) => Some(get_edit_text(hwnd_edit)),
+        Err(path) => {
+            let text =
+                crate::app_windows::find_in_files_window::read_text_for_search(&path, language);
+            if text.is_none() {
+                let msg = i18n::tr(language, "compare.read_error");
+                show_status(hwnd, &msg);
+                SetFocus(hwnd_source);
+            }
+            text
+        }
+    }
+}
+
+/// Compares the documents again and fills the list, selecting the difference at
+/// `select`, then announces how many differences there are.
+unsafe fn refresh(hwnd: HWND, select: usize, after_merge: bool) {
+    let Some((parent, hwnd_edit, hwnd_list, hwnd_send_other, is_tab)) =
+        with_compare_state(hwnd, |s| {
+            let selected = SendMessageW(s.hwnd_source, CB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+            let is_tab = usize::try_from(selected)
+                .ok()
+                .and_then(|idx| s.sources.get(idx))
+                .is_some_and(|source| matches!(source, CompareSource::Tab(_)));
+            (
+                s.parent,
+                s.hwnd_edit,
+                s.hwnd_list,
+                s.hwnd_send_other,
+                is_tab,
+            )
+        })
+    else {
+        return;
+    };
+    let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+    // The saved file can only be updated by saving the document.
+    EnableWindow(hwnd_send_other, is_tab);
+    SendMessageW(hwnd_list, LB_RESETCONTENT, WPARAM(0), LPARAM(0));
+    with_compare_state(hwnd, |s| s.hunks.clear());
+
+    let Some(other) = other_text(hwnd, language) else {
+        return;
+    };
+    let current = get_edit_text(hwnd_edit);
+    let old_lines = split_lines(&other);
+    let new_lines = split_lines(&current);
+    let hunks = diff_lines(&old_lines, &new_lines);
+    for hunk in &hunks {
+        let text = describe_hunk(language, hunk, &old_lines, &new_lines);
+        let wide = to_wide(&text);
+        SendMessageW(
+            hwnd_list,
+            LB_ADDSTRING,
+            WPARAM(0),
+            LPARAM(wide.as_ptr() as isize),
+        );
+    }
+    if !hunks.is_empty() {
+        let index = select.min(hunks.len() - 1);
+        SendMessageW(hwnd_list, LB_SETCURSEL, WPARAM(index), LPARAM(0));
+    }
+
+    let count = hunks.len().to_string();
+    let message = if after_merge {
+        i18n::tr_f(language, "compare.merged", &[("count", &count)])
+    } else if hunks.is_empty() {
+        i18n::tr(language, "compare.identical")
+    } else {
+        i18n::tr_f(language, "compare.summary", &[("count", &count)])
+    };
+    with_compare_state(hwnd, |s| s.hunks = hunks);
+    let status = GetDlgItem(hwnd, COMPARE_ID_STATUS as i32);
+    let wide = to_wide(&message);
+    crate::log_if_err!(SetWindowTextW(status, PCWSTR(wide.as_ptr())));
+    nvda_speak(&message);
+}
+
+unsafe fn show_status(hwnd: HWND, message: &str) {
+    let status = GetDlgItem(hwnd, COMPARE_ID_STATUS as i32);
+    let wide = to_wide(message);
+    crate::log_if_err!(SetWindowTextW(status, PCWSTR(wide.as_ptr())));
+    nvda_speak(message);
+}
+
+unsafe fn selected_index(hwnd: HWND) -> Option<usize> {
+    let hwnd_list = with_compare_state(hwnd, |s| s.hwnd_list)?;
+    let selected = SendMessageW(hwnd_list, LB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+    usize::try_from(selected).ok()
+}
+
+/// Selects the lines of the chosen difference in the current document and closes.
+unsafe fn goto_selected(hwnd: HWND) {
+    let Some(index) = selected_index(hwnd) else {
+        return;
+    };
+    let Some((hwnd_edit, hunk)) = with_compare_state(hwnd, |s| {
+        s.hunks.get(index).map(|h| (s.hwnd_edit, h.clone()))
+    })
+    .flatten() else {
+        return;
+    };
+    select_edit_lines(hwnd_edit, hunk.new);
+    crate::log_if_err!(DestroyWindow(hwnd));
+}
+
+/// Copies the chosen difference from the other version into the current document,
+/// or from the current document into the other tab when `to_other` is set.
+unsafe fn merge_selected(hwnd: HWND, to_other: bool) {
+    let Some(index) = selected_index(hwnd) else {
+        return;
+    };
+    let Some((parent, hwnd_edit, hwnd_list)) =
+        with_compare_state(hwnd, |s| (s.parent, s.hwnd_edit, s.hwnd_list))
+    else {
+        return;
+    };
+    let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+    let Some(other) = other_text(hwnd, language) else {
+        return;
+    };
+    // Diff again rather than trusting the list, in case the saved file changed meanwhile.
+    let current = get_edit_text(hwnd_edit);
+    let old_lines = split_lines(&other);
+    let new_lines = split_lines(&current);
+    let Some(hunk) = diff_lines(&old_lines, &new_lines).into_iter().nth(index) else {
+        return;
+    };
+    if to_other {
+        let Some(hwnd_other) = with_compare_state(hwnd, |s| {
+            let selected = SendMessageW(s.hwnd_source, CB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+            match usize::try_from(selected)
+                .ok()
+                .and_then(|idx| s.sources.get(idx))
+            {
+                Some(CompareSource::Tab(hwnd_other)) => Some(*hwnd_other),
+                _ => None,
+            }
+        })
+        .flatten() else {
+            return;
+        };
+        replace_edit_lines(parent, hwnd_other, hunk.old, &new_lines[hunk.new]);
+    } else {
+        replace_edit_lines(parent, hwnd_edit, hunk.new, &old_lines[hunk.old]);
+    }
+    refresh(hwnd, index, true);
+    SetFocus(hwnd_list);
+}
+
+/// One list entry: what changed, where in the current document, and an excerpt.
+fn describe_hunk(
+    language: Language,
+    hunk: &Hunk,
+    old_lines: &[&str],
+    new_lines: &[&str],
+) -> String {
+    let line = (hunk.new.start + 1).to_string();
+    let last = hunk.new.end.to_string();
+    match hunk.kind {
+        HunkKind::Added => {
+            let text = excerpt(&new_lines[hunk.new.clone()].join(" "));
+            if hunk.new.len() == 1 {
+                i18n::tr_f(
+                    language,
+                    "compare.added_line",
+                    &[("line", &line), ("text", &text)],
+                )
+            } else {
+                i18n::tr_f(
+                    language,
+                    "compare.added_lines",
+                    &[("from", &line), ("to", &last), ("text", &text)],
+                )
+            }
+        }
+        HunkKind::Removed => {
+            let text = excerpt(&old_lines[hunk.old.clone()].join(" "));
+            let count = hunk.old.len().to_string();
+            if hunk.new.start >= new_lines.len() {
+                i18n::tr_f(
+                    language,
+                    "compare.removed_end",
+                    &[("count", &count), ("text", &text)],
+                )
+            } else {
+                i18n::tr_f(
+                    language,
+                    "compare.removed",
+                    &[("count", &count), ("line", &line), ("text", &text)],
+                )
+            }
+        }
+        HunkKind::Changed => {
+            let changes = diff_words(
+                &old_lines[hunk.old.clone()].join("\n"),
+                &new_lines[hunk.new.clone()].join("\n"),
+            );
+            let details = describe_word_changes(language, &changes);
+            if hunk.new.len() == 1 {
+                i18n::tr_f(
+                    language,
+                    "compare.changed_line",
+                    &[("line", &line), ("details", &details)],
+                )
+            } else {
+                i18n::tr_f(
+                    language,
+                    "compare.changed_lines",
+                    &[("from", &line), ("to", &last), ("details", &details)],
+                )
+            }
+        }
+    }
+}
+
+fn describe_word_changes(language: Language, changes: &[WordChange]) -> String {
+    if changes.is_empty() {
+        return i18n::tr(language, "compare.spacing_only");
+    }
+    let mut parts: Vec<String> = changes
+        .iter()
+        .take(MAX_WORD_CHANGES)
+        .map(|change| {
+            let old = excerpt(&change.removed);
+            let new = excerpt(&change.added);
+            if change.removed.is_empty() {
+                i18n::tr_f(language, "compare.word_added", &[("new", &new)])
+            } else if change.added.is_empty() {
+                i18n::tr_f(language, "compare.word_removed", &[("old", &old)])
+            } else {
+                i18n::tr_f(
+                    language,
+                    "compare.word_replaced",
+                    &[("old", &old), ("new", &new)],
+                )
+            }
+        })
+        .collect();
+    if changes.len() > MAX_WORD_CHANGES {
+        let more = (changes.len() - MAX_WORD_CHANGES).to_string();
+        parts.push(i18n::tr_f(language, "compare.more", &[("count", &more)]));
+    }
+    parts.join("; ")
+}
+
+fn excerpt(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= EXCERPT_CHARS {
+        return text;
+    }
+    let mut out: String = text.chars().take(EXCERPT_CHARS).collect();
+    out.push('…');
+    out
+}
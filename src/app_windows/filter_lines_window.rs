@@ -0,0 +1,432 @@
+use crate::accessibility::{handle_accessibility, nvda_speak, to_wide};
+use crate::i18n;
+use crate::settings::{FilterAction, save_settings};
+use crate::with_state;
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{COLOR_WINDOW, HBRUSH};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Controls::{BST_CHECKED, WC_BUTTON, WC_COMBOBOXW};
+use windows::Win32::UI::Input::KeyboardAndMouse::{EnableWindow, SetFocus};
+use windows::Win32::UI::WindowsAndMessaging::{
+    BM_GETCHECK, BM_SETCHECK, BS_AUTOCHECKBOX, BS_DEFPUSHBUTTON, CB_ADDSTRING, CB_GETCURSEL,
+    CB_SETCURSEL, CBS_DROPDOWNLIST, CREATESTRUCTW, CW_USEDEFAULT, CreateWindowExW, DefWindowProcW,
+    DestroyWindow, GWLP_USERDATA, GetDlgItem, GetWindowLongPtrW, HMENU, IDC_ARROW, LoadCursorW,
+    RegisterClassW, SendMessageW, SetForegroundWindow, SetWindowLongPtrW, SetWindowTextW,
+    WINDOW_STYLE, WM_COMMAND, WM_CREATE, WM_DESTROY, WM_GETTEXT, WM_GETTEXTLENGTH, WM_KEYDOWN,
+    WM_NCDESTROY, WNDCLASSW, WS_CAPTION, WS_CHILD, WS_EX_CLIENTEDGE, WS_EX_DLGMODALFRAME, WS_POPUP,
+    WS_TABSTOP, WS_VISIBLE,
+};
+use windows::core::{PCWSTR, w};
+
+const FILTER_LINES_CLASS: &str = "NovapadFilterLines";
+const FILTER_LINES_PATTERN_ID: usize = 1851;
+const FILTER_LINES_ACTION_ID: usize = 1852;
+const FILTER_LINES_REGEX_ID: usize = 1853;
+const FILTER_LINES_CASE_ID: usize = 1854;
+const FILTER_LINES_WHOLE_WORD_ID: usize = 1855;
+const FILTER_LINES_OK_ID: usize = 1856;
+const FILTER_LINES_CANCEL_ID: usize = 1857;
+const FILTER_LINES_STATUS_ID: usize = 1858;
+
+const FILTER_ACTIONS: [(FilterAction, &str); 3] = [
+    (FilterAction::Keep, "filter_lines.action_keep"),
+    (FilterAction::Remove, "filter_lines.action_remove"),
+    (FilterAction::CopyToNewTab, "filter_lines.action_copy"),
+];
+
+struct FilterLinesState {
+    parent: HWND,
+}
+
+pub unsafe fn open(parent: HWND) {
+    let existing = with_state(parent, |state| state.filter_lines_dialog).unwrap_or(HWND(0));
+    if existing.0 != 0 {
+        SetForegroundWindow(existing);
+        return;
+    }
+
+    let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+    let class_name = to_wide(FILTER_LINES_CLASS);
+    let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+    let title_w = to_wide(&i18n::tr(language, "filter_lines.title"));
+
+    let wc = WNDCLASSW {
+        hCursor: windows::Win32::UI::WindowsAndMessaging::HCURSOR(
+            LoadCursorW(None, IDC_ARROW).unwrap_or_default().0,
+        ),
+        hInstance: hinstance,
+        lpszClassName: PCWSTR(class_name.as_ptr()),
+        lpfnWndProc: Some(filter_lines_wndproc),
+        hbrBackground: HBRUSH((COLOR_WINDOW.0 + 1) as isize),
+        ..Default::default()
+    };
+    RegisterClassW(&wc);
+
+    let state = Box::new(FilterLinesState { parent });
+    let state_ptr = Box::into_raw(state);
+    let hwnd = CreateWindowExW(
+        WS_EX_DLGMODALFRAME,
+        PCWSTR(class_name.as_ptr()),
+        PCWSTR(title_w.as_ptr()),
+        WS_POPUP | WS_CAPTION | WS_VISIBLE,
+        CW_USEDEFAULT,
+        CW_USEDEFAULT,
+        420,
+        320,
+        parent,
+        HMENU(0),
+        hinstance,
+        Some(state_ptr as *const _),
+    );
+    if hwnd.0 == 0 {
+        drop(Box::from_raw(state_ptr));
+        return;
+    }
+    EnableWindow(parent, false);
+    with_state(parent, |state| state.filter_lines_dialog = hwnd);
+}
+
+unsafe fn create_label(hwnd: HWND, hinstance: HINSTANCE, text: &str, y: i32, id: isize) {
+    CreateWindowExW(
+        Default::default(),
+        w!("STATIC"),
+        PCWSTR(to_wide(text).as_ptr()),
+        WS_CHILD | WS_VISIBLE,
+        10,
+        y,
+        390,
+        16,
+        hwnd,
+        HMENU(id),
+        hinstance,
+        None,
+    );
+}
+
+unsafe fn create_checkbox(
+    hwnd: HWND,
+    hinstance: HINSTANCE,
+    text: &str,
+    y: i32,
+    id: usize,
+    checked: bool,
+) {
+    let checkbox = CreateWindowExW(
+        Default::default(),
+        WC_BUTTON,
+        PCWSTR(to_wide(text).as_ptr()),
+        WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_AUTOCHECKBOX as u32),
+        10,
+        y,
+        390,
+        22,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    );
+    if checked {
+        SendMessageW(
+            checkbox,
+            BM_SETCHECK,
+            WPARAM(BST_CHECKED.0 as usize),
+            LPARAM(0),
+        );
+    }
+}
+
+unsafe fn is_checked(hwnd: HWND, id: usize) -> bool {
+    let checkbox = GetDlgItem(hwnd, id as i32);
+    SendMessageW(checkbox, BM_GETCHECK, WPARAM(0), LPARAM(0)).0 == BST_CHECKED.0 as isize
+}
+
+unsafe fn read_input(hwnd: HWND, id: usize) -> String {
+    let input = GetDlgItem(hwnd, id as i32);
+    let len = SendMessageW(input, WM_GETTEXTLENGTH, WPARAM(0), LPARAM(0)).0;
+    let mut buf = vec![0u16; len as usize + 1];
+    SendMessageW(
+        input,
+        WM_GETTEXT,
+        WPARAM(buf.len()),
+        LPARAM(buf.as_mut_ptr() as isize),
+    );
+    String::from_utf16_lossy(&buf[..len as usize])
+}
+
+unsafe fn show_status(hwnd: HWND, message: &str, focus_id: usize) {
+    let status = GetDlgItem(hwnd, FILTER_LINES_STATUS_ID as i32);
+    let wide = to_wide(message);
+    crate::log_if_err!(SetWindowTextW(status, PCWSTR(wide.as_ptr())));
+    nvda_speak(message);
+    SetFocus(GetDlgItem(hwnd, focus_id as i32));
+}
+
+/// Last text searched with Find, offered as the initial pattern.
+unsafe fn last_find_text(parent: HWND) -> String {
+    with_state(parent, |state| {
+        let end = state
+            .find_text
+            .iter()
+            .position(|&ch| ch == 0)
+            .unwrap_or(state.find_text.len());
+        String::from_utf16_lossy(&state.find_text[..end])
+    })
+    .unwrap_or_default()
+}
+
+unsafe extern "system" fn filter_lines_wndproc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_CREATE => {
+            let cs = lparam.0 as *const CREATESTRUCTW;
+            let init_ptr = (*cs).lpCreateParams as *mut FilterLinesState;
+            if init_ptr.is_null() {
+                return LRESULT(0);
+            }
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, init_ptr as isize);
+            let parent = (*init_ptr).parent;
+            let Some(settings) = with_state(parent, |state| state.settings.clone()) else {
+                return LRESULT(0);
+            };
+            let language = settings.language;
+
+            let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+            create_label(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "filter_lines.pattern"),
+                12,
+                1,
+            );
+            let pattern = CreateWindowExW(
+                WS_EX_CLIENTEDGE,
+                w!("EDIT"),
+                PCWSTR(to_wide(&last_find_text(parent)).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP,
+                10,
+                30,
+                390,
+                24,
+                hwnd,
+                HMENU(FILTER_LINES_PATTERN_ID as isize),
+                hinstance,
+                None,
+            );
+            create_label(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "filter_lines.action"),
+                66,
+                2,
+            );
+            let combo = CreateWindowExW(
+                WS_EX_CLIENTEDGE,
+                WC_COMBOBOXW,
+                PCWSTR::null(),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(CBS_DROPDOWNLIST as u32),
+                10,
+                84,
+                390,
+                120,
+                hwnd,
+                HMENU(FILTER_LINES_ACTION_ID as isize),
+                hinstance,
+                None,
+            );
+            for (idx, (action, key)) in FILTER_ACTIONS.iter().enumerate() {
+                let label_w = to_wide(&i18n::tr(language, key));
+                SendMessageW(
+                    combo,
+                    CB_ADDSTRING,
+                    WPARAM(0),
+                    LPARAM(label_w.as_ptr() as isize),
+                );
+                if *action == settings.filter_action {
+                    SendMessageW(combo, CB_SETCURSEL, WPARAM(idx), LPARAM(0));
+                }
+            }
+            create_checkbox(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "filter_lines.regex"),
+                120,
+                FILTER_LINES_REGEX_ID,
+                settings.filter_use_regex,
+            );
+            create_checkbox(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "filter_lines.match_case"),
+                146,
+                FILTER_LINES_CASE_ID,
+                settings.filter_match_case,
+            );
+            create_checkbox(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "filter_lines.whole_word"),
+                172,
+                FILTER_LINES_WHOLE_WORD_ID,
+                settings.filter_whole_word,
+            );
+            create_label(hwnd, hinstance, "", 202, FILTER_LINES_STATUS_ID as isize);
+            CreateWindowExW(
+                Default::default(),
+                w!("BUTTON"),
+                PCWSTR(to_wide(&i18n::tr(language, "filter_lines.ok")).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_DEFPUSHBUTTON as u32),
+                230,
+                230,
+                80,
+                26,
+                hwnd,
+                HMENU(FILTER_LINES_OK_ID as isize),
+                hinstance,
+                None,
+            );
+            CreateWindowExW(
+                Default::default(),
+                w!("BUTTON"),
+                PCWSTR(to_wide(&i18n::tr(language, "filter_lines.cancel")).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP,
+                320,
+                230,
+                80,
+                26,
+                hwnd,
+                HMENU(FILTER_LINES_CANCEL_ID as isize),
+                hinstance,
+                None,
+            );
+
+            SetFocus(pattern);
+            LRESULT(0)
+        }
+        WM_KEYDOWN => {
+            if wparam.0 as u32 == windows::Win32::UI::Input::KeyboardAndMouse::VK_ESCAPE.0 as u32 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_COMMAND => {
+            let id = wparam.0 & 0xffff;
+            if id == FILTER_LINES_CANCEL_ID || id == 2 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            if id == FILTER_LINES_OK_ID || id == 1 {
+                let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const FilterLinesState;
+                if ptr.is_null() {
+                    return LRESULT(0);
+                }
+                let parent = (*ptr).parent;
+                let language =
+                    with_state(parent, |state| state.settings.language).unwrap_or_default();
+                let pattern = read_input(hwnd, FILTER_LINES_PATTERN_ID);
+                if pattern.is_empty() {
+                    let msg = i18n::tr(language, "filter_lines.empty_pattern");
+                    show_status(hwnd, &msg, FILTER_LINES_PATTERN_ID);
+                    return LRESULT(0);
+                }
+                let use_regex = is_checked(hwnd, FILTER_LINES_REGEX_ID);
+                let match_case = is_checked(hwnd, FILTER_LINES_CASE_ID);
+                let whole_word = is_checked(hwnd, FILTER_LINES_WHOLE_WORD_ID);
+                let regex = match crate::search::build_line_filter(
+                    &pattern, use_regex, match_case, whole_word,
+                ) {
+                    Ok(regex) => regex,
+                    Err(err) => {
+                        let msg = i18n::tr_f(language, "find.regex_error", &[("err", &err)]);
+                        show_status(hwnd, &msg, FILTER_LINES_PATTERN_ID);
+                        return LRESULT(0);
+                    }
+                };
+                let combo = GetDlgItem(hwnd, FILTER_LINES_ACTION_ID as i32);
+                let selected = SendMessageW(combo, CB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+                let action = usize::try_from(selected)
+                    .ok()
+                    .and_then(|idx| FILTER_ACTIONS.get(idx))
+                    .map(|(action, _)| *action)
+                    .unwrap_or_default();
+
+                let is_match = |line: &str| regex.is_match(line).unwrap_or(false);
+                let Some(count) = (match action {
+                    FilterAction::Keep => {
+                        crate::editor_manager::filter_lines_active_edit(parent, is_match, true)
+                    }
+                    FilterAction::Remove => {
+                        crate::editor_manager::filter_lines_active_edit(parent, is_match, false)
+                    }
+                    FilterAction::CopyToNewTab => {
+                        let title = i18n::tr(language, "filter_lines.new_tab_title");
+                        crate::editor_manager::copy_matching_lines_to_new_tab(
+                            parent, &title, is_match,
+                        )
+                    }
+                }) else {
+                    let msg = i18n::tr(language, "large_file.read_only");
+                    show_status(hwnd, &msg, FILTER_LINES_PATTERN_ID);
+                    return LRESULT(0);
+                };
+                if count == 0 {
+                    let msg = i18n::tr(language, "filter_lines.no_match");
+                    show_status(hwnd, &msg, FILTER_LINES_PATTERN_ID);
+                    return LRESULT(0);
+                }
+
+                let settings = with_state(parent, |state| {
+                    state.settings.filter_action = action;
+                    state.settings.filter_use_regex = use_regex;
+                    state.settings.filter_match_case = match_case;
+                    state.settings.filter_whole_word = whole_word;
+                    state.settings.clone()
+                });
+                if let Some(settings) = settings {
+                    save_settings(settings);
+                }
+                crate::log_if_err!(DestroyWindow(hwnd));
+                let key = match action {
+                    FilterAction::Keep => "filter_lines.kept",
+                    FilterAction::Remove => "filter_lines.removed",
+                    FilterAction::CopyToNewTab => "filter_lines.copied",
+                };
+                nvda_speak(&i18n::tr_f(language, key, &[("count", &count.to_string())]));
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_DESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const FilterLinesState;
+            if !ptr.is_null() {
+                let parent = (*ptr).parent;
+                EnableWindow(parent, true);
+                SetForegroundWindow(parent);
+                with_state(parent, |s| s.filter_lines_dialog = HWND(0));
+                if let Some(hwnd_edit) = crate::get_active_edit(parent) {
+                    SetFocus(hwnd_edit);
+                }
+            }
+            LRESULT(0)
+        }
+        WM_NCDESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut FilterLinesState;
+            if !ptr.is_null() {
+                drop(Box::from_raw(ptr));
+            }
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+pub unsafe fn handle_navigation(
+    hwnd: HWND,
+    msg: &windows::Win32::UI::WindowsAndMessaging::MSG,
+) -> bool {
+    handle_accessibility(hwnd, msg)
+}
@@ -0,0 +1,331 @@
+use crate::accessibility::{handle_accessibility, to_wide};
+use crate::i18n;
+use crate::with_state;
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{COLOR_WINDOW, HBRUSH, HFONT};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Controls::RichEdit::{CHARRANGE, EM_EXGETSEL};
+use windows::Win32::UI::Controls::{WC_BUTTON, WC_LISTBOXW};
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    EnableWindow, GetFocus, SetFocus, VK_ESCAPE, VK_RETURN,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    BS_DEFPUSHBUTTON, CREATESTRUCTW, CW_USEDEFAULT, CreateWindowExW, DefWindowProcW, DestroyWindow,
+    GWLP_USERDATA, GetWindowLongPtrW, HMENU, IDC_ARROW, LB_ADDSTRING, LB_GETCURSEL, LB_SETCURSEL,
+    LBN_DBLCLK, LBS_HASSTRINGS, LBS_NOTIFY, LoadCursorW, MSG, RegisterClassW, SendMessageW,
+    SetForegroundWindow, SetWindowLongPtrW, WINDOW_STYLE, WM_CLOSE, WM_COMMAND, WM_CREATE,
+    WM_DESTROY, WM_KEYDOWN, WM_NCDESTROY, WM_SETFONT, WNDCLASSW, WS_CAPTION, WS_CHILD,
+    WS_EX_CLIENTEDGE, WS_EX_CONTROLPARENT, WS_EX_DLGMODALFRAME, WS_SYSMENU, WS_TABSTOP, WS_VISIBLE,
+    WS_VSCROLL,
+};
+use windows::core::{PCWSTR, w};
+
+const FIND_ALL_CLASS_NAME: &str = "NovapadFindAll";
+const FIND_ALL_ID_LIST: usize = 1925;
+const FIND_ALL_ID_GO: usize = 1926;
+const FIND_ALL_ID_CLOSE: usize = 1927;
+
+struct FindAllWindowState {
+    parent: HWND,
+    hwnd_list: HWND,
+    hwnd_edit: HWND,
+    matches: Vec<FindAllItem>,
+}
+
+/// A match of Find all: its range in editor positions, its 1-based line and the text
+/// around it.
+pub struct FindAllItem {
+    pub start: i32,
+    pub end: i32,
+    pub line: usize,
+    pub context: String,
+}
+
+pub unsafe fn handle_navigation(hwnd: HWND, msg: &MSG) -> bool {
+    if msg.message == WM_KEYDOWN && msg.wParam.0 as u32 == VK_RETURN.0 as u32 {
+        let list = with_find_all_state(hwnd, |s| s.hwnd_list).unwrap_or(HWND(0));
+        if GetFocus() == list {
+            go_to_selected(hwnd);
+            return true;
+        }
+    }
+    handle_accessibility(hwnd, msg)
+}
+
+/// Lists `matches` of `hwnd_edit` with the first one after the caret selected, and
+/// announces how many there are.
+pub unsafe fn open(parent: HWND, hwnd_edit: HWND, matches: Vec<FindAllItem>) {
+    let existing = with_state(parent, |state| state.find_all_window).unwrap_or(HWND(0));
+    if existing.0 != 0 {
+        SetForegroundWindow(existing);
+        return;
+    }
+    let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+    let mut selection = CHARRANGE { cpMin: 0, cpMax: 0 };
+    SendMessageW(
+        hwnd_edit,
+        EM_EXGETSEL,
+        WPARAM(0),
+        LPARAM(&mut selection as *mut _ as isize),
+    );
+    let select = matches
+        .iter()
+        .position(|item| item.start >= selection.cpMin)
+        .unwrap_or(0);
+    let count = i18n::tr_f(
+        language,
+        "find_all.count",
+        &[("count", &matches.len().to_string())],
+    );
+
+    let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+    let class_name = to_wide(FIND_ALL_CLASS_NAME);
+    let wc = WNDCLASSW {
+        hCursor: windows::Win32::UI::WindowsAndMessaging::HCURSOR(
+            LoadCursorW(None, IDC_ARROW).unwrap_or_default().0,
+        ),
+        hInstance: hinstance,
+        lpszClassName: PCWSTR(class_name.as_ptr()),
+        lpfnWndProc: Some(find_all_wndproc),
+        hbrBackground: HBRUSH((COLOR_WINDOW.0 + 1) as isize),
+        ..Default::default()
+    };
+    RegisterClassW(&wc);
+
+    let title = to_wide(&i18n::tr(language, "find_all.title"));
+    let state = Box::new(FindAllWindowState {
+        parent,
+        hwnd_list: HWND(0),
+        hwnd_edit,
+        matches,
+    });
+    let state_ptr = Box::into_raw(state);
+    let window = CreateWindowExW(
+        WS_EX_CONTROLPARENT | WS_EX_DLGMODALFRAME,
+        PCWSTR(class_name.as_ptr()),
+        PCWSTR(title.as_ptr()),
+        WS_CAPTION | WS_SYSMENU | WS_VISIBLE,
+        CW_USEDEFAULT,
+        CW_USEDEFAULT,
+        560,
+        420,
+        parent,
+        None,
+        hinstance,
+        Some(state_ptr as *const _),
+    );
+    if window.0 == 0 {
+        drop(Box::from_raw(state_ptr));
+        return;
+    }
+
+    let Some(hwnd_list) = with_find_all_state(window, |s| s.hwnd_list) else {
+        return;
+    };
+    with_state(parent, |state| state.find_all_window = window);
+    EnableWindow(parent, false);
+    SetForegroundWindow(window);
+    SendMessageW(hwnd_list, LB_SETCURSEL, WPARAM(select), LPARAM(0));
+    SetFocus(hwnd_list);
+    crate::accessibility::nvda_speak(&count);
+}
+
+unsafe extern "system" fn find_all_wndproc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_CREATE => {
+            let create_struct = lparam.0 as *const CREATESTRUCTW;
+            let state_ptr = (*create_struct).lpCreateParams as *mut FindAllWindowState;
+            if state_ptr.is_null() {
+                return LRESULT(0);
+            }
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, state_ptr as isize);
+            let parent = (*state_ptr).parent;
+            let hfont = with_state(parent, |state| state.hfont).unwrap_or(HFONT(0));
+            let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+            let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+
+            let label = CreateWindowExW(
+                Default::default(),
+                w!("STATIC"),
+                PCWSTR(to_wide(&i18n::tr(language, "find_all.list")).as_ptr()),
+                WS_CHILD | WS_VISIBLE,
+                10,
+                10,
+                520,
+                18,
+                hwnd,
+                HMENU(0),
+                hinstance,
+                None,
+            );
+            let hwnd_list = CreateWindowExW(
+                WS_EX_CLIENTEDGE,
+                WC_LISTBOXW,
+                PCWSTR::null(),
+                WS_CHILD
+                    | WS_VISIBLE
+                    | WS_VSCROLL
+                    | WS_TABSTOP
+                    | WINDOW_STYLE((LBS_NOTIFY | LBS_HASSTRINGS) as u32),
+                10,
+                30,
+                520,
+                290,
+                hwnd,
+                HMENU(FIND_ALL_ID_LIST as isize),
+                hinstance,
+                None,
+            );
+            let go = create_button(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "find_all.go"),
+                280,
+                FIND_ALL_ID_GO,
+                true,
+            );
+            let close = create_button(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "find_all.close"),
+                410,
+                FIND_ALL_ID_CLOSE,
+                false,
+            );
+            for ctrl in [label, hwnd_list, go, close] {
+                if ctrl.0 != 0 && hfont.0 != 0 {
+                    SendMessageW(ctrl, WM_SETFONT, WPARAM(hfont.0 as usize), LPARAM(1));
+                }
+            }
+
+            for item in &(*state_ptr).matches {
+                let text = i18n::tr_f(
+                    language,
+                    "find_all.item",
+                    &[("line", &item.line.to_string()), ("context", &item.context)],
+                );
+                let wide = to_wide(&text);
+                SendMessageW(
+                    hwnd_list,
+                    LB_ADDSTRING,
+                    WPARAM(0),
+                    LPARAM(wide.as_ptr() as isize),
+                );
+            }
+            (*state_ptr).hwnd_list = hwnd_list;
+            LRESULT(0)
+        }
+        WM_KEYDOWN => {
+            if wparam.0 as u32 == VK_ESCAPE.0 as u32 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_COMMAND => {
+            let cmd_id = wparam.0 & 0xffff;
+            let notify = (wparam.0 >> 16) as u16;
+            match cmd_id {
+                FIND_ALL_ID_LIST if notify == LBN_DBLCLK as u16 => {
+                    go_to_selected(hwnd);
+                    LRESULT(0)
+                }
+                FIND_ALL_ID_GO => {
+                    go_to_selected(hwnd);
+                    LRESULT(0)
+                }
+                cmd if cmd == FIND_ALL_ID_CLOSE || cmd == 2 => {
+                    crate::log_if_err!(DestroyWindow(hwnd));
+                    LRESULT(0)
+                }
+                _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+            }
+        }
+        WM_CLOSE => {
+            crate::log_if_err!(DestroyWindow(hwnd));
+            LRESULT(0)
+        }
+        WM_DESTROY => {
+            let parent = with_find_all_state(hwnd, |s| s.parent).unwrap_or(HWND(0));
+            if parent.0 != 0 {
+                EnableWindow(parent, true);
+                SetForegroundWindow(parent);
+                with_state(parent, |state| state.find_all_window = HWND(0));
+                if let Some(hwnd_edit) = crate::get_active_edit(parent) {
+                    SetFocus(hwnd_edit);
+                }
+            }
+            LRESULT(0)
+        }
+        WM_NCDESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut FindAllWindowState;
+            if !ptr.is_null() {
+                drop(Box::from_raw(ptr));
+            }
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+unsafe fn create_button(
+    hwnd: HWND,
+    hinstance: HINSTANCE,
+    text: &str,
+    x: i32,
+    id: usize,
+    default: bool,
+) -> HWND {
+    let mut style = WS_CHILD | WS_VISIBLE | WS_TABSTOP;
+    if default {
+        style |= WINDOW_STYLE(BS_DEFPUSHBUTTON as u32);
+    }
+    CreateWindowExW(
+        Default::default(),
+        WC_BUTTON,
+        PCWSTR(to_wide(text).as_ptr()),
+        style,
+        x,
+        330,
+        120,
+        30,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    )
+}
+
+unsafe fn with_find_all_state<F, R>(hwnd: HWND, f: F) -> Option<R>
+where
+    F: FnOnce(&mut FindAllWindowState) -> R,
+{
+    let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut FindAllWindowState;
+    if ptr.is_null() {
+        None
+    } else {
+        Some(f(&mut *ptr))
+    }
+}
+
+/// Closes the list and selects the chosen match in the editor.
+unsafe fn go_to_selected(hwnd: HWND) {
+    let Some((hwnd_edit, start, end)) = with_find_all_state(hwnd, |s| {
+        let selected = SendMessageW(s.hwnd_list, LB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+        let item = usize::try_from(selected)
+            .ok()
+            .and_then(|index| s.matches.get(index))?;
+        Some((s.hwnd_edit, item.start, item.end))
+    })
+    .flatten() else {
+        return;
+    };
+    crate::log_if_err!(DestroyWindow(hwnd));
+    crate::editor_manager::select_edit_range(hwnd_edit, start, end);
+    SetFocus(hwnd_edit);
+}
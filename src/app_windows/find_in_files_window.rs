@@ -35,10 +35,11 @@ use windows::Win32::UI::WindowsAndMessaging::{
 use windows::core::{PCWSTR, PWSTR, w};
 
 use crate::accessibility::{EM_SCROLLCARET, normalize_to_crlf, to_wide};
+use crate::archive::{ArchiveSource, extract_searchable_entries, is_archive_path};
 use crate::file_handler::{
-    decode_text, is_doc_path, is_docx_path, is_epub_path, is_html_path, is_mp3_path, is_pdf_path,
-    is_ppt_path, is_pptx_path, is_spreadsheet_path, read_doc_text, read_docx_text, read_epub_text,
-    read_html_text, read_ppt_text, read_spreadsheet_text,
+    decode_text, is_doc_path, is_docx_path, is_epub_path, is_html_path, is_mobi_path, is_mp3_path,
+    is_pdf_path, is_ppt_path, is_pptx_path, is_spreadsheet_path, read_doc_text, read_docx_text,
+    read_epub_text, read_html_text, read_mobi_text, read_ppt_text, read_spreadsheet_text,
 };
 use crate::i18n;
 use crate::settings::Language;
@@ -103,6 +104,7 @@ pub(crate) struct SearchResult {
     len_utf16: i32,
     line: usize,
     snippet: String,
+    archive: Option<ArchiveSource>,
 }
 
 #[derive(Clone)]
@@ -828,7 +830,25 @@ fn start_search(state: &mut FindInFilesState) {
             if cancel.load(Ordering::Relaxed) {
                 return;
             }
-            if let Some(text) = read_text_for_search(path, language) {
+            let targets = if is_archive_path(path) {
+                extract_searchable_entries(path, language)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(entry, extracted)| {
+                        let source = ArchiveSource {
+                            archive: path.clone(),
+                            entry,
+                        };
+                        (extracted, Some(source))
+                    })
+                    .collect()
+            } else {
+                vec![(path.clone(), None)]
+            };
+            for (target, source) in &targets {
+                let Some(text) = read_text_for_search(target, language) else {
+                    continue;
+                };
                 if term_has_newline {
                     let normalized = normalize_to_crlf(&text);
                     let term_len_utf16 = term_norm.encode_utf16().count() as i32;
@@ -837,12 +857,21 @@ fn start_search(state: &mut FindInFilesState) {
                         &term_norm,
                         term_len_utf16,
                         true,
-                        path,
+                        target,
+                        source.as_ref(),
                         &mut results,
                     );
                 } else {
                     let term_len_utf16 = term.encode_utf16().count() as i32;
-                    collect_matches(&text, &term, term_len_utf16, false, path, &mut results);
+                    collect_matches(
+                        &text,
+                        &term,
+                        term_len_utf16,
+                        false,
+                        target,
+                        source.as_ref(),
+                        &mut results,
+                    );
                 }
             }
             let percent = ((idx + 1) * 100 / total) as u32;
@@ -887,7 +916,16 @@ fn open_selected_result(state: &mut FindInFilesState) {
     };
     let term = read_control_text(state.term_edit).trim().to_string();
     unsafe {
-        crate::editor_manager::open_document(state.parent, &result.path);
+        match &result.archive {
+            Some(source) => {
+                crate::editor_manager::open_archive_entry(
+                    state.parent,
+                    &source.archive,
+                    &source.entry,
+                );
+            }
+            None => crate::editor_manager::open_document(state.parent, &result.path),
+        }
         if let Some(hwnd_edit) = crate::get_active_edit(state.parent)
             && !select_snippet_exact(hwnd_edit, &result.snippet)
         {
@@ -964,7 +1002,13 @@ fn populate_results_tree(state: &mut FindInFilesState) {
 
     let mut first_parent: Option<windows::Win32::UI::Controls::HTREEITEM> = None;
     for (group_idx, group) in state.results_groups.iter().enumerate() {
-        let parent_text = format!("{} ({})", group.path.display(), group.indices.len());
+        let name = group
+            .indices
+            .first()
+            .and_then(|idx| state.results[*idx].archive.as_ref())
+            .map(|source| source.display_name())
+            .unwrap_or_else(|| group.path.display().to_string());
+        let parent_text = format!("{name} ({})", group.indices.len());
         let parent_param = -1 - (group_idx as isize);
         let parent_item = insert_tree_item(
             state.results_tree,
@@ -1173,7 +1217,7 @@ fn collect_search_files(folder: &Path) -> Vec<PathBuf> {
     files
 }
 
-fn read_text_for_search(path: &Path, language: Language) -> Option<String> {
+pub(crate) fn read_text_for_search(path: &Path, language: Language) -> Option<String> {
     if is_docx_path(path) {
         return read_docx_text(path, language).ok();
     }
@@ -1183,6 +1227,9 @@ fn read_text_for_search(path: &Path, language: Language) -> Option<String> {
     if is_epub_path(path) {
         return read_epub_text(path, language).ok();
     }
+    if is_mobi_path(path) {
+        return read_mobi_text(path, language).ok();
+    }
     if is_html_path(path) {
         return read_html_text(path, language).ok().map(|(text, _)| text);
     }
@@ -1203,6 +1250,7 @@ fn collect_matches(
     term_len_utf16: i32,
     normalized_offsets: bool,
     path: &Path,
+    archive: Option<&ArchiveSource>,
     out: &mut Vec<SearchResult>,
 ) {
     if term.is_empty() {
@@ -1228,6 +1276,7 @@ fn collect_matches(
             len_utf16: term_len_utf16,
             line,
             snippet,
+            archive: archive.cloned(),
         });
 
         start = byte_index + term.len();
@@ -1311,7 +1360,12 @@ fn word_bounds(chars: &[char], match_start: usize, match_end: usize) -> (usize,
     (start, end)
 }
 
-fn snippet_for_match(line: &str, match_start: usize, match_len: usize, max_chars: usize) -> String {
+pub(crate) fn snippet_for_match(
+    line: &str,
+    match_start: usize,
+    match_len: usize,
+    max_chars: usize,
+) -> String {
     if max_chars == 0 || line.is_empty() {
         return String::new();
     }
@@ -0,0 +1,265 @@
+use crate::accessibility::{handle_accessibility, nvda_speak, to_wide};
+use crate::i18n;
+use crate::with_state;
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{COLOR_WINDOW, HBRUSH};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Input::KeyboardAndMouse::{EnableWindow, SetFocus};
+use windows::Win32::UI::WindowsAndMessaging::{
+    BS_DEFPUSHBUTTON, CREATESTRUCTW, CW_USEDEFAULT, CreateWindowExW, DefWindowProcW, DestroyWindow,
+    GWLP_USERDATA, GetDlgItem, GetWindowLongPtrW, HMENU, IDC_ARROW, LoadCursorW, RegisterClassW,
+    SendMessageW, SetForegroundWindow, SetWindowLongPtrW, SetWindowTextW, WINDOW_STYLE, WM_COMMAND,
+    WM_CREATE, WM_DESTROY, WM_GETTEXT, WM_GETTEXTLENGTH, WM_KEYDOWN, WM_NCDESTROY, WNDCLASSW,
+    WS_CAPTION, WS_CHILD, WS_EX_CLIENTEDGE, WS_EX_DLGMODALFRAME, WS_POPUP, WS_TABSTOP, WS_VISIBLE,
+};
+use windows::core::{PCWSTR, w};
+
+const GO_TO_LINE_CLASS: &str = "NovapadGoToLine";
+const GO_TO_LINE_EDIT_ID: usize = 1811;
+const GO_TO_LINE_OK_ID: usize = 1812;
+const GO_TO_LINE_CANCEL_ID: usize = 1813;
+const GO_TO_LINE_STATUS_ID: usize = 1814;
+const ES_NUMBER: u32 = 0x2000;
+
+struct GoToLineState {
+    parent: HWND,
+    line_count: usize,
+}
+
+pub unsafe fn open(parent: HWND) {
+    let existing = with_state(parent, |state| state.go_to_line_dialog).unwrap_or(HWND(0));
+    if existing.0 != 0 {
+        SetForegroundWindow(existing);
+        return;
+    }
+    let line_count = crate::editor_manager::current_line_count(parent);
+    if line_count == 0 {
+        return;
+    }
+
+    let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+    let class_name = to_wide(GO_TO_LINE_CLASS);
+    let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+    let title_w = to_wide(&i18n::tr(language, "go_to_line.title"));
+
+    let wc = WNDCLASSW {
+        hCursor: windows::Win32::UI::WindowsAndMessaging::HCURSOR(
+            LoadCursorW(None, IDC_ARROW).unwrap_or_default().0,
+        ),
+        hInstance: hinstance,
+        lpszClassName: PCWSTR(class_name.as_ptr()),
+        lpfnWndProc: Some(go_to_line_wndproc),
+        hbrBackground: HBRUSH((COLOR_WINDOW.0 + 1) as isize),
+        ..Default::default()
+    };
+    RegisterClassW(&wc);
+
+    let state = Box::new(GoToLineState { parent, line_count });
+    let state_ptr = Box::into_raw(state);
+    let hwnd = CreateWindowExW(
+        WS_EX_DLGMODALFRAME,
+        PCWSTR(class_name.as_ptr()),
+        PCWSTR(title_w.as_ptr()),
+        WS_POPUP | WS_CAPTION | WS_VISIBLE,
+        CW_USEDEFAULT,
+        CW_USEDEFAULT,
+        360,
+        160,
+        parent,
+        HMENU(0),
+        hinstance,
+        Some(state_ptr as *const _),
+    );
+    if hwnd.0 == 0 {
+        drop(Box::from_raw(state_ptr));
+        return;
+    }
+    EnableWindow(parent, false);
+    with_state(parent, |state| state.go_to_line_dialog = hwnd);
+}
+
+unsafe extern "system" fn go_to_line_wndproc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_CREATE => {
+            let cs = lparam.0 as *const CREATESTRUCTW;
+            let init_ptr = (*cs).lpCreateParams as *mut GoToLineState;
+            if init_ptr.is_null() {
+                return LRESULT(0);
+            }
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, init_ptr as isize);
+            let parent = (*init_ptr).parent;
+            let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+
+            let label = i18n::tr_f(
+                language,
+                "go_to_line.label",
+                &[("max", &(*init_ptr).line_count.to_string())],
+            );
+            let ok_text = i18n::tr(language, "go_to_line.ok");
+            let cancel_text = i18n::tr(language, "go_to_line.cancel");
+
+            let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+            CreateWindowExW(
+                Default::default(),
+                w!("STATIC"),
+                PCWSTR(to_wide(&label).as_ptr()),
+                WS_CHILD | WS_VISIBLE,
+                10,
+                12,
+                330,
+                16,
+                hwnd,
+                HMENU(1),
+                hinstance,
+                None,
+            );
+            let input = CreateWindowExW(
+                WS_EX_CLIENTEDGE,
+                w!("EDIT"),
+                PCWSTR::null(),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(ES_NUMBER),
+                10,
+                30,
+                160,
+                24,
+                hwnd,
+                HMENU(GO_TO_LINE_EDIT_ID as isize),
+                hinstance,
+                None,
+            );
+            CreateWindowExW(
+                Default::default(),
+                w!("STATIC"),
+                PCWSTR::null(),
+                WS_CHILD | WS_VISIBLE,
+                10,
+                60,
+                330,
+                16,
+                hwnd,
+                HMENU(GO_TO_LINE_STATUS_ID as isize),
+                hinstance,
+                None,
+            );
+            CreateWindowExW(
+                Default::default(),
+                w!("BUTTON"),
+                PCWSTR(to_wide(&ok_text).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_DEFPUSHBUTTON as u32),
+                170,
+                90,
+                80,
+                26,
+                hwnd,
+                HMENU(GO_TO_LINE_OK_ID as isize),
+                hinstance,
+                None,
+            );
+            CreateWindowExW(
+                Default::default(),
+                w!("BUTTON"),
+                PCWSTR(to_wide(&cancel_text).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP,
+                260,
+                90,
+                80,
+                26,
+                hwnd,
+                HMENU(GO_TO_LINE_CANCEL_ID as isize),
+                hinstance,
+                None,
+            );
+
+            SetFocus(input);
+            LRESULT(0)
+        }
+        WM_KEYDOWN => {
+            if wparam.0 as u32 == windows::Win32::UI::Input::KeyboardAndMouse::VK_ESCAPE.0 as u32 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_COMMAND => {
+            let id = wparam.0 & 0xffff;
+            if id == GO_TO_LINE_CANCEL_ID || id == 2 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            if id == GO_TO_LINE_OK_ID || id == 1 {
+                let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const GoToLineState;
+                if ptr.is_null() {
+                    return LRESULT(0);
+                }
+                let (parent, line_count) = ((*ptr).parent, (*ptr).line_count);
+                let language =
+                    with_state(parent, |state| state.settings.language).unwrap_or_default();
+                let input = GetDlgItem(hwnd, GO_TO_LINE_EDIT_ID as i32);
+                let len = SendMessageW(input, WM_GETTEXTLENGTH, WPARAM(0), LPARAM(0)).0;
+                let mut buf = vec![0u16; len as usize + 1];
+                SendMessageW(
+                    input,
+                    WM_GETTEXT,
+                    WPARAM(buf.len()),
+                    LPARAM(buf.as_mut_ptr() as isize),
+                );
+                let text = String::from_utf16_lossy(&buf[..len as usize]);
+                let line = text
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|line| (1..=line_count).contains(line));
+                let Some(line) = line else {
+                    let msg = i18n::tr_f(
+                        language,
+                        "go_to_line.invalid",
+                        &[("max", &line_count.to_string())],
+                    );
+                    let status = GetDlgItem(hwnd, GO_TO_LINE_STATUS_ID as i32);
+                    let wide = to_wide(&msg);
+                    crate::log_if_err!(SetWindowTextW(status, PCWSTR(wide.as_ptr())));
+                    nvda_speak(&msg);
+                    SetFocus(input);
+                    return LRESULT(0);
+                };
+                crate::log_if_err!(DestroyWindow(hwnd));
+                crate::editor_manager::go_to_line(parent, line);
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_DESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const GoToLineState;
+            if !ptr.is_null() {
+                let parent = (*ptr).parent;
+                EnableWindow(parent, true);
+                SetForegroundWindow(parent);
+                with_state(parent, |s| s.go_to_line_dialog = HWND(0));
+                if let Some(hwnd_edit) = crate::get_active_edit(parent) {
+                    SetFocus(hwnd_edit);
+                }
+            }
+            LRESULT(0)
+        }
+        WM_NCDESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut GoToLineState;
+            if !ptr.is_null() {
+                drop(Box::from_raw(ptr));
+            }
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+pub unsafe fn handle_navigation(
+    hwnd: HWND,
+    msg: &windows::Win32::UI::WindowsAndMessaging::MSG,
+) -> bool {
+    handle_accessibility(hwnd, msg)
+}
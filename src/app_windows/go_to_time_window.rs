@@ -26,6 +26,8 @@ struct GoToTimeState {
     input: HWND,
     status: HWND,
     prev_focus: HWND,
+    /// Jumps to a cue of the current subtitle document instead of seeking the player.
+    subtitles: bool,
 }
 
 pub unsafe fn open(parent: HWND) {
@@ -35,7 +37,8 @@ pub unsafe fn open(parent: HWND) {
         return;
     }
     let has_player = with_state(parent, |state| state.active_audiobook.is_some()).unwrap_or(false);
-    if !has_player {
+    let subtitles = crate::editor_manager::current_is_subtitles(parent);
+    if !has_player && !subtitles {
         return;
     }
 
@@ -62,6 +65,7 @@ pub unsafe fn open(parent: HWND) {
         input: HWND(0),
         status: HWND(0),
         prev_focus,
+        subtitles,
     });
     let state_ptr = Box::into_raw(state);
     let hwnd = CreateWindowExW(
@@ -108,7 +112,12 @@ unsafe extern "system" fn go_to_time_wndproc(
             let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
 
             let label = i18n::tr(language, "go_to_time.label_time");
-            let hint = i18n::tr(language, "go_to_time.hint");
+            let hint_key = if (*init_ptr).subtitles {
+                "go_to_time.hint_subtitles"
+            } else {
+                "go_to_time.hint"
+            };
+            let hint = i18n::tr(language, hint_key);
             let ok_text = i18n::tr(language, "go_to_time.ok");
             let cancel_text = i18n::tr(language, "go_to_time.cancel");
 
@@ -236,6 +245,27 @@ unsafe extern "system" fn go_to_time_wndproc(
                     LPARAM(buf.as_mut_ptr() as isize),
                 );
                 let text = String::from_utf16_lossy(&buf[..len as usize]);
+                let ptr =
+                    GetWindowLongPtrW(hwnd, windows::Win32::UI::WindowsAndMessaging::GWLP_USERDATA)
+                        as *const GoToTimeState;
+                if !ptr.is_null() && (*ptr).subtitles {
+                    let target_ms = parse_time_input(&text)
+                        .ok()
+                        .map(|secs| secs * 1000)
+                        .or_else(|| crate::subtitles::parse_time(text.trim()));
+                    let Some(target_ms) = target_ms else {
+                        let msg = i18n::tr(language, "go_to_time.invalid_time");
+                        let status = GetDlgItem(hwnd, GO_TO_TIME_STATUS_ID as i32);
+                        let wide = to_wide(&msg);
+                        crate::log_if_err!(SetWindowTextW(status, PCWSTR(wide.as_ptr())));
+                        nvda_speak(&msg);
+                        SetFocus(input);
+                        return LRESULT(0);
+                    };
+                    crate::log_if_err!(DestroyWindow(hwnd));
+                    crate::editor_manager::go_to_subtitle_time(parent, target_ms);
+                    return LRESULT(0);
+                }
                 let target = match parse_time_input(&text) {
                     Ok(v) => v,
                     Err(_) => {
@@ -0,0 +1,279 @@
+use crate::accessibility::{handle_accessibility, nvda_speak, to_wide};
+use crate::i18n;
+use crate::settings::Language;
+use crate::text_ops::InvisibleKind;
+use crate::with_state;
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{COLOR_WINDOW, HBRUSH};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Controls::{BST_CHECKED, WC_BUTTON};
+use windows::Win32::UI::Input::KeyboardAndMouse::{EnableWindow, SetFocus};
+use windows::Win32::UI::WindowsAndMessaging::{
+    BM_GETCHECK, BM_SETCHECK, BS_AUTOCHECKBOX, BS_DEFPUSHBUTTON, CREATESTRUCTW, CW_USEDEFAULT,
+    CreateWindowExW, DefWindowProcW, DestroyWindow, GWLP_USERDATA, GetDlgItem, GetWindowLongPtrW,
+    HMENU, IDC_ARROW, LoadCursorW, RegisterClassW, SendMessageW, SetForegroundWindow,
+    SetWindowLongPtrW, WINDOW_STYLE, WM_COMMAND, WM_CREATE, WM_DESTROY, WM_KEYDOWN, WM_NCDESTROY,
+    WNDCLASSW, WS_CAPTION, WS_CHILD, WS_EX_DLGMODALFRAME, WS_POPUP, WS_TABSTOP, WS_VISIBLE,
+};
+use windows::core::{PCWSTR, w};
+
+const INVISIBLE_CHARS_CLASS: &str = "NovapadInvisibleChars";
+const INVISIBLE_CHARS_OK_ID: usize = 1900;
+const INVISIBLE_CHARS_CANCEL_ID: usize = 1901;
+
+/// Kinds the cleanup can remove, with their checkbox. Combining marks are left to
+/// Unicode normalization.
+const CLEANABLE_KINDS: [(InvisibleKind, usize); 5] = [
+    (InvisibleKind::ZeroWidth, 1895),
+    (InvisibleKind::SoftHyphen, 1896),
+    (InvisibleKind::SpecialSpace, 1897),
+    (InvisibleKind::Bidi, 1898),
+    (InvisibleKind::Control, 1899),
+];
+
+/// Kinds counted in the invisible characters report, in report order.
+pub const REPORTED_KINDS: [InvisibleKind; 6] = [
+    InvisibleKind::ZeroWidth,
+    InvisibleKind::SoftHyphen,
+    InvisibleKind::SpecialSpace,
+    InvisibleKind::Bidi,
+    InvisibleKind::Control,
+    InvisibleKind::CombiningMark,
+];
+
+struct InvisibleCharsState {
+    parent: HWND,
+}
+
+pub fn kind_label(language: Language, kind: InvisibleKind) -> String {
+    let key = match kind {
+        InvisibleKind::ZeroWidth => "unicode.kind_zero_width",
+        InvisibleKind::SoftHyphen => "unicode.kind_soft_hyphen",
+        InvisibleKind::SpecialSpace => "unicode.kind_special_space",
+        InvisibleKind::Bidi => "unicode.kind_bidi",
+        InvisibleKind::Control => "unicode.kind_control",
+        InvisibleKind::CombiningMark => "unicode.kind_combining",
+    };
+    i18n::tr(language, key)
+}
+
+/// Asks which kinds of invisible characters to clean from the selection, or the
+/// whole document.
+pub unsafe fn open(parent: HWND) {
+    let existing = with_state(parent, |state| state.invisible_chars_dialog).unwrap_or(HWND(0));
+    if existing.0 != 0 {
+        SetForegroundWindow(existing);
+        return;
+    }
+
+    let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+    let class_name = to_wide(INVISIBLE_CHARS_CLASS);
+    let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+    let title_w = to_wide(&i18n::tr(language, "unicode.clean_title"));
+
+    let wc = WNDCLASSW {
+        hCursor: windows::Win32::UI::WindowsAndMessaging::HCURSOR(
+            LoadCursorW(None, IDC_ARROW).unwrap_or_default().0,
+        ),
+        hInstance: hinstance,
+        lpszClassName: PCWSTR(class_name.as_ptr()),
+        lpfnWndProc: Some(invisible_chars_wndproc),
+        hbrBackground: HBRUSH((COLOR_WINDOW.0 + 1) as isize),
+        ..Default::default()
+    };
+    RegisterClassW(&wc);
+
+    let state = Box::new(InvisibleCharsState { parent });
+    let state_ptr = Box::into_raw(state);
+    let hwnd = CreateWindowExW(
+        WS_EX_DLGMODALFRAME,
+        PCWSTR(class_name.as_ptr()),
+        PCWSTR(title_w.as_ptr()),
+        WS_POPUP | WS_CAPTION | WS_VISIBLE,
+        CW_USEDEFAULT,
+        CW_USEDEFAULT,
+        420,
+        280,
+        parent,
+        HMENU(0),
+        hinstance,
+        Some(state_ptr as *const _),
+    );
+    if hwnd.0 == 0 {
+        drop(Box::from_raw(state_ptr));
+        return;
+    }
+    EnableWindow(parent, false);
+    with_state(parent, |state| state.invisible_chars_dialog = hwnd);
+}
+
+unsafe fn create_checkbox(hwnd: HWND, hinstance: HINSTANCE, text: &str, y: i32, id: usize) {
+    let checkbox = CreateWindowExW(
+        Default::default(),
+        WC_BUTTON,
+        PCWSTR(to_wide(text).as_ptr()),
+        WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_AUTOCHECKBOX as u32),
+        10,
+        y,
+        390,
+        22,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    );
+    SendMessageW(
+        checkbox,
+        BM_SETCHECK,
+        WPARAM(BST_CHECKED.0 as usize),
+        LPARAM(0),
+    );
+}
+
+unsafe fn is_checked(hwnd: HWND, id: usize) -> bool {
+    let checkbox = GetDlgItem(hwnd, id as i32);
+    SendMessageW(checkbox, BM_GETCHECK, WPARAM(0), LPARAM(0)).0 == BST_CHECKED.0 as isize
+}
+
+unsafe extern "system" fn invisible_chars_wndproc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_CREATE => {
+            let cs = lparam.0 as *const CREATESTRUCTW;
+            let init_ptr = (*cs).lpCreateParams as *mut InvisibleCharsState;
+            if init_ptr.is_null() {
+                return LRESULT(0);
+            }
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, init_ptr as isize);
+            let parent = (*init_ptr).parent;
+            let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+
+            let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+            CreateWindowExW(
+                Default::default(),
+                w!("STATIC"),
+                PCWSTR(to_wide(&i18n::tr(language, "unicode.clean_label")).as_ptr()),
+                WS_CHILD | WS_VISIBLE,
+                10,
+                12,
+                390,
+                32,
+                hwnd,
+                HMENU(1),
+                hinstance,
+                None,
+            );
+            let mut y = 50;
+            for (kind, id) in CLEANABLE_KINDS {
+                create_checkbox(hwnd, hinstance, &kind_label(language, kind), y, id);
+                y += 26;
+            }
+            CreateWindowExW(
+                Default::default(),
+                w!("BUTTON"),
+                PCWSTR(to_wide(&i18n::tr(language, "unicode.clean_ok")).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_DEFPUSHBUTTON as u32),
+                230,
+                y + 10,
+                80,
+                26,
+                hwnd,
+                HMENU(INVISIBLE_CHARS_OK_ID as isize),
+                hinstance,
+                None,
+            );
+            CreateWindowExW(
+                Default::default(),
+                w!("BUTTON"),
+                PCWSTR(to_wide(&i18n::tr(language, "unicode.clean_cancel")).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP,
+                320,
+                y + 10,
+                80,
+                26,
+                hwnd,
+                HMENU(INVISIBLE_CHARS_CANCEL_ID as isize),
+                hinstance,
+                None,
+            );
+
+            SetFocus(GetDlgItem(hwnd, CLEANABLE_KINDS[0].1 as i32));
+            LRESULT(0)
+        }
+        WM_KEYDOWN => {
+            if wparam.0 as u32 == windows::Win32::UI::Input::KeyboardAndMouse::VK_ESCAPE.0 as u32 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_COMMAND => {
+            let id = wparam.0 & 0xffff;
+            if id == INVISIBLE_CHARS_CANCEL_ID || id == 2 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            if id == INVISIBLE_CHARS_OK_ID || id == 1 {
+                let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const InvisibleCharsState;
+                if ptr.is_null() {
+                    return LRESULT(0);
+                }
+                let parent = (*ptr).parent;
+                let language =
+                    with_state(parent, |state| state.settings.language).unwrap_or_default();
+                let kinds: Vec<InvisibleKind> = CLEANABLE_KINDS
+                    .iter()
+                    .filter(|(_, id)| is_checked(hwnd, *id))
+                    .map(|(kind, _)| *kind)
+                    .collect();
+                crate::log_if_err!(DestroyWindow(hwnd));
+                let changed =
+                    crate::editor_manager::remove_invisible_chars_active_edit(parent, &kinds);
+                let message = if changed == 0 {
+                    i18n::tr(language, "unicode.nothing_cleaned")
+                } else {
+                    i18n::tr_f(
+                        language,
+                        "unicode.cleaned",
+                        &[("count", &changed.to_string())],
+                    )
+                };
+                nvda_speak(&message);
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_DESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const InvisibleCharsState;
+            if !ptr.is_null() {
+                let parent = (*ptr).parent;
+                EnableWindow(parent, true);
+                SetForegroundWindow(parent);
+                with_state(parent, |s| s.invisible_chars_dialog = HWND(0));
+                if let Some(hwnd_edit) = crate::get_active_edit(parent) {
+                    SetFocus(hwnd_edit);
+                }
+            }
+            LRESULT(0)
+        }
+        WM_NCDESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut InvisibleCharsState;
+            if !ptr.is_null() {
+                drop(Box::from_raw(ptr));
+            }
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+pub unsafe fn handle_navigation(
+    hwnd: HWND,
+    msg: &windows::Win32::UI::WindowsAndMessaging::MSG,
+) -> bool {
+    handle_accessibility(hwnd, msg)
+}
@@ -0,0 +1,624 @@
+use crate::accessibility::{handle_accessibility, nvda_speak, to_wide};
+use crate::i18n;
+use crate::settings::{Language, TextMacro, save_settings};
+use crate::text_macros::{run_on_files, run_on_tabs};
+use crate::with_state;
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{COLOR_WINDOW, HBRUSH, HFONT};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Controls::{WC_BUTTON, WC_COMBOBOXW, WC_LISTBOXW};
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    EnableWindow, GetFocus, SetFocus, VK_ESCAPE, VK_RETURN,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    BS_DEFPUSHBUTTON, CB_ADDSTRING, CB_GETCURSEL, CB_SETCURSEL, CBS_DROPDOWNLIST, CREATESTRUCTW,
+    CW_USEDEFAULT, CreateWindowExW, DefWindowProcW, DestroyWindow, ES_AUTOHSCROLL, GWLP_USERDATA,
+    GetDlgItem, GetWindowLongPtrW, HMENU, IDC_ARROW, LB_ADDSTRING, LB_GETCURSEL, LB_RESETCONTENT,
+    LB_SETCURSEL, LBN_SELCHANGE, LBS_HASSTRINGS, LBS_NOTIFY, LoadCursorW, MSG, RegisterClassW,
+    SendMessageW, SetForegroundWindow, SetWindowLongPtrW, SetWindowTextW, WINDOW_STYLE, WM_CLOSE,
+    WM_COMMAND, WM_CREATE, WM_DESTROY, WM_GETTEXT, WM_GETTEXTLENGTH, WM_KEYDOWN, WM_NCDESTROY,
+    WM_SETFONT, WNDCLASSW, WS_CAPTION, WS_CHILD, WS_EX_CLIENTEDGE, WS_EX_CONTROLPARENT,
+    WS_EX_DLGMODALFRAME, WS_SYSMENU, WS_TABSTOP, WS_VISIBLE, WS_VSCROLL,
+};
+use windows::core::{PCWSTR, w};
+
+const MACROS_CLASS_NAME: &str = "NovapadMacros";
+const MACROS_ID_LIST: usize = 1871;
+const MACROS_ID_NAME: usize = 1872;
+const MACROS_ID_SHORTCUT: usize = 1873;
+const MACROS_ID_SAVE: usize = 1874;
+const MACROS_ID_RUN: usize = 1875;
+const MACROS_ID_RUN_ALL: usize = 1876;
+const MACROS_ID_RUN_FILES: usize = 1877;
+const MACROS_ID_DELETE: usize = 1878;
+const MACROS_ID_CLOSE: usize = 1879;
+const MACROS_ID_STATUS: usize = 1880;
+const EM_SETSEL: u32 = 0x00B1;
+
+struct MacrosWindowState {
+    parent: HWND,
+    hwnd_list: HWND,
+    hwnd_name: HWND,
+    hwnd_shortcut: HWND,
+}
+
+pub unsafe fn handle_navigation(hwnd: HWND, msg: &MSG) -> bool {
+    if msg.message == WM_KEYDOWN && msg.wParam.0 as u32 == VK_RETURN.0 as u32 {
+        let focus = GetFocus();
+        let Some((list, name, shortcut)) =
+            with_macros_state(hwnd, |s| (s.hwnd_list, s.hwnd_name, s.hwnd_shortcut))
+        else {
+            return false;
+        };
+        if focus == list {
+            run_selected(hwnd, false);
+            return true;
+        }
+        if focus == name || focus == shortcut {
+            save_selected(hwnd);
+            return true;
+        }
+    }
+    handle_accessibility(hwnd, msg)
+}
+
+/// Opens the macro manager. With `select` set, that macro is selected and its name
+/// field focused, as after recording a new one.
+pub unsafe fn open(parent: HWND, select: Option<usize>) {
+    let existing = with_state(parent, |state| state.macros_window).unwrap_or(HWND(0));
+    if existing.0 != 0 {
+        SetForegroundWindow(existing);
+        return;
+    }
+    let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+
+    let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+    let class_name = to_wide(MACROS_CLASS_NAME);
+    let wc = WNDCLASSW {
+        hCursor: windows::Win32::UI::WindowsAndMessaging::HCURSOR(
+            LoadCursorW(None, IDC_ARROW).unwrap_or_default().0,
+        ),
+        hInstance: hinstance,
+        lpszClassName: PCWSTR(class_name.as_ptr()),
+        lpfnWndProc: Some(macros_wndproc),
+        hbrBackground: HBRUSH((COLOR_WINDOW.0 + 1) as isize),
+        ..Default::default()
+    };
+    RegisterClassW(&wc);
+
+    let title = to_wide(&i18n::tr(language, "macros.title"));
+    let state = Box::new(MacrosWindowState {
+        parent,
+        hwnd_list: HWND(0),
+        hwnd_name: HWND(0),
+        hwnd_shortcut: HWND(0),
+    });
+    let state_ptr = Box::into_raw(state);
+    let window = CreateWindowExW(
+        WS_EX_CONTROLPARENT | WS_EX_DLGMODALFRAME,
+        PCWSTR(class_name.as_ptr()),
+        PCWSTR(title.as_ptr()),
+        WS_CAPTION | WS_SYSMENU | WS_VISIBLE,
+        CW_USEDEFAULT,
+        CW_USEDEFAULT,
+        560,
+        470,
+        parent,
+        None,
+        hinstance,
+        Some(state_ptr as *const _),
+    );
+    if window.0 == 0 {
+        drop(Box::from_raw(state_ptr));
+        return;
+    }
+
+    let Some((hwnd_list, hwnd_name, hwnd_shortcut)) =
+        with_macros_state(window, |s| (s.hwnd_list, s.hwnd_name, s.hwnd_shortcut))
+    else {
+        return;
+    };
+    let none = to_wide(&i18n::tr(language, "macros.shortcut_none"));
+    SendMessageW(
+        hwnd_shortcut,
+        CB_ADDSTRING,
+        WPARAM(0),
+        LPARAM(none.as_ptr() as isize),
+    );
+    for digit in 1..=9 {
+        let wide = to_wide(&format!("Ctrl+Shift+{digit}"));
+        SendMessageW(
+            hwnd_shortcut,
+            CB_ADDSTRING,
+            WPARAM(0),
+            LPARAM(wide.as_ptr() as isize),
+        );
+    }
+    with_state(parent, |state| state.macros_window = window);
+    EnableWindow(parent, false);
+    SetForegroundWindow(window);
+    refresh_list(window, select.unwrap_or(0));
+    if select.is_some() {
+        SetFocus(hwnd_name);
+        SendMessageW(hwnd_name, EM_SETSEL, WPARAM(0), LPARAM(-1));
+    } else {
+        SetFocus(hwnd_list);
+    }
+}
+
+unsafe extern "system" fn macros_wndproc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_CREATE => {
+            let create_struct = lparam.0 as *const CREATESTRUCTW;
+            let state_ptr = (*create_struct).lpCreateParams as *mut MacrosWindowState;
+            if state_ptr.is_null() {
+                return LRESULT(0);
+            }
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, state_ptr as isize);
+            let parent = (*state_ptr).parent;
+            let hfont = with_state(parent, |state| state.hfont).unwrap_or(HFONT(0));
+            let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+            let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+
+            let label_list =
+                create_static(hwnd, hinstance, &i18n::tr(language, "macros.list"), 10, 0);
+            let hwnd_list = CreateWindowExW(
+                WS_EX_CLIENTEDGE,
+                WC_LISTBOXW,
+                PCWSTR::null(),
+                WS_CHILD
+                    | WS_VISIBLE
+                    | WS_VSCROLL
+                    | WS_TABSTOP
+                    | WINDOW_STYLE((LBS_NOTIFY | LBS_HASSTRINGS) as u32),
+                10,
+                30,
+                520,
+                160,
+                hwnd,
+                HMENU(MACROS_ID_LIST as isize),
+                hinstance,
+                None,
+            );
+            let label_name =
+                create_static(hwnd, hinstance, &i18n::tr(language, "macros.name"), 200, 0);
+            let hwnd_name = CreateWindowExW(
+                WS_EX_CLIENTEDGE,
+                w!("EDIT"),
+                PCWSTR::null(),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(ES_AUTOHSCROLL as u32),
+                10,
+                220,
+                520,
+                24,
+                hwnd,
+                HMENU(MACROS_ID_NAME as isize),
+                hinstance,
+                None,
+            );
+            let label_shortcut = create_static(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "macros.shortcut"),
+                254,
+                0,
+            );
+            let hwnd_shortcut = CreateWindowExW(
+                WS_EX_CLIENTEDGE,
+                WC_COMBOBOXW,
+                PCWSTR::null(),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(CBS_DROPDOWNLIST as u32),
+                10,
+                274,
+                200,
+                200,
+                hwnd,
+                HMENU(MACROS_ID_SHORTCUT as isize),
+                hinstance,
+                None,
+            );
+            let status = create_static(hwnd, hinstance, "", 308, MACROS_ID_STATUS);
+            let buttons = [
+                ("macros.save", MACROS_ID_SAVE, 10, 336),
+                ("macros.run", MACROS_ID_RUN, 140, 336),
+                ("macros.run_all", MACROS_ID_RUN_ALL, 270, 336),
+                ("macros.run_files", MACROS_ID_RUN_FILES, 400, 336),
+                ("macros.delete", MACROS_ID_DELETE, 10, 376),
+                ("macros.close", MACROS_ID_CLOSE, 400, 376),
+            ]
+            .map(|(key, id, x, y)| {
+                create_button(
+                    hwnd,
+                    hinstance,
+                    &i18n::tr(language, key),
+                    x,
+                    y,
+                    id,
+                    id == MACROS_ID_RUN,
+                )
+            });
+
+            for ctrl in [
+                label_list,
+                hwnd_list,
+                label_name,
+                hwnd_name,
+                label_shortcut,
+                hwnd_shortcut,
+                status,
+            ]
+            .into_iter()
+            .chain(buttons)
+            {
+                if ctrl.0 != 0 && hfont.0 != 0 {
+                    SendMessageW(ctrl, WM_SETFONT, WPARAM(hfont.0 as usize), LPARAM(1));
+                }
+            }
+
+            (*state_ptr).hwnd_list = hwnd_list;
+            (*state_ptr).hwnd_name = hwnd_name;
+            (*state_ptr).hwnd_shortcut = hwnd_shortcut;
+            LRESULT(0)
+        }
+        WM_KEYDOWN => {
+            if wparam.0 as u32 == VK_ESCAPE.0 as u32 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_COMMAND => {
+            let cmd_id = wparam.0 & 0xffff;
+            let notify = (wparam.0 >> 16) as u16;
+            match cmd_id {
+                MACROS_ID_LIST if notify == LBN_SELCHANGE as u16 => {
+                    fill_fields(hwnd);
+                    LRESULT(0)
+                }
+                MACROS_ID_SAVE => {
+                    save_selected(hwnd);
+                    LRESULT(0)
+                }
+                MACROS_ID_RUN => {
+                    run_selected(hwnd, false);
+                    LRESULT(0)
+                }
+                MACROS_ID_RUN_ALL => {
+                    run_selected(hwnd, true);
+                    LRESULT(0)
+                }
+                MACROS_ID_RUN_FILES => {
+                    run_selected_on_files(hwnd);
+                    LRESULT(0)
+                }
+                MACROS_ID_DELETE => {
+                    delete_selected(hwnd);
+                    LRESULT(0)
+                }
+                cmd if cmd == MACROS_ID_CLOSE || cmd == 2 => {
+                    crate::log_if_err!(DestroyWindow(hwnd));
+                    LRESULT(0)
+                }
+                _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+            }
+        }
+        WM_CLOSE => {
+            crate::log_if_err!(DestroyWindow(hwnd));
+            LRESULT(0)
+        }
+        WM_DESTROY => {
+            let parent = with_macros_state(hwnd, |s| s.parent).unwrap_or(HWND(0));
+            if parent.0 != 0 {
+                EnableWindow(parent, true);
+                SetForegroundWindow(parent);
+                with_state(parent, |state| state.macros_window = HWND(0));
+                if let Some(hwnd_edit) = crate::get_active_edit(parent) {
+                    SetFocus(hwnd_edit);
+                }
+            }
+            LRESULT(0)
+        }
+        WM_NCDESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut MacrosWindowState;
+            if !ptr.is_null() {
+                drop(Box::from_raw(ptr));
+            }
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+unsafe fn create_static(hwnd: HWND, hinstance: HINSTANCE, text: &str, y: i32, id: usize) -> HWND {
+    CreateWindowExW(
+        Default::default(),
+        w!("STATIC"),
+        PCWSTR(to_wide(text).as_ptr()),
+        WS_CHILD | WS_VISIBLE,
+        10,
+        y,
+        520,
+        18,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    )
+}
+
+unsafe fn create_button(
+    hwnd: HWND,
+    hinstance: HINSTANCE,
+    text: &str,
+    x: i32,
+    y: i32,
+    id: usize,
+    default: bool,
+) -> HWND {
+    let mut style = WS_CHILD | WS_VISIBLE | WS_TABSTOP;
+    if default {
+        style |= WINDOW_STYLE(BS_DEFPUSHBUTTON as u32);
+    }
+    CreateWindowExW(
+        Default::default(),
+        WC_BUTTON,
+        PCWSTR(to_wide(text).as_ptr()),
+        style,
+        x,
+        y,
+        120,
+        30,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    )
+}
+
+unsafe fn with_macros_state<F, R>(hwnd: HWND, f: F) -> Option<R>
+where
+    F: FnOnce(&mut MacrosWindowState) -> R,
+{
+    let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut MacrosWindowState;
+    if ptr.is_null() {
+        None
+    } else {
+        Some(f(&mut *ptr))
+    }
+}
+
+fn describe_macro(language: Language, text_macro: &TextMacro) -> String {
+    let count = text_macro.steps.len().to_string();
+    if text_macro.shortcut == 0 {
+        i18n::tr_f(
+            language,
+            "macros.item",
+            &[("name", &text_macro.name), ("count", &count)],
+        )
+    } else {
+        let shortcut = format!("Ctrl+Shift+{}", text_macro.shortcut);
+        i18n::tr_f(
+            language,
+            "macros.item_shortcut",
+            &[
+                ("name", &text_macro.name),
+                ("shortcut", &shortcut),
+                ("count", &count),
+            ],
+        )
+    }
+}
+
+/// Fills the list from the saved macros and selects the one at `select`.
+unsafe fn refresh_list(hwnd: HWND, select: usize) {
+    let Some((parent, hwnd_list)) = with_macros_state(hwnd, |s| (s.parent, s.hwnd_list)) else {
+        return;
+    };
+    let items = with_state(parent, |state| {
+        let language = state.settings.language;
+        state
+            .settings
+            .macros
+            .iter()
+            .map(|m| describe_macro(language, m))
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_default();
+    SendMessageW(hwnd_list, LB_RESETCONTENT, WPARAM(0), LPARAM(0));
+    for item in &items {
+        let wide = to_wide(item);
+        SendMessageW(
+            hwnd_list,
+            LB_ADDSTRING,
+            WPARAM(0),
+            LPARAM(wide.as_ptr() as isize),
+        );
+    }
+    if !items.is_empty() {
+        let index = select.min(items.len() - 1);
+        SendMessageW(hwnd_list, LB_SETCURSEL, WPARAM(index), LPARAM(0));
+    }
+    fill_fields(hwnd);
+}
+
+/// Shows the name and shortcut of the selected macro in the edit fields.
+unsafe fn fill_fields(hwnd: HWND) {
+    let Some((parent, hwnd_name, hwnd_shortcut)) =
+        with_macros_state(hwnd, |s| (s.parent, s.hwnd_name, s.hwnd_shortcut))
+    else {
+        return;
+    };
+    let selected = selected_index(hwnd).and_then(|index| {
+        with_state(parent, |state| {
+            state
+                .settings
+                .macros
+                .get(index)
+                .map(|m| (m.name.clone(), m.shortcut))
+        })
+        .flatten()
+    });
+    let (name, shortcut) = selected.unwrap_or_default();
+    let wide = to_wide(&name);
+    crate::log_if_err!(SetWindowTextW(hwnd_name, PCWSTR(wide.as_ptr())));
+    SendMessageW(
+        hwnd_shortcut,
+        CB_SETCURSEL,
+        WPARAM(shortcut as usize),
+        LPARAM(0),
+    );
+}
+
+unsafe fn selected_index(hwnd: HWND) -> Option<usize> {
+    let hwnd_list = with_macros_state(hwnd, |s| s.hwnd_list)?;
+    let selected = SendMessageW(hwnd_list, LB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+    usize::try_from(selected).ok()
+}
+
+unsafe fn read_name(hwnd_name: HWND) -> String {
+    let len = SendMessageW(hwnd_name, WM_GETTEXTLENGTH, WPARAM(0), LPARAM(0)).0;
+    let mut buf = vec![0u16; len as usize + 1];
+    SendMessageW(
+        hwnd_name,
+        WM_GETTEXT,
+        WPARAM(buf.len()),
+        LPARAM(buf.as_mut_ptr() as isize),
+    );
+    String::from_utf16_lossy(&buf[..len as usize])
+}
+
+unsafe fn show_status(hwnd: HWND, message: &str) {
+    let status = GetDlgItem(hwnd, MACROS_ID_STATUS as i32);
+    let wide = to_wide(message);
+    crate::log_if_err!(SetWindowTextW(status, PCWSTR(wide.as_ptr())));
+    nvda_speak(message);
+}
+
+/// Stores the edited name and shortcut of the selected macro. A shortcut can only
+/// belong to one macro, so it is taken away from any other.
+unsafe fn save_selected(hwnd: HWND) {
+    let Some(index) = selected_index(hwnd) else {
+        return;
+    };
+    let Some((parent, hwnd_name, hwnd_shortcut)) =
+        with_macros_state(hwnd, |s| (s.parent, s.hwnd_name, s.hwnd_shortcut))
+    else {
+        return;
+    };
+    let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+    let name = read_name(hwnd_name).trim().to_string();
+    if name.is_empty() {
+        show_status(hwnd, &i18n::tr(language, "macros.empty_name"));
+        SetFocus(hwnd_name);
+        return;
+    }
+    let shortcut = SendMessageW(hwnd_shortcut, CB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+    let shortcut = u8::try_from(shortcut).unwrap_or(0);
+    with_state(parent, |state| {
+        for (i, text_macro) in state.settings.macros.iter_mut().enumerate() {
+            if i == index {
+                text_macro.name = name.clone();
+                text_macro.shortcut = shortcut;
+            } else if shortcut != 0 && text_macro.shortcut == shortcut {
+                text_macro.shortcut = 0;
+            }
+        }
+        save_settings(state.settings.clone());
+    });
+    refresh_list(hwnd, index);
+    show_status(
+        hwnd,
+        &i18n::tr_f(language, "macros.saved", &[("name", &name)]),
+    );
+}
+
+unsafe fn selected_macro(hwnd: HWND) -> Option<(HWND, Language, TextMacro)> {
+    let index = selected_index(hwnd)?;
+    let parent = with_macros_state(hwnd, |s| s.parent)?;
+    with_state(parent, |state| {
+        state
+            .settings
+            .macros
+            .get(index)
+            .cloned()
+            .map(|m| (parent, state.settings.language, m))
+    })
+    .flatten()
+}
+
+unsafe fn run_selected(hwnd: HWND, all_tabs: bool) {
+    let Some((parent, language, text_macro)) = selected_macro(hwnd) else {
+        return;
+    };
+    let message = match run_on_tabs(parent, &text_macro.steps, all_tabs) {
+        Ok(changed) => i18n::tr_f(
+            language,
+            "macros.run_done",
+            &[("name", &text_macro.name), ("count", &changed.to_string())],
+        ),
+        Err(err) => i18n::tr_f(language, "macro.error", &[("error", &err)]),
+    };
+    show_status(hwnd, &message);
+}
+
+unsafe fn run_selected_on_files(hwnd: HWND) {
+    let Some((parent, language, text_macro)) = selected_macro(hwnd) else {
+        return;
+    };
+    let Some(paths) =
+        crate::app_windows::batch_audiobooks_window::open_files_dialog(hwnd, language)
+    else {
+        return;
+    };
+    if paths.is_empty() {
+        return;
+    }
+    let Some(settings) = with_state(parent, |state| state.settings.clone()) else {
+        return;
+    };
+    let failed = run_on_files(&paths, &text_macro.steps, &settings);
+    let done = (paths.len() - failed.len()).to_string();
+    let message = if failed.is_empty() {
+        i18n::tr_f(language, "macros.files_done", &[("count", &done)])
+    } else {
+        let names = failed
+            .iter()
+            .filter_map(|path| path.file_name())
+            .map(|name| name.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(", ");
+        i18n::tr_f(
+            language,
+            "macros.files_failed",
+            &[("count", &done), ("files", &names)],
+        )
+    };
+    show_status(hwnd, &message);
+}
+
+unsafe fn delete_selected(hwnd: HWND) {
+    let Some(index) = selected_index(hwnd) else {
+        return;
+    };
+    let Some((parent, hwnd_list)) = with_macros_state(hwnd, |s| (s.parent, s.hwnd_list)) else {
+        return;
+    };
+    let Some((language, removed)) = with_state(parent, |state| {
+        let removed = state.settings.macros.remove(index);
+        save_settings(state.settings.clone());
+        (state.settings.language, removed)
+    }) else {
+        return;
+    };
+    refresh_list(hwnd, index);
+    SetFocus(hwnd_list);
+    show_status(
+        hwnd,
+        &i18n::tr_f(language, "macros.deleted", &[("name", &removed.name)]),
+    );
+}
@@ -1,19 +1,35 @@
 pub mod about_window;
+pub mod archive_window;
 pub mod audiobook_window;
+pub mod autocorrect_window;
 pub mod batch_audiobooks_window;
 pub mod bookmarks_window;
+pub mod braille_export_window;
+pub mod columns_window;
+pub mod compare_window;
 pub mod dictionary_window;
+pub mod filter_lines_window;
+pub mod find_all_window;
 pub mod find_in_files_window;
+pub mod go_to_line_window;
 pub mod go_to_time_window;
 pub mod help_window;
+pub mod invisible_chars_window;
+pub mod macros_window;
 pub mod marker_select_window;
+pub mod number_lines_window;
 pub mod options_window;
+pub mod outline_window;
+pub mod password_window;
 pub mod podcast_chapters_window;
 pub mod podcast_save_window;
 pub mod podcast_window;
 pub mod podcasts_window;
 pub mod prompt_window;
 pub mod rss_window;
+pub mod snippets_window;
+pub mod sort_lines_window;
+pub mod subtitle_timing_window;
 pub mod wikipedia_window;
 pub mod wiktionary_window;
 pub mod youtube_transcript_window;
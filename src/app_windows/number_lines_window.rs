@@ -0,0 +1,337 @@
+use super::sort_lines_window::{display_delimiter, parse_delimiter};
+use crate::accessibility::{handle_accessibility, nvda_speak, to_wide};
+use crate::i18n;
+use crate::settings::save_settings;
+use crate::text_ops::NumberingOptions;
+use crate::with_state;
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{COLOR_WINDOW, HBRUSH};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Input::KeyboardAndMouse::{EnableWindow, SetFocus};
+use windows::Win32::UI::WindowsAndMessaging::{
+    BS_DEFPUSHBUTTON, CREATESTRUCTW, CW_USEDEFAULT, CreateWindowExW, DefWindowProcW, DestroyWindow,
+    GWLP_USERDATA, GetDlgItem, GetWindowLongPtrW, HMENU, IDC_ARROW, LoadCursorW, RegisterClassW,
+    SendMessageW, SetForegroundWindow, SetWindowLongPtrW, SetWindowTextW, WINDOW_STYLE, WM_COMMAND,
+    WM_CREATE, WM_DESTROY, WM_GETTEXT, WM_GETTEXTLENGTH, WM_KEYDOWN, WM_NCDESTROY, WNDCLASSW,
+    WS_CAPTION, WS_CHILD, WS_EX_CLIENTEDGE, WS_EX_DLGMODALFRAME, WS_POPUP, WS_TABSTOP, WS_VISIBLE,
+};
+use windows::core::{PCWSTR, w};
+
+const NUMBER_LINES_CLASS: &str = "NovapadNumberLines";
+const NUMBER_LINES_START_ID: usize = 1902;
+const NUMBER_LINES_STEP_ID: usize = 1903;
+const NUMBER_LINES_WIDTH_ID: usize = 1904;
+const NUMBER_LINES_SEPARATOR_ID: usize = 1905;
+const NUMBER_LINES_OK_ID: usize = 1906;
+const NUMBER_LINES_CANCEL_ID: usize = 1907;
+const NUMBER_LINES_STATUS_ID: usize = 1908;
+
+const MAX_WIDTH: usize = 12;
+
+struct NumberLinesState {
+    parent: HWND,
+}
+
+/// Asks how to number the selected lines, or every line of the document.
+pub unsafe fn open(parent: HWND) {
+    let existing = with_state(parent, |state| state.number_lines_dialog).unwrap_or(HWND(0));
+    if existing.0 != 0 {
+        SetForegroundWindow(existing);
+        return;
+    }
+
+    let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+    let class_name = to_wide(NUMBER_LINES_CLASS);
+    let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+    let title_w = to_wide(&i18n::tr(language, "number_lines.title"));
+
+    let wc = WNDCLASSW {
+        hCursor: windows::Win32::UI::WindowsAndMessaging::HCURSOR(
+            LoadCursorW(None, IDC_ARROW).unwrap_or_default().0,
+        ),
+        hInstance: hinstance,
+        lpszClassName: PCWSTR(class_name.as_ptr()),
+        lpfnWndProc: Some(number_lines_wndproc),
+        hbrBackground: HBRUSH((COLOR_WINDOW.0 + 1) as isize),
+        ..Default::default()
+    };
+    RegisterClassW(&wc);
+
+    let state = Box::new(NumberLinesState { parent });
+    let state_ptr = Box::into_raw(state);
+    let hwnd = CreateWindowExW(
+        WS_EX_DLGMODALFRAME,
+        PCWSTR(class_name.as_ptr()),
+        PCWSTR(title_w.as_ptr()),
+        WS_POPUP | WS_CAPTION | WS_VISIBLE,
+        CW_USEDEFAULT,
+        CW_USEDEFAULT,
+        420,
+        330,
+        parent,
+        HMENU(0),
+        hinstance,
+        Some(state_ptr as *const _),
+    );
+    if hwnd.0 == 0 {
+        drop(Box::from_raw(state_ptr));
+        return;
+    }
+    EnableWindow(parent, false);
+    with_state(parent, |state| state.number_lines_dialog = hwnd);
+}
+
+unsafe fn create_label(hwnd: HWND, hinstance: HINSTANCE, text: &str, y: i32, id: isize) {
+    CreateWindowExW(
+        Default::default(),
+        w!("STATIC"),
+        PCWSTR(to_wide(text).as_ptr()),
+        WS_CHILD | WS_VISIBLE,
+        10,
+        y,
+        390,
+        16,
+        hwnd,
+        HMENU(id),
+        hinstance,
+        None,
+    );
+}
+
+unsafe fn create_input(hwnd: HWND, hinstance: HINSTANCE, text: &str, y: i32, id: usize) {
+    CreateWindowExW(
+        WS_EX_CLIENTEDGE,
+        w!("EDIT"),
+        PCWSTR(to_wide(text).as_ptr()),
+        WS_CHILD | WS_VISIBLE | WS_TABSTOP,
+        10,
+        y,
+        120,
+        24,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    );
+}
+
+unsafe fn read_input(hwnd: HWND, id: usize) -> String {
+    let input = GetDlgItem(hwnd, id as i32);
+    let len = SendMessageW(input, WM_GETTEXTLENGTH, WPARAM(0), LPARAM(0)).0;
+    let mut buf = vec![0u16; len as usize + 1];
+    SendMessageW(
+        input,
+        WM_GETTEXT,
+        WPARAM(buf.len()),
+        LPARAM(buf.as_mut_ptr() as isize),
+    );
+    String::from_utf16_lossy(&buf[..len as usize])
+}
+
+unsafe fn show_status(hwnd: HWND, message: &str, focus_id: usize) {
+    let status = GetDlgItem(hwnd, NUMBER_LINES_STATUS_ID as i32);
+    let wide = to_wide(message);
+    crate::log_if_err!(SetWindowTextW(status, PCWSTR(wide.as_ptr())));
+    nvda_speak(message);
+    SetFocus(GetDlgItem(hwnd, focus_id as i32));
+}
+
+/// An empty width box means no padding.
+fn parse_width(text: &str) -> Option<usize> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Some(0);
+    }
+    text.parse::<usize>()
+        .ok()
+        .filter(|width| *width <= MAX_WIDTH)
+}
+
+unsafe extern "system" fn number_lines_wndproc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_CREATE => {
+            let cs = lparam.0 as *const CREATESTRUCTW;
+            let init_ptr = (*cs).lpCreateParams as *mut NumberLinesState;
+            if init_ptr.is_null() {
+                return LRESULT(0);
+            }
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, init_ptr as isize);
+            let parent = (*init_ptr).parent;
+            let Some(settings) = with_state(parent, |state| state.settings.clone()) else {
+                return LRESULT(0);
+            };
+            let language = settings.language;
+
+            let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+            let fields = [
+                (
+                    "number_lines.start",
+                    settings.number_start.to_string(),
+                    NUMBER_LINES_START_ID,
+                ),
+                (
+                    "number_lines.step",
+                    settings.number_step.to_string(),
+                    NUMBER_LINES_STEP_ID,
+                ),
+                (
+                    "number_lines.width",
+                    settings.number_width.to_string(),
+                    NUMBER_LINES_WIDTH_ID,
+                ),
+                (
+                    "number_lines.separator",
+                    display_delimiter(&settings.number_separator),
+                    NUMBER_LINES_SEPARATOR_ID,
+                ),
+            ];
+            let mut y = 12;
+            for (label_id, (key, value, id)) in fields.iter().enumerate() {
+                create_label(
+                    hwnd,
+                    hinstance,
+                    &i18n::tr(language, key),
+                    y,
+                    label_id as isize + 1,
+                );
+                create_input(hwnd, hinstance, value, y + 18, *id);
+                y += 54;
+            }
+            create_label(hwnd, hinstance, "", y, NUMBER_LINES_STATUS_ID as isize);
+            CreateWindowExW(
+                Default::default(),
+                w!("BUTTON"),
+                PCWSTR(to_wide(&i18n::tr(language, "number_lines.ok")).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_DEFPUSHBUTTON as u32),
+                230,
+                y + 28,
+                80,
+                26,
+                hwnd,
+                HMENU(NUMBER_LINES_OK_ID as isize),
+                hinstance,
+                None,
+            );
+            CreateWindowExW(
+                Default::default(),
+                w!("BUTTON"),
+                PCWSTR(to_wide(&i18n::tr(language, "number_lines.cancel")).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP,
+                320,
+                y + 28,
+                80,
+                26,
+                hwnd,
+                HMENU(NUMBER_LINES_CANCEL_ID as isize),
+                hinstance,
+                None,
+            );
+
+            SetFocus(GetDlgItem(hwnd, NUMBER_LINES_START_ID as i32));
+            LRESULT(0)
+        }
+        WM_KEYDOWN => {
+            if wparam.0 as u32 == windows::Win32::UI::Input::KeyboardAndMouse::VK_ESCAPE.0 as u32 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_COMMAND => {
+            let id = wparam.0 & 0xffff;
+            if id == NUMBER_LINES_CANCEL_ID || id == 2 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            if id == NUMBER_LINES_OK_ID || id == 1 {
+                let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const NumberLinesState;
+                if ptr.is_null() {
+                    return LRESULT(0);
+                }
+                let parent = (*ptr).parent;
+                let language =
+                    with_state(parent, |state| state.settings.language).unwrap_or_default();
+                let Ok(start) = read_input(hwnd, NUMBER_LINES_START_ID)
+                    .trim()
+                    .parse::<i64>()
+                else {
+                    let msg = i18n::tr(language, "number_lines.invalid_number");
+                    show_status(hwnd, &msg, NUMBER_LINES_START_ID);
+                    return LRESULT(0);
+                };
+                let Ok(step) = read_input(hwnd, NUMBER_LINES_STEP_ID).trim().parse::<i64>() else {
+                    let msg = i18n::tr(language, "number_lines.invalid_number");
+                    show_status(hwnd, &msg, NUMBER_LINES_STEP_ID);
+                    return LRESULT(0);
+                };
+                let Some(width) = parse_width(&read_input(hwnd, NUMBER_LINES_WIDTH_ID)) else {
+                    let msg = i18n::tr_f(
+                        language,
+                        "number_lines.invalid_width",
+                        &[("max", &MAX_WIDTH.to_string())],
+                    );
+                    show_status(hwnd, &msg, NUMBER_LINES_WIDTH_ID);
+                    return LRESULT(0);
+                };
+                let options = NumberingOptions {
+                    start,
+                    step,
+                    width,
+                    separator: parse_delimiter(&read_input(hwnd, NUMBER_LINES_SEPARATOR_ID)),
+                };
+                let settings = with_state(parent, |state| {
+                    state.settings.number_start = options.start;
+                    state.settings.number_step = options.step;
+                    state.settings.number_width = options.width as u32;
+                    state.settings.number_separator = options.separator.clone();
+                    state.settings.clone()
+                });
+                if let Some(settings) = settings {
+                    save_settings(settings);
+                }
+                crate::log_if_err!(DestroyWindow(hwnd));
+                let key = if crate::editor_manager::number_lines_active_edit(parent, &options) {
+                    "number_lines.numbered"
+                } else {
+                    "number_lines.unchanged"
+                };
+                nvda_speak(&i18n::tr(language, key));
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_DESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const NumberLinesState;
+            if !ptr.is_null() {
+                let parent = (*ptr).parent;
+                EnableWindow(parent, true);
+                SetForegroundWindow(parent);
+                with_state(parent, |s| s.number_lines_dialog = HWND(0));
+                if let Some(hwnd_edit) = crate::get_active_edit(parent) {
+                    SetFocus(hwnd_edit);
+                }
+            }
+            LRESULT(0)
+        }
+        WM_NCDESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut NumberLinesState;
+            if !ptr.is_null() {
+                drop(Box::from_raw(ptr));
+            }
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+pub unsafe fn handle_navigation(
+    hwnd: HWND,
+    msg: &windows::Win32::UI::WindowsAndMessaging::MSG,
+) -> bool {
+    handle_accessibility(hwnd, msg)
+}
@@ -0,0 +1,321 @@
+use crate::accessibility::{handle_accessibility, to_wide};
+use crate::i18n;
+use crate::outline::{Heading, heading_at};
+use crate::settings::Language;
+use crate::with_state;
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{COLOR_WINDOW, HBRUSH, HFONT};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Controls::{WC_BUTTON, WC_LISTBOXW};
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    EnableWindow, GetFocus, SetFocus, VK_ESCAPE, VK_RETURN,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    BS_DEFPUSHBUTTON, CREATESTRUCTW, CW_USEDEFAULT, CreateWindowExW, DefWindowProcW, DestroyWindow,
+    GWLP_USERDATA, GetWindowLongPtrW, HMENU, IDC_ARROW, LB_ADDSTRING, LB_GETCURSEL, LB_SETCURSEL,
+    LBN_DBLCLK, LBS_HASSTRINGS, LBS_NOTIFY, LoadCursorW, MSG, RegisterClassW, SendMessageW,
+    SetForegroundWindow, SetWindowLongPtrW, WINDOW_STYLE, WM_CLOSE, WM_COMMAND, WM_CREATE,
+    WM_DESTROY, WM_KEYDOWN, WM_NCDESTROY, WM_SETFONT, WNDCLASSW, WS_CAPTION, WS_CHILD,
+    WS_EX_CLIENTEDGE, WS_EX_CONTROLPARENT, WS_EX_DLGMODALFRAME, WS_SYSMENU, WS_TABSTOP, WS_VISIBLE,
+    WS_VSCROLL,
+};
+use windows::core::{PCWSTR, w};
+
+const OUTLINE_CLASS_NAME: &str = "NovapadOutline";
+const OUTLINE_ID_LIST: usize = 1891;
+const OUTLINE_ID_GO: usize = 1892;
+const OUTLINE_ID_CLOSE: usize = 1893;
+
+struct OutlineWindowState {
+    parent: HWND,
+    hwnd_list: HWND,
+    headings: Vec<Heading>,
+}
+
+/// What the list shows and the screen reader announces for a heading.
+pub fn describe_heading(language: Language, heading: &Heading) -> String {
+    i18n::tr_f(
+        language,
+        "outline.item",
+        &[
+            ("title", &heading.title),
+            ("level", &heading.level.to_string()),
+        ],
+    )
+}
+
+pub unsafe fn handle_navigation(hwnd: HWND, msg: &MSG) -> bool {
+    if msg.message == WM_KEYDOWN && msg.wParam.0 as u32 == VK_RETURN.0 as u32 {
+        let list = with_outline_state(hwnd, |s| s.hwnd_list).unwrap_or(HWND(0));
+        if GetFocus() == list {
+            go_to_selected(hwnd);
+            return true;
+        }
+    }
+    handle_accessibility(hwnd, msg)
+}
+
+/// Lists the headings of the current document, with the one around the caret
+/// selected.
+pub unsafe fn open(parent: HWND) {
+    let existing = with_state(parent, |state| state.outline_window).unwrap_or(HWND(0));
+    if existing.0 != 0 {
+        SetForegroundWindow(existing);
+        return;
+    }
+    let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+    let Some((headings, caret_line)) = crate::editor_manager::current_outline(parent) else {
+        return;
+    };
+    if headings.is_empty() {
+        crate::show_info(parent, language, &i18n::tr(language, "outline.no_headings"));
+        return;
+    }
+    let select = heading_at(&headings, caret_line).unwrap_or(0);
+
+    let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+    let class_name = to_wide(OUTLINE_CLASS_NAME);
+    let wc = WNDCLASSW {
+        hCursor: windows::Win32::UI::WindowsAndMessaging::HCURSOR(
+            LoadCursorW(None, IDC_ARROW).unwrap_or_default().0,
+        ),
+        hInstance: hinstance,
+        lpszClassName: PCWSTR(class_name.as_ptr()),
+        lpfnWndProc: Some(outline_wndproc),
+        hbrBackground: HBRUSH((COLOR_WINDOW.0 + 1) as isize),
+        ..Default::default()
+    };
+    RegisterClassW(&wc);
+
+    let title = to_wide(&i18n::tr(language, "outline.title"));
+    let state = Box::new(OutlineWindowState {
+        parent,
+        hwnd_list: HWND(0),
+        headings,
+    });
+    let state_ptr = Box::into_raw(state);
+    let window = CreateWindowExW(
+        WS_EX_CONTROLPARENT | WS_EX_DLGMODALFRAME,
+        PCWSTR(class_name.as_ptr()),
+        PCWSTR(title.as_ptr()),
+        WS_CAPTION | WS_SYSMENU | WS_VISIBLE,
+        CW_USEDEFAULT,
+        CW_USEDEFAULT,
+        560,
+        420,
+        parent,
+        None,
+        hinstance,
+        Some(state_ptr as *const _),
+    );
+    if window.0 == 0 {
+        drop(Box::from_raw(state_ptr));
+        return;
+    }
+
+    let Some(hwnd_list) = with_outline_state(window, |s| s.hwnd_list) else {
+        return;
+    };
+    with_state(parent, |state| state.outline_window = window);
+    EnableWindow(parent, false);
+    SetForegroundWindow(window);
+    SendMessageW(hwnd_list, LB_SETCURSEL, WPARAM(select), LPARAM(0));
+    SetFocus(hwnd_list);
+}
+
+unsafe extern "system" fn outline_wndproc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_CREATE => {
+            let create_struct = lparam.0 as *const CREATESTRUCTW;
+            let state_ptr = (*create_struct).lpCreateParams as *mut OutlineWindowState;
+            if state_ptr.is_null() {
+                return LRESULT(0);
+            }
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, state_ptr as isize);
+            let parent = (*state_ptr).parent;
+            let hfont = with_state(parent, |state| state.hfont).unwrap_or(HFONT(0));
+            let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+            let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+
+            let label = CreateWindowExW(
+                Default::default(),
+                w!("STATIC"),
+                PCWSTR(to_wide(&i18n::tr(language, "outline.list")).as_ptr()),
+                WS_CHILD | WS_VISIBLE,
+                10,
+                10,
+                520,
+                18,
+                hwnd,
+                HMENU(0),
+                hinstance,
+                None,
+            );
+            let hwnd_list = CreateWindowExW(
+                WS_EX_CLIENTEDGE,
+                WC_LISTBOXW,
+                PCWSTR::null(),
+                WS_CHILD
+                    | WS_VISIBLE
+                    | WS_VSCROLL
+                    | WS_TABSTOP
+                    | WINDOW_STYLE((LBS_NOTIFY | LBS_HASSTRINGS) as u32),
+                10,
+                30,
+                520,
+                290,
+                hwnd,
+                HMENU(OUTLINE_ID_LIST as isize),
+                hinstance,
+                None,
+            );
+            let go = create_button(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "outline.go"),
+                280,
+                OUTLINE_ID_GO,
+                true,
+            );
+            let close = create_button(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "outline.close"),
+                410,
+                OUTLINE_ID_CLOSE,
+                false,
+            );
+            for ctrl in [label, hwnd_list, go, close] {
+                if ctrl.0 != 0 && hfont.0 != 0 {
+                    SendMessageW(ctrl, WM_SETFONT, WPARAM(hfont.0 as usize), LPARAM(1));
+                }
+            }
+
+            // Deeper levels are indented for sighted users; the level is also spoken.
+            for heading in &(*state_ptr).headings {
+                let indent = "    ".repeat(heading.level.saturating_sub(1));
+                let item = format!("{indent}{}", describe_heading(language, heading));
+                let wide = to_wide(&item);
+                SendMessageW(
+                    hwnd_list,
+                    LB_ADDSTRING,
+                    WPARAM(0),
+                    LPARAM(wide.as_ptr() as isize),
+                );
+            }
+            (*state_ptr).hwnd_list = hwnd_list;
+            LRESULT(0)
+        }
+        WM_KEYDOWN => {
+            if wparam.0 as u32 == VK_ESCAPE.0 as u32 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_COMMAND => {
+            let cmd_id = wparam.0 & 0xffff;
+            let notify = (wparam.0 >> 16) as u16;
+            match cmd_id {
+                OUTLINE_ID_LIST if notify == LBN_DBLCLK as u16 => {
+                    go_to_selected(hwnd);
+                    LRESULT(0)
+                }
+                OUTLINE_ID_GO => {
+                    go_to_selected(hwnd);
+                    LRESULT(0)
+                }
+                cmd if cmd == OUTLINE_ID_CLOSE || cmd == 2 => {
+                    crate::log_if_err!(DestroyWindow(hwnd));
+                    LRESULT(0)
+                }
+                _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+            }
+        }
+        WM_CLOSE => {
+            crate::log_if_err!(DestroyWindow(hwnd));
+            LRESULT(0)
+        }
+        WM_DESTROY => {
+            let parent = with_outline_state(hwnd, |s| s.parent).unwrap_or(HWND(0));
+            if parent.0 != 0 {
+                EnableWindow(parent, true);
+                SetForegroundWindow(parent);
+                with_state(parent, |state| state.outline_window = HWND(0));
+                if let Some(hwnd_edit) = crate::get_active_edit(parent) {
+                    SetFocus(hwnd_edit);
+                }
+            }
+            LRESULT(0)
+        }
+        WM_NCDESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut OutlineWindowState;
+            if !ptr.is_null() {
+                drop(Box::from_raw(ptr));
+            }
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+unsafe fn create_button(
+    hwnd: HWND,
+    hinstance: HINSTANCE,
+    text: &str,
+    x: i32,
+    id: usize,
+    default: bool,
+) -> HWND {
+    let mut style = WS_CHILD | WS_VISIBLE | WS_TABSTOP;
+    if default {
+        style |= WINDOW_STYLE(BS_DEFPUSHBUTTON as u32);
+    }
+    CreateWindowExW(
+        Default::default(),
+        WC_BUTTON,
+        PCWSTR(to_wide(text).as_ptr()),
+        style,
+        x,
+        330,
+        120,
+        30,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    )
+}
+
+unsafe fn with_outline_state<F, R>(hwnd: HWND, f: F) -> Option<R>
+where
+    F: FnOnce(&mut OutlineWindowState) -> R,
+{
+    let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut OutlineWindowState;
+    if ptr.is_null() {
+        None
+    } else {
+        Some(f(&mut *ptr))
+    }
+}
+
+/// Closes the outline and moves the caret to the selected heading.
+unsafe fn go_to_selected(hwnd: HWND) {
+    let Some((parent, line)) = with_outline_state(hwnd, |s| {
+        let selected = SendMessageW(s.hwnd_list, LB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+        let heading = usize::try_from(selected)
+            .ok()
+            .and_then(|index| s.headings.get(index))?;
+        Some((s.parent, heading.line))
+    })
+    .flatten() else {
+        return;
+    };
+    crate::log_if_err!(DestroyWindow(hwnd));
+    crate::editor_manager::go_to_line(parent, line);
+}
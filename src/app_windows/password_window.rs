@@ -0,0 +1,362 @@
+use std::sync::{Arc, Mutex};
+
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{COLOR_WINDOW, HBRUSH, HFONT};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Controls::{WC_BUTTON, WC_STATIC};
+use windows::Win32::UI::Input::KeyboardAndMouse::{EnableWindow, SetFocus, VK_ESCAPE};
+use windows::Win32::UI::WindowsAndMessaging::{
+    BS_DEFPUSHBUTTON, CREATESTRUCTW, CW_USEDEFAULT, CreateWindowExW, DefWindowProcW, DestroyWindow,
+    DispatchMessageW, ES_AUTOHSCROLL, ES_PASSWORD, GWLP_USERDATA, GetMessageW, GetWindowLongPtrW,
+    HMENU, IDC_ARROW, IsDialogMessageW, IsWindow, LoadCursorW, MSG, PostMessageW, RegisterClassW,
+    SendMessageW, SetForegroundWindow, SetWindowLongPtrW, SetWindowTextW, TranslateMessage,
+    WINDOW_STYLE, WM_CLOSE, WM_COMMAND, WM_CREATE, WM_DESTROY, WM_GETTEXT, WM_GETTEXTLENGTH,
+    WM_KEYDOWN, WM_NCDESTROY, WM_SETFONT, WNDCLASSW, WS_CAPTION, WS_CHILD, WS_EX_CLIENTEDGE,
+    WS_EX_CONTROLPARENT, WS_EX_DLGMODALFRAME, WS_SYSMENU, WS_TABSTOP, WS_VISIBLE,
+};
+use windows::core::{PCWSTR, w};
+
+use crate::accessibility::{nvda_speak, to_wide};
+use crate::i18n;
+use crate::settings::Language;
+use crate::with_state;
+
+const PASSWORD_CLASS_NAME: &str = "NovapadPassword";
+const PASSWORD_ID_INPUT: usize = 9105;
+const PASSWORD_ID_CONFIRM: usize = 9106;
+const PASSWORD_ID_STATUS: usize = 9107;
+const PASSWORD_ID_OK: usize = 9108;
+const PASSWORD_ID_CANCEL: usize = 9109;
+
+struct PasswordInit {
+    parent: HWND,
+    language: Language,
+    confirm: bool,
+    status: String,
+    result: Arc<Mutex<Option<String>>>,
+}
+
+struct PasswordState {
+    parent: HWND,
+    language: Language,
+    input: HWND,
+    /// Second password box, only when choosing a new password.
+    confirm: Option<HWND>,
+    status: HWND,
+    result: Arc<Mutex<Option<String>>>,
+}
+
+/// Asks for a password in a modal window; with `confirm` it must be typed twice, as
+/// when encrypting. `status` is shown, and spoken, when the window opens, such as
+/// after a wrong password. None when cancelled.
+pub fn ask_password(
+    parent: HWND,
+    language: Language,
+    title: &str,
+    confirm: bool,
+    status: &str,
+) -> Option<String> {
+    let hinstance = HINSTANCE(unsafe { GetModuleHandleW(None).unwrap_or_default().0 });
+    let class_name = to_wide(PASSWORD_CLASS_NAME);
+    let wc = WNDCLASSW {
+        hCursor: windows::Win32::UI::WindowsAndMessaging::HCURSOR(unsafe {
+            LoadCursorW(None, IDC_ARROW).unwrap_or_default().0
+        }),
+        hInstance: hinstance,
+        lpszClassName: PCWSTR(class_name.as_ptr()),
+        lpfnWndProc: Some(password_wndproc),
+        hbrBackground: HBRUSH((COLOR_WINDOW.0 + 1) as isize),
+        ..Default::default()
+    };
+    unsafe { RegisterClassW(&wc) };
+
+    let result = Arc::new(Mutex::new(None));
+    let init = Box::new(PasswordInit {
+        parent,
+        language,
+        confirm,
+        status: status.to_string(),
+        result: result.clone(),
+    });
+    let title = to_wide(title);
+    let hwnd = unsafe {
+        CreateWindowExW(
+            WS_EX_CONTROLPARENT | WS_EX_DLGMODALFRAME,
+            PCWSTR(class_name.as_ptr()),
+            PCWSTR(title.as_ptr()),
+            WS_CAPTION | WS_SYSMENU | WS_VISIBLE,
+            CW_USEDEFAULT,
+            CW_USEDEFAULT,
+            420,
+            if confirm { 250 } else { 190 },
+            parent,
+            HMENU(0),
+            hinstance,
+            Some(Box::into_raw(init) as *const _),
+        )
+    };
+    if hwnd.0 == 0 {
+        return None;
+    }
+
+    unsafe {
+        EnableWindow(parent, false);
+        SetForegroundWindow(hwnd);
+    }
+    if !status.is_empty() {
+        nvda_speak(status);
+    }
+
+    let mut msg = MSG::default();
+    loop {
+        if !unsafe { IsWindow(hwnd).as_bool() } {
+            break;
+        }
+        let res = unsafe { GetMessageW(&mut msg, HWND(0), 0, 0) };
+        if res.0 == 0 {
+            break;
+        }
+        unsafe {
+            if msg.message == WM_KEYDOWN && msg.wParam.0 as u32 == VK_ESCAPE.0 as u32 {
+                crate::log_if_err!(PostMessageW(
+                    hwnd,
+                    WM_COMMAND,
+                    WPARAM(PASSWORD_ID_CANCEL),
+                    LPARAM(0)
+                ));
+                continue;
+            }
+            if IsDialogMessageW(hwnd, &msg).as_bool() {
+                continue;
+            }
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    }
+
+    unsafe {
+        EnableWindow(parent, true);
+        SetForegroundWindow(parent);
+    }
+
+    result.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+unsafe fn create_label(hwnd: HWND, text: &str, y: i32, id: usize) -> HWND {
+    CreateWindowExW(
+        Default::default(),
+        WC_STATIC,
+        PCWSTR(to_wide(text).as_ptr()),
+        WS_CHILD | WS_VISIBLE,
+        16,
+        y,
+        370,
+        20,
+        hwnd,
+        HMENU(id as isize),
+        HINSTANCE(0),
+        None,
+    )
+}
+
+unsafe fn create_password_input(hwnd: HWND, y: i32, id: usize) -> HWND {
+    CreateWindowExW(
+        WS_EX_CLIENTEDGE,
+        w!("EDIT"),
+        PCWSTR::null(),
+        WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE((ES_AUTOHSCROLL | ES_PASSWORD) as u32),
+        16,
+        y,
+        370,
+        24,
+        hwnd,
+        HMENU(id as isize),
+        HINSTANCE(0),
+        None,
+    )
+}
+
+unsafe fn create_button(hwnd: HWND, text: &str, x: i32, y: i32, id: usize) -> HWND {
+    let mut style = WS_CHILD | WS_VISIBLE | WS_TABSTOP;
+    if id == PASSWORD_ID_OK {
+        style |= WINDOW_STYLE(BS_DEFPUSHBUTTON as u32);
+    }
+    CreateWindowExW(
+        Default::default(),
+        WC_BUTTON,
+        PCWSTR(to_wide(text).as_ptr()),
+        style,
+        x,
+        y,
+        90,
+        28,
+        hwnd,
+        HMENU(id as isize),
+        HINSTANCE(0),
+        None,
+    )
+}
+
+unsafe fn read_password(input: HWND) -> String {
+    let len = SendMessageW(input, WM_GETTEXTLENGTH, WPARAM(0), LPARAM(0)).0;
+    let mut buf = vec![0u16; len as usize + 1];
+    SendMessageW(
+        input,
+        WM_GETTEXT,
+        WPARAM(buf.len()),
+        LPARAM(buf.as_mut_ptr() as isize),
+    );
+    String::from_utf16_lossy(&buf[..len as usize])
+}
+
+unsafe fn show_status(state: &PasswordState, key: &str, focus: HWND) {
+    let message = i18n::tr(state.language, key);
+    crate::log_if_err!(SetWindowTextW(
+        state.status,
+        PCWSTR(to_wide(&message).as_ptr())
+    ));
+    nvda_speak(&message);
+    SetFocus(focus);
+}
+
+unsafe extern "system" fn password_wndproc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_CREATE => {
+            let create_struct = lparam.0 as *const CREATESTRUCTW;
+            let init_ptr = (*create_struct).lpCreateParams as *mut PasswordInit;
+            if init_ptr.is_null() {
+                return LRESULT(0);
+            }
+            let init = Box::from_raw(init_ptr);
+            let language = init.language;
+            let hfont = with_state(init.parent, |state| state.hfont).unwrap_or(HFONT(0));
+
+            let mut controls = vec![create_label(
+                hwnd,
+                &i18n::tr(language, "encryption.password"),
+                14,
+                0,
+            )];
+            let input = create_password_input(hwnd, 36, PASSWORD_ID_INPUT);
+            controls.push(input);
+            let mut y = 72;
+            let confirm = if init.confirm {
+                controls.push(create_label(
+                    hwnd,
+                    &i18n::tr(language, "encryption.confirm_password"),
+                    y,
+                    0,
+                ));
+                let confirm = create_password_input(hwnd, y + 22, PASSWORD_ID_CONFIRM);
+                controls.push(confirm);
+                y += 58;
+                Some(confirm)
+            } else {
+                None
+            };
+            let status = create_label(hwnd, &init.status, y, PASSWORD_ID_STATUS);
+            controls.push(status);
+            controls.push(create_button(
+                hwnd,
+                &i18n::tr(language, "encryption.ok"),
+                200,
+                y + 28,
+                PASSWORD_ID_OK,
+            ));
+            controls.push(create_button(
+                hwnd,
+                &i18n::tr(language, "encryption.cancel"),
+                296,
+                y + 28,
+                PASSWORD_ID_CANCEL,
+            ));
+            for control in controls {
+                if control.0 != 0 && hfont.0 != 0 {
+                    SendMessageW(control, WM_SETFONT, WPARAM(hfont.0 as usize), LPARAM(1));
+                }
+            }
+
+            let state = Box::new(PasswordState {
+                parent: init.parent,
+                language,
+                input,
+                confirm,
+                status,
+                result: init.result.clone(),
+            });
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, Box::into_raw(state) as isize);
+            SetFocus(input);
+            LRESULT(0)
+        }
+        WM_COMMAND => {
+            let cmd_id = wparam.0 & 0xffff;
+            if cmd_id == PASSWORD_ID_OK || cmd_id == 1 {
+                let accepted = with_password_state(hwnd, |state| {
+                    let password = read_password(state.input);
+                    if password.is_empty() {
+                        show_status(state, "encryption.empty_password", state.input);
+                        return false;
+                    }
+                    if let Some(confirm) = state.confirm
+                        && read_password(confirm) != password
+                    {
+                        show_status(state, "encryption.password_mismatch", confirm);
+                        return false;
+                    }
+                    *state.result.lock().unwrap_or_else(|e| e.into_inner()) = Some(password);
+                    true
+                })
+                .unwrap_or(false);
+                if accepted {
+                    crate::log_if_err!(PostMessageW(hwnd, WM_CLOSE, WPARAM(0), LPARAM(0)));
+                }
+                LRESULT(0)
+            } else if cmd_id == PASSWORD_ID_CANCEL || cmd_id == 2 {
+                crate::log_if_err!(PostMessageW(hwnd, WM_CLOSE, WPARAM(0), LPARAM(0)));
+                LRESULT(0)
+            } else {
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
+        }
+        WM_CLOSE => {
+            crate::log_if_err!(DestroyWindow(hwnd));
+            LRESULT(0)
+        }
+        WM_DESTROY => {
+            if with_password_state(hwnd, |state| {
+                EnableWindow(state.parent, true);
+                SetForegroundWindow(state.parent);
+            })
+            .is_none()
+            {
+                crate::log_debug("Failed to access password state");
+            }
+            LRESULT(0)
+        }
+        WM_NCDESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut PasswordState;
+            if !ptr.is_null() {
+                drop(Box::from_raw(ptr));
+            }
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+unsafe fn with_password_state<F, R>(hwnd: HWND, f: F) -> Option<R>
+where
+    F: FnOnce(&mut PasswordState) -> R,
+{
+    let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut PasswordState;
+    if ptr.is_null() {
+        None
+    } else {
+        Some(f(&mut *ptr))
+    }
+}
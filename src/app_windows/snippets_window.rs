@@ -0,0 +1,600 @@
+use crate::accessibility::{handle_accessibility, nvda_speak, to_wide};
+use crate::i18n;
+use crate::snippets::{MAX_ABBREVIATION_CHARS, Snippet, save_snippets, templates_dir};
+use crate::with_state;
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{COLOR_WINDOW, HBRUSH, HFONT};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Controls::{WC_BUTTON, WC_LISTBOXW};
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    EnableWindow, GetFocus, SetFocus, VK_ESCAPE, VK_RETURN,
+};
+use windows::Win32::UI::Shell::ShellExecuteW;
+use windows::Win32::UI::WindowsAndMessaging::{
+    BS_DEFPUSHBUTTON, CREATESTRUCTW, CW_USEDEFAULT, CreateWindowExW, DefWindowProcW, DestroyWindow,
+    ES_AUTOHSCROLL, ES_AUTOVSCROLL, ES_MULTILINE, ES_WANTRETURN, GWLP_USERDATA, GetDlgItem,
+    GetWindowLongPtrW, HMENU, IDC_ARROW, LB_ADDSTRING, LB_GETCURSEL, LB_RESETCONTENT, LB_SETCURSEL,
+    LBN_SELCHANGE, LBS_HASSTRINGS, LBS_NOTIFY, LoadCursorW, MSG, RegisterClassW, SW_SHOW,
+    SendMessageW, SetForegroundWindow, SetWindowLongPtrW, SetWindowTextW, WINDOW_STYLE, WM_CLOSE,
+    WM_COMMAND, WM_CREATE, WM_DESTROY, WM_GETTEXT, WM_GETTEXTLENGTH, WM_KEYDOWN, WM_NCDESTROY,
+    WM_SETFONT, WNDCLASSW, WS_CAPTION, WS_CHILD, WS_EX_CLIENTEDGE, WS_EX_CONTROLPARENT,
+    WS_EX_DLGMODALFRAME, WS_SYSMENU, WS_TABSTOP, WS_VISIBLE, WS_VSCROLL,
+};
+use windows::core::{PCWSTR, w};
+
+const SNIPPETS_CLASS_NAME: &str = "NovapadSnippets";
+const SNIPPETS_ID_LIST: usize = 1881;
+const SNIPPETS_ID_ABBREVIATION: usize = 1882;
+const SNIPPETS_ID_TEXT: usize = 1883;
+const SNIPPETS_ID_NEW: usize = 1884;
+const SNIPPETS_ID_SAVE: usize = 1885;
+const SNIPPETS_ID_DELETE: usize = 1886;
+const SNIPPETS_ID_OPEN_FOLDER: usize = 1887;
+const SNIPPETS_ID_CLOSE: usize = 1888;
+const SNIPPETS_ID_STATUS: usize = 1889;
+
+struct SnippetsWindowState {
+    parent: HWND,
+    hwnd_list: HWND,
+    hwnd_abbreviation: HWND,
+    hwnd_text: HWND,
+}
+
+pub unsafe fn handle_navigation(hwnd: HWND, msg: &MSG) -> bool {
+    if msg.message == WM_KEYDOWN && msg.wParam.0 as u32 == VK_RETURN.0 as u32 {
+        let focus = GetFocus();
+        let Some((list, abbreviation)) =
+            with_snippets_state(hwnd, |s| (s.hwnd_list, s.hwnd_abbreviation))
+        else {
+            return false;
+        };
+        if focus == list {
+            SetFocus(abbreviation);
+            return true;
+        }
+        if focus == abbreviation {
+            save_snippet(hwnd);
+            return true;
+        }
+    }
+    handle_accessibility(hwnd, msg)
+}
+
+pub unsafe fn open(parent: HWND) {
+    let existing = with_state(parent, |state| state.snippets_window).unwrap_or(HWND(0));
+    if existing.0 != 0 {
+        SetForegroundWindow(existing);
+        return;
+    }
+    let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+
+    let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+    let class_name = to_wide(SNIPPETS_CLASS_NAME);
+    let wc = WNDCLASSW {
+        hCursor: windows::Win32::UI::WindowsAndMessaging::HCURSOR(
+            LoadCursorW(None, IDC_ARROW).unwrap_or_default().0,
+        ),
+        hInstance: hinstance,
+        lpszClassName: PCWSTR(class_name.as_ptr()),
+        lpfnWndProc: Some(snippets_wndproc),
+        hbrBackground: HBRUSH((COLOR_WINDOW.0 + 1) as isize),
+        ..Default::default()
+    };
+    RegisterClassW(&wc);
+
+    let title = to_wide(&i18n::tr(language, "snippets.title"));
+    let state = Box::new(SnippetsWindowState {
+        parent,
+        hwnd_list: HWND(0),
+        hwnd_abbreviation: HWND(0),
+        hwnd_text: HWND(0),
+    });
+    let state_ptr = Box::into_raw(state);
+    let window = CreateWindowExW(
+        WS_EX_CONTROLPARENT | WS_EX_DLGMODALFRAME,
+        PCWSTR(class_name.as_ptr()),
+        PCWSTR(title.as_ptr()),
+        WS_CAPTION | WS_SYSMENU | WS_VISIBLE,
+        CW_USEDEFAULT,
+        CW_USEDEFAULT,
+        560,
+        570,
+        parent,
+        None,
+        hinstance,
+        Some(state_ptr as *const _),
+    );
+    if window.0 == 0 {
+        drop(Box::from_raw(state_ptr));
+        return;
+    }
+
+    let Some(hwnd_list) = with_snippets_state(window, |s| s.hwnd_list) else {
+        return;
+    };
+    with_state(parent, |state| state.snippets_window = window);
+    EnableWindow(parent, false);
+    SetForegroundWindow(window);
+    refresh_list(window, Some(0));
+    SetFocus(hwnd_list);
+}
+
+unsafe extern "system" fn snippets_wndproc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_CREATE => {
+            let create_struct = lparam.0 as *const CREATESTRUCTW;
+            let state_ptr = (*create_struct).lpCreateParams as *mut SnippetsWindowState;
+            if state_ptr.is_null() {
+                return LRESULT(0);
+            }
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, state_ptr as isize);
+            let parent = (*state_ptr).parent;
+            let hfont = with_state(parent, |state| state.hfont).unwrap_or(HFONT(0));
+            let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+            let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+
+            let label_list = create_static(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "snippets.list"),
+                10,
+                18,
+                0,
+            );
+            let hwnd_list = CreateWindowExW(
+                WS_EX_CLIENTEDGE,
+                WC_LISTBOXW,
+                PCWSTR::null(),
+                WS_CHILD
+                    | WS_VISIBLE
+                    | WS_VSCROLL
+                    | WS_TABSTOP
+                    | WINDOW_STYLE((LBS_NOTIFY | LBS_HASSTRINGS) as u32),
+                10,
+                30,
+                520,
+                120,
+                hwnd,
+                HMENU(SNIPPETS_ID_LIST as isize),
+                hinstance,
+                None,
+            );
+            let label_abbreviation = create_static(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "snippets.abbreviation"),
+                160,
+                18,
+                0,
+            );
+            let hwnd_abbreviation = CreateWindowExW(
+                WS_EX_CLIENTEDGE,
+                w!("EDIT"),
+                PCWSTR::null(),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(ES_AUTOHSCROLL as u32),
+                10,
+                180,
+                520,
+                24,
+                hwnd,
+                HMENU(SNIPPETS_ID_ABBREVIATION as isize),
+                hinstance,
+                None,
+            );
+            let label_text = create_static(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "snippets.text"),
+                214,
+                18,
+                0,
+            );
+            let hwnd_text = CreateWindowExW(
+                WS_EX_CLIENTEDGE,
+                w!("EDIT"),
+                PCWSTR::null(),
+                WS_CHILD
+                    | WS_VISIBLE
+                    | WS_TABSTOP
+                    | WS_VSCROLL
+                    | WINDOW_STYLE((ES_MULTILINE | ES_WANTRETURN | ES_AUTOVSCROLL) as u32),
+                10,
+                234,
+                520,
+                110,
+                hwnd,
+                HMENU(SNIPPETS_ID_TEXT as isize),
+                hinstance,
+                None,
+            );
+            let help = create_static(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "snippets.help"),
+                352,
+                54,
+                0,
+            );
+            let status = create_static(hwnd, hinstance, "", 412, 18, SNIPPETS_ID_STATUS);
+            let buttons = [
+                ("snippets.new", SNIPPETS_ID_NEW, 10, 440),
+                ("snippets.save", SNIPPETS_ID_SAVE, 140, 440),
+                ("snippets.delete", SNIPPETS_ID_DELETE, 270, 440),
+                ("snippets.open_folder", SNIPPETS_ID_OPEN_FOLDER, 10, 480),
+                ("snippets.close", SNIPPETS_ID_CLOSE, 400, 480),
+            ]
+            .map(|(key, id, x, y)| {
+                let width = if id == SNIPPETS_ID_OPEN_FOLDER {
+                    250
+                } else {
+                    120
+                };
+                create_button(hwnd, hinstance, &i18n::tr(language, key), x, y, width, id)
+            });
+
+            for ctrl in [
+                label_list,
+                hwnd_list,
+                label_abbreviation,
+                hwnd_abbreviation,
+                label_text,
+                hwnd_text,
+                help,
+                status,
+            ]
+            .into_iter()
+            .chain(buttons)
+            {
+                if ctrl.0 != 0 && hfont.0 != 0 {
+                    SendMessageW(ctrl, WM_SETFONT, WPARAM(hfont.0 as usize), LPARAM(1));
+                }
+            }
+
+            (*state_ptr).hwnd_list = hwnd_list;
+            (*state_ptr).hwnd_abbreviation = hwnd_abbreviation;
+            (*state_ptr).hwnd_text = hwnd_text;
+            LRESULT(0)
+        }
+        WM_KEYDOWN => {
+            if wparam.0 as u32 == VK_ESCAPE.0 as u32 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_COMMAND => {
+            let cmd_id = wparam.0 & 0xffff;
+            let notify = (wparam.0 >> 16) as u16;
+            match cmd_id {
+                SNIPPETS_ID_LIST if notify == LBN_SELCHANGE as u16 => {
+                    fill_fields(hwnd);
+                    LRESULT(0)
+                }
+                SNIPPETS_ID_NEW => {
+                    new_snippet(hwnd);
+                    LRESULT(0)
+                }
+                SNIPPETS_ID_SAVE => {
+                    save_snippet(hwnd);
+                    LRESULT(0)
+                }
+                SNIPPETS_ID_DELETE => {
+                    delete_selected(hwnd);
+                    LRESULT(0)
+                }
+                SNIPPETS_ID_OPEN_FOLDER => {
+                    open_templates_folder();
+                    LRESULT(0)
+                }
+                cmd if cmd == SNIPPETS_ID_CLOSE || cmd == 2 => {
+                    crate::log_if_err!(DestroyWindow(hwnd));
+                    LRESULT(0)
+                }
+                _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+            }
+        }
+        WM_CLOSE => {
+            crate::log_if_err!(DestroyWindow(hwnd));
+            LRESULT(0)
+        }
+        WM_DESTROY => {
+            let parent = with_snippets_state(hwnd, |s| s.parent).unwrap_or(HWND(0));
+            if parent.0 != 0 {
+                EnableWindow(parent, true);
+                SetForegroundWindow(parent);
+                with_state(parent, |state| state.snippets_window = HWND(0));
+                if let Some(hwnd_edit) = crate::get_active_edit(parent) {
+                    SetFocus(hwnd_edit);
+                }
+            }
+            LRESULT(0)
+        }
+        WM_NCDESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut SnippetsWindowState;
+            if !ptr.is_null() {
+                drop(Box::from_raw(ptr));
+            }
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+unsafe fn create_static(
+    hwnd: HWND,
+    hinstance: HINSTANCE,
+    text: &str,
+    y: i32,
+    height: i32,
+    id: usize,
+) -> HWND {
+    CreateWindowExW(
+        Default::default(),
+        w!("STATIC"),
+        PCWSTR(to_wide(text).as_ptr()),
+        WS_CHILD | WS_VISIBLE,
+        10,
+        y,
+        520,
+        height,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    )
+}
+
+unsafe fn create_button(
+    hwnd: HWND,
+    hinstance: HINSTANCE,
+    text: &str,
+    x: i32,
+    y: i32,
+    width: i32,
+    id: usize,
+) -> HWND {
+    let mut style = WS_CHILD | WS_VISIBLE | WS_TABSTOP;
+    if id == SNIPPETS_ID_SAVE {
+        style |= WINDOW_STYLE(BS_DEFPUSHBUTTON as u32);
+    }
+    CreateWindowExW(
+        Default::default(),
+        WC_BUTTON,
+        PCWSTR(to_wide(text).as_ptr()),
+        style,
+        x,
+        y,
+        width,
+        30,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    )
+}
+
+unsafe fn with_snippets_state<F, R>(hwnd: HWND, f: F) -> Option<R>
+where
+    F: FnOnce(&mut SnippetsWindowState) -> R,
+{
+    let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut SnippetsWindowState;
+    if ptr.is_null() {
+        None
+    } else {
+        Some(f(&mut *ptr))
+    }
+}
+
+/// Fills the list from the snippet library and selects the entry at `select`.
+unsafe fn refresh_list(hwnd: HWND, select: Option<usize>) {
+    let Some((parent, hwnd_list)) = with_snippets_state(hwnd, |s| (s.parent, s.hwnd_list)) else {
+        return;
+    };
+    let items = with_state(parent, |state| {
+        let language = state.settings.language;
+        state
+            .snippets
+            .snippets
+            .iter()
+            .map(|snippet| {
+                let first_line = snippet.text.lines().next().unwrap_or_default();
+                i18n::tr_f(
+                    language,
+                    "snippets.item",
+                    &[
+                        ("abbreviation", &snippet.abbreviation),
+                        ("text", first_line),
+                    ],
+                )
+            })
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_default();
+    SendMessageW(hwnd_list, LB_RESETCONTENT, WPARAM(0), LPARAM(0));
+    for item in &items {
+        let wide = to_wide(item);
+        SendMessageW(
+            hwnd_list,
+            LB_ADDSTRING,
+            WPARAM(0),
+            LPARAM(wide.as_ptr() as isize),
+        );
+    }
+    if let Some(select) = select.filter(|_| !items.is_empty()) {
+        let index = select.min(items.len() - 1);
+        SendMessageW(hwnd_list, LB_SETCURSEL, WPARAM(index), LPARAM(0));
+    }
+    fill_fields(hwnd);
+}
+
+/// Shows the abbreviation and text of the selected snippet in the edit fields.
+unsafe fn fill_fields(hwnd: HWND) {
+    let Some((parent, hwnd_abbreviation, hwnd_text)) =
+        with_snippets_state(hwnd, |s| (s.parent, s.hwnd_abbreviation, s.hwnd_text))
+    else {
+        return;
+    };
+    let selected = selected_index(hwnd).and_then(|index| {
+        with_state(parent, |state| state.snippets.snippets.get(index).cloned()).flatten()
+    });
+    let (abbreviation, text) = selected
+        .map(|snippet| (snippet.abbreviation, snippet.text))
+        .unwrap_or_default();
+    let text = text.replace("\r\n", "\n").replace('\n', "\r\n");
+    let wide = to_wide(&abbreviation);
+    crate::log_if_err!(SetWindowTextW(hwnd_abbreviation, PCWSTR(wide.as_ptr())));
+    let wide = to_wide(&text);
+    crate::log_if_err!(SetWindowTextW(hwnd_text, PCWSTR(wide.as_ptr())));
+}
+
+unsafe fn selected_index(hwnd: HWND) -> Option<usize> {
+    let hwnd_list = with_snippets_state(hwnd, |s| s.hwnd_list)?;
+    let selected = SendMessageW(hwnd_list, LB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+    usize::try_from(selected).ok()
+}
+
+unsafe fn read_text(hwnd_edit: HWND) -> String {
+    let len = SendMessageW(hwnd_edit, WM_GETTEXTLENGTH, WPARAM(0), LPARAM(0)).0;
+    let mut buf = vec![0u16; len as usize + 1];
+    SendMessageW(
+        hwnd_edit,
+        WM_GETTEXT,
+        WPARAM(buf.len()),
+        LPARAM(buf.as_mut_ptr() as isize),
+    );
+    String::from_utf16_lossy(&buf[..len as usize])
+}
+
+unsafe fn show_status(hwnd: HWND, message: &str) {
+    let status = GetDlgItem(hwnd, SNIPPETS_ID_STATUS as i32);
+    let wide = to_wide(message);
+    crate::log_if_err!(SetWindowTextW(status, PCWSTR(wide.as_ptr())));
+    nvda_speak(message);
+}
+
+/// Clears the selection and the fields so that Save adds a new snippet.
+unsafe fn new_snippet(hwnd: HWND) {
+    let Some((hwnd_list, hwnd_abbreviation)) =
+        with_snippets_state(hwnd, |s| (s.hwnd_list, s.hwnd_abbreviation))
+    else {
+        return;
+    };
+    SendMessageW(hwnd_list, LB_SETCURSEL, WPARAM(usize::MAX), LPARAM(0));
+    fill_fields(hwnd);
+    SetFocus(hwnd_abbreviation);
+}
+
+/// Stores the edited snippet over the selected one, or adds it when nothing is
+/// selected. An abbreviation can only belong to one snippet, so any other snippet
+/// with the same abbreviation is replaced.
+unsafe fn save_snippet(hwnd: HWND) {
+    let Some((parent, hwnd_abbreviation, hwnd_text)) =
+        with_snippets_state(hwnd, |s| (s.parent, s.hwnd_abbreviation, s.hwnd_text))
+    else {
+        return;
+    };
+    let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+    let abbreviation = read_text(hwnd_abbreviation).trim().to_string();
+    if abbreviation.is_empty()
+        || abbreviation.chars().any(char::is_whitespace)
+        || abbreviation.chars().count() > MAX_ABBREVIATION_CHARS
+    {
+        let max = MAX_ABBREVIATION_CHARS.to_string();
+        show_status(
+            hwnd,
+            &i18n::tr_f(language, "snippets.invalid_abbreviation", &[("max", &max)]),
+        );
+        SetFocus(hwnd_abbreviation);
+        return;
+    }
+    let text = read_text(hwnd_text);
+    if text.trim().is_empty() {
+        show_status(hwnd, &i18n::tr(language, "snippets.empty_text"));
+        SetFocus(hwnd_text);
+        return;
+    }
+    let selected = selected_index(hwnd);
+    let Some(index) = with_state(parent, |state| {
+        let snippets = &mut state.snippets.snippets;
+        let snippet = Snippet {
+            abbreviation: abbreviation.clone(),
+            text,
+        };
+        let mut index = match selected.filter(|&index| index < snippets.len()) {
+            Some(index) => {
+                snippets[index] = snippet;
+                index
+            }
+            None => {
+                snippets.push(snippet);
+                snippets.len() - 1
+            }
+        };
+        if let Some(duplicate) =
+            (0..snippets.len()).find(|&i| i != index && snippets[i].abbreviation == abbreviation)
+        {
+            snippets.remove(duplicate);
+            if duplicate < index {
+                index -= 1;
+            }
+        }
+        save_snippets(&state.snippets);
+        index
+    }) else {
+        return;
+    };
+    refresh_list(hwnd, Some(index));
+    show_status(
+        hwnd,
+        &i18n::tr_f(
+            language,
+            "snippets.saved",
+            &[("abbreviation", &abbreviation)],
+        ),
+    );
+}
+
+unsafe fn delete_selected(hwnd: HWND) {
+    let Some(index) = selected_index(hwnd) else {
+        return;
+    };
+    let Some((parent, hwnd_list)) = with_snippets_state(hwnd, |s| (s.parent, s.hwnd_list)) else {
+        return;
+    };
+    let Some((language, removed)) = with_state(parent, |state| {
+        let removed = state.snippets.snippets.remove(index);
+        save_snippets(&state.snippets);
+        (state.settings.language, removed)
+    }) else {
+        return;
+    };
+    refresh_list(hwnd, Some(index));
+    SetFocus(hwnd_list);
+    show_status(
+        hwnd,
+        &i18n::tr_f(
+            language,
+            "snippets.deleted",
+            &[("abbreviation", &removed.abbreviation)],
+        ),
+    );
+}
+
+unsafe fn open_templates_folder() {
+    let dir = templates_dir();
+    crate::log_if_err!(std::fs::create_dir_all(&dir));
+    let wide = to_wide(&dir.to_string_lossy());
+    ShellExecuteW(
+        HWND(0),
+        w!("open"),
+        PCWSTR(wide.as_ptr()),
+        PCWSTR::null(),
+        PCWSTR::null(),
+        SW_SHOW,
+    );
+}
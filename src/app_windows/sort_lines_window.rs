@@ -0,0 +1,460 @@
+use crate::accessibility::{handle_accessibility, nvda_speak, to_wide};
+use crate::i18n;
+use crate::settings::{SortMode, save_settings};
+use crate::text_ops::SortOptions;
+use crate::with_state;
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{COLOR_WINDOW, HBRUSH};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Controls::{BST_CHECKED, WC_BUTTON, WC_COMBOBOXW};
+use windows::Win32::UI::Input::KeyboardAndMouse::{EnableWindow, SetFocus};
+use windows::Win32::UI::WindowsAndMessaging::{
+    BM_GETCHECK, BM_SETCHECK, BS_AUTOCHECKBOX, BS_DEFPUSHBUTTON, CB_ADDSTRING, CB_GETCURSEL,
+    CB_SETCURSEL, CBS_DROPDOWNLIST, CREATESTRUCTW, CW_USEDEFAULT, CreateWindowExW, DefWindowProcW,
+    DestroyWindow, GWLP_USERDATA, GetDlgItem, GetWindowLongPtrW, HMENU, IDC_ARROW, LoadCursorW,
+    RegisterClassW, SendMessageW, SetForegroundWindow, SetWindowLongPtrW, SetWindowTextW,
+    WINDOW_STYLE, WM_COMMAND, WM_CREATE, WM_DESTROY, WM_GETTEXT, WM_GETTEXTLENGTH, WM_KEYDOWN,
+    WM_NCDESTROY, WNDCLASSW, WS_CAPTION, WS_CHILD, WS_EX_CLIENTEDGE, WS_EX_DLGMODALFRAME, WS_POPUP,
+    WS_TABSTOP, WS_VISIBLE,
+};
+use windows::core::{PCWSTR, w};
+
+const SORT_LINES_CLASS: &str = "NovapadSortLines";
+const SORT_LINES_MODE_ID: usize = 1841;
+const SORT_LINES_FIELD_ID: usize = 1842;
+const SORT_LINES_DELIMITER_ID: usize = 1843;
+const SORT_LINES_DESCENDING_ID: usize = 1844;
+const SORT_LINES_CASE_ID: usize = 1845;
+const SORT_LINES_ACCENTS_ID: usize = 1846;
+const SORT_LINES_DUPLICATES_ID: usize = 1847;
+const SORT_LINES_OK_ID: usize = 1848;
+const SORT_LINES_CANCEL_ID: usize = 1849;
+const SORT_LINES_STATUS_ID: usize = 1850;
+
+const MAX_FIELD: usize = 99;
+
+const SORT_MODES: [(SortMode, &str); 3] = [
+    (SortMode::Alphabetical, "sort_lines.mode_alphabetical"),
+    (SortMode::Natural, "sort_lines.mode_natural"),
+    (SortMode::Numeric, "sort_lines.mode_numeric"),
+];
+
+struct SortLinesState {
+    parent: HWND,
+}
+
+pub unsafe fn open(parent: HWND) {
+    let existing = with_state(parent, |state| state.sort_lines_dialog).unwrap_or(HWND(0));
+    if existing.0 != 0 {
+        SetForegroundWindow(existing);
+        return;
+    }
+
+    let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+    let class_name = to_wide(SORT_LINES_CLASS);
+    let language = with_state(parent, |state| state.settings.language).unwrap_or_default();
+    let title_w = to_wide(&i18n::tr(language, "sort_lines.title"));
+
+    let wc = WNDCLASSW {
+        hCursor: windows::Win32::UI::WindowsAndMessaging::HCURSOR(
+            LoadCursorW(None, IDC_ARROW).unwrap_or_default().0,
+        ),
+        hInstance: hinstance,
+        lpszClassName: PCWSTR(class_name.as_ptr()),
+        lpfnWndProc: Some(sort_lines_wndproc),
+        hbrBackground: HBRUSH((COLOR_WINDOW.0 + 1) as isize),
+        ..Default::default()
+    };
+    RegisterClassW(&wc);
+
+    let state = Box::new(SortLinesState { parent });
+    let state_ptr = Box::into_raw(state);
+    let hwnd = CreateWindowExW(
+        WS_EX_DLGMODALFRAME,
+        PCWSTR(class_name.as_ptr()),
+        PCWSTR(title_w.as_ptr()),
+        WS_POPUP | WS_CAPTION | WS_VISIBLE,
+        CW_USEDEFAULT,
+        CW_USEDEFAULT,
+        420,
+        390,
+        parent,
+        HMENU(0),
+        hinstance,
+        Some(state_ptr as *const _),
+    );
+    if hwnd.0 == 0 {
+        drop(Box::from_raw(state_ptr));
+        return;
+    }
+    EnableWindow(parent, false);
+    with_state(parent, |state| state.sort_lines_dialog = hwnd);
+}
+
+unsafe fn create_label(hwnd: HWND, hinstance: HINSTANCE, text: &str, y: i32, id: isize) {
+    CreateWindowExW(
+        Default::default(),
+        w!("STATIC"),
+        PCWSTR(to_wide(text).as_ptr()),
+        WS_CHILD | WS_VISIBLE,
+        10,
+        y,
+        390,
+        16,
+        hwnd,
+        HMENU(id),
+        hinstance,
+        None,
+    );
+}
+
+unsafe fn create_input(hwnd: HWND, hinstance: HINSTANCE, text: &str, y: i32, id: usize) {
+    CreateWindowExW(
+        WS_EX_CLIENTEDGE,
+        w!("EDIT"),
+        PCWSTR(to_wide(text).as_ptr()),
+        WS_CHILD | WS_VISIBLE | WS_TABSTOP,
+        10,
+        y,
+        120,
+        24,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    );
+}
+
+unsafe fn create_checkbox(
+    hwnd: HWND,
+    hinstance: HINSTANCE,
+    text: &str,
+    y: i32,
+    id: usize,
+    checked: bool,
+) {
+    let checkbox = CreateWindowExW(
+        Default::default(),
+        WC_BUTTON,
+        PCWSTR(to_wide(text).as_ptr()),
+        WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_AUTOCHECKBOX as u32),
+        10,
+        y,
+        390,
+        22,
+        hwnd,
+        HMENU(id as isize),
+        hinstance,
+        None,
+    );
+    if checked {
+        SendMessageW(
+            checkbox,
+            BM_SETCHECK,
+            WPARAM(BST_CHECKED.0 as usize),
+            LPARAM(0),
+        );
+    }
+}
+
+unsafe fn is_checked(hwnd: HWND, id: usize) -> bool {
+    let checkbox = GetDlgItem(hwnd, id as i32);
+    SendMessageW(checkbox, BM_GETCHECK, WPARAM(0), LPARAM(0)).0 == BST_CHECKED.0 as isize
+}
+
+unsafe fn read_input(hwnd: HWND, id: usize) -> String {
+    let input = GetDlgItem(hwnd, id as i32);
+    let len = SendMessageW(input, WM_GETTEXTLENGTH, WPARAM(0), LPARAM(0)).0;
+    let mut buf = vec![0u16; len as usize + 1];
+    SendMessageW(
+        input,
+        WM_GETTEXT,
+        WPARAM(buf.len()),
+        LPARAM(buf.as_mut_ptr() as isize),
+    );
+    String::from_utf16_lossy(&buf[..len as usize])
+}
+
+unsafe fn show_status(hwnd: HWND, message: &str, focus_id: usize) {
+    let status = GetDlgItem(hwnd, SORT_LINES_STATUS_ID as i32);
+    let wide = to_wide(message);
+    crate::log_if_err!(SetWindowTextW(status, PCWSTR(wide.as_ptr())));
+    nvda_speak(message);
+    SetFocus(GetDlgItem(hwnd, focus_id as i32));
+}
+
+/// An empty field box means the whole line.
+fn parse_field(text: &str) -> Option<usize> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Some(0);
+    }
+    text.parse::<usize>()
+        .ok()
+        .filter(|field| *field <= MAX_FIELD)
+}
+
+/// The delimiter box accepts "\t" for a tab, which cannot be typed in an edit control.
+pub fn parse_delimiter(text: &str) -> String {
+    if text == "\\t" {
+        "\t".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+pub fn display_delimiter(delimiter: &str) -> String {
+    if delimiter == "\t" {
+        "\\t".to_string()
+    } else {
+        delimiter.to_string()
+    }
+}
+
+unsafe extern "system" fn sort_lines_wndproc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_CREATE => {
+            let cs = lparam.0 as *const CREATESTRUCTW;
+            let init_ptr = (*cs).lpCreateParams as *mut SortLinesState;
+            if init_ptr.is_null() {
+                return LRESULT(0);
+            }
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, init_ptr as isize);
+            let parent = (*init_ptr).parent;
+            let Some(settings) = with_state(parent, |state| state.settings.clone()) else {
+                return LRESULT(0);
+            };
+            let language = settings.language;
+
+            let hinstance = HINSTANCE(GetModuleHandleW(None).unwrap_or_default().0);
+            create_label(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "sort_lines.mode"),
+                12,
+                1,
+            );
+            let combo = CreateWindowExW(
+                WS_EX_CLIENTEDGE,
+                WC_COMBOBOXW,
+                PCWSTR::null(),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(CBS_DROPDOWNLIST as u32),
+                10,
+                30,
+                390,
+                120,
+                hwnd,
+                HMENU(SORT_LINES_MODE_ID as isize),
+                hinstance,
+                None,
+            );
+            for (idx, (mode, key)) in SORT_MODES.iter().enumerate() {
+                let label_w = to_wide(&i18n::tr(language, key));
+                SendMessageW(
+                    combo,
+                    CB_ADDSTRING,
+                    WPARAM(0),
+                    LPARAM(label_w.as_ptr() as isize),
+                );
+                if *mode == settings.sort_mode {
+                    SendMessageW(combo, CB_SETCURSEL, WPARAM(idx), LPARAM(0));
+                }
+            }
+            create_label(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "sort_lines.field"),
+                66,
+                2,
+            );
+            let field = if settings.sort_field == 0 {
+                String::new()
+            } else {
+                settings.sort_field.to_string()
+            };
+            create_input(hwnd, hinstance, &field, 84, SORT_LINES_FIELD_ID);
+            create_label(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "sort_lines.delimiter"),
+                116,
+                3,
+            );
+            create_input(
+                hwnd,
+                hinstance,
+                &display_delimiter(&settings.sort_delimiter),
+                134,
+                SORT_LINES_DELIMITER_ID,
+            );
+            create_checkbox(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "sort_lines.descending"),
+                166,
+                SORT_LINES_DESCENDING_ID,
+                settings.sort_descending,
+            );
+            create_checkbox(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "sort_lines.case_sensitive"),
+                192,
+                SORT_LINES_CASE_ID,
+                settings.sort_case_sensitive,
+            );
+            create_checkbox(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "sort_lines.ignore_accents"),
+                218,
+                SORT_LINES_ACCENTS_ID,
+                settings.sort_ignore_accents,
+            );
+            create_checkbox(
+                hwnd,
+                hinstance,
+                &i18n::tr(language, "sort_lines.remove_duplicates"),
+                244,
+                SORT_LINES_DUPLICATES_ID,
+                settings.sort_remove_duplicates,
+            );
+            create_label(hwnd, hinstance, "", 274, SORT_LINES_STATUS_ID as isize);
+            CreateWindowExW(
+                Default::default(),
+                w!("BUTTON"),
+                PCWSTR(to_wide(&i18n::tr(language, "sort_lines.ok")).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_DEFPUSHBUTTON as u32),
+                230,
+                302,
+                80,
+                26,
+                hwnd,
+                HMENU(SORT_LINES_OK_ID as isize),
+                hinstance,
+                None,
+            );
+            CreateWindowExW(
+                Default::default(),
+                w!("BUTTON"),
+                PCWSTR(to_wide(&i18n::tr(language, "sort_lines.cancel")).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP,
+                320,
+                302,
+                80,
+                26,
+                hwnd,
+                HMENU(SORT_LINES_CANCEL_ID as isize),
+                hinstance,
+                None,
+            );
+
+            SetFocus(combo);
+            LRESULT(0)
+        }
+        WM_KEYDOWN => {
+            if wparam.0 as u32 == windows::Win32::UI::Input::KeyboardAndMouse::VK_ESCAPE.0 as u32 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_COMMAND => {
+            let id = wparam.0 & 0xffff;
+            if id == SORT_LINES_CANCEL_ID || id == 2 {
+                crate::log_if_err!(DestroyWindow(hwnd));
+                return LRESULT(0);
+            }
+            if id == SORT_LINES_OK_ID || id == 1 {
+                let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const SortLinesState;
+                if ptr.is_null() {
+                    return LRESULT(0);
+                }
+                let parent = (*ptr).parent;
+                let language =
+                    with_state(parent, |state| state.settings.language).unwrap_or_default();
+                let Some(field) = parse_field(&read_input(hwnd, SORT_LINES_FIELD_ID)) else {
+                    let msg = i18n::tr_f(
+                        language,
+                        "sort_lines.invalid_field",
+                        &[("max", &MAX_FIELD.to_string())],
+                    );
+                    show_status(hwnd, &msg, SORT_LINES_FIELD_ID);
+                    return LRESULT(0);
+                };
+                let combo = GetDlgItem(hwnd, SORT_LINES_MODE_ID as i32);
+                let selected = SendMessageW(combo, CB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+                let mode = usize::try_from(selected)
+                    .ok()
+                    .and_then(|idx| SORT_MODES.get(idx))
+                    .map(|(mode, _)| *mode)
+                    .unwrap_or_default();
+                let options = SortOptions {
+                    mode,
+                    descending: is_checked(hwnd, SORT_LINES_DESCENDING_ID),
+                    case_sensitive: is_checked(hwnd, SORT_LINES_CASE_ID),
+                    ignore_accents: is_checked(hwnd, SORT_LINES_ACCENTS_ID),
+                    field,
+                    delimiter: parse_delimiter(&read_input(hwnd, SORT_LINES_DELIMITER_ID)),
+                    remove_duplicates: is_checked(hwnd, SORT_LINES_DUPLICATES_ID),
+                    language,
+                };
+                let settings = with_state(parent, |state| {
+                    state.settings.sort_mode = options.mode;
+                    state.settings.sort_descending = options.descending;
+                    state.settings.sort_case_sensitive = options.case_sensitive;
+                    state.settings.sort_ignore_accents = options.ignore_accents;
+                    state.settings.sort_field = options.field as u32;
+                    state.settings.sort_delimiter = options.delimiter.clone();
+                    state.settings.sort_remove_duplicates = options.remove_duplicates;
+                    state.settings.clone()
+                });
+                if let Some(settings) = settings {
+                    save_settings(settings);
+                }
+                crate::log_if_err!(DestroyWindow(hwnd));
+                let key = if crate::editor_manager::sort_lines_active_edit(parent, &options) {
+                    "sort_lines.sorted"
+                } else {
+                    "sort_lines.unchanged"
+                };
+                nvda_speak(&i18n::tr(language, key));
+                return LRESULT(0);
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+        WM_DESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const SortLinesState;
+            if !ptr.is_null() {
+                let parent = (*ptr).parent;
+                EnableWindow(parent, true);
+                SetForegroundWindow(parent);
+                with_state(parent, |s| s.sort_lines_dialog = HWND(0));
+                if let Some(hwnd_edit) = crate::get_active_edit(parent) {
+                    SetFocus(hwnd_edit);
+                }
+            }
+            LRESULT(0)
+        }
+        WM_NCDESTROY => {
+            let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut SortLinesState;
+            if !ptr.is_null() {
+                drop(Box::from_raw(ptr));
+            }
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+pub unsafe fn handle_navigation(
+    hwnd: HWND,
+    msg: &windows::Win32::UI::WindowsAndMessaging::MSG,
+) -> bool {
+    handle_accessibility(hwnd, msg)
+}
@@ -31,6 +31,7 @@ use crate::accessibility::{EM_REPLACESEL, to_wide, to_wide_normalized};
 use crate::editor_manager::get_edit_text;
 use crate::i18n;
 use crate::settings::{Language, save_settings};
+use crate::subtitles::{Cue, SubtitleFormat, SubtitleTrack};
 use crate::with_state;
 use crate::{WM_FOCUS_EDITOR, get_active_edit, show_error};
 
@@ -41,6 +42,7 @@ const YT_ID_LANG: usize = 9303;
 const YT_ID_TIMESTAMP: usize = 9304;
 const YT_ID_OK: usize = 9305;
 const YT_ID_CANCEL: usize = 9306;
+const YT_ID_SUBTITLES: usize = 9307;
 const WM_YT_LOAD_COMPLETE: u32 = WM_APP + 40;
 const EVENT_OBJECT_FOCUS: u32 = 0x8005;
 const EVENT_OBJECT_VALUECHANGE: u32 = 0x800E;
@@ -51,12 +53,14 @@ const CHILDID_SELF: i32 = 0;
 struct ImportResult {
     transcript: Transcript,
     include_timestamps: bool,
+    as_subtitles: bool,
 }
 
 struct ImportInit {
     parent: HWND,
     language: Language,
     include_timestamps: bool,
+    as_subtitles: bool,
     result: Arc<Mutex<Option<ImportResult>>>,
 }
 
@@ -67,6 +71,7 @@ struct ImportState {
     load_button: HWND,
     lang_combo: HWND,
     timestamp_check: HWND,
+    subtitles_check: HWND,
     ok_button: HWND,
     status_label: HWND,
     loading: bool,
@@ -80,6 +85,7 @@ struct Labels {
     load: String,
     language: String,
     include_timestamps: String,
+    as_subtitles: String,
     loading: String,
     ok: String,
     cancel: String,
@@ -98,6 +104,7 @@ fn labels(language: Language) -> Labels {
         load: i18n::tr(language, "youtube.load"),
         language: i18n::tr(language, "youtube.language"),
         include_timestamps: i18n::tr(language, "youtube.include_timestamps"),
+        as_subtitles: i18n::tr(language, "youtube.as_subtitles"),
         loading: i18n::tr(language, "youtube.loading"),
         ok: i18n::tr(language, "youtube.ok"),
         cancel: i18n::tr(language, "youtube.cancel"),
@@ -111,16 +118,18 @@ fn labels(language: Language) -> Labels {
 }
 
 pub fn import_youtube_transcript(parent: HWND) {
-    let (language, include_timestamps) = unsafe {
+    let (language, include_timestamps, as_subtitles) = unsafe {
         with_state(parent, |state| {
             (
                 state.settings.language,
                 state.settings.youtube_include_timestamps,
+                state.settings.youtube_as_subtitles,
             )
         })
-        .unwrap_or((Language::Italian, true))
+        .unwrap_or((Language::Italian, true, false))
     };
-    let Some(result) = show_import_dialog(parent, language, include_timestamps) else {
+    let Some(result) = show_import_dialog(parent, language, include_timestamps, as_subtitles)
+    else {
         unsafe {
             if let Err(e) = PostMessageW(parent, WM_FOCUS_EDITOR, WPARAM(0), LPARAM(0)) {
                 crate::log_debug(&format!("Failed to post WM_FOCUS_EDITOR: {}", e));
@@ -131,6 +140,7 @@ pub fn import_youtube_transcript(parent: HWND) {
     unsafe {
         if with_state(parent, |state| {
             state.settings.youtube_include_timestamps = result.include_timestamps;
+            state.settings.youtube_as_subtitles = result.as_subtitles;
             save_settings(state.settings.clone());
         })
         .is_none()
@@ -139,8 +149,8 @@ pub fn import_youtube_transcript(parent: HWND) {
         }
     }
 
-    let text = match fetch_transcript_text(&result.transcript, result.include_timestamps) {
-        Ok(text) => text,
+    let fetched = match fetch_transcript(&result.transcript) {
+        Ok(fetched) => fetched,
         Err(err) => {
             unsafe {
                 show_error(parent, language, &error_message(language, &err));
@@ -152,6 +162,30 @@ pub fn import_youtube_transcript(parent: HWND) {
         }
     };
 
+    if result.as_subtitles {
+        let title = i18n::tr_f(
+            language,
+            "youtube.subtitles_title",
+            &[("language", result.transcript.language())],
+        );
+        unsafe {
+            crate::editor_manager::new_subtitle_document(
+                parent,
+                &title,
+                subtitles_from_fetched(&fetched),
+            );
+            if let Err(e) = PostMessageW(parent, WM_FOCUS_EDITOR, WPARAM(0), LPARAM(0)) {
+                crate::log_debug(&format!("Failed to post WM_FOCUS_EDITOR: {}", e));
+            }
+        }
+        return;
+    }
+    let text = if result.include_timestamps {
+        format_with_timestamps(&fetched)
+    } else {
+        format_without_timestamps(&fetched)
+    };
+
     unsafe {
         let Some(hwnd_edit) = get_active_edit(parent) else {
             show_error(parent, language, &labels(language).no_document);
@@ -208,6 +242,7 @@ fn show_import_dialog(
     parent: HWND,
     language: Language,
     include_timestamps: bool,
+    as_subtitles: bool,
 ) -> Option<ImportResult> {
     let hinstance = HINSTANCE(unsafe { GetModuleHandleW(None).unwrap_or_default().0 });
     let class_name = to_wide(YT_IMPORT_CLASS_NAME);
@@ -228,6 +263,7 @@ fn show_import_dialog(
         parent,
         language,
         include_timestamps,
+        as_subtitles,
         result: result.clone(),
     });
     let labels = labels(language);
@@ -242,7 +278,7 @@ fn show_import_dialog(
             CW_USEDEFAULT,
             CW_USEDEFAULT,
             520,
-            240,
+            270,
             parent,
             HMENU(0),
             hinstance,
@@ -422,13 +458,28 @@ unsafe extern "system" fn import_wndproc(
                 None,
             );
 
+            let subtitles_check = CreateWindowExW(
+                Default::default(),
+                WC_BUTTON,
+                PCWSTR(to_wide(&labels.as_subtitles).as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_AUTOCHECKBOX as u32),
+                110,
+                138,
+                390,
+                22,
+                hwnd,
+                HMENU(YT_ID_SUBTITLES as isize),
+                HINSTANCE(0),
+                None,
+            );
+
             let ok_button = CreateWindowExW(
                 Default::default(),
                 WC_BUTTON,
                 PCWSTR(to_wide(&labels.ok).as_ptr()),
                 WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_DEFPUSHBUTTON as u32),
                 310,
-                154,
+                184,
                 90,
                 28,
                 hwnd,
@@ -443,7 +494,7 @@ unsafe extern "system" fn import_wndproc(
                 PCWSTR(to_wide(&labels.cancel).as_ptr()),
                 WS_CHILD | WS_VISIBLE | WS_TABSTOP,
                 410,
-                154,
+                184,
                 90,
                 28,
                 hwnd,
@@ -460,6 +511,7 @@ unsafe extern "system" fn import_wndproc(
                 lang_combo,
                 status_label,
                 timestamp_check,
+                subtitles_check,
                 ok_button,
                 cancel_button,
             ] {
@@ -475,6 +527,7 @@ unsafe extern "system" fn import_wndproc(
                 load_button,
                 lang_combo,
                 timestamp_check,
+                subtitles_check,
                 ok_button,
                 status_label,
                 loading: false,
@@ -494,6 +547,13 @@ unsafe extern "system" fn import_wndproc(
                 WPARAM(initial_check as usize),
                 LPARAM(0),
             );
+            let subtitles_initial = if init.as_subtitles { BST_CHECKED.0 } else { 0 };
+            SendMessageW(
+                subtitles_check,
+                BM_SETCHECK,
+                WPARAM(subtitles_initial as usize),
+                LPARAM(0),
+            );
             SetFocus(url_edit);
             LRESULT(0)
         }
@@ -522,10 +582,14 @@ unsafe extern "system" fn import_wndproc(
                     let include_timestamps =
                         SendMessageW(state.timestamp_check, BM_GETCHECK, WPARAM(0), LPARAM(0)).0
                             == BST_CHECKED.0 as isize;
+                    let as_subtitles =
+                        SendMessageW(state.subtitles_check, BM_GETCHECK, WPARAM(0), LPARAM(0)).0
+                            == BST_CHECKED.0 as isize;
                     let transcript = state.transcripts[idx as usize].clone();
                     *state.result.lock().unwrap_or_else(|e| e.into_inner()) = Some(ImportResult {
                         transcript,
                         include_timestamps,
+                        as_subtitles,
                     });
                     should_close = true;
                 })
@@ -910,25 +974,19 @@ fn fetch_transcript_list(video_id: &str) -> Result<TranscriptList, ImportError>
     })
 }
 
-fn fetch_transcript_text(
+fn fetch_transcript(
     transcript: &Transcript,
-    include_timestamps: bool,
-) -> Result<String, ImportError> {
+) -> Result<yt_transcript_rs::FetchedTranscript, ImportError> {
     let rt = tokio::runtime::Builder::new_current_thread()
         .enable_all()
         .build()
         .map_err(|_| ImportError::Other)?;
     rt.block_on(async {
         let client = reqwest::Client::new();
-        let fetched = transcript
+        transcript
             .fetch(&client, false)
             .await
-            .map_err(map_transcript_error)?;
-        if include_timestamps {
-            Ok(format_with_timestamps(&fetched))
-        } else {
-            Ok(format_without_timestamps(&fetched))
-        }
+            .map_err(map_transcript_error)
     })
 }
 
@@ -971,6 +1029,27 @@ fn format_with_timestamps(fetched: &yt_transcript_rs::FetchedTranscript) -> Stri
     lines.join("\n")
 }
 
+/// Builds an SRT track from the transcript, one cue per caption.
+fn subtitles_from_fetched(fetched: &yt_transcript_rs::FetchedTranscript) -> SubtitleTrack {
+    let to_ms = |seconds: f64| (seconds.max(0.0) * 1000.0).round() as u64;
+    let mut cues = Vec::new();
+    for part in fetched.parts() {
+        // Blank lines would split the cue when the document is read back.
+        let text = clean_transcript_text(&part.text)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if text.is_empty() {
+            continue;
+        }
+        let start = to_ms(part.start);
+        cues.push(Cue::new(start, start + to_ms(part.duration), &text));
+    }
+    SubtitleTrack::from_cues(SubtitleFormat::Srt, cues)
+}
+
 fn format_without_timestamps(fetched: &yt_transcript_rs::FetchedTranscript) -> String {
     let mut parts = Vec::new();
     for part in fetched.parts() {
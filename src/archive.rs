@@ -0,0 +1,277 @@
+use crate::i18n;
+use crate::settings::Language;
+use sevenz_rust::{Password, SevenZReader};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+use zip::write::{FileOptions, ZipWriter};
+
+/// A file stored inside a ZIP or 7z archive.
+#[derive(Clone)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub size: u64,
+}
+
+/// Where a document opened from an archive came from, so it can be written back.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ArchiveSource {
+    pub archive: PathBuf,
+    pub entry: String,
+}
+
+impl ArchiveSource {
+    pub fn display_name(&self) -> String {
+        let archive = self
+            .archive
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        format!("{archive} › {}", self.entry)
+    }
+}
+
+pub fn is_zip_archive_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|s| s.to_str())
+        .map(|s| s.eq_ignore_ascii_case("zip"))
+        .unwrap_or(false)
+}
+
+pub fn is_7z_archive_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|s| s.to_str())
+        .map(|s| s.eq_ignore_ascii_case("7z"))
+        .unwrap_or(false)
+}
+
+pub fn is_archive_path(path: &Path) -> bool {
+    is_zip_archive_path(path) || is_7z_archive_path(path)
+}
+
+/// Lists the files (not folders) stored in a ZIP or 7z archive, sorted by name.
+pub fn list_archive_entries(path: &Path, language: Language) -> Result<Vec<ArchiveEntry>, String> {
+    let mut entries = if is_7z_archive_path(path) {
+        let reader =
+            SevenZReader::open(path, Password::empty()).map_err(|e| read_error(language, e))?;
+        reader
+            .archive()
+            .files
+            .iter()
+            .filter(|file| !file.is_directory())
+            .map(|file| ArchiveEntry {
+                name: file.name().replace('\\', "/"),
+                size: file.size(),
+            })
+            .collect()
+    } else {
+        let file = std::fs::File::open(path).map_err(|e| read_error(language, e))?;
+        let mut zip = ZipArchive::new(file).map_err(|e| read_error(language, e))?;
+        let mut entries = Vec::with_capacity(zip.len());
+        for index in 0..zip.len() {
+            let file = zip.by_index(index).map_err(|e| read_error(language, e))?;
+            if file.is_dir() {
+                continue;
+            }
+            entries.push(ArchiveEntry {
+                name: file.name().to_string(),
+                size: file.size(),
+            });
+        }
+        entries
+    };
+    entries.sort_by_key(|entry| entry.name.to_lowercase());
+    Ok(entries)
+}
+
+/// Extracts one entry into Novapad's temporary archive folder and returns its path,
+/// so it can be opened with the regular `file_handler` readers.
+pub fn extract_archive_entry(
+    archive: &Path,
+    entry: &str,
+    language: Language,
+) -> Result<PathBuf, String> {
+    let target = extracted_entry_path(archive, entry)
+        .ok_or_else(|| i18n::tr(language, "archive.entry_not_found"))?;
+    let data = if is_7z_archive_path(archive) {
+        let mut reader =
+            SevenZReader::open(archive, Password::empty()).map_err(|e| read_error(language, e))?;
+        let mut found = None;
+        reader
+            .for_each_entries(|file, content| {
+                if file.name().replace('\\', "/") != entry {
+                    return Ok(true);
+                }
+                let mut data = Vec::new();
+                content.read_to_end(&mut data)?;
+                found = Some(data);
+                Ok(false)
+            })
+            .map_err(|e| read_error(language, e))?;
+        found.ok_or_else(|| i18n::tr(language, "archive.entry_not_found"))?
+    } else {
+        let file = std::fs::File::open(archive).map_err(|e| read_error(language, e))?;
+        let mut zip = ZipArchive::new(file).map_err(|e| read_error(language, e))?;
+        let mut file = zip
+            .by_name(entry)
+            .map_err(|_| i18n::tr(language, "archive.entry_not_found"))?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)
+            .map_err(|e| read_error(language, e))?;
+        data
+    };
+    write_extracted(&target, &data, language)?;
+    Ok(target)
+}
+
+/// Extracts every entry that may contain searchable text, for find in files.
+/// Returns `(entry name, extracted path)` pairs; nested archives, PDFs and audio are skipped.
+pub fn extract_searchable_entries(
+    archive: &Path,
+    language: Language,
+) -> Result<Vec<(String, PathBuf)>, String> {
+    let is_searchable = |name: &str| {
+        let path = Path::new(name);
+        !is_archive_path(path)
+            && !crate::file_handler::is_pdf_path(path)
+            && !crate::file_handler::is_mp3_path(path)
+    };
+    let mut extracted = Vec::new();
+    if is_7z_archive_path(archive) {
+        let mut reader =
+            SevenZReader::open(archive, Password::empty()).map_err(|e| read_error(language, e))?;
+        let mut failure = None;
+        reader
+            .for_each_entries(|file, content| {
+                let name = file.name().replace('\\', "/");
+                if file.is_directory() || !is_searchable(&name) {
+                    return Ok(true);
+                }
+                let mut data = Vec::new();
+                content.read_to_end(&mut data)?;
+                if let Some(target) = extracted_entry_path(archive, &name) {
+                    match write_extracted(&target, &data, language) {
+                        Ok(()) => extracted.push((name, target)),
+                        Err(message) => {
+                            failure = Some(message);
+                            return Ok(false);
+                        }
+                    }
+                }
+                Ok(true)
+            })
+            .map_err(|e| read_error(language, e))?;
+        if let Some(message) = failure {
+            return Err(message);
+        }
+    } else {
+        let file = std::fs::File::open(archive).map_err(|e| read_error(language, e))?;
+        let mut zip = ZipArchive::new(file).map_err(|e| read_error(language, e))?;
+        for index in 0..zip.len() {
+            let mut file = zip.by_index(index).map_err(|e| read_error(language, e))?;
+            let name = file.name().to_string();
+            if file.is_dir() || !is_searchable(&name) {
+                continue;
+            }
+            let Some(target) = extracted_entry_path(archive, &name) else {
+                continue;
+            };
+            let mut data = Vec::new();
+            file.read_to_end(&mut data)
+                .map_err(|e| read_error(language, e))?;
+            write_extracted(&target, &data, language)?;
+            extracted.push((name, target));
+        }
+    }
+    Ok(extracted)
+}
+
+/// Replaces (or adds) `entry` inside a ZIP archive, copying every other entry unchanged.
+pub fn write_zip_entry(
+    archive: &Path,
+    entry: &str,
+    data: &[u8],
+    language: Language,
+) -> Result<(), String> {
+    let source = std::fs::File::open(archive).map_err(|e| write_error(language, e))?;
+    let mut zip = ZipArchive::new(source).map_err(|e| write_error(language, e))?;
+    let mut temp_path = archive.to_path_buf();
+    temp_path.set_extension("zip.tmp");
+    let temp = std::fs::File::create(&temp_path).map_err(|e| write_error(language, e))?;
+    let mut writer = ZipWriter::new(temp);
+    let result = (|| -> zip::result::ZipResult<()> {
+        for index in 0..zip.len() {
+            let file = zip.by_index_raw(index)?;
+            if file.name() == entry {
+                continue;
+            }
+            writer.raw_copy_file(file)?;
+        }
+        writer.start_file(entry, FileOptions::default())?;
+        writer.write_all(data)?;
+        writer.finish()?;
+        Ok(())
+    })();
+    drop(writer);
+    drop(zip);
+    if let Err(err) = result {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(write_error(language, err));
+    }
+    std::fs::rename(&temp_path, archive).map_err(|e| {
+        let _ = std::fs::remove_file(&temp_path);
+        write_error(language, e)
+    })
+}
+
+/// True for files Novapad extracted from an archive; these never go into the recent list.
+pub fn is_extracted_entry_path(path: &Path) -> bool {
+    path.starts_with(extraction_root())
+}
+
+fn extraction_root() -> PathBuf {
+    std::env::temp_dir().join("Novapad").join("archives")
+}
+
+/// Maps an entry to a stable location under the extraction root. Each archive gets
+/// its own folder and `..`, drive and root components are dropped, so entries
+/// can never escape it.
+fn extracted_entry_path(archive: &Path, entry: &str) -> Option<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    archive.hash(&mut hasher);
+    let stem = archive
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("archive");
+    let mut target = extraction_root().join(format!("{stem}-{:016x}", hasher.finish()));
+    let mut has_name = false;
+    for part in entry.split(['/', '\\']) {
+        if part.is_empty() || part == "." || part == ".." || part.contains(':') {
+            continue;
+        }
+        target.push(part);
+        has_name = true;
+    }
+    has_name.then_some(target)
+}
+
+fn write_extracted(target: &Path, data: &[u8], language: Language) -> Result<(), String> {
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| read_error(language, e))?;
+    }
+    std::fs::write(target, data).map_err(|e| read_error(language, e))
+}
+
+fn read_error(language: Language, err: impl std::fmt::Display) -> String {
+    i18n::tr_f(language, "archive.read_error", &[("err", &err.to_string())])
+}
+
+fn write_error(language: Language, err: impl std::fmt::Display) -> String {
+    i18n::tr_f(
+        language,
+        "archive.write_error",
+        &[("err", &err.to_string())],
+    )
+}
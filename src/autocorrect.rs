@@ -0,0 +1,348 @@
+use crate::settings::Language;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Longest word looked up before the caret when correcting.
+pub const MAX_WORD_CHARS: usize = 40;
+
+/// Opening punctuation skipped before a word, so "(teh" is corrected like "teh".
+const OPENING_PUNCTUATION: &[char] = &['(', '[', '{', '"', '\'', '“', '‘', '«', '¿', '¡'];
+
+/// Replacements of every language; they are matched exactly.
+const SYMBOLS: &[(&str, &str)] = &[
+    ("(c)", "©"),
+    ("(C)", "©"),
+    ("(r)", "®"),
+    ("(R)", "®"),
+    ("(tm)", "™"),
+    ("(TM)", "™"),
+    ("(e)", "€"),
+    ("--", "—"),
+    ("...", "…"),
+    ("->", "→"),
+    ("<-", "←"),
+    ("=>", "⇒"),
+    ("1/2", "½"),
+    ("1/4", "¼"),
+    ("3/4", "¾"),
+];
+
+/// Common misspellings, in lowercase; the correction follows the case of the typed
+/// word.
+const ENGLISH: &[(&str, &str)] = &[
+    ("teh", "the"),
+    ("adn", "and"),
+    ("recieve", "receive"),
+    ("beleive", "believe"),
+    ("occured", "occurred"),
+    ("seperate", "separate"),
+    ("definately", "definitely"),
+    ("untill", "until"),
+    ("wich", "which"),
+    ("thier", "their"),
+    ("becuase", "because"),
+    ("accomodate", "accommodate"),
+    ("acheive", "achieve"),
+    ("goverment", "government"),
+    ("tommorow", "tomorrow"),
+    ("wierd", "weird"),
+    ("alot", "a lot"),
+    ("dont", "don't"),
+    ("doesnt", "doesn't"),
+    ("didnt", "didn't"),
+    ("isnt", "isn't"),
+    ("youre", "you're"),
+];
+
+const ITALIAN: &[(&str, &str)] = &[
+    ("perchè", "perché"),
+    ("perche", "perché"),
+    ("poichè", "poiché"),
+    ("affinchè", "affinché"),
+    ("benchè", "benché"),
+    ("nè", "né"),
+    ("e'", "è"),
+    ("e’", "è"),
+    ("qual'è", "qual è"),
+    ("qual’è", "qual è"),
+    ("un'altro", "un altro"),
+    ("un’altro", "un altro"),
+    ("pò", "po'"),
+    ("daccordo", "d'accordo"),
+    ("propio", "proprio"),
+    ("sopratutto", "soprattutto"),
+    ("efficente", "efficiente"),
+];
+
+const SPANISH: &[(&str, &str)] = &[
+    ("tambien", "también"),
+    ("despues", "después"),
+    ("asi", "así"),
+    ("aqui", "aquí"),
+    ("ahi", "ahí"),
+    ("facil", "fácil"),
+    ("dificil", "difícil"),
+    ("informacion", "información"),
+    ("ademas", "además"),
+    ("atravez", "a través"),
+    ("algun", "algún"),
+    ("ningun", "ningún"),
+];
+
+const PORTUGUESE: &[(&str, &str)] = &[
+    ("voce", "você"),
+    ("nao", "não"),
+    ("tambem", "também"),
+    ("entao", "então"),
+    ("porem", "porém"),
+    ("ja", "já"),
+    ("concerteza", "com certeza"),
+    ("derrepente", "de repente"),
+    ("excessão", "exceção"),
+    ("previlégio", "privilégio"),
+];
+
+const VIETNAMESE: &[(&str, &str)] = &[
+    ("ko", "không"),
+    ("đc", "được"),
+    ("bít", "biết"),
+    ("nhìu", "nhiều"),
+];
+
+/// Words kept with two initial capitals until the user changes the list: units
+/// and degrees that the double capitals fix would spoil.
+const DOUBLE_CAPITALS_EXCEPTIONS: &[&str] = &[
+    "MHz", "GHz", "THz", "MWh", "GWh", "TWh", "KBps", "MBps", "GBps", "BSc", "MSc", "DSc",
+];
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AutocorrectEntry {
+    pub from: String,
+    pub to: String,
+}
+
+/// The user's own replacements. They take precedence over the built-in ones, and an
+/// entry that replaces a word with itself turns a built-in correction off.
+#[derive(Serialize, Deserialize)]
+pub struct AutocorrectStore {
+    pub entries: Vec<AutocorrectEntry>,
+    /// Words the double capitals fix leaves alone, matched exactly.
+    #[serde(default = "default_exceptions")]
+    pub exceptions: Vec<String>,
+}
+
+impl Default for AutocorrectStore {
+    fn default() -> Self {
+        AutocorrectStore {
+            entries: Vec::new(),
+            exceptions: default_exceptions(),
+        }
+    }
+}
+
+fn default_exceptions() -> Vec<String> {
+    DOUBLE_CAPITALS_EXCEPTIONS
+        .iter()
+        .map(|word| word.to_string())
+        .collect()
+}
+
+pub struct Correction {
+    /// Byte offset in the text before the caret where the corrected word starts.
+    pub start: usize,
+    pub replacement: String,
+}
+
+fn autocorrect_store_path() -> PathBuf {
+    crate::settings::settings_dir().join("autocorrect.json")
+}
+
+pub fn load_autocorrect() -> AutocorrectStore {
+    std::fs::read_to_string(autocorrect_store_path())
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_autocorrect(store: &AutocorrectStore) {
+    let path = autocorrect_store_path();
+    if let Some(parent) = path.parent() {
+        crate::log_if_err!(std::fs::create_dir_all(parent));
+    }
+    if let Ok(json) = serde_json::to_string_pretty(store) {
+        crate::log_if_err!(std::fs::write(path, json));
+    }
+}
+
+fn builtin_table(language: Language) -> &'static [(&'static str, &'static str)] {
+    match language {
+        Language::English => ENGLISH,
+        Language::Italian => ITALIAN,
+        Language::Spanish => SPANISH,
+        Language::Portuguese => PORTUGUESE,
+        Language::Vietnamese => VIETNAMESE,
+    }
+}
+
+/// `replacement` written in the case of `word`: lowercase, capitalized or all
+/// capitals. None for mixed case such as "tEh", which is left alone.
+fn match_case(word: &str, replacement: &str) -> Option<String> {
+    let letters: Vec<char> = word.chars().filter(|ch| ch.is_alphabetic()).collect();
+    if letters.iter().all(|ch| ch.is_lowercase()) {
+        return Some(replacement.to_string());
+    }
+    if letters.len() > 1 && letters.iter().all(|ch| ch.is_uppercase()) {
+        return Some(replacement.to_uppercase());
+    }
+    if letters[0].is_uppercase() && letters[1..].iter().all(|ch| ch.is_lowercase()) {
+        let mut chars = replacement.chars();
+        let first = chars.next()?;
+        return Some(first.to_uppercase().chain(chars).collect());
+    }
+    None
+}
+
+/// Looks `word` up in a table: exactly first, then in lowercase with the case of
+/// the typed word carried over.
+fn lookup<'a>(
+    word: &str,
+    mut entries: impl Iterator<Item = (&'a str, &'a str)> + Clone,
+) -> Option<String> {
+    if let Some((_, to)) = entries.clone().find(|(from, _)| *from == word) {
+        return Some(to.to_string());
+    }
+    let lower = word.to_lowercase();
+    let (_, to) = entries.find(|(from, _)| *from == lower)?;
+    match_case(word, to)
+}
+
+/// "THe" becomes "The": two capitals followed by lowercase letters only. Plurals
+/// of abbreviations, such as "CDs", are kept.
+fn fix_double_capitals(word: &str) -> Option<String> {
+    let chars: Vec<char> = word.chars().collect();
+    if chars.len() < 3
+        || !chars[..2].iter().all(|ch| ch.is_uppercase())
+        || !chars[2..].iter().all(|ch| ch.is_lowercase())
+        || chars[2..] == ['s']
+    {
+        return None;
+    }
+    Some(
+        std::iter::once(chars[0])
+            .chain(chars[1].to_lowercase())
+            .chain(chars[2..].iter().copied())
+            .collect(),
+    )
+}
+
+/// The correction of the word just typed at the end of `before`, if any: the
+/// user's replacements, then the symbols, the misspellings of `language` and the
+/// double capitals fix, unless the word is one of the user's exceptions.
+pub fn correct(before: &str, language: Language, store: &AutocorrectStore) -> Option<Correction> {
+    let token = crate::snippets::abbreviation_before(before);
+    if token.is_empty() {
+        return None;
+    }
+    let token_start = before.len() - token.len();
+    let word = token.trim_start_matches(OPENING_PUNCTUATION);
+    let mut candidates = vec![(token_start, token)];
+    if word != token && !word.is_empty() {
+        candidates.push((before.len() - word.len(), word));
+    }
+    for (start, candidate) in candidates {
+        let user = store
+            .entries
+            .iter()
+            .map(|entry| (entry.from.as_str(), entry.to.as_str()));
+        let replacement = lookup(candidate, user)
+            .or_else(|| lookup(candidate, SYMBOLS.iter().copied()))
+            .or_else(|| lookup(candidate, builtin_table(language).iter().copied()));
+        if let Some(replacement) = replacement {
+            return (replacement != candidate).then_some(Correction { start, replacement });
+        }
+    }
+    if store.exceptions.iter().any(|exception| exception == word) {
+        return None;
+    }
+    let replacement = fix_double_capitals(word)?;
+    Some(Correction {
+        start: before.len() - word.len(),
+        replacement,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn corrected(before: &str, language: Language, store: &AutocorrectStore) -> Option<String> {
+        correct(before, language, store)
+            .map(|correction| format!("{}{}", &before[..correction.start], correction.replacement))
+    }
+
+    #[test]
+    fn test_builtin_corrections() {
+        let store = AutocorrectStore::default();
+        let en = Language::English;
+        assert_eq!(
+            corrected("I saw teh", en, &store).as_deref(),
+            Some("I saw the")
+        );
+        assert_eq!(corrected("Teh", en, &store).as_deref(), Some("The"));
+        assert_eq!(corrected("(TEH", en, &store).as_deref(), Some("(THE"));
+        assert_eq!(corrected("tEh", en, &store), None);
+        assert_eq!(corrected("Note (c)", en, &store).as_deref(), Some("Note ©"));
+        assert_eq!(corrected("yes --", en, &store).as_deref(), Some("yes —"));
+        assert_eq!(corrected("THe", en, &store).as_deref(), Some("The"));
+        assert_eq!(corrected("NASA CDs", en, &store), None);
+        assert_eq!(corrected("the", en, &store), None);
+        assert_eq!(corrected("line\r", en, &store), None);
+        assert_eq!(
+            corrected("E'", Language::Italian, &store).as_deref(),
+            Some("È")
+        );
+        assert_eq!(corrected("teh", Language::Italian, &store), None);
+    }
+
+    #[test]
+    fn test_user_entries() {
+        let store = AutocorrectStore {
+            entries: vec![
+                AutocorrectEntry {
+                    from: "np".to_string(),
+                    to: "Novapad".to_string(),
+                },
+                AutocorrectEntry {
+                    from: "alot".to_string(),
+                    to: "alot".to_string(),
+                },
+            ],
+            ..Default::default()
+        };
+        let en = Language::English;
+        assert_eq!(
+            corrected("use np", en, &store).as_deref(),
+            Some("use Novapad")
+        );
+        assert_eq!(corrected("NP", en, &store).as_deref(), Some("NOVAPAD"));
+        assert_eq!(corrected("alot", en, &store), None);
+    }
+
+    #[test]
+    fn test_double_capitals_exceptions() {
+        let mut store = AutocorrectStore::default();
+        let en = Language::English;
+        assert_eq!(corrected("at 5 GHz", en, &store), None);
+        assert_eq!(corrected("100 MHz", en, &store), None);
+        assert_eq!(corrected("3 TWh", en, &store), None);
+        assert_eq!(corrected("an MSc", en, &store), None);
+        assert_eq!(corrected("THe", en, &store).as_deref(), Some("The"));
+
+        store.exceptions = vec!["NOx".to_string()];
+        assert_eq!(corrected("NOx", en, &store), None);
+        assert_eq!(corrected("GHz", en, &store).as_deref(), Some("Ghz"));
+
+        let old: AutocorrectStore = serde_json::from_str(r#"{"entries":[]}"#).unwrap();
+        assert!(old.exceptions.iter().any(|word| word == "MHz"));
+    }
+}
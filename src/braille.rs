@@ -0,0 +1,394 @@
+//! Braille translation and BRF (Braille Ready Format) pagination. Translation tables
+//! are plain text files under `braille/`; the header of each file documents the rules.
+
+use crate::settings::Language;
+use std::collections::HashMap;
+
+/// North American ASCII braille indexed by dot bitmask (dot 1 = bit 0 ... dot 6 = bit 5).
+const BRF_CHARS: &[u8; 64] = b" A1B'K2L@CIF/MSP\"E3H9O6R^DJG>NTQ,*5<-U8V.%[$+X!&;:4\\0Z7(_?W]#Y)=";
+
+const TABLES: &[(&str, &str)] = &[
+    ("it-g1", include_str!("../braille/it-g1.tbl")),
+    ("en-g1", include_str!("../braille/en-g1.tbl")),
+    ("es-g1", include_str!("../braille/es-g1.tbl")),
+    ("pt-g1", include_str!("../braille/pt-g1.tbl")),
+    ("vi-g1", include_str!("../braille/vi-g1.tbl")),
+];
+
+pub fn table_ids() -> impl Iterator<Item = &'static str> {
+    TABLES.iter().map(|(id, _)| *id)
+}
+
+pub fn default_table_id(language: Language) -> &'static str {
+    match language {
+        Language::Italian => "it-g1",
+        Language::English => "en-g1",
+        Language::Spanish => "es-g1",
+        Language::Portuguese => "pt-g1",
+        Language::Vietnamese => "vi-g1",
+    }
+}
+
+pub fn load_table(id: &str) -> Result<BrailleTable, String> {
+    let (_, source) = TABLES
+        .iter()
+        .find(|(table_id, _)| *table_id == id)
+        .ok_or_else(|| format!("unknown braille table {id}"))?;
+    BrailleTable::parse(source).map_err(|err| format!("{id}: {err}"))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Position {
+    Anywhere,
+    Word,
+    BegWord,
+    MidWord,
+    EndWord,
+}
+
+struct Rule {
+    text: Vec<char>,
+    cells: Vec<u8>,
+    position: Position,
+}
+
+pub struct BrailleTable {
+    pub name: String,
+    capsign: Vec<u8>,
+    capsword: Vec<u8>,
+    numsign: Vec<u8>,
+    letsign: Vec<u8>,
+    digits: HashMap<char, Vec<u8>>,
+    /// Rules keyed by their first character, longest first.
+    rules: HashMap<char, Vec<Rule>>,
+}
+
+#[derive(Clone, Copy)]
+pub struct BrfLayout {
+    pub cells_per_line: usize,
+    pub lines_per_page: usize,
+    pub page_numbers: bool,
+}
+
+pub struct BrfDocument {
+    pub text: String,
+    pub pages: usize,
+    /// Characters that no rule of the table covers; they are left out.
+    pub untranslated: usize,
+}
+
+fn parse_cells(dots: &str) -> Result<Vec<u8>, String> {
+    dots.split('-')
+        .map(|cell| {
+            if cell == "0" {
+                return Ok(0);
+            }
+            if cell.is_empty() {
+                return Err(format!("invalid dots \"{dots}\""));
+            }
+            cell.chars().try_fold(0u8, |mask, dot| match dot {
+                '1'..='6' => Ok(mask | 1 << (dot as u8 - b'1')),
+                _ => Err(format!("invalid dots \"{dots}\"")),
+            })
+        })
+        .collect()
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric()
+}
+
+fn lowercase(ch: char) -> char {
+    ch.to_lowercase().next().unwrap_or(ch)
+}
+
+impl BrailleTable {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut table = BrailleTable {
+            name: String::new(),
+            capsign: Vec::new(),
+            capsword: Vec::new(),
+            numsign: Vec::new(),
+            letsign: Vec::new(),
+            digits: HashMap::new(),
+            rules: HashMap::new(),
+        };
+        for (idx, raw) in source.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line_error = |message: String| format!("line {}: {message}", idx + 1);
+            let (opcode, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let rest = rest.trim();
+            match opcode {
+                "name" => table.name = rest.to_string(),
+                "grade" => {
+                    if !matches!(rest, "1" | "2") {
+                        return Err(line_error(format!("unsupported grade \"{rest}\"")));
+                    }
+                }
+                "capsign" | "capsword" | "numsign" | "letsign" => {
+                    let cells = parse_cells(rest).map_err(line_error)?;
+                    match opcode {
+                        "capsign" => table.capsign = cells,
+                        "capsword" => table.capsword = cells,
+                        "numsign" => table.numsign = cells,
+                        _ => table.letsign = cells,
+                    }
+                }
+                "sign" | "digit" | "always" | "word" | "begword" | "midword" | "endword" => {
+                    let mut parts = rest.split_whitespace();
+                    let (Some(text), Some(dots), None) = (parts.next(), parts.next(), parts.next())
+                    else {
+                        return Err(line_error(format!(
+                            "expected \"{opcode} <characters> <dots>\""
+                        )));
+                    };
+                    let cells = parse_cells(dots).map_err(line_error)?;
+                    let text: Vec<char> = text.chars().map(lowercase).collect();
+                    if matches!(opcode, "sign" | "digit") && text.len() != 1 {
+                        return Err(line_error(format!("{opcode} takes a single character")));
+                    }
+                    if opcode == "digit" {
+                        table.digits.insert(text[0], cells);
+                        continue;
+                    }
+                    let position = match opcode {
+                        "word" => Position::Word,
+                        "begword" => Position::BegWord,
+                        "midword" => Position::MidWord,
+                        "endword" => Position::EndWord,
+                        _ => Position::Anywhere,
+                    };
+                    table.rules.entry(text[0]).or_default().push(Rule {
+                        text,
+                        cells,
+                        position,
+                    });
+                }
+                _ => return Err(line_error(format!("unknown opcode \"{opcode}\""))),
+            }
+        }
+        for rules in table.rules.values_mut() {
+            rules.sort_by_key(|rule| std::cmp::Reverse(rule.text.len()));
+        }
+        Ok(table)
+    }
+
+    fn rule_at(&self, lower: &[char], index: usize) -> Option<&Rule> {
+        let at_start = index == 0 || !is_word_char(lower[index - 1]);
+        self.rules.get(&lower[index])?.iter().find(|rule| {
+            let end = index + rule.text.len();
+            if end > lower.len() || lower[index..end] != rule.text[..] {
+                return false;
+            }
+            let at_end = end == lower.len() || !is_word_char(lower[end]);
+            match rule.position {
+                Position::Anywhere => true,
+                Position::Word => at_start && at_end,
+                Position::BegWord => at_start && !at_end,
+                Position::MidWord => !at_start && !at_end,
+                Position::EndWord => !at_start && at_end,
+            }
+        })
+    }
+
+    /// Translates one line of text into cells; whitespace becomes blank cells.
+    fn translate_line(&self, line: &str, untranslated: &mut usize) -> Vec<u8> {
+        let chars: Vec<char> = line.chars().collect();
+        let lower: Vec<char> = chars.iter().copied().map(lowercase).collect();
+        let mut cells = Vec::with_capacity(chars.len());
+        let mut in_number = false;
+        let mut caps_word_end = 0usize;
+        let mut index = 0usize;
+        while index < chars.len() {
+            let ch = chars[index];
+            if ch.is_whitespace() {
+                cells.push(0);
+                in_number = false;
+                index += 1;
+                continue;
+            }
+            if let Some(digit) = self.digits.get(&ch) {
+                if !in_number {
+                    cells.extend_from_slice(&self.numsign);
+                    in_number = true;
+                }
+                cells.extend_from_slice(digit);
+                index += 1;
+                continue;
+            }
+            let after_digit = std::mem::replace(&mut in_number, false);
+            if !self.capsword.is_empty()
+                && index >= caps_word_end
+                && (index == 0 || !is_word_char(chars[index - 1]))
+            {
+                let end = (index..chars.len())
+                    .find(|&i| !is_word_char(chars[i]))
+                    .unwrap_or(chars.len());
+                let word = &chars[index..end];
+                if word.iter().filter(|c| c.is_alphabetic()).count() >= 2
+                    && word
+                        .iter()
+                        .filter(|c| c.is_alphabetic())
+                        .all(|c| c.is_uppercase())
+                {
+                    cells.extend_from_slice(&self.capsword);
+                    caps_word_end = end;
+                }
+            }
+            let Some(rule) = self.rule_at(&lower, index) else {
+                *untranslated += 1;
+                index += 1;
+                continue;
+            };
+            if ch.is_uppercase() && index >= caps_word_end {
+                cells.extend_from_slice(&self.capsign);
+            }
+            if after_digit && ('a'..='j').contains(&lower[index]) {
+                cells.extend_from_slice(&self.letsign);
+            }
+            cells.extend_from_slice(&rule.cells);
+            index += rule.text.len();
+        }
+        cells
+    }
+
+    fn page_number(&self, page: usize) -> Vec<u8> {
+        let mut cells = self.numsign.clone();
+        for digit in page.to_string().chars() {
+            if let Some(digit_cells) = self.digits.get(&digit) {
+                cells.extend_from_slice(digit_cells);
+            }
+        }
+        cells
+    }
+
+    /// Translates `text` and lays it out as BRF pages separated by form feeds.
+    pub fn to_brf(&self, text: &str, layout: BrfLayout) -> BrfDocument {
+        let width = layout.cells_per_line.max(1);
+        let mut untranslated = 0usize;
+        let mut lines: Vec<Vec<u8>> = Vec::new();
+        for source_line in text.lines() {
+            let cells = self.translate_line(source_line.trim_end(), &mut untranslated);
+            wrap_cells(&cells, width, &mut lines);
+        }
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        let body_lines = layout
+            .lines_per_page
+            .saturating_sub(usize::from(layout.page_numbers))
+            .max(1);
+        let pages: Vec<&[Vec<u8>]> = if lines.is_empty() {
+            vec![&[]]
+        } else {
+            lines.chunks(body_lines).collect()
+        };
+        let mut out = String::new();
+        for (idx, page) in pages.iter().enumerate() {
+            if idx > 0 {
+                out.push('\x0c');
+            }
+            for line in page.iter() {
+                out.push_str(&cells_to_brf(line));
+                out.push_str("\r\n");
+            }
+            if layout.page_numbers {
+                for _ in page.len()..body_lines {
+                    out.push_str("\r\n");
+                }
+                let number = self.page_number(idx + 1);
+                out.push_str(&" ".repeat(width.saturating_sub(number.len())));
+                out.push_str(&cells_to_brf(&number));
+                out.push_str("\r\n");
+            }
+        }
+        BrfDocument {
+            text: out,
+            pages: pages.len(),
+            untranslated,
+        }
+    }
+}
+
+/// Word-wraps a line of cells; words longer than a line are split.
+fn wrap_cells(cells: &[u8], width: usize, out: &mut Vec<Vec<u8>>) {
+    let mut current: Vec<u8> = Vec::new();
+    for word in cells.split(|&cell| cell == 0) {
+        if word.is_empty() {
+            continue;
+        }
+        let needed = if current.is_empty() {
+            word.len()
+        } else {
+            current.len() + 1 + word.len()
+        };
+        if needed > width && !current.is_empty() {
+            out.push(std::mem::take(&mut current));
+        }
+        let mut rest = word;
+        while rest.len() > width {
+            let (head, tail) = rest.split_at(width);
+            out.push(head.to_vec());
+            rest = tail;
+        }
+        if !current.is_empty() {
+            current.push(0);
+        }
+        current.extend_from_slice(rest);
+    }
+    out.push(current);
+}
+
+fn cells_to_brf(cells: &[u8]) -> String {
+    cells
+        .iter()
+        .map(|&cell| BRF_CHARS[usize::from(cell & 0x3f)] as char)
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_tables_parse() {
+        for id in table_ids() {
+            let table = load_table(id).unwrap();
+            assert!(!table.name.is_empty(), "{id}");
+        }
+    }
+
+    #[test]
+    fn translates_capitals_numbers_and_punctuation() {
+        let table = load_table("en-g1").unwrap();
+        let mut untranslated = 0;
+        let cells = table.translate_line("Hi NASA, 12a.", &mut untranslated);
+        assert_eq!(cells_to_brf(&cells), ",HI ,,NASA1 #AB;A4");
+        assert_eq!(untranslated, 0);
+
+        let table = load_table("vi-g1").unwrap();
+        let cells = table.translate_line("Việt", &mut untranslated);
+        assert_eq!(cells, parse_cells("46-1236-24-6-126-2345").unwrap());
+    }
+
+    #[test]
+    fn paginates_with_page_numbers() {
+        let table = load_table("it-g1").unwrap();
+        let layout = BrfLayout {
+            cells_per_line: 10,
+            lines_per_page: 3,
+            page_numbers: true,
+        };
+        let brf = table.to_brf("uno due tre quattro\ncinque", layout);
+        assert_eq!(brf.pages, 2);
+        assert_eq!(
+            brf.text,
+            "UNO DUE\r\nTRE\r\n        #A\r\n\x0cQUATTRO\r\nCINQUE\r\n        #B\r\n"
+        );
+    }
+}
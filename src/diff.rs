@@ -0,0 +1,317 @@
+use std::ops::Range;
+
+/// Above this many inserted plus deleted lines the comparison stops looking
+/// for matches and reports the remaining region as a single change.
+const MAX_EDIT_DISTANCE: usize = 2000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HunkKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A run of differing lines between an old and a new text. Both ranges are
+/// 0-based and half-open; an empty range marks where the other side's lines
+/// would go.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Hunk {
+    pub kind: HunkKind,
+    pub old: Range<usize>,
+    pub new: Range<usize>,
+}
+
+/// Text removed and added at one spot inside a changed hunk.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct WordChange {
+    pub removed: String,
+    pub added: String,
+}
+
+/// Splits text into lines without their terminators; a final newline does not
+/// start an extra empty line.
+pub fn split_lines(text: &str) -> Vec<&str> {
+    let text = text
+        .strip_suffix("\r\n")
+        .or_else(|| text.strip_suffix('\n'))
+        .unwrap_or(text);
+    if text.is_empty() {
+        return Vec::new();
+    }
+    text.split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect()
+}
+
+pub fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Hunk> {
+    let pairs = matching_pairs(old, new);
+    let mut hunks = Vec::new();
+    let (mut next_old, mut next_new) = (0, 0);
+    for (i, j) in pairs
+        .into_iter()
+        .chain(std::iter::once((old.len(), new.len())))
+    {
+        if i > next_old || j > next_new {
+            let kind = if i == next_old {
+                HunkKind::Added
+            } else if j == next_new {
+                HunkKind::Removed
+            } else {
+                HunkKind::Changed
+            };
+            hunks.push(Hunk {
+                kind,
+                old: next_old..i,
+                new: next_new..j,
+            });
+        }
+        next_old = i + 1;
+        next_new = j + 1;
+    }
+    hunks
+}
+
+/// Word-level differences between the two sides of a changed hunk.
+pub fn diff_words(old: &str, new: &str) -> Vec<WordChange> {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    let pairs = matching_pairs(&old_tokens, &new_tokens);
+    let mut changes = Vec::new();
+    let (mut next_old, mut next_new) = (0, 0);
+    let end = (old_tokens.len(), new_tokens.len());
+    for (i, j) in pairs.into_iter().chain(std::iter::once(end)) {
+        let removed = old_tokens[next_old..i].concat();
+        let added = new_tokens[next_new..j].concat();
+        let removed = removed.trim();
+        let added = added.trim();
+        if !removed.is_empty() || !added.is_empty() {
+            changes.push(WordChange {
+                removed: removed.to_string(),
+                added: added.to_string(),
+            });
+        }
+        next_old = i + 1;
+        next_new = j + 1;
+    }
+    changes
+}
+
+/// Rebuilds `target` with the lines in `range` replaced by `replacement`,
+/// keeping its line ending style and trailing newline.
+pub fn replace_lines(target: &str, range: Range<usize>, replacement: &[&str]) -> String {
+    let eol = if target.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let has_trailing_newline = target.ends_with('\n');
+    let mut lines = split_lines(target);
+    let end = range.end.min(lines.len());
+    let start = range.start.min(end);
+    lines.splice(start..end, replacement.iter().copied());
+    let mut out = lines.join(eol);
+    if !lines.is_empty() && (has_trailing_newline || target.is_empty()) {
+        out.push_str(eol);
+    }
+    out
+}
+
+/// Byte ranges of `old` and `new` that differ once their common start and end
+/// are set aside. A CRLF pair is never split between the two.
+pub fn changed_span(old: &str, new: &str) -> (Range<usize>, Range<usize>) {
+    let mut prefix = old
+        .char_indices()
+        .zip(new.chars())
+        .find(|((_, a), b)| a != b)
+        .map(|((idx, _), _)| idx)
+        .unwrap_or(old.len().min(new.len()));
+    while prefix > 0 && old[..prefix].ends_with('\r') {
+        prefix -= 1;
+    }
+    let limit = old.len().min(new.len()) - prefix;
+    let mut suffix = 0;
+    for (a, b) in old[prefix..].chars().rev().zip(new[prefix..].chars().rev()) {
+        if a != b || suffix + a.len_utf8() > limit {
+            break;
+        }
+        suffix += a.len_utf8();
+    }
+    while suffix > 0
+        && old[old.len() - suffix..].starts_with('\n')
+        && old[..old.len() - suffix].ends_with('\r')
+    {
+        suffix -= 1;
+    }
+    (prefix..old.len() - suffix, prefix..new.len() - suffix)
+}
+
+/// Words, runs of whitespace and single punctuation characters.
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (idx, ch) in text.char_indices() {
+        if ch.is_alphanumeric() {
+            start.get_or_insert(idx);
+            continue;
+        }
+        if let Some(word_start) = start.take() {
+            tokens.push(&text[word_start..idx]);
+        }
+        tokens.push(&text[idx..idx + ch.len_utf8()]);
+    }
+    if let Some(word_start) = start {
+        tokens.push(&text[word_start..]);
+    }
+    tokens
+}
+
+/// Index pairs of equal items in a shortest edit script (Myers), in order.
+fn matching_pairs<T: PartialEq>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let middle_a = &a[prefix..a.len() - suffix];
+    let middle_b = &b[prefix..b.len() - suffix];
+
+    let mut pairs: Vec<(usize, usize)> = (0..prefix).map(|i| (i, i)).collect();
+    if let Some(middle) = myers(middle_a, middle_b) {
+        pairs.extend(middle.into_iter().map(|(i, j)| (i + prefix, j + prefix)));
+    }
+    let a_tail = a.len() - suffix;
+    let b_tail = b.len() - suffix;
+    pairs.extend((0..suffix).map(|i| (a_tail + i, b_tail + i)));
+    pairs
+}
+
+fn myers<T: PartialEq>(a: &[T], b: &[T]) -> Option<Vec<(usize, usize)>> {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let max = (a.len() + b.len()).min(MAX_EDIT_DISTANCE) as isize;
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * max as usize + 3];
+    // Each snapshot holds the diagonals -(d+1)..=d+1 as they were before step d.
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    let mut found = false;
+    for d in 0..=max {
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        let mut k = -d;
+        while k <= d {
+            let idx = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+                v[idx + 1]
+            } else {
+                v[idx - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                found = true;
+                break;
+            }
+            k += 2;
+        }
+        if found {
+            break;
+        }
+    }
+    if !found {
+        return None;
+    }
+
+    let mut pairs = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, snapshot) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let at = |k: isize| snapshot[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            pairs.push((x as usize, y as usize));
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    pairs.reverse();
+    Some(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        let old = split_lines("a\nb\nc\nd\ne\n");
+        let new = split_lines("a\nc\nD\ne\nf");
+        assert_eq!(
+            diff_lines(&old, &new),
+            vec![
+                Hunk {
+                    kind: HunkKind::Removed,
+                    old: 1..2,
+                    new: 1..1,
+                },
+                Hunk {
+                    kind: HunkKind::Changed,
+                    old: 3..4,
+                    new: 2..3,
+                },
+                Hunk {
+                    kind: HunkKind::Added,
+                    old: 5..5,
+                    new: 4..5,
+                },
+            ]
+        );
+        assert!(diff_lines(&old, &old).is_empty());
+        assert_eq!(diff_lines(&[], &old).len(), 1);
+    }
+
+    #[test]
+    fn test_diff_words() {
+        assert_eq!(
+            diff_words("Payment due in thirty days.", "Payment due in sixty days."),
+            vec![WordChange {
+                removed: "thirty".to_string(),
+                added: "sixty".to_string(),
+            }]
+        );
+        assert_eq!(
+            diff_words("one two", "one new two"),
+            vec![WordChange {
+                removed: String::new(),
+                added: "new".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_replace_lines() {
+        assert_eq!(
+            replace_lines("a\r\nb\r\nc", 1..2, &["x", "y"]),
+            "a\r\nx\r\ny\r\nc"
+        );
+        assert_eq!(replace_lines("a\nb\n", 2..2, &["c"]), "a\nb\nc\n");
+        assert_eq!(replace_lines("a\nb", 0..1, &[]), "b");
+        assert_eq!(replace_lines("", 0..0, &["a"]), "a\n");
+        assert_eq!(changed_span("a\r\nb", "a\r\nc"), (3..4, 3..4));
+        assert_eq!(changed_span("a\r\nb", "a\nb"), (1..3, 1..2));
+        assert_eq!(changed_span("aa", "aaa"), (2..2, 2..3));
+    }
+}
@@ -1,14 +1,23 @@
-use crate::accessibility::{EM_REPLACESEL, to_wide, to_wide_normalized};
+use crate::accessibility::{EM_REPLACESEL, EM_SCROLLCARET, to_wide, to_wide_normalized};
+use crate::archive::{
+    ArchiveSource, is_archive_path, is_extracted_entry_path, is_zip_archive_path,
+};
 use crate::file_handler::decode_text_with_encoding;
 use crate::file_handler::*;
+use crate::large_file::LargeFile;
 use crate::settings::{
-    FileFormat, ModifiedMarkerPosition, TextEncoding, confirm_save_message, confirm_title,
-    untitled_title,
+    AppSettings, FileFormat, LineEnding, MacroStep, ModifiedMarkerPosition, TextEncoding,
+    confirm_save_message, confirm_title, untitled_title,
 };
+use crate::subtitles::{SubtitleFormat, SubtitleTrack, SubtitleView};
+use crate::text_ops::CaseConversion;
 use crate::{log_debug, with_state};
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, RECT, WPARAM};
+use windows::Win32::Globalization::{
+    NormalizationC, NormalizationD, NormalizationKC, NormalizeString,
+};
 use windows::Win32::Graphics::Gdi::HFONT;
 use windows::Win32::UI::Controls::RichEdit::{
     CFM_COLOR, CFM_SIZE, CHARFORMAT2W, CHARRANGE, EM_EXGETSEL, EM_EXSETSEL, EM_GETTEXTRANGE,
@@ -23,10 +32,11 @@ use windows::Win32::UI::Input::KeyboardAndMouse::SetFocus;
 use windows::Win32::UI::WindowsAndMessaging::{
     CallWindowProcW, DefWindowProcW, DestroyWindow, ES_AUTOHSCROLL, ES_AUTOVSCROLL, ES_MULTILINE,
     ES_WANTRETURN, GWLP_USERDATA, GWLP_WNDPROC, GetClientRect, GetParent, GetWindowLongPtrW,
-    GetWindowTextLengthW, GetWindowTextW, HMENU, IDNO, IDYES, MB_ICONWARNING, MB_YESNOCANCEL,
-    MessageBoxW, MoveWindow, SW_HIDE, SW_SHOW, SendMessageW, SetWindowLongPtrW, SetWindowTextW,
-    ShowWindow, WM_CHAR, WM_CONTEXTMENU, WM_SETFONT, WS_CHILD, WS_CLIPCHILDREN, WS_EX_CLIENTEDGE,
-    WS_GROUP, WS_HSCROLL, WS_VSCROLL,
+    GetWindowTextLengthW, GetWindowTextW, HMENU, IDNO, IDYES, MB_ICONINFORMATION, MB_ICONQUESTION,
+    MB_ICONWARNING, MB_YESNO, MB_YESNOCANCEL, MessageBoxW, MoveWindow, SW_HIDE, SW_SHOW,
+    SendMessageW, SetWindowLongPtrW, SetWindowTextW, ShowWindow, WM_CHAR, WM_CONTEXTMENU,
+    WM_SETFONT, WM_UNDO, WS_CHILD, WS_CLIPCHILDREN, WS_EX_CLIENTEDGE, WS_GROUP, WS_HSCROLL,
+    WS_VSCROLL,
 };
 use windows::core::{PCWSTR, PWSTR};
 
@@ -34,6 +44,7 @@ const EM_LIMITTEXT: u32 = 0x00C5;
 const EM_BEGINUNDOACTION: u32 = 0x0459;
 const EM_ENDUNDOACTION: u32 = 0x045A;
 const EM_STOPGROUPTYPING: u32 = 0x0477;
+const EM_REDO: u32 = 0x0454;
 const EM_SETTEXTEX: u32 = 0x0461;
 const EM_GETTEXTLENGTHEX: u32 = 0x045F;
 const ST_KEEPUNDO: u32 = 0x0001;
@@ -45,6 +56,11 @@ const VOICE_PANEL_ROW_HEIGHT: i32 = 22;
 const VOICE_PANEL_SPACING: i32 = 6;
 const VOICE_PANEL_LABEL_WIDTH: i32 = 140;
 const VOICE_PANEL_COMBO_HEIGHT: i32 = 140;
+/// Most frequent words listed in the text statistics report.
+const TEXT_STATS_TOP_WORDS: usize = 10;
+/// Invisible characters listed one by one in the report; the rest are only counted.
+const MAX_INVISIBLE_REPORT_ENTRIES: usize = 2000;
+const MAX_NORMALIZE_ATTEMPTS: usize = 4;
 
 unsafe fn should_use_opening_quote(hwnd_edit: HWND) -> bool {
     let mut selection = CHARRANGE { cpMin: 0, cpMax: 0 };
@@ -104,6 +120,10 @@ unsafe extern "system" fn edit_subclass_proc(
 ) -> LRESULT {
     if msg == WM_CHAR {
         let ch = wparam.0 as u32;
+        if ch == 9 && handle_snippet_tab(hwnd) {
+            return LRESULT(0);
+        }
+        autocorrect_before_char(hwnd, ch);
         if matches!(
             ch,
             9 | 13 | 32 | 44 | 46 | 58 | 59 | 33 | 63 | 41 | 93 | 125
@@ -189,7 +209,26 @@ pub struct Document {
     pub format: FileFormat,
     pub opened_text_encoding: Option<TextEncoding>,
     pub current_save_text_encoding: Option<TextEncoding>,
+    pub line_ending: LineEnding,
+    pub archive_source: Option<ArchiveSource>,
+    /// Set when the file is too large to load whole and is paged through read-only.
+    pub large_file: Option<LargeFile>,
+    /// Cues of an SRT/WebVTT file; the editor shows their text.
+    pub subtitles: Option<SubtitleView>,
     pub from_rss: bool,
+    /// Set when an HTML file is shown in reader mode, with `[n]` link markers.
+    pub html_reader: bool,
+}
+
+#[derive(Clone)]
+pub struct AutocorrectUndo {
+    pub hwnd_edit: HWND,
+    /// Where the replacement starts, in characters.
+    pub start: i32,
+    pub original: String,
+    pub replacement: String,
+    /// The character typed after the word, which triggered the correction.
+    pub trigger: String,
 }
 
 #[derive(Clone)]
@@ -211,7 +250,12 @@ impl Default for Document {
             format: FileFormat::Text(TextEncoding::Utf8),
             opened_text_encoding: None,
             current_save_text_encoding: None,
+            line_ending: LineEnding::default(),
+            archive_source: None,
+            large_file: None,
+            subtitles: None,
             from_rss: false,
+            html_reader: false,
         }
     }
 }
@@ -277,42 +321,313 @@ pub unsafe fn remove_duplicate_consecutive_lines_active_edit(hwnd: HWND) -> bool
     apply_text_op_active_edit(hwnd, crate::text_ops::remove_duplicate_consecutive_lines)
 }
 
-unsafe fn apply_text_op_active_edit<F>(hwnd: HWND, op: F) -> bool
-where
-    F: Fn(&str) -> String,
-{
-    let Some(hwnd_edit) = crate::get_active_edit(hwnd) else {
-        return false;
+/// Repairs UTF-8 text decoded as Windows-1252; returns how many sequences were fixed.
+pub unsafe fn fix_mojibake_active_edit(hwnd: HWND) -> usize {
+    let repaired = std::cell::Cell::new(0);
+    apply_text_op_active_edit(hwnd, |text| {
+        let (fixed, count) = crate::text_ops::fix_mojibake(text);
+        repaired.set(count);
+        fixed
+    });
+    repaired.get()
+}
+
+/// Strips accents and transliterates the selection, or the whole document, to Latin
+/// letters; with `punctuation` typographic quotes and dashes become ASCII too.
+pub unsafe fn transliterate_active_edit(hwnd: HWND, punctuation: bool) -> bool {
+    apply_text_op_active_edit(hwnd, |text| {
+        crate::transliterate::to_ascii(text, punctuation)
+    })
+}
+
+/// Encodes or decodes the selection, or the whole document; None when the text is
+/// not valid for `codec`.
+pub unsafe fn codec_active_edit(
+    hwnd: HWND,
+    codec: crate::text_codecs::TextCodec,
+    encode: bool,
+) -> Option<bool> {
+    let invalid = std::cell::Cell::new(false);
+    let changed = apply_text_op_active_edit(hwnd, |text| {
+        if encode {
+            return codec.encode(text);
+        }
+        codec.decode(text).unwrap_or_else(|| {
+            invalid.set(true);
+            text.to_string()
+        })
+    });
+    (!invalid.get()).then_some(changed)
+}
+
+/// Normalization forms of Edit > Unicode.
+#[derive(Clone, Copy)]
+pub enum UnicodeForm {
+    Nfc,
+    Nfd,
+    Nfkc,
+}
+
+/// Normalizes `text` with the Windows normalization API.
+fn normalize_unicode(text: &str, form: UnicodeForm) -> Option<String> {
+    let norm_form = match form {
+        UnicodeForm::Nfc => NormalizationC,
+        UnicodeForm::Nfd => NormalizationD,
+        UnicodeForm::Nfkc => NormalizationKC,
+    };
+    let source: Vec<u16> = text.encode_utf16().collect();
+    if source.is_empty() {
+        return Some(String::new());
+    }
+    let source_len = source.len() as i32;
+    // The first call only estimates the length; a buffer that is still too small
+    // gets a new estimate back as a negative number.
+    let mut capacity = unsafe {
+        NormalizeString(
+            norm_form,
+            PCWSTR(source.as_ptr()),
+            source_len,
+            PWSTR::null(),
+            0,
+        )
     };
+    for _ in 0..MAX_NORMALIZE_ATTEMPTS {
+        if capacity <= 0 {
+            return None;
+        }
+        let mut buffer = vec![0u16; capacity as usize];
+        let written = unsafe {
+            NormalizeString(
+                norm_form,
+                PCWSTR(source.as_ptr()),
+                source_len,
+                PWSTR(buffer.as_mut_ptr()),
+                capacity,
+            )
+        };
+        if written > 0 {
+            return Some(String::from_utf16_lossy(&buffer[..written as usize]));
+        }
+        capacity = (-written).max(capacity * 2);
+    }
+    None
+}
 
-    let mut selection = CHARRANGE { cpMin: 0, cpMax: 0 };
-    SendMessageW(
-        hwnd_edit,
-        EM_EXGETSEL,
-        WPARAM(0),
-        LPARAM(&mut selection as *mut _ as isize),
-    );
+/// Applies a Unicode normalization form to the selection, or the whole document.
+pub unsafe fn normalize_unicode_active_edit(hwnd: HWND, form: UnicodeForm) -> bool {
+    apply_text_op_active_edit(hwnd, |text| {
+        normalize_unicode(text, form).unwrap_or_else(|| text.to_string())
+    })
+}
 
-    if selection.cpMin > selection.cpMax {
-        std::mem::swap(&mut selection.cpMin, &mut selection.cpMax);
+/// Removes, or replaces with a plain space, the invisible characters of `kinds` in the
+/// selection or the whole document; returns how many characters changed.
+pub unsafe fn remove_invisible_chars_active_edit(
+    hwnd: HWND,
+    kinds: &[crate::text_ops::InvisibleKind],
+) -> usize {
+    let changed = std::cell::Cell::new(0);
+    apply_text_op_active_edit(hwnd, |text| {
+        let (cleaned, count) = crate::text_ops::remove_invisible_chars(text, kinds);
+        changed.set(count);
+        cleaned
+    });
+    changed.get()
+}
+
+/// Opens a report of the invisible characters of the current document in a new tab;
+/// returns how many were found. In a large file only the loaded section is scanned.
+pub unsafe fn invisible_chars_report_active_edit(hwnd: HWND) -> usize {
+    use crate::app_windows::invisible_chars_window::{REPORTED_KINDS, kind_label};
+    let Some((hwnd_edit, first_line, language)) = with_state(hwnd, |state| {
+        let doc = state.docs.get(state.current)?;
+        let first_line = doc
+            .large_file
+            .as_ref()
+            .map(|large| large.window_lines().0)
+            .unwrap_or(1);
+        Some((doc.hwnd_edit, first_line, state.settings.language))
+    })
+    .flatten() else {
+        return 0;
+    };
+    let found = crate::text_ops::find_invisible_chars(&get_edit_text(hwnd_edit));
+    if found.is_empty() {
+        return 0;
     }
 
-    let (affected, replace_range) = if selection.cpMin != selection.cpMax {
-        let affected = get_text_range(hwnd_edit, selection);
-        if affected.is_empty() {
-            return false;
+    let mut report = vec![crate::i18n::tr_f(
+        language,
+        "unicode.report_summary",
+        &[("count", &found.len().to_string())],
+    )];
+    for kind in REPORTED_KINDS {
+        let count = found.iter().filter(|item| item.kind == kind).count();
+        if count > 0 {
+            report.push(format!(
+                "{}: {count}",
+                kind_label(language, kind).replace('&', "")
+            ));
         }
-        (affected, selection)
-    } else {
-        let text = get_edit_text(hwnd_edit);
-        if text.is_empty() {
-            return false;
+    }
+    report.push(String::new());
+    for item in found.iter().take(MAX_INVISIBLE_REPORT_ENTRIES) {
+        report.push(crate::i18n::tr_f(
+            language,
+            "unicode.report_item",
+            &[
+                ("line", &(item.line + first_line - 1).to_string()),
+                ("column", &item.column.to_string()),
+                ("code", &format!("U+{:04X}", item.ch as u32)),
+                ("name", crate::text_ops::invisible_char_name(item.ch)),
+            ],
+        ));
+    }
+    if found.len() > MAX_INVISIBLE_REPORT_ENTRIES {
+        report.push(crate::i18n::tr_f(
+            language,
+            "unicode.report_truncated",
+            &[("count", &MAX_INVISIBLE_REPORT_ENTRIES.to_string())],
+        ));
+    }
+    let title = crate::i18n::tr(language, "unicode.report_title");
+    new_text_document(hwnd, &title, &report.join("\n"));
+    found.len()
+}
+
+/// Sorts the selected lines, or the whole document, with the Sort lines dialog options.
+pub unsafe fn sort_lines_active_edit(hwnd: HWND, options: &crate::text_ops::SortOptions) -> bool {
+    apply_text_op_active_edit(hwnd, |text| crate::text_ops::sort_lines(text, options))
+}
+
+/// Numbers the selected lines, or every line of the document, as a single undo step.
+pub unsafe fn number_lines_active_edit(
+    hwnd: HWND,
+    options: &crate::text_ops::NumberingOptions,
+) -> bool {
+    apply_line_op_active_edit(hwnd, |text| crate::text_ops::number_lines(text, options))
+}
+
+/// Removes the leading numbers of the selected lines, or of the whole document; returns
+/// how many lines had one.
+pub unsafe fn remove_line_numbers_active_edit(hwnd: HWND) -> usize {
+    let removed = std::cell::Cell::new(0);
+    apply_line_op_active_edit(hwnd, |text| {
+        let (stripped, count) = crate::text_ops::remove_line_numbers(text);
+        removed.set(count);
+        stripped
+    });
+    removed.get()
+}
+
+/// Extracts (`keep`) or deletes a field range of the selected lines, or of every line of
+/// the document, as a single undo step.
+pub unsafe fn select_fields_active_edit(
+    hwnd: HWND,
+    delimiter: &str,
+    fields: (usize, usize),
+    keep: bool,
+) -> bool {
+    apply_line_op_active_edit(hwnd, |text| {
+        crate::text_ops::select_fields(text, delimiter, fields.0, fields.1, keep)
+    })
+}
+
+/// Changes the case of the selection, or of the whole document, as a single undo step.
+pub unsafe fn convert_case_active_edit(
+    hwnd: HWND,
+    conversion: crate::text_ops::CaseConversion,
+) -> bool {
+    let language = with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+    apply_text_op_active_edit(hwnd, |text| {
+        crate::text_ops::convert_case(text, conversion, language)
+    })
+}
+
+/// Keeps, or removes, the lines of the selection or the whole document accepted by
+/// `is_match`; returns how many lines matched. Nothing changes when no line matches.
+/// Returns `None` for a large file, which is read-only.
+pub unsafe fn filter_lines_active_edit<F>(hwnd: HWND, is_match: F, keep: bool) -> Option<usize>
+where
+    F: Fn(&str) -> bool,
+{
+    if current_is_large_file(hwnd) {
+        return None;
+    }
+    let matched = std::cell::Cell::new(0);
+    apply_text_op_active_edit(hwnd, |text| {
+        let (kept, removed, count) = crate::text_ops::partition_lines(text, &is_match);
+        matched.set(count);
+        if count == 0 {
+            text.to_string()
+        } else if keep {
+            kept
+        } else {
+            removed
         }
-        let replace_range = CHARRANGE {
-            cpMin: 0,
-            cpMax: byte_index_to_utf16(&text, text.len()),
-        };
-        (text, replace_range)
+    });
+    Some(matched.get())
+}
+
+/// Opens the lines of the selection, or the whole document, accepted by `is_match` in a
+/// new tab; returns how many lines were copied. Returns `None` for a large file, of
+/// which only a section is loaded.
+pub unsafe fn copy_matching_lines_to_new_tab<F>(
+    hwnd: HWND,
+    title: &str,
+    is_match: F,
+) -> Option<usize>
+where
+    F: Fn(&str) -> bool,
+{
+    if current_is_large_file(hwnd) {
+        return None;
+    }
+    let Some(hwnd_edit) = crate::get_active_edit(hwnd) else {
+        return Some(0);
+    };
+    let Some((text, _)) = edit_scope(hwnd_edit) else {
+        return Some(0);
+    };
+    let (matched, _, count) = crate::text_ops::partition_lines(&text, is_match);
+    if count > 0 {
+        new_text_document(hwnd, title, &matched);
+    }
+    Some(count)
+}
+
+unsafe fn apply_text_op_active_edit<F>(hwnd: HWND, op: F) -> bool
+where
+    F: Fn(&str) -> String,
+{
+    apply_scoped_op_active_edit(hwnd, edit_scope, op)
+}
+
+/// Like `apply_text_op_active_edit`, but a selection is widened to the whole lines it
+/// touches, for operations that work on line starts or fields.
+unsafe fn apply_line_op_active_edit<F>(hwnd: HWND, op: F) -> bool
+where
+    F: Fn(&str) -> String,
+{
+    apply_scoped_op_active_edit(hwnd, line_scope, op)
+}
+
+unsafe fn apply_scoped_op_active_edit<F>(
+    hwnd: HWND,
+    scope: unsafe fn(HWND) -> Option<(String, CHARRANGE)>,
+    op: F,
+) -> bool
+where
+    F: Fn(&str) -> String,
+{
+    if current_is_large_file(hwnd) {
+        return false;
+    }
+    let Some(hwnd_edit) = crate::get_active_edit(hwnd) else {
+        return false;
+    };
+    let Some((affected, replace_range)) = scope(hwnd_edit) else {
+        return false;
     };
 
     let processed = op(&affected);
@@ -364,6 +679,62 @@ where
     true
 }
 
+/// Text the line operations work on: the selection, or the whole document when nothing
+/// is selected, with its character range. None when that text is empty.
+unsafe fn edit_scope(hwnd_edit: HWND) -> Option<(String, CHARRANGE)> {
+    let mut selection = CHARRANGE { cpMin: 0, cpMax: 0 };
+    SendMessageW(
+        hwnd_edit,
+        EM_EXGETSEL,
+        WPARAM(0),
+        LPARAM(&mut selection as *mut _ as isize),
+    );
+
+    if selection.cpMin > selection.cpMax {
+        std::mem::swap(&mut selection.cpMin, &mut selection.cpMax);
+    }
+
+    if selection.cpMin != selection.cpMax {
+        let affected = get_text_range(hwnd_edit, selection);
+        if affected.is_empty() {
+            return None;
+        }
+        Some((affected, selection))
+    } else {
+        let text = get_edit_text(hwnd_edit);
+        if text.is_empty() {
+            return None;
+        }
+        let range = CHARRANGE {
+            cpMin: 0,
+            cpMax: byte_index_to_utf16(&text, text.len()),
+        };
+        Some((text, range))
+    }
+}
+
+/// The whole lines touched by the selection, or the whole document when nothing is
+/// selected. A selection ending at the start of a line leaves that line out.
+unsafe fn line_scope(hwnd_edit: HWND) -> Option<(String, CHARRANGE)> {
+    let (_, selection) = edit_scope(hwnd_edit)?;
+    let text = get_edit_text(hwnd_edit);
+    let start_byte = utf16_index_to_byte(&text, selection.cpMin);
+    let mut end_byte = utf16_index_to_byte(&text, selection.cpMax);
+    if end_byte > start_byte && text.as_bytes()[end_byte - 1] == b'\n' {
+        end_byte -= 1;
+    }
+    let line_start = text[..start_byte].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = text[end_byte..]
+        .find('\n')
+        .map(|i| end_byte + i + 1)
+        .unwrap_or(text.len());
+    let range = CHARRANGE {
+        cpMin: byte_index_to_utf16(&text, line_start),
+        cpMax: byte_index_to_utf16(&text, line_end),
+    };
+    Some((text[line_start..line_end].to_string(), range))
+}
+
 fn get_text_range(hwnd_edit: HWND, range: CHARRANGE) -> String {
     let len = (range.cpMax - range.cpMin).max(0) as usize;
     if len == 0 {
@@ -462,6 +833,145 @@ fn end_single_undo_action(hwnd_edit: HWND) {
     }
 }
 
+/// Forgets the last autocorrection once anything else is typed, and corrects the
+/// word before the caret when `ch` ends it.
+unsafe fn autocorrect_before_char(hwnd_edit: HWND, ch: u32) {
+    let hwnd = GetParent(hwnd_edit);
+    let enabled = with_state(hwnd, |state| {
+        state.last_autocorrection = None;
+        state.settings.autocorrect
+    })
+    .unwrap_or(false);
+    if !enabled
+        || !matches!(
+            ch,
+            9 | 13 | 32 | 44 | 46 | 58 | 59 | 33 | 63 | 41 | 93 | 125
+        )
+        || current_is_large_file(hwnd)
+    {
+        return;
+    }
+    let mut selection = CHARRANGE { cpMin: 0, cpMax: 0 };
+    SendMessageW(
+        hwnd_edit,
+        EM_EXGETSEL,
+        WPARAM(0),
+        LPARAM(&mut selection as *mut _ as isize),
+    );
+    if selection.cpMin != selection.cpMax {
+        return;
+    }
+    let caret = selection.cpMax;
+    let before = get_text_range(
+        hwnd_edit,
+        CHARRANGE {
+            cpMin: (caret - crate::autocorrect::MAX_WORD_CHARS as i32).max(0),
+            cpMax: caret,
+        },
+    );
+    let Some(correction) = with_state(hwnd, |state| {
+        crate::autocorrect::correct(&before, state.settings.language, &state.autocorrect)
+    })
+    .flatten() else {
+        return;
+    };
+    let original = before[correction.start..].to_string();
+    let start = caret - original.encode_utf16().count() as i32;
+    select_edit_range(hwnd_edit, start, caret);
+    begin_single_undo_action(hwnd_edit);
+    let replace_wide = to_wide(&correction.replacement);
+    SendMessageW(
+        hwnd_edit,
+        EM_REPLACESEL,
+        WPARAM(1),
+        LPARAM(replace_wide.as_ptr() as isize),
+    );
+    end_single_undo_action(hwnd_edit);
+    // The trigger character becomes an undo step of its own, after the correction.
+    SendMessageW(hwnd_edit, EM_STOPGROUPTYPING, WPARAM(0), LPARAM(0));
+    mark_dirty_from_edit(hwnd, hwnd_edit);
+    with_state(hwnd, |state| {
+        state.last_autocorrection = Some(AutocorrectUndo {
+            hwnd_edit,
+            start,
+            original,
+            replacement: correction.replacement,
+            trigger: char::from_u32(ch).map(String::from).unwrap_or_default(),
+        });
+    });
+}
+
+/// Ctrl+Z right after an autocorrection, with the caret still after the character
+/// that triggered it, restores the word as typed.
+///
+/// The undo stack then ends with the typed word, the correction and the trigger
+/// character. The last two are undone and the trigger character is typed again, so
+/// the stack ends with the typed word and the trigger character: the next Ctrl+Z
+/// removes the character and the one after works on the word, as without the
+/// correction.
+pub unsafe fn try_autocorrect_undo(hwnd: HWND) -> bool {
+    let Some(undo) = with_state(hwnd, |state| state.last_autocorrection.take()).flatten() else {
+        return false;
+    };
+    if crate::get_active_edit(hwnd) != Some(undo.hwnd_edit) {
+        return false;
+    }
+    let mut selection = CHARRANGE { cpMin: 0, cpMax: 0 };
+    SendMessageW(
+        undo.hwnd_edit,
+        EM_EXGETSEL,
+        WPARAM(0),
+        LPARAM(&mut selection as *mut _ as isize),
+    );
+    let end = undo.start + undo.replacement.encode_utf16().count() as i32;
+    if selection.cpMin != selection.cpMax
+        || selection.cpMax != end + 1
+        || get_text_range(
+            undo.hwnd_edit,
+            CHARRANGE {
+                cpMin: undo.start,
+                cpMax: end,
+            },
+        ) != undo.replacement
+    {
+        return false;
+    }
+    SendMessageW(undo.hwnd_edit, WM_UNDO, WPARAM(0), LPARAM(0));
+    SendMessageW(undo.hwnd_edit, WM_UNDO, WPARAM(0), LPARAM(0));
+    let restored_end = undo.start + undo.original.encode_utf16().count() as i32;
+    let restored = get_text_range(
+        undo.hwnd_edit,
+        CHARRANGE {
+            cpMin: undo.start,
+            cpMax: restored_end,
+        },
+    );
+    if restored != undo.original {
+        // The stack was not laid out as expected: put everything back and let the
+        // caller undo normally.
+        SendMessageW(undo.hwnd_edit, EM_REDO, WPARAM(0), LPARAM(0));
+        SendMessageW(undo.hwnd_edit, EM_REDO, WPARAM(0), LPARAM(0));
+        return false;
+    }
+    select_edit_range(undo.hwnd_edit, restored_end, restored_end);
+    let trigger_wide = to_wide(&undo.trigger);
+    SendMessageW(
+        undo.hwnd_edit,
+        EM_REPLACESEL,
+        WPARAM(1),
+        LPARAM(trigger_wide.as_ptr() as isize),
+    );
+    SendMessageW(undo.hwnd_edit, EM_STOPGROUPTYPING, WPARAM(0), LPARAM(0));
+    mark_dirty_from_edit(hwnd, undo.hwnd_edit);
+    let language = with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+    crate::accessibility::nvda_speak(&crate::i18n::tr_f(
+        language,
+        "autocorrect.undone",
+        &[("word", &undo.original)],
+    ));
+    true
+}
+
 pub unsafe fn try_normalize_undo(hwnd: HWND) -> bool {
     let mut undo = None;
     if with_state(hwnd, |state| {
@@ -1301,17 +1811,130 @@ pub unsafe fn clean_end_of_line_hyphens_active_edit(hwnd: HWND) -> bool {
     true
 }
 
+/// Plays back macro `steps` on a whole document, with the same options the menu
+/// commands use. Fails only on an invalid recorded regular expression.
+pub fn apply_macro_steps(
+    text: &str,
+    steps: &[MacroStep],
+    settings: &AppSettings,
+) -> Result<String, String> {
+    let mut text = text.to_string();
+    for step in steps {
+        let line_ending = if text.contains("\r\n") { "\r\n" } else { "\n" };
+        let trailing = text.ends_with('\n');
+        let case = |conversion| crate::text_ops::convert_case(&text, conversion, settings.language);
+        text = match step {
+            MacroStep::StripMarkdown => {
+                strip_markdown_text(&text, settings.strip_markdown_keep_bullets)
+            }
+            MacroStep::NormalizeWhitespace => normalize_whitespace_block(&text, line_ending),
+            MacroStep::HardLineBreak => reflow_block_text(
+                &text,
+                settings.wrap_width.max(1) as usize,
+                line_ending,
+                trailing,
+            ),
+            MacroStep::OrderItems => order_lines_block(&text, line_ending, trailing),
+            MacroStep::KeepUniqueItems => keep_unique_lines_block(&text, line_ending, trailing),
+            MacroStep::ReverseItems => reverse_lines_block(&text, line_ending, trailing),
+            MacroStep::QuoteLines if !settings.quote_prefix.is_empty() => {
+                quote_lines_block(&text, line_ending, trailing, &settings.quote_prefix)
+            }
+            MacroStep::UnquoteLines if !settings.quote_prefix.is_empty() => {
+                unquote_lines_block(&text, line_ending, trailing, &settings.quote_prefix)
+            }
+            MacroStep::QuoteLines | MacroStep::UnquoteLines => text.clone(),
+            MacroStep::JoinLines => join_lines_block(&text, line_ending, trailing),
+            MacroStep::CleanEolHyphens => {
+                clean_end_of_line_hyphens_block(&text, line_ending, trailing)
+            }
+            MacroStep::RemoveDuplicateLines => crate::text_ops::remove_duplicate_lines(&text),
+            MacroStep::RemoveDuplicateConsecutiveLines => {
+                crate::text_ops::remove_duplicate_consecutive_lines(&text)
+            }
+            MacroStep::FixMojibake => crate::text_ops::fix_mojibake(&text).0,
+            MacroStep::UpperCase => case(CaseConversion::Upper),
+            MacroStep::LowerCase => case(CaseConversion::Lower),
+            MacroStep::SentenceCase => case(CaseConversion::Sentence),
+            MacroStep::TitleCase => case(CaseConversion::Title),
+            MacroStep::ToggleCase => case(CaseConversion::Toggle),
+            MacroStep::CapitalizeSentences => case(CaseConversion::CapitalizeSentences),
+            MacroStep::Transliterate => crate::transliterate::to_ascii(&text, false),
+            MacroStep::TransliterateToAscii => crate::transliterate::to_ascii(&text, true),
+            MacroStep::RemoveLineNumbers => crate::text_ops::remove_line_numbers(&text).0,
+            MacroStep::ReplaceAll {
+                find,
+                replace,
+                match_case,
+                whole_word,
+                use_regex,
+                dot_matches_newline,
+            } => {
+                crate::search::replace_all_in_text(
+                    &text,
+                    find,
+                    replace,
+                    *match_case,
+                    *whole_word,
+                    *use_regex,
+                    *dot_matches_newline,
+                )?
+                .0
+            }
+        };
+    }
+    Ok(text)
+}
+
+/// Plays back a macro on the whole text of `hwnd_edit` as a single undo step; returns
+/// whether the text changed.
+pub unsafe fn apply_macro_to_edit(
+    hwnd: HWND,
+    hwnd_edit: HWND,
+    steps: &[MacroStep],
+    settings: &AppSettings,
+) -> Result<bool, String> {
+    let text = get_edit_text(hwnd_edit);
+    let processed = apply_macro_steps(&text, steps, settings)?;
+    Ok(replace_changed_text(hwnd, hwnd_edit, &text, &processed))
+}
+
+/// Editors of the open tabs a macro can run on: everything but audiobooks and
+/// large files, which are read-only.
+pub unsafe fn macro_target_edits(hwnd: HWND, all_tabs: bool) -> Vec<HWND> {
+    with_state(hwnd, |state| {
+        state
+            .docs
+            .iter()
+            .enumerate()
+            .filter(|(index, doc)| {
+                (all_tabs || *index == state.current)
+                    && doc.large_file.is_none()
+                    && !matches!(doc.format, FileFormat::Audiobook)
+            })
+            .map(|(_, doc)| doc.hwnd_edit)
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
 pub unsafe fn text_stats_active_edit(hwnd: HWND) {
     let Some(hwnd_edit) = crate::get_active_edit(hwnd) else {
         return;
     };
     let text = get_edit_text(hwnd_edit);
-    let language = with_state(hwnd, |state| state.settings.language).unwrap_or_default();
-    if text.is_empty() {
-        let message = build_text_stats_message(language, 0, 0, 0, 0);
-        crate::show_info(hwnd, language, &message);
-        return;
-    }
+    let (language, tts_rate) = with_state(hwnd, |state| {
+        (state.settings.language, state.settings.tts_rate)
+    })
+    .unwrap_or_default();
+    let line_ending = with_state(hwnd, |state| {
+        state
+            .docs
+            .get(state.current)
+            .map(|doc| doc.line_ending)
+            .unwrap_or_default()
+    })
+    .unwrap_or_default();
 
     let mut selection = CHARRANGE { cpMin: 0, cpMax: 0 };
     SendMessageW(
@@ -1321,7 +1944,7 @@ pub unsafe fn text_stats_active_edit(hwnd: HWND) {
         LPARAM(&mut selection as *mut _ as isize),
     );
 
-    let target = if selection.cpMin != selection.cpMax {
+    let target = if !text.is_empty() && selection.cpMin != selection.cpMax {
         let start_byte = utf16_index_to_byte(&text, selection.cpMin);
         let end_byte = utf16_index_to_byte(&text, selection.cpMax);
         &text[start_byte..end_byte]
@@ -1329,22 +1952,23 @@ pub unsafe fn text_stats_active_edit(hwnd: HWND) {
         &text[..]
     };
 
-    let chars_with_spaces = target.chars().count();
-    let chars_without_spaces = target.chars().filter(|c| !c.is_whitespace()).count();
-    let words = target.split_whitespace().count();
-    let lines = if target.is_empty() {
-        0
-    } else {
-        target.as_bytes().iter().filter(|b| **b == b'\n').count() + 1
-    };
-    let message = build_text_stats_message(
-        language,
-        chars_with_spaces,
-        chars_without_spaces,
-        words,
-        lines,
+    let report = build_text_stats_message(language, target, line_ending, tts_rate);
+    let prompt = format!(
+        "{report}\n\n{}",
+        crate::i18n::tr(language, "text_stats.open_report")
+    );
+    let title = crate::settings::info_title(language);
+    let answer = MessageBoxW(
+        hwnd,
+        PCWSTR(to_wide(&prompt).as_ptr()),
+        PCWSTR(to_wide(&title).as_ptr()),
+        MB_YESNO | MB_ICONINFORMATION,
     );
-    crate::show_info(hwnd, language, &message);
+    if answer == IDYES {
+        let title = crate::i18n::tr(language, "text_stats.report_title");
+        new_text_document(hwnd, &title, &report);
+        return;
+    }
     SetFocus(hwnd_edit);
 }
 
@@ -1610,32 +2234,104 @@ fn clean_end_of_line_hyphens_block(
 
 fn build_text_stats_message(
     language: crate::settings::Language,
-    chars_with_spaces: usize,
-    chars_without_spaces: usize,
-    words: usize,
-    lines: usize,
+    target: &str,
+    line_ending: LineEnding,
+    tts_rate: i32,
 ) -> String {
-    let with_spaces = crate::i18n::tr_f(
-        language,
-        "text_stats.characters_with_spaces",
-        &[("count", &chars_with_spaces.to_string())],
-    );
-    let without_spaces = crate::i18n::tr_f(
-        language,
-        "text_stats.characters_without_spaces",
-        &[("count", &chars_without_spaces.to_string())],
-    );
-    let words = crate::i18n::tr_f(
+    use crate::i18n::{tr, tr_f};
+    use crate::readability::{self, ReadabilityIndex};
+
+    let count_line =
+        |key: &str, count: usize| tr_f(language, key, &[("count", &count.to_string())]);
+    let duration = |seconds: u64| {
+        tr_f(
+            language,
+            "text_stats.duration",
+            &[
+                ("minutes", &(seconds / 60).to_string()),
+                ("seconds", &(seconds % 60).to_string()),
+            ],
+        )
+    };
+    let lines = if target.is_empty() {
+        0
+    } else {
+        target.as_bytes().iter().filter(|b| **b == b'\n').count() + 1
+    };
+    let metrics = readability::measure(target, language);
+    let mut report = vec![
+        count_line("text_stats.characters_with_spaces", target.chars().count()),
+        count_line(
+            "text_stats.characters_without_spaces",
+            target.chars().filter(|c| !c.is_whitespace()).count(),
+        ),
+        count_line("text_stats.words", metrics.words),
+        count_line("text_stats.lines", lines),
+        tr_f(
+            language,
+            "text_stats.line_ending",
+            &[("eol", line_ending.label())],
+        ),
+        count_line("text_stats.sentences", metrics.sentences),
+        count_line("text_stats.paragraphs", metrics.paragraphs),
+    ];
+    if metrics.sentences > 0 {
+        let average = metrics.words as f64 / metrics.sentences as f64;
+        report.push(tr_f(
+            language,
+            "text_stats.average_sentence",
+            &[("count", &format!("{average:.1}"))],
+        ));
+    }
+    match readability::readability_index(&metrics, language) {
+        Some(ReadabilityIndex::Gulpease(score)) => report.push(tr_f(
+            language,
+            "text_stats.gulpease",
+            &[("score", &format!("{score:.0}"))],
+        )),
+        Some(ReadabilityIndex::FleschKincaid(grade)) => report.push(tr_f(
+            language,
+            "text_stats.flesch_kincaid",
+            &[("score", &format!("{grade:.1}"))],
+        )),
+        Some(ReadabilityIndex::FernandezHuerta(score)) => report.push(tr_f(
+            language,
+            "text_stats.fernandez_huerta",
+            &[("score", &format!("{score:.0}"))],
+        )),
+        None => {}
+    }
+    report.push(tr_f(
         language,
-        "text_stats.words",
-        &[("count", &words.to_string())],
-    );
-    let lines = crate::i18n::tr_f(
+        "text_stats.reading_time",
+        &[(
+            "time",
+            &duration(readability::reading_seconds(metrics.words)),
+        )],
+    ));
+    report.push(tr_f(
         language,
-        "text_stats.lines",
-        &[("count", &lines.to_string())],
-    );
-    format!("{with_spaces}.\n{without_spaces}.\n{words}.\n{lines}.")
+        "text_stats.listening_time",
+        &[(
+            "time",
+            &duration(readability::listening_seconds(metrics.words, tts_rate)),
+        )],
+    ));
+    let mut message = report.join(".\n") + ".";
+    let top_words = readability::top_words(target, language, TEXT_STATS_TOP_WORDS);
+    if !top_words.is_empty() {
+        message.push_str("\n\n");
+        message.push_str(&tr(language, "text_stats.top_words"));
+        for (word, count) in top_words {
+            message.push('\n');
+            message.push_str(&tr_f(
+                language,
+                "text_stats.top_word",
+                &[("word", &word), ("count", &count.to_string())],
+            ));
+        }
+    }
+    message
 }
 
 fn reflow_block_text(
@@ -1937,7 +2633,12 @@ pub unsafe fn new_document(hwnd: HWND) {
             format: FileFormat::Text(TextEncoding::Utf8),
             opened_text_encoding: None,
             current_save_text_encoding: None,
+            line_ending: LineEnding::default(),
+            archive_source: None,
+            large_file: None,
+            subtitles: None,
             from_rss: false,
+            html_reader: false,
         };
         state.docs.push(doc);
         insert_tab(state.hwnd_tab, &title, (state.docs.len() - 1) as i32);
@@ -1963,7 +2664,18 @@ pub unsafe fn open_document_with_encoding(
         crate::open_pdf_document_async(hwnd, path);
         return;
     }
-    let (content, format, opened_text_encoding) = if is_docx_path(path) {
+    if is_archive_path(path) {
+        crate::app_windows::archive_window::open(hwnd, path);
+        return;
+    }
+    let mut large_file = None;
+    let mut html_reader = false;
+    let (content, format, opened_text_encoding) = if crate::encryption::is_encrypted_file(path) {
+        match read_encrypted_document(hwnd, path, language) {
+            Some((text, key)) => (text, FileFormat::Encrypted(key), None),
+            None => return,
+        }
+    } else if is_docx_path(path) {
         match read_docx_text(path, language) {
             Ok(text) => (text, FileFormat::Docx, None),
             Err(message) => {
@@ -1995,9 +2707,27 @@ pub unsafe fn open_document_with_encoding(
                 return;
             }
         }
+    } else if is_mobi_path(path) {
+        match read_mobi_text(path, language) {
+            Ok(text) => (text, FileFormat::Mobi, None),
+            Err(message) => {
+                crate::show_error(hwnd, language, &message);
+                return;
+            }
+        }
     } else if is_html_path(path) {
-        match read_html_text(path, language) {
-            Ok((text, _encoding)) => (text, FileFormat::Html, None),
+        let reader_mode =
+            with_state(hwnd, |state| state.settings.html_reader_mode).unwrap_or(false);
+        let result = if reader_mode {
+            read_html_reader_text(path, language)
+        } else {
+            read_html_text(path, language)
+        };
+        match result {
+            Ok((text, _encoding)) => {
+                html_reader = reader_mode;
+                (text, FileFormat::Html, None)
+            }
             Err(message) => {
                 crate::show_error(hwnd, language, &message);
                 return;
@@ -2021,6 +2751,21 @@ pub unsafe fn open_document_with_encoding(
                 return;
             }
         }
+    } else if crate::large_file::is_large_file(path)
+        && user_encoding.is_none_or(crate::large_file::supports_encoding)
+    {
+        match LargeFile::open(path, user_encoding, language) {
+            Ok(large) => {
+                let encoding = large.encoding();
+                let text = large.window_text();
+                large_file = Some(large);
+                (text, FileFormat::Text(encoding), Some(encoding))
+            }
+            Err(message) => {
+                crate::show_error(hwnd, language, &message);
+                return;
+            }
+        }
     } else {
         match std::fs::read(path) {
             Ok(bytes) => {
@@ -2055,6 +2800,35 @@ pub unsafe fn open_document_with_encoding(
         }
     };
 
+    let line_ending = if matches!(format, FileFormat::Text(_) | FileFormat::Encrypted(_)) {
+        crate::text_ops::detect_line_ending(&content)
+    } else {
+        LineEnding::default()
+    };
+
+    let mut content = content;
+    let mut subtitles = None;
+    if large_file.is_none()
+        && matches!(format, FileFormat::Text(_))
+        && let Some(subtitle_format) = SubtitleFormat::from_path(path)
+    {
+        // A malformed file still opens, as plain text, so it can be fixed by hand.
+        match SubtitleTrack::parse(&content, subtitle_format, language) {
+            Ok(track) => {
+                let show_timestamps =
+                    with_state(hwnd, |state| state.settings.subtitles_show_timestamps)
+                        .unwrap_or(true);
+                let view = SubtitleView {
+                    track,
+                    show_timestamps,
+                };
+                content = view.display_text();
+                subtitles = Some(view);
+            }
+            Err(message) => log_debug(&format!("Subtitles opened as text: {message}")),
+        }
+    }
+
     let new_index = with_state(hwnd, |state| {
         let title = path.file_name().and_then(|s| s.to_str()).unwrap_or("File");
         let hwnd_edit = create_edit(
@@ -2074,8 +2848,18 @@ pub unsafe fn open_document_with_encoding(
             format,
             opened_text_encoding,
             current_save_text_encoding: None,
+            line_ending,
+            archive_source: None,
+            large_file,
+            subtitles,
             from_rss: false,
+            html_reader,
         };
+        if doc.large_file.is_some() {
+            unsafe {
+                SendMessageW(hwnd_edit, EM_SETREADONLY, WPARAM(1), LPARAM(0));
+            }
+        }
         if matches!(format, FileFormat::Audiobook) {
             unsafe {
                 SendMessageW(hwnd_edit, EM_SETREADONLY, WPARAM(1), LPARAM(0));
@@ -2094,163 +2878,1097 @@ pub unsafe fn open_document_with_encoding(
             crate::audio_player::start_audiobook_playback(hwnd, path);
         }
     }
-    crate::push_recent_file(hwnd, path);
+    if !is_extracted_entry_path(path) {
+        crate::push_recent_file(hwnd, path);
+    }
+}
+
+/// Asks for the password of an encrypted document until it decrypts or the user
+/// cancels. None when cancelled or the file cannot be read.
+unsafe fn read_encrypted_document(
+    hwnd: HWND,
+    path: &Path,
+    language: crate::settings::Language,
+) -> Option<(String, crate::encryption::DocumentKey)> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            crate::show_error(
+                hwnd,
+                language,
+                &crate::settings::error_open_file_message(language, err),
+            );
+            return None;
+        }
+    };
+    let name = path.file_name().and_then(|s| s.to_str()).unwrap_or("File");
+    let title = crate::i18n::tr_f(language, "encryption.open_title", &[("name", name)]);
+    let mut status = String::new();
+    loop {
+        let password = crate::app_windows::password_window::ask_password(
+            hwnd, language, &title, false, &status,
+        )?;
+        match crate::encryption::decrypt(&bytes, &password) {
+            Ok(opened) => return Some(opened),
+            Err(crate::encryption::DecryptError::WrongPassword) => {
+                status = crate::i18n::tr(language, "encryption.wrong_password");
+            }
+            Err(crate::encryption::DecryptError::Unsupported) => {
+                crate::show_error(
+                    hwnd,
+                    language,
+                    &crate::i18n::tr(language, "encryption.unsupported"),
+                );
+                return None;
+            }
+        }
+    }
 }
 
 pub unsafe fn open_document(hwnd: HWND, path: &Path) {
     open_document_with_encoding(hwnd, path, None);
 }
 
-pub unsafe fn mark_current_document_from_rss(hwnd: HWND, from_rss: bool) {
-    let result = with_state(hwnd, |state| {
-        if let Some(doc) = state.docs.get_mut(state.current) {
-            doc.from_rss = from_rss;
+/// Extracts `entry` from `archive` and opens it like a regular file, remembering
+/// where it came from so saving can update the archive.
+pub unsafe fn open_archive_entry(hwnd: HWND, archive: &Path, entry: &str) -> bool {
+    let language = with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+    let extracted = match crate::archive::extract_archive_entry(archive, entry, language) {
+        Ok(path) => path,
+        Err(message) => {
+            crate::show_error(hwnd, language, &message);
+            return false;
         }
-    });
-    if result.is_none() {
-        crate::log_debug("Failed to access editor state");
+    };
+    let before = with_state(hwnd, |state| state.docs.len()).unwrap_or(0);
+    open_document(hwnd, &extracted);
+    let opened = with_state(hwnd, |state| {
+        if state.docs.len() <= before {
+            return false;
+        }
+        let index = state.docs.len() - 1;
+        state.docs[index].archive_source = Some(ArchiveSource {
+            archive: archive.to_path_buf(),
+            entry: entry.to_string(),
+        });
+        true
+    })
+    .unwrap_or(false);
+    if opened {
+        crate::push_recent_file(hwnd, archive);
     }
+    opened
 }
 
-pub unsafe fn current_document_is_from_rss(hwnd: HWND) -> bool {
+/// Changes the line ending the current document is saved with; returns false when nothing changed.
+pub unsafe fn set_current_line_ending(hwnd: HWND, line_ending: LineEnding) -> bool {
+    let changed = with_state(hwnd, |state| {
+        let current = state.current;
+        let doc = state.docs.get_mut(current)?;
+        if matches!(doc.format, FileFormat::Audiobook)
+            || doc.large_file.is_some()
+            || doc.line_ending == line_ending
+        {
+            return None;
+        }
+        doc.line_ending = line_ending;
+        doc.dirty = true;
+        update_tab_title(state.hwnd_tab, current, &doc.title, true);
+        Some(())
+    })
+    .flatten()
+    .is_some();
+    if changed {
+        update_window_title(hwnd);
+    }
+    changed
+}
+
+/// True when the current document was opened in large-file mode.
+pub unsafe fn current_is_large_file(hwnd: HWND) -> bool {
     with_state(hwnd, |state| {
         state
             .docs
             .get(state.current)
-            .map(|doc| doc.from_rss)
-            .unwrap_or(false)
+            .is_some_and(|doc| doc.large_file.is_some())
     })
     .unwrap_or(false)
 }
 
-pub unsafe fn select_tab(hwnd: HWND, index: usize) {
-    let result = with_state(hwnd, |state| {
-        if index >= state.docs.len() {
+/// Shows the next or previous section of a large file. Returns the first and last
+/// line shown and the total line count, or `None` when there is no section that way.
+pub unsafe fn step_large_file_section(hwnd: HWND, forward: bool) -> Option<(usize, usize, usize)> {
+    let loaded = with_state(hwnd, |state| {
+        let current = state.current;
+        let doc = state.docs.get_mut(current)?;
+        let large = doc.large_file.as_mut()?;
+        let moved = if forward {
+            large.next_window()
+        } else {
+            large.previous_window()
+        };
+        if !moved {
             return None;
         }
-        let prev = state.current;
-        let prev_edit = state.docs.get(prev).map(|doc| doc.hwnd_edit);
-        let new_doc = state.docs.get(index);
-        let new_edit = new_doc.map(|doc| doc.hwnd_edit);
-        let is_audiobook = new_doc
-            .map(|doc| matches!(doc.format, FileFormat::Audiobook))
-            .unwrap_or(false);
-        state.current = index;
-        Some((state.hwnd_tab, prev_edit, new_edit, is_audiobook))
+        let (first, last) = large.window_lines();
+        Some((
+            doc.hwnd_edit,
+            large.window_text(),
+            (first, last, large.line_count()),
+        ))
     })
     .flatten();
-
-    let Some((hwnd_tab, prev_edit, new_edit, is_audiobook)) = result else {
-        return;
+    let (hwnd_edit, text, lines) = loaded?;
+    set_edit_text(hwnd_edit, &text);
+    // Going back, continue reading from the end of the previous section.
+    let caret = if forward {
+        0
+    } else {
+        crate::large_file::edit_position(&text, text.len())
     };
-
-    if let Some(hwnd_edit) = prev_edit {
-        ShowWindow(hwnd_edit, SW_HIDE);
-    }
-    SendMessageW(hwnd_tab, TCM_SETCURSEL, WPARAM(index), LPARAM(0));
-    if let Some(hwnd_edit) = new_edit {
-        if is_audiobook {
-            ShowWindow(hwnd_edit, SW_HIDE);
-            SetFocus(hwnd_tab);
-        } else {
-            ShowWindow(hwnd_edit, SW_SHOW);
-            SetFocus(hwnd_edit);
-        }
-    }
-    update_window_title(hwnd);
-    crate::menu::update_playback_menu(hwnd, is_audiobook);
-    layout_children(hwnd);
+    select_edit_range(hwnd_edit, caret, caret);
+    Some(lines)
 }
 
-pub unsafe fn insert_tab(hwnd_tab: HWND, title: &str, index: i32) {
-    let mut text = to_wide(title);
-    let mut item = TCITEMW {
-        mask: TCIF_TEXT,
-        pszText: PWSTR(text.as_mut_ptr()),
-        ..Default::default()
+/// Number of lines in the current document, including the parts of a large file not loaded.
+pub unsafe fn current_line_count(hwnd: HWND) -> usize {
+    let Some((hwnd_edit, large_count)) = with_state(hwnd, |state| {
+        state.docs.get(state.current).map(|doc| {
+            (
+                doc.hwnd_edit,
+                doc.large_file.as_ref().map(LargeFile::line_count),
+            )
+        })
+    })
+    .flatten() else {
+        return 0;
     };
-    SendMessageW(
-        hwnd_tab,
-        TCM_INSERTITEMW,
-        WPARAM(index as usize),
-        LPARAM(&mut item as *mut _ as isize),
-    );
+    large_count.unwrap_or_else(|| crate::text_ops::line_count(&get_edit_text(hwnd_edit)))
 }
 
-pub unsafe fn update_tab_title(hwnd_tab: HWND, index: usize, title: &str, dirty: bool) {
-    let label = if dirty {
-        format!("{title}*")
-    } else {
-        title.to_string()
+/// Moves the caret to the start of `line` (1-based), loading the section of a
+/// large file that contains it.
+pub unsafe fn go_to_line(hwnd: HWND, line: usize) -> bool {
+    let Some((hwnd_edit, reloaded, first_line)) = with_state(hwnd, |state| {
+        let current = state.current;
+        let doc = state.docs.get_mut(current)?;
+        let Some(large) = doc.large_file.as_mut() else {
+            return Some((doc.hwnd_edit, None, 1));
+        };
+        let (first, last) = large.window_lines();
+        if (first..=last).contains(&line) {
+            return Some((doc.hwnd_edit, None, first));
+        }
+        large.load_line(line.saturating_sub(1));
+        let (first, _) = large.window_lines();
+        Some((doc.hwnd_edit, Some(large.window_text()), first))
+    })
+    .flatten() else {
+        return false;
     };
-    let mut text = to_wide(&label);
-    let mut item = TCITEMW {
-        mask: TCIF_TEXT,
-        pszText: PWSTR(text.as_mut_ptr()),
-        ..Default::default()
+    if let Some(text) = &reloaded {
+        set_edit_text(hwnd_edit, text);
+    }
+    let text = reloaded.unwrap_or_else(|| get_edit_text(hwnd_edit));
+    let Some(index) = crate::text_ops::line_start_index(&text, line.saturating_sub(first_line))
+    else {
+        return false;
     };
-    SendMessageW(
-        hwnd_tab,
-        TCM_SETITEMW,
-        WPARAM(index),
-        LPARAM(&mut item as *mut _ as isize),
-    );
+    let caret = crate::large_file::edit_position(&text, index);
+    select_edit_range(hwnd_edit, caret, caret);
+    SetFocus(hwnd_edit);
+    true
 }
 
-pub unsafe fn mark_dirty_from_edit(hwnd: HWND, hwnd_edit: HWND) {
-    if with_state(hwnd, |state| {
-        for (i, doc) in state.docs.iter_mut().enumerate() {
-            if doc.hwnd_edit == hwnd_edit && !doc.dirty {
-                doc.dirty = true;
-                update_tab_title(state.hwnd_tab, i, &doc.title, true);
-                update_window_title(hwnd);
-                break;
-            }
-        }
+/// 1-based line number of the first line in the editor: 1, or the first line of the
+/// loaded section of a large file.
+pub unsafe fn current_first_line(hwnd: HWND) -> usize {
+    with_state(hwnd, |state| {
+        state
+            .docs
+            .get(state.current)
+            .and_then(|doc| doc.large_file.as_ref())
+            .map(|large| large.window_lines().0)
     })
-    .is_none()
-    {
-        crate::log_debug("Failed to access editor state");
-    }
+    .flatten()
+    .unwrap_or(1)
 }
 
-pub unsafe fn update_window_title(hwnd: HWND) {
-    if with_state(hwnd, |state| {
-        if let Some(doc) = state.docs.get(state.current) {
-            let display_title = &doc.title;
-            let base_title = if display_title.trim().is_empty() {
-                "Novapad".to_string()
-            } else {
-                format!("{display_title} - Novapad")
-            };
-            let full_title = apply_modified_marker(
-                &base_title,
-                doc.dirty,
-                state.settings.modified_marker_position,
-            );
-            let wide = to_wide(&full_title);
-            if let Err(e) = SetWindowTextW(hwnd, PCWSTR(wide.as_ptr())) {
-                crate::log_debug(&format!("Failed to set window title: {}", e));
-            }
-        }
+/// Headings of the current document with 1-based line numbers, for `go_to_line`,
+/// and the line of the caret. In a large file only the loaded section is scanned.
+pub unsafe fn current_outline(hwnd: HWND) -> Option<(Vec<crate::outline::Heading>, usize)> {
+    let hwnd_edit = with_state(hwnd, |state| {
+        state.docs.get(state.current).map(|doc| doc.hwnd_edit)
     })
-    .is_none()
-    {
-        crate::log_debug("Failed to access editor state");
+    .flatten()?;
+    let first_line = current_first_line(hwnd);
+    let text = get_edit_text(hwnd_edit);
+    let mut selection = CHARRANGE { cpMin: 0, cpMax: 0 };
+    SendMessageW(
+        hwnd_edit,
+        EM_EXGETSEL,
+        WPARAM(0),
+        LPARAM(&mut selection as *mut _ as isize),
+    );
+    let caret = crate::large_file::byte_index_at_edit_position(&text, selection.cpMin);
+    let caret_line = first_line + crate::text_ops::line_count(&text[..caret]) - 1;
+    let mut headings = crate::outline::detect_headings(&text);
+    for heading in &mut headings {
+        heading.line += first_line;
     }
+    Some((headings, caret_line))
 }
 
-fn apply_modified_marker(title: &str, dirty: bool, position: ModifiedMarkerPosition) -> String {
-    if !dirty {
-        return title.to_string();
-    }
-    match position {
-        ModifiedMarkerPosition::Beginning => format!("* {title}"),
-        _ => format!("{title} *"),
+/// Selects `lines` (0-based, end excluded) of `hwnd_edit` without their final line
+/// break; an empty range puts the caret where such lines would start.
+pub unsafe fn select_edit_lines(hwnd_edit: HWND, lines: std::ops::Range<usize>) {
+    let text = get_edit_text(hwnd_edit);
+    let start = crate::text_ops::line_start_index(&text, lines.start).unwrap_or(text.len());
+    let mut end = start;
+    if !lines.is_empty() {
+        end = crate::text_ops::line_start_index(&text, lines.end).unwrap_or(text.len());
+        let selected = &text[start..end];
+        let trimmed = selected
+            .strip_suffix("\r\n")
+            .or_else(|| selected.strip_suffix('\n'))
+            .unwrap_or(selected);
+        end = start + trimmed.len();
     }
+    select_edit_range(
+        hwnd_edit,
+        crate::large_file::edit_position(&text, start),
+        crate::large_file::edit_position(&text, end),
+    );
 }
 
-pub unsafe fn layout_children(hwnd: HWND) {
+/// Replaces `lines` of `hwnd_edit` with `replacement` as a single undo step and
+/// selects the inserted lines.
+pub unsafe fn replace_edit_lines(
+    hwnd: HWND,
+    hwnd_edit: HWND,
+    lines: std::ops::Range<usize>,
+    replacement: &[&str],
+) {
+    let text = get_edit_text(hwnd_edit);
+    let updated = crate::diff::replace_lines(&text, lines.clone(), replacement);
+    replace_changed_text(hwnd, hwnd_edit, &text, &updated);
+    select_edit_lines(hwnd_edit, lines.start..lines.start + replacement.len());
+}
+
+/// Turns the editor's `text` into `updated` by replacing only the part that differs,
+/// as a single undo step; returns false when there was nothing to change.
+unsafe fn replace_changed_text(hwnd: HWND, hwnd_edit: HWND, text: &str, updated: &str) -> bool {
+    let (old_span, new_span) = crate::diff::changed_span(text, updated);
+    if old_span.is_empty() && new_span.is_empty() {
+        return false;
+    }
+    select_edit_range(
+        hwnd_edit,
+        crate::large_file::edit_position(text, old_span.start),
+        crate::large_file::edit_position(text, old_span.end),
+    );
+    begin_single_undo_action(hwnd_edit);
+    let replace_wide = to_wide(&updated[new_span]);
+    SendMessageW(
+        hwnd_edit,
+        EM_REPLACESEL,
+        WPARAM(1),
+        LPARAM(replace_wide.as_ptr() as isize),
+    );
+    end_single_undo_action(hwnd_edit);
+    mark_dirty_from_edit(hwnd, hwnd_edit);
+    true
+}
+
+/// Continues a search past the loaded section of a large file, scanning the mapped
+/// file from the current selection and loading the section with the next match.
+pub unsafe fn find_in_large_file(
+    hwnd: HWND,
+    search: &str,
+    down: bool,
+    match_case: bool,
+    wrap: bool,
+) -> bool {
+    let found = with_state(hwnd, |state| {
+        let current = state.current;
+        let doc = state.docs.get_mut(current)?;
+        let hwnd_edit = doc.hwnd_edit;
+        let large = doc.large_file.as_mut()?;
+        let mut selection = CHARRANGE { cpMin: 0, cpMax: 0 };
+        SendMessageW(
+            hwnd_edit,
+            EM_EXGETSEL,
+            WPARAM(0),
+            LPARAM(&mut selection as *mut _ as isize),
+        );
+        let from = large.offset_of_edit_position(if down {
+            selection.cpMax
+        } else {
+            selection.cpMin
+        });
+        let offset = large.find(search, from, down, match_case).or_else(|| {
+            let restart = if down { 0 } else { usize::MAX };
+            wrap.then(|| large.find(search, restart, down, match_case))
+                .flatten()
+        })?;
+        let len = large.encoded_len(search);
+        let moved = large.load_range(offset, len);
+        let text = moved.then(|| large.window_text());
+        let start = large.edit_position_of_offset(offset);
+        let end = large.edit_position_of_offset(offset + len);
+        Some((hwnd_edit, text, start, end))
+    })
+    .flatten();
+    let Some((hwnd_edit, text, start, end)) = found else {
+        return false;
+    };
+    if let Some(text) = text {
+        set_edit_text(hwnd_edit, &text);
+    }
+    select_edit_range(hwnd_edit, start, end);
+    SetFocus(hwnd_edit);
+    true
+}
+
+/// Selects `start..end` of `hwnd_edit`, in editor positions, and scrolls it into view.
+pub unsafe fn select_edit_range(hwnd_edit: HWND, start: i32, end: i32) {
+    let range = CHARRANGE {
+        cpMin: start,
+        cpMax: end,
+    };
+    SendMessageW(
+        hwnd_edit,
+        EM_EXSETSEL,
+        WPARAM(0),
+        LPARAM(&range as *const _ as isize),
+    );
+    SendMessageW(hwnd_edit, EM_SCROLLCARET, WPARAM(0), LPARAM(0));
+}
+
+/// Reloads the current plain-text document from disk, decoding it with `encoding`.
+pub unsafe fn reopen_current_document_with_encoding(hwnd: HWND, encoding: TextEncoding) {
+    let language = with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+    let Some((index, title, path, format)) = with_state(hwnd, |state| {
+        state.docs.get(state.current).map(|doc| {
+            (
+                state.current,
+                doc.title.clone(),
+                doc.path.clone(),
+                doc.format,
+            )
+        })
+    })
+    .flatten() else {
+        return;
+    };
+    let Some(path) = path.filter(|_| matches!(format, FileFormat::Text(_))) else {
+        crate::show_error(
+            hwnd,
+            language,
+            &crate::i18n::tr(language, "file.reopen_encoding_unavailable"),
+        );
+        return;
+    };
+    if !confirm_save_if_dirty_entry(hwnd, index, &title) {
+        return;
+    }
+    if current_is_large_file(hwnd) {
+        if !crate::large_file::supports_encoding(encoding) {
+            crate::show_error(
+                hwnd,
+                language,
+                &crate::i18n::tr(language, "large_file.encoding_unsupported"),
+            );
+            return;
+        }
+        let reloaded = with_state(hwnd, |state| {
+            let doc = state.docs.get_mut(index)?;
+            let large = doc.large_file.as_mut()?;
+            large.set_encoding(encoding);
+            doc.format = FileFormat::Text(encoding);
+            doc.opened_text_encoding = Some(encoding);
+            Some((doc.hwnd_edit, large.window_text()))
+        })
+        .flatten();
+        if let Some((hwnd_edit, text)) = reloaded {
+            set_edit_text(hwnd_edit, &text);
+            update_window_title(hwnd);
+            SetFocus(hwnd_edit);
+        }
+        return;
+    }
+    let text = match std::fs::read(&path) {
+        Ok(bytes) => match decode_text_with_encoding(&bytes, encoding, language) {
+            Ok(text) => text,
+            Err(message) => {
+                crate::show_error(hwnd, language, &message);
+                return;
+            }
+        },
+        Err(err) => {
+            crate::show_error(
+                hwnd,
+                language,
+                &crate::settings::error_open_file_message(language, err),
+            );
+            return;
+        }
+    };
+    let hwnd_edit = with_state(hwnd, |state| {
+        let doc = state.docs.get_mut(index)?;
+        doc.format = FileFormat::Text(encoding);
+        doc.opened_text_encoding = Some(encoding);
+        doc.current_save_text_encoding = None;
+        doc.line_ending = crate::text_ops::detect_line_ending(&text);
+        doc.dirty = false;
+        update_tab_title(state.hwnd_tab, index, &doc.title, false);
+        Some(doc.hwnd_edit)
+    })
+    .flatten();
+    if let Some(hwnd_edit) = hwnd_edit {
+        set_edit_text(hwnd_edit, &text);
+        update_window_title(hwnd);
+        SetFocus(hwnd_edit);
+    }
+}
+
+/// True when the current document is an SRT/WebVTT file shown as cues.
+pub unsafe fn current_is_subtitles(hwnd: HWND) -> bool {
+    with_state(hwnd, |state| {
+        state
+            .docs
+            .get(state.current)
+            .is_some_and(|doc| doc.subtitles.is_some())
+    })
+    .unwrap_or(false)
+}
+
+/// Reads the edited cue text of the current subtitle document back into its
+/// track and runs `f` on it. Returns the editor, its text with `\n` breaks and
+/// the result of `f`; errors in the text are reported and `f` is not run.
+unsafe fn update_current_subtitles<R>(
+    hwnd: HWND,
+    f: impl FnOnce(&mut SubtitleView) -> R,
+) -> Option<(HWND, String, R)> {
+    let language = with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+    let hwnd_edit = with_state(hwnd, |state| {
+        state
+            .docs
+            .get(state.current)
+            .filter(|doc| doc.subtitles.is_some())
+            .map(|doc| doc.hwnd_edit)
+    })
+    .flatten()?;
+    let text = crate::text_ops::convert_line_endings(&get_edit_text(hwnd_edit), LineEnding::Lf);
+    let result = with_state(hwnd, |state| {
+        let current = state.current;
+        let view = state.docs.get_mut(current)?.subtitles.as_mut()?;
+        Some(view.sync_from_text(&text, language).map(|()| f(view)))
+    })
+    .flatten()?;
+    match result {
+        Ok(value) => Some((hwnd_edit, text, value)),
+        Err(message) => {
+            crate::show_error(hwnd, language, &message);
+            None
+        }
+    }
+}
+
+/// Shows the cues again after a change of layout or timing, keeping the caret
+/// on the cue it was in.
+unsafe fn redisplay_subtitles(hwnd: HWND, hwnd_edit: HWND, old_text: &str, display: &str) {
+    let mut selection = CHARRANGE { cpMin: 0, cpMax: 0 };
+    SendMessageW(
+        hwnd_edit,
+        EM_EXGETSEL,
+        WPARAM(0),
+        LPARAM(&mut selection as *mut _ as isize),
+    );
+    let caret = crate::large_file::byte_index_at_edit_position(old_text, selection.cpMin);
+    let cue = crate::subtitles::cue_at_offset(old_text, caret).unwrap_or(0);
+    set_edit_text(hwnd_edit, display);
+    let offset = crate::subtitles::cue_offset(display, cue).unwrap_or(0);
+    let position = crate::large_file::edit_position(display, offset);
+    select_edit_range(hwnd_edit, position, position);
+    update_window_title(hwnd);
+}
+
+/// Shows or hides the `[start --> end]` line above each cue of the current
+/// subtitle document. Returns the new state, or `None` if nothing changed.
+pub unsafe fn toggle_subtitle_timestamps(hwnd: HWND) -> Option<bool> {
+    let (hwnd_edit, text, (show, display)) = update_current_subtitles(hwnd, |view| {
+        view.show_timestamps = !view.show_timestamps;
+        (view.show_timestamps, view.display_text())
+    })?;
+    with_state(hwnd, |state| {
+        state.settings.subtitles_show_timestamps = show
+    });
+    redisplay_subtitles(hwnd, hwnd_edit, &text, &display);
+    Some(show)
+}
+
+/// Moves the caret to the cue playing at `ms`, or to the next one.
+pub unsafe fn go_to_subtitle_time(hwnd: HWND, ms: u64) -> bool {
+    let Some((hwnd_edit, text, Some(index))) =
+        update_current_subtitles(hwnd, |view| view.track.cue_index_at(ms))
+    else {
+        return false;
+    };
+    let Some(offset) = crate::subtitles::cue_offset(&text, index) else {
+        return false;
+    };
+    let position = crate::large_file::edit_position(&text, offset);
+    select_edit_range(hwnd_edit, position, position);
+    SetFocus(hwnd_edit);
+    true
+}
+
+/// Stretches every cue time by `factor` and then shifts it by `shift_ms`.
+pub unsafe fn adjust_subtitle_timing(hwnd: HWND, factor: f64, shift_ms: i64) -> bool {
+    let Some((hwnd_edit, text, display)) = update_current_subtitles(hwnd, |view| {
+        view.track.adjust_timing(factor, shift_ms);
+        view.display_text()
+    }) else {
+        return false;
+    };
+    with_state(hwnd, |state| {
+        let current = state.current;
+        if let Some(doc) = state.docs.get_mut(current) {
+            doc.dirty = true;
+            update_tab_title(state.hwnd_tab, current, &doc.title, true);
+        }
+    });
+    redisplay_subtitles(hwnd, hwnd_edit, &text, &display);
+    SetFocus(hwnd_edit);
+    true
+}
+
+/// Opens `track` in a new unsaved tab; saving writes it back as subtitles.
+pub unsafe fn new_subtitle_document(hwnd: HWND, title: &str, track: SubtitleTrack) {
+    let new_index = with_state(hwnd, |state| {
+        let view = SubtitleView {
+            track,
+            show_timestamps: state.settings.subtitles_show_timestamps,
+        };
+        let hwnd_edit = create_edit(
+            hwnd,
+            state.hfont,
+            state.settings.word_wrap,
+            state.settings.text_color,
+            state.settings.text_size,
+        );
+        set_edit_text(hwnd_edit, &view.display_text());
+        let doc = Document {
+            title: title.to_string(),
+            hwnd_edit,
+            dirty: true,
+            subtitles: Some(view),
+            ..Default::default()
+        };
+        state.docs.push(doc);
+        insert_tab(state.hwnd_tab, title, (state.docs.len() - 1) as i32);
+        update_tab_title(state.hwnd_tab, state.docs.len() - 1, title, true);
+        state.docs.len() - 1
+    })
+    .unwrap_or(0);
+    select_tab(hwnd, new_index);
+}
+
+/// Opens `text` in a new unsaved tab titled `title`.
+pub unsafe fn new_text_document(hwnd: HWND, title: &str, text: &str) {
+    let new_index = with_state(hwnd, |state| {
+        let hwnd_edit = create_edit(
+            hwnd,
+            state.hfont,
+            state.settings.word_wrap,
+            state.settings.text_color,
+            state.settings.text_size,
+        );
+        set_edit_text(hwnd_edit, text);
+        let doc = Document {
+            title: title.to_string(),
+            hwnd_edit,
+            dirty: true,
+            ..Default::default()
+        };
+        state.docs.push(doc);
+        insert_tab(state.hwnd_tab, title, (state.docs.len() - 1) as i32);
+        update_tab_title(state.hwnd_tab, state.docs.len() - 1, title, true);
+        state.docs.len() - 1
+    })
+    .unwrap_or(0);
+    select_tab(hwnd, new_index);
+}
+
+/// Tab-stop fields of the last inserted snippet or template that Tab still visits.
+pub struct SnippetFields {
+    hwnd_edit: HWND,
+    /// The field being filled in, in editor positions.
+    current: std::ops::Range<i32>,
+    /// The fields after it, in editor positions as of `text_len`.
+    pending: Vec<std::ops::Range<i32>>,
+    /// Length of the document when `current` was selected; typing in the field
+    /// moves the later fields by the difference.
+    text_len: i32,
+}
+
+unsafe fn edit_char_count(hwnd_edit: HWND) -> i32 {
+    let mut length_info = GetTextLengthEx {
+        flags: GTL_NUMCHARS,
+        codepage: CP_UNICODE,
+    };
+    SendMessageW(
+        hwnd_edit,
+        EM_GETTEXTLENGTHEX,
+        WPARAM(&mut length_info as *mut _ as usize),
+        LPARAM(0),
+    )
+    .0 as i32
+}
+
+/// Handles Tab in an editor: moves on to the next field of the snippet just
+/// inserted, or expands the abbreviation before the caret. Returns false when Tab
+/// should insert a tab as usual.
+unsafe fn handle_snippet_tab(hwnd_edit: HWND) -> bool {
+    let hwnd = GetParent(hwnd_edit);
+    next_snippet_field(hwnd, hwnd_edit) || expand_snippet_at_caret(hwnd, hwnd_edit)
+}
+
+unsafe fn next_snippet_field(hwnd: HWND, hwnd_edit: HWND) -> bool {
+    let Some(fields) = with_state(hwnd, |state| state.snippet_fields.take()).flatten() else {
+        return false;
+    };
+    if fields.hwnd_edit != hwnd_edit {
+        return false;
+    }
+    let mut selection = CHARRANGE { cpMin: 0, cpMax: 0 };
+    SendMessageW(
+        hwnd_edit,
+        EM_EXGETSEL,
+        WPARAM(0),
+        LPARAM(&mut selection as *mut _ as isize),
+    );
+    let text_len = edit_char_count(hwnd_edit);
+    let delta = text_len - fields.text_len;
+    let current_end = fields.current.end + delta;
+    // Once the caret leaves the field the fields are forgotten and Tab is a tab again.
+    if selection.cpMin < fields.current.start || selection.cpMax > current_end {
+        return false;
+    }
+    let pending = fields
+        .pending
+        .into_iter()
+        .map(|field| field.start + delta..field.end + delta)
+        .collect();
+    select_snippet_fields(hwnd, hwnd_edit, pending, text_len);
+    true
+}
+
+/// Selects the first of `fields` and keeps the rest for the following Tabs.
+unsafe fn select_snippet_fields(
+    hwnd: HWND,
+    hwnd_edit: HWND,
+    mut fields: Vec<std::ops::Range<i32>>,
+    text_len: i32,
+) {
+    if fields.is_empty() {
+        return;
+    }
+    let current = fields.remove(0);
+    select_edit_range(hwnd_edit, current.start, current.end);
+    if !fields.is_empty() {
+        with_state(hwnd, |state| {
+            state.snippet_fields = Some(SnippetFields {
+                hwnd_edit,
+                current,
+                pending: fields,
+                text_len,
+            });
+        });
+    }
+}
+
+/// Editor positions of the fields of an expansion inserted at `start`.
+fn field_positions(
+    expansion: &crate::snippets::Expansion,
+    start: i32,
+) -> Vec<std::ops::Range<i32>> {
+    expansion
+        .fields
+        .iter()
+        .map(|field| start + field.start as i32..start + field.end as i32)
+        .collect()
+}
+
+unsafe fn expand_snippet_at_caret(hwnd: HWND, hwnd_edit: HWND) -> bool {
+    if current_is_large_file(hwnd) {
+        return false;
+    }
+    let mut selection = CHARRANGE { cpMin: 0, cpMax: 0 };
+    SendMessageW(
+        hwnd_edit,
+        EM_EXGETSEL,
+        WPARAM(0),
+        LPARAM(&mut selection as *mut _ as isize),
+    );
+    if selection.cpMin != selection.cpMax {
+        return false;
+    }
+    let caret = selection.cpMax;
+    let before = get_text_range(
+        hwnd_edit,
+        CHARRANGE {
+            cpMin: (caret - crate::snippets::MAX_ABBREVIATION_CHARS as i32).max(0),
+            cpMax: caret,
+        },
+    );
+    let abbreviation = crate::snippets::abbreviation_before(&before);
+    if abbreviation.is_empty() {
+        return false;
+    }
+    let Some((template, language, filename)) = with_state(hwnd, |state| {
+        let snippet = state.snippets.find(abbreviation)?;
+        let filename = state.docs.get(state.current).map(|doc| {
+            doc.path
+                .as_ref()
+                .and_then(|path| path.file_name())
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| doc.title.clone())
+        });
+        Some((
+            snippet.text.clone(),
+            state.settings.language,
+            filename.unwrap_or_default(),
+        ))
+    })
+    .flatten() else {
+        return false;
+    };
+    let values = placeholder_values(hwnd, language, filename);
+    let expansion = crate::snippets::expand(&template, &values);
+    let start = caret - abbreviation.encode_utf16().count() as i32;
+    select_edit_range(hwnd_edit, start, caret);
+    begin_single_undo_action(hwnd_edit);
+    let replace_wide = to_wide(&expansion.text);
+    SendMessageW(
+        hwnd_edit,
+        EM_REPLACESEL,
+        WPARAM(1),
+        LPARAM(replace_wide.as_ptr() as isize),
+    );
+    end_single_undo_action(hwnd_edit);
+    mark_dirty_from_edit(hwnd, hwnd_edit);
+    let fields = field_positions(&expansion, start);
+    select_snippet_fields(hwnd, hwnd_edit, fields, edit_char_count(hwnd_edit));
+    true
+}
+
+unsafe fn placeholder_values(
+    hwnd: HWND,
+    language: crate::settings::Language,
+    filename: String,
+) -> crate::snippets::PlaceholderValues {
+    let now = chrono::Local::now();
+    crate::snippets::PlaceholderValues {
+        date: now
+            .format(crate::snippets::date_format(language))
+            .to_string(),
+        time: now.format("%H:%M").to_string(),
+        filename,
+        clipboard: read_clipboard_text(hwnd),
+    }
+}
+
+unsafe fn read_clipboard_text(hwnd: HWND) -> String {
+    use windows::Win32::Foundation::HGLOBAL;
+    use windows::Win32::System::DataExchange::{CloseClipboard, GetClipboardData, OpenClipboard};
+    use windows::Win32::System::Memory::{GlobalLock, GlobalUnlock};
+
+    const CF_UNICODETEXT: u32 = 13;
+
+    if OpenClipboard(hwnd).is_err() {
+        return String::new();
+    }
+    let mut text = String::new();
+    if let Ok(handle) = GetClipboardData(CF_UNICODETEXT) {
+        let global = HGLOBAL(handle.0 as *mut _);
+        let ptr = GlobalLock(global) as *const u16;
+        if !ptr.is_null() {
+            let mut len = 0;
+            while *ptr.add(len) != 0 {
+                len += 1;
+            }
+            text = String::from_utf16_lossy(std::slice::from_raw_parts(ptr, len));
+            crate::log_if_err!(GlobalUnlock(global));
+        }
+    }
+    if let Err(e) = CloseClipboard() {
+        log_debug(&format!("CloseClipboard failed: {}", e));
+    }
+    text
+}
+
+/// Opens a new tab with the template at `path`, its placeholders filled in and its
+/// first field selected.
+pub unsafe fn new_document_from_template(hwnd: HWND, path: &Path) {
+    let language = with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+    let template = std::fs::read(path)
+        .map_err(|err| crate::settings::error_open_file_message(language, err))
+        .and_then(|bytes| decode_text(&bytes, language).map(|(text, _)| text));
+    let template = match template {
+        Ok(template) => template,
+        Err(message) => {
+            crate::show_error(hwnd, language, &message);
+            return;
+        }
+    };
+    let title = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let values = placeholder_values(hwnd, language, title.clone());
+    let expansion = crate::snippets::expand(&template, &values);
+    new_text_document(hwnd, &title, &expansion.text);
+    let Some(hwnd_edit) = crate::get_active_edit(hwnd) else {
+        return;
+    };
+    let fields = field_positions(&expansion, 0);
+    select_snippet_fields(hwnd, hwnd_edit, fields, edit_char_count(hwnd_edit));
+}
+
+/// Reloads the current HTML document so a change of the reader mode setting
+/// takes effect. Returns false when the current tab is not an HTML file.
+pub unsafe fn reload_current_html_document(hwnd: HWND) -> bool {
+    let Some((index, title, path, reader_mode, language)) = with_state(hwnd, |state| {
+        state.docs.get(state.current).and_then(|doc| {
+            let path = doc
+                .path
+                .clone()
+                .filter(|_| doc.format == FileFormat::Html)?;
+            Some((
+                state.current,
+                doc.title.clone(),
+                path,
+                state.settings.html_reader_mode,
+                state.settings.language,
+            ))
+        })
+    })
+    .flatten() else {
+        return false;
+    };
+    if !confirm_save_if_dirty_entry(hwnd, index, &title) {
+        return true;
+    }
+    let result = if reader_mode {
+        read_html_reader_text(&path, language)
+    } else {
+        read_html_text(&path, language)
+    };
+    let text = match result {
+        Ok((text, _encoding)) => text,
+        Err(message) => {
+            crate::show_error(hwnd, language, &message);
+            return true;
+        }
+    };
+    let hwnd_edit = with_state(hwnd, |state| {
+        let doc = state.docs.get_mut(index)?;
+        doc.dirty = false;
+        doc.html_reader = reader_mode;
+        update_tab_title(state.hwnd_tab, index, &doc.title, false);
+        Some(doc.hwnd_edit)
+    })
+    .flatten();
+    if let Some(hwnd_edit) = hwnd_edit {
+        set_edit_text(hwnd_edit, &text);
+        update_window_title(hwnd);
+        SetFocus(hwnd_edit);
+    }
+    true
+}
+
+/// True when the current document is an HTML file shown in reader mode, the only
+/// text with link markers.
+pub unsafe fn current_is_html_reader(hwnd: HWND) -> bool {
+    with_state(hwnd, |state| {
+        state
+            .docs
+            .get(state.current)
+            .is_some_and(|doc| doc.html_reader)
+    })
+    .unwrap_or(false)
+}
+
+/// Target of the reader-mode link marker at the caret of the current document.
+pub unsafe fn current_link_target(hwnd: HWND) -> Option<String> {
+    if !current_is_html_reader(hwnd) {
+        return None;
+    }
+    let hwnd_edit = crate::get_active_edit(hwnd)?;
+    let mut selection = CHARRANGE { cpMin: 0, cpMax: 0 };
+    SendMessageW(
+        hwnd_edit,
+        EM_EXGETSEL,
+        WPARAM(0),
+        LPARAM(&mut selection as *mut _ as isize),
+    );
+    let text = get_edit_text(hwnd_edit);
+    let index = crate::large_file::byte_index_at_edit_position(&text, selection.cpMin);
+    crate::tools::reader::link_target_at(&text, index)
+}
+
+pub unsafe fn mark_current_document_from_rss(hwnd: HWND, from_rss: bool) {
+    let result = with_state(hwnd, |state| {
+        if let Some(doc) = state.docs.get_mut(state.current) {
+            doc.from_rss = from_rss;
+        }
+    });
+    if result.is_none() {
+        crate::log_debug("Failed to access editor state");
+    }
+}
+
+pub unsafe fn current_document_is_from_rss(hwnd: HWND) -> bool {
+    with_state(hwnd, |state| {
+        state
+            .docs
+            .get(state.current)
+            .map(|doc| doc.from_rss)
+            .unwrap_or(false)
+    })
+    .unwrap_or(false)
+}
+
+pub unsafe fn select_tab(hwnd: HWND, index: usize) {
+    let result = with_state(hwnd, |state| {
+        if index >= state.docs.len() {
+            return None;
+        }
+        let prev = state.current;
+        let prev_edit = state.docs.get(prev).map(|doc| doc.hwnd_edit);
+        let new_doc = state.docs.get(index);
+        let new_edit = new_doc.map(|doc| doc.hwnd_edit);
+        let is_audiobook = new_doc
+            .map(|doc| matches!(doc.format, FileFormat::Audiobook))
+            .unwrap_or(false);
+        state.current = index;
+        Some((state.hwnd_tab, prev_edit, new_edit, is_audiobook))
+    })
+    .flatten();
+
+    let Some((hwnd_tab, prev_edit, new_edit, is_audiobook)) = result else {
+        return;
+    };
+
+    if let Some(hwnd_edit) = prev_edit {
+        ShowWindow(hwnd_edit, SW_HIDE);
+    }
+    SendMessageW(hwnd_tab, TCM_SETCURSEL, WPARAM(index), LPARAM(0));
+    if let Some(hwnd_edit) = new_edit {
+        if is_audiobook {
+            ShowWindow(hwnd_edit, SW_HIDE);
+            SetFocus(hwnd_tab);
+        } else {
+            ShowWindow(hwnd_edit, SW_SHOW);
+            SetFocus(hwnd_edit);
+        }
+    }
+    update_window_title(hwnd);
+    crate::menu::update_playback_menu(hwnd, is_audiobook);
+    layout_children(hwnd);
+}
+
+pub unsafe fn insert_tab(hwnd_tab: HWND, title: &str, index: i32) {
+    let mut text = to_wide(title);
+    let mut item = TCITEMW {
+        mask: TCIF_TEXT,
+        pszText: PWSTR(text.as_mut_ptr()),
+        ..Default::default()
+    };
+    SendMessageW(
+        hwnd_tab,
+        TCM_INSERTITEMW,
+        WPARAM(index as usize),
+        LPARAM(&mut item as *mut _ as isize),
+    );
+}
+
+pub unsafe fn update_tab_title(hwnd_tab: HWND, index: usize, title: &str, dirty: bool) {
+    let label = if dirty {
+        format!("{title}*")
+    } else {
+        title.to_string()
+    };
+    let mut text = to_wide(&label);
+    let mut item = TCITEMW {
+        mask: TCIF_TEXT,
+        pszText: PWSTR(text.as_mut_ptr()),
+        ..Default::default()
+    };
+    SendMessageW(
+        hwnd_tab,
+        TCM_SETITEMW,
+        WPARAM(index),
+        LPARAM(&mut item as *mut _ as isize),
+    );
+}
+
+pub unsafe fn mark_dirty_from_edit(hwnd: HWND, hwnd_edit: HWND) {
+    if with_state(hwnd, |state| {
+        for (i, doc) in state.docs.iter_mut().enumerate() {
+            if doc.hwnd_edit == hwnd_edit && !doc.dirty {
+                doc.dirty = true;
+                update_tab_title(state.hwnd_tab, i, &doc.title, true);
+                update_window_title(hwnd);
+                break;
+            }
+        }
+    })
+    .is_none()
+    {
+        crate::log_debug("Failed to access editor state");
+    }
+}
+
+pub unsafe fn update_window_title(hwnd: HWND) {
+    if with_state(hwnd, |state| {
+        if let Some(doc) = state.docs.get(state.current) {
+            let display_title = &doc.title;
+            let base_title = if display_title.trim().is_empty() {
+                "Novapad".to_string()
+            } else {
+                format!("{display_title} - Novapad")
+            };
+            let full_title = apply_modified_marker(
+                &base_title,
+                doc.dirty,
+                state.settings.modified_marker_position,
+            );
+            let wide = to_wide(&full_title);
+            if let Err(e) = SetWindowTextW(hwnd, PCWSTR(wide.as_ptr())) {
+                crate::log_debug(&format!("Failed to set window title: {}", e));
+            }
+        }
+    })
+    .is_none()
+    {
+        crate::log_debug("Failed to access editor state");
+    }
+}
+
+fn apply_modified_marker(title: &str, dirty: bool, position: ModifiedMarkerPosition) -> String {
+    if !dirty {
+        return title.to_string();
+    }
+    match position {
+        ModifiedMarkerPosition::Beginning => format!("* {title}"),
+        _ => format!("{title} *"),
+    }
+}
+
+pub unsafe fn layout_children(hwnd: HWND) {
     let state_data = with_state(hwnd, |state| {
         (
             state.hwnd_tab,
@@ -2605,6 +4323,57 @@ pub unsafe fn save_current_document_as(hwnd: HWND) -> bool {
     save_document_at(hwnd, get_current_index(hwnd), true)
 }
 
+/// Asks for a new password and saves the current document encrypted; later saves
+/// reuse its key. Audiobooks and large files cannot be encrypted.
+pub unsafe fn save_current_document_encrypted(hwnd: HWND) -> bool {
+    let index = get_current_index(hwnd);
+    let Some((language, format, refused)) = with_state(hwnd, |state| {
+        let doc = state.docs.get(index)?;
+        let refused = matches!(doc.format, FileFormat::Audiobook) || doc.large_file.is_some();
+        Some((state.settings.language, doc.format, refused))
+    })
+    .flatten() else {
+        return false;
+    };
+    if refused {
+        crate::show_info(
+            hwnd,
+            language,
+            &crate::i18n::tr(language, "encryption.not_supported"),
+        );
+        return false;
+    }
+    let title = crate::i18n::tr(language, "encryption.save_title");
+    let Some(password) =
+        crate::app_windows::password_window::ask_password(hwnd, language, &title, true, "")
+    else {
+        return false;
+    };
+    let Some(key) = crate::encryption::DocumentKey::new(&password) else {
+        crate::show_error(
+            hwnd,
+            language,
+            &crate::i18n::tr(language, "encryption.failed"),
+        );
+        return false;
+    };
+    let set_format = |format| {
+        with_state(hwnd, |state| {
+            if let Some(doc) = state.docs.get_mut(index) {
+                doc.format = format;
+            }
+        })
+    };
+    set_format(FileFormat::Encrypted(key));
+    if save_document_at(hwnd, index, true) {
+        crate::accessibility::nvda_speak(&crate::i18n::tr(language, "encryption.saved"));
+        true
+    } else {
+        set_format(format);
+        false
+    }
+}
+
 pub unsafe fn save_all_documents(hwnd: HWND) -> bool {
     let dirty_indices = with_state(hwnd, |state| {
         state
@@ -2633,7 +4402,20 @@ pub unsafe fn save_document_at(hwnd: HWND, index: usize, force_dialog: bool) ->
             return None;
         }
         let language = state.settings.language;
+        // Only one section of a large file is loaded, so saving it would truncate the file.
+        if state.docs[index].large_file.is_some() {
+            crate::show_info(
+                hwnd,
+                language,
+                &crate::i18n::tr(language, "large_file.read_only"),
+            );
+            return None;
+        }
         let text = get_edit_text(state.docs[index].hwnd_edit);
+        let encryption_key = match state.docs[index].format {
+            FileFormat::Encrypted(key) => Some(key),
+            _ => None,
+        };
         let is_lossy_doc = matches!(
             state.docs[index].format,
             FileFormat::Docx
@@ -2641,14 +4423,22 @@ pub unsafe fn save_document_at(hwnd: HWND, index: usize, force_dialog: bool) ->
                 | FileFormat::Pdf
                 | FileFormat::Spreadsheet
                 | FileFormat::Epub
+                | FileFormat::Mobi
                 | FileFormat::Html
                 | FileFormat::Ppt
                 | FileFormat::Pptx
         );
+        // The first line of an encrypted document is not shown as a file name.
         let mut suggested_name = crate::suggested_filename_from_text(&text)
-            .filter(|name| !name.is_empty())
+            .filter(|name| !name.is_empty() && encryption_key.is_none())
             .unwrap_or_else(|| state.docs[index].title.clone());
-        if is_lossy_doc {
+        if encryption_key.is_some() {
+            let mut name_path = PathBuf::from(&suggested_name);
+            name_path.set_extension(crate::encryption::EXTENSION);
+            if let Some(name) = name_path.file_name().and_then(|name| name.to_str()) {
+                suggested_name = name.to_string();
+            }
+        } else if is_lossy_doc {
             let mut name_path = PathBuf::from(&suggested_name);
             name_path.set_extension("txt");
             suggested_name = name_path
@@ -2657,8 +4447,20 @@ pub unsafe fn save_document_at(hwnd: HWND, index: usize, force_dialog: bool) ->
                 .unwrap_or("document.txt")
                 .to_string();
         }
+        if let Some(view) = &state.docs[index].subtitles {
+            let mut name_path = PathBuf::from(&state.docs[index].title);
+            name_path.set_extension(view.track.format.extension());
+            if let Some(name) = name_path.file_name().and_then(|name| name.to_str()) {
+                suggested_name = name.to_string();
+            }
+        }
 
-        let path_info = if !force_dialog && !is_lossy_doc {
+        // 7z archives are read-only: entries opened from them are saved elsewhere.
+        let from_read_only_archive = state.docs[index]
+            .archive_source
+            .as_ref()
+            .is_some_and(|source| !is_zip_archive_path(&source.archive));
+        let path_info = if !force_dialog && !is_lossy_doc && !from_read_only_archive {
             state.docs[index].path.clone().map(|p| (p, None))
         } else {
             None
@@ -2666,6 +4468,10 @@ pub unsafe fn save_document_at(hwnd: HWND, index: usize, force_dialog: bool) ->
 
         let (path, user_selected_encoding) = match path_info {
             Some((path, enc)) => (path, enc),
+            None if encryption_key.is_some() => (
+                crate::save_encrypted_dialog(hwnd, Some(&suggested_name))?,
+                None,
+            ),
             None => {
                 let initial_encoding = state.docs[index]
                     .current_save_text_encoding
@@ -2690,7 +4496,25 @@ pub unsafe fn save_document_at(hwnd: HWND, index: usize, force_dialog: bool) ->
 
         let is_docx = is_docx_path(&path);
         let is_pdf = is_pdf_path(&path);
-        if !is_lossy_doc && is_docx {
+        if let Some(key) = encryption_key {
+            let text = crate::text_ops::convert_line_endings(&text, state.docs[index].line_ending);
+            let Some(bytes) = crate::encryption::encrypt(&text, &key) else {
+                crate::show_error(
+                    hwnd,
+                    language,
+                    &crate::i18n::tr(language, "encryption.failed"),
+                );
+                return None;
+            };
+            if let Err(err) = std::fs::write(&path, bytes) {
+                crate::show_error(
+                    hwnd,
+                    language,
+                    &crate::settings::error_save_file_message(language, err),
+                );
+                return None;
+            }
+        } else if !is_lossy_doc && is_docx {
             if let Err(message) = write_docx_text(&path, &text, language) {
                 crate::show_error(hwnd, language, &message);
                 return None;
@@ -2716,7 +4540,43 @@ pub unsafe fn save_document_at(hwnd: HWND, index: usize, force_dialog: bool) ->
                     .or(state.docs[index].opened_text_encoding)
                     .unwrap_or_default()
             };
-            let bytes = encode_text(&text, encoding);
+            let subtitle_format = SubtitleFormat::from_path(&path);
+            let text = match (subtitle_format, state.docs[index].subtitles.as_mut()) {
+                (Some(subtitle_format), Some(view)) => {
+                    if let Err(message) = view.sync_from_text(&text, language) {
+                        crate::show_error(hwnd, language, &message);
+                        return None;
+                    }
+                    view.track.format = subtitle_format;
+                    view.track.serialize(subtitle_format)
+                }
+                _ => text,
+            };
+            let text = crate::text_ops::convert_line_endings(&text, state.docs[index].line_ending);
+            let (mut bytes, unmappable) = encode_text_checked(&text, encoding);
+            let mut encoding = encoding;
+            if unmappable {
+                let message = crate::i18n::tr_f(
+                    language,
+                    "encoding.unmappable",
+                    &[("encoding", &crate::i18n::tr(language, encoding.label_key()))],
+                );
+                let answer = MessageBoxW(
+                    hwnd,
+                    PCWSTR(to_wide(&message).as_ptr()),
+                    PCWSTR(to_wide(&confirm_title(language)).as_ptr()),
+                    MB_YESNOCANCEL | MB_ICONWARNING,
+                );
+                match answer {
+                    IDYES => {
+                        encoding = TextEncoding::Utf8;
+                        state.docs[index].current_save_text_encoding = Some(encoding);
+                        bytes = encode_text(&text, encoding);
+                    }
+                    IDNO => {}
+                    _ => return None,
+                }
+            }
             if let Err(err) = std::fs::write(&path, bytes) {
                 crate::show_error(
                     hwnd,
@@ -2728,6 +4588,14 @@ pub unsafe fn save_document_at(hwnd: HWND, index: usize, force_dialog: bool) ->
             state.docs[index].format = FileFormat::Text(encoding);
         }
 
+        if state.docs[index].path.as_ref() != Some(&path) {
+            state.docs[index].archive_source = None;
+        } else if let Some(source) = state.docs[index].archive_source.clone()
+            && is_zip_archive_path(&source.archive)
+        {
+            update_archive_entry(hwnd, language, &source, &path);
+        }
+
         let hwnd_edit = state.docs[index].hwnd_edit;
         state.docs[index].path = Some(path.clone());
         state.docs[index].dirty = false;
@@ -2745,13 +4613,47 @@ pub unsafe fn save_document_at(hwnd: HWND, index: usize, force_dialog: bool) ->
     }
 
     if let Some(Some(path)) = result {
-        crate::push_recent_file(hwnd, &path);
+        if !is_extracted_entry_path(&path) {
+            crate::push_recent_file(hwnd, &path);
+        }
         true
     } else {
         false
     }
 }
 
+/// Offers to copy a saved archive entry back into its ZIP archive.
+unsafe fn update_archive_entry(
+    hwnd: HWND,
+    language: crate::settings::Language,
+    source: &ArchiveSource,
+    saved_path: &Path,
+) {
+    let message = crate::i18n::tr_f(
+        language,
+        "archive.confirm_update",
+        &[("entry", &source.display_name())],
+    );
+    let title = crate::i18n::tr(language, "archive.title");
+    let answer = MessageBoxW(
+        hwnd,
+        PCWSTR(to_wide(&message).as_ptr()),
+        PCWSTR(to_wide(&title).as_ptr()),
+        MB_YESNO | MB_ICONQUESTION,
+    );
+    if answer != IDYES {
+        return;
+    }
+    let result = std::fs::read(saved_path)
+        .map_err(|err| crate::settings::error_save_file_message(language, err))
+        .and_then(|data| {
+            crate::archive::write_zip_entry(&source.archive, &source.entry, &data, language)
+        });
+    if let Err(message) = result {
+        crate::show_error(hwnd, language, &message);
+    }
+}
+
 pub unsafe fn close_current_document(hwnd: HWND) {
     let index = match with_state(hwnd, |state| state.current) {
         Some(i) => i,
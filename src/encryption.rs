@@ -0,0 +1,210 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, Payload};
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use std::io::Read;
+use std::path::Path;
+
+/// Extension suggested when saving an encrypted document. Files are recognized by
+/// their header, so a renamed one still opens.
+pub const EXTENSION: &str = "npenc";
+
+const MAGIC: &[u8] = b"NOVAPADENC";
+const VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+/// Magic, version, the three Argon2id costs, salt and nonce.
+const HEADER_LEN: usize = MAGIC.len() + 1 + 12 + SALT_LEN + NONCE_LEN;
+
+/// Argon2id cost of new documents: 64 MiB and three passes.
+const DEFAULT_KDF: KdfParams = KdfParams {
+    memory_kib: 64 * 1024,
+    passes: 3,
+    lanes: 1,
+};
+/// Costs above these are refused rather than tried, so a crafted file cannot make
+/// Novapad allocate gigabytes while checking a password.
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_PASSES: u32 = 64;
+const MAX_LANES: u32 = 16;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct KdfParams {
+    memory_kib: u32,
+    passes: u32,
+    lanes: u32,
+}
+
+/// The key of an encrypted document, kept while its tab is open so saving again does
+/// not ask for the password. Every save uses a fresh nonce.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct DocumentKey {
+    kdf: KdfParams,
+    salt: [u8; SALT_LEN],
+    key: [u8; KEY_LEN],
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DecryptError {
+    /// Not a Novapad encrypted document, a newer version or a damaged header.
+    Unsupported,
+    /// The password is wrong or the file was modified.
+    WrongPassword,
+}
+
+impl DocumentKey {
+    /// Derives a key from `password` with a new random salt.
+    pub fn new(password: &str) -> Option<Self> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        Self::derive(password, DEFAULT_KDF, salt)
+    }
+
+    fn derive(password: &str, kdf: KdfParams, salt: [u8; SALT_LEN]) -> Option<Self> {
+        let params = Params::new(kdf.memory_kib, kdf.passes, kdf.lanes, Some(KEY_LEN)).ok()?;
+        let mut key = [0u8; KEY_LEN];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.as_bytes(), &salt, &mut key)
+            .ok()?;
+        Some(Self { kdf, salt, key })
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(Key::from_slice(&self.key))
+    }
+}
+
+/// True when `bytes` start like a Novapad encrypted document.
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// True when the file at `path` is a Novapad encrypted document, whatever its name.
+pub fn is_encrypted_file(path: &Path) -> bool {
+    let mut head = [0u8; MAGIC.len()];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut head))
+        .is_ok()
+        && is_encrypted(&head)
+}
+
+/// Encrypts `text` with XChaCha20-Poly1305. The header, with the Argon2id costs, salt
+/// and nonce, is authenticated along with the text.
+pub fn encrypt(text: &str, key: &DocumentKey) -> Option<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let mut out = Vec::with_capacity(HEADER_LEN + text.len() + 16);
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    for cost in [key.kdf.memory_kib, key.kdf.passes, key.kdf.lanes] {
+        out.extend_from_slice(&cost.to_le_bytes());
+    }
+    out.extend_from_slice(&key.salt);
+    out.extend_from_slice(&nonce);
+    let payload = Payload {
+        msg: text.as_bytes(),
+        aad: &out,
+    };
+    let sealed = key
+        .cipher()
+        .encrypt(XNonce::from_slice(&nonce), payload)
+        .ok()?;
+    out.extend_from_slice(&sealed);
+    Some(out)
+}
+
+/// Decrypts a document written by `encrypt`; returns its text and the key to save it
+/// again with.
+pub fn decrypt(bytes: &[u8], password: &str) -> Result<(String, DocumentKey), DecryptError> {
+    let header = bytes
+        .get(..HEADER_LEN)
+        .filter(|header| is_encrypted(header) && header[MAGIC.len()] == VERSION)
+        .ok_or(DecryptError::Unsupported)?;
+    let (costs, rest) = header[MAGIC.len() + 1..].split_at(12);
+    let cost = |index: usize| {
+        u32::from_le_bytes(
+            costs[index * 4..index * 4 + 4]
+                .try_into()
+                .unwrap_or_default(),
+        )
+    };
+    let kdf = KdfParams {
+        memory_kib: cost(0),
+        passes: cost(1),
+        lanes: cost(2),
+    };
+    if kdf.memory_kib > MAX_MEMORY_KIB || kdf.passes > MAX_PASSES || kdf.lanes > MAX_LANES {
+        return Err(DecryptError::Unsupported);
+    }
+    let (salt, nonce) = rest.split_at(SALT_LEN);
+    let salt: [u8; SALT_LEN] = salt.try_into().unwrap_or_default();
+    let key = DocumentKey::derive(password, kdf, salt).ok_or(DecryptError::Unsupported)?;
+    let payload = Payload {
+        msg: &bytes[HEADER_LEN..],
+        aad: header,
+    };
+    let plain = key
+        .cipher()
+        .decrypt(XNonce::from_slice(nonce), payload)
+        .map_err(|_| DecryptError::WrongPassword)?;
+    let text = String::from_utf8(plain).map_err(|_| DecryptError::Unsupported)?;
+    Ok((text, key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cheap costs, so the tests do not spend seconds in Argon2.
+    fn test_key(password: &str) -> DocumentKey {
+        let kdf = KdfParams {
+            memory_kib: 64,
+            passes: 1,
+            lanes: 1,
+        };
+        DocumentKey::derive(password, kdf, [7; SALT_LEN]).expect("key")
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let key = test_key("correct horse");
+        let sealed = encrypt("Note segrete: però sì\r\n", &key).expect("encrypt");
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.windows(4).any(|w| w == b"Note"));
+        let (text, reopened) = decrypt(&sealed, "correct horse").expect("decrypt");
+        assert_eq!(text, "Note segrete: però sì\r\n");
+        assert!(reopened == key);
+        // A fresh nonce every time.
+        assert_ne!(encrypt("x", &key), encrypt("x", &key));
+    }
+
+    #[test]
+    fn test_rejects_wrong_password_and_tampering() {
+        let key = test_key("secret");
+        let sealed = encrypt("text", &key).expect("encrypt");
+        assert_eq!(
+            decrypt(&sealed, "Secret").err(),
+            Some(DecryptError::WrongPassword)
+        );
+        let mut tampered = sealed.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert_eq!(
+            decrypt(&tampered, "secret").err(),
+            Some(DecryptError::WrongPassword)
+        );
+        // The costs are part of the authenticated header.
+        let mut weakened = sealed.clone();
+        weakened[MAGIC.len() + 5] = 2;
+        assert_eq!(
+            decrypt(&weakened, "secret").err(),
+            Some(DecryptError::WrongPassword)
+        );
+        assert_eq!(
+            decrypt(b"plain text", "secret").err(),
+            Some(DecryptError::Unsupported)
+        );
+    }
+}
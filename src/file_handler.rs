@@ -6,13 +6,14 @@ use docx_rs::{
     DocumentChild, Docx, Paragraph, ParagraphChild, Run, RunChild, Table, TableCellContent,
     read_docx,
 };
-use encoding_rs::{Encoding, WINDOWS_1252};
+use encoding_rs::{DecoderResult, Encoding, WINDOWS_1252};
 use pdf_extract::extract_text;
 use printpdf::{BuiltinFont, Mm, PdfDocument};
 use quick_xml::Reader as XmlReader;
 use quick_xml::events::Event;
 use std::io::{BufWriter, Read};
 use std::path::Path;
+use windows::Win32::Foundation::BOOL;
 use windows::Win32::Globalization::{CP_ACP, WideCharToMultiByte};
 use zip::ZipArchive;
 
@@ -74,6 +75,18 @@ pub fn is_html_path(path: &Path) -> bool {
         .unwrap_or(false)
 }
 
+pub fn is_mobi_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|s| s.to_str())
+        .map(|s| {
+            s.eq_ignore_ascii_case("mobi")
+                || s.eq_ignore_ascii_case("azw")
+                || s.eq_ignore_ascii_case("azw3")
+                || s.eq_ignore_ascii_case("prc")
+        })
+        .unwrap_or(false)
+}
+
 pub fn is_mp3_path(path: &Path) -> bool {
     path.extension()
         .and_then(|s| s.to_str())
@@ -140,6 +153,16 @@ pub fn decode_text_with_encoding(
             let (text, _, _) = WINDOWS_1252.decode(bytes);
             Ok(text.into_owned())
         }
+        TextEncoding::Iso8859_1 => Ok(bytes.iter().map(|&b| b as char).collect()),
+        other => {
+            let Some(encoding) = legacy_encoding(other) else {
+                return Err(error_invalid_encoding_message(language));
+            };
+            encoding
+                .decode_without_bom_handling_and_without_replacement(bytes)
+                .map(|text| text.into_owned())
+                .ok_or_else(|| error_invalid_encoding_message(language))
+        }
     }
 }
 
@@ -184,18 +207,30 @@ pub fn decode_text(bytes: &[u8], language: Language) -> Result<(String, TextEnco
         return Ok((text, TextEncoding::Utf8));
     }
 
+    if let Some(encoding) = detect_legacy_encoding(bytes)
+        && let Ok(text) = decode_text_with_encoding(bytes, encoding, language)
+    {
+        return Ok((text, encoding));
+    }
+
     let (text, _, _) = WINDOWS_1252.decode(bytes);
     Ok((text.into_owned(), TextEncoding::Ansi))
 }
 
 pub fn encode_text(text: &str, encoding: TextEncoding) -> Vec<u8> {
+    encode_text_checked(text, encoding).0
+}
+
+/// Encodes `text` and tells whether some characters could not be represented in
+/// `encoding` and were replaced, by `?` or by HTML numeric references like `&#8364;`.
+pub fn encode_text_checked(text: &str, encoding: TextEncoding) -> (Vec<u8>, bool) {
     match encoding {
-        TextEncoding::Utf8 => text.as_bytes().to_vec(),
+        TextEncoding::Utf8 => (text.as_bytes().to_vec(), false),
         TextEncoding::Utf8Bom => {
             let mut out = Vec::with_capacity(3 + text.len());
             out.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
             out.extend_from_slice(text.as_bytes());
-            out
+            (out, false)
         }
         TextEncoding::Utf16Le => {
             let mut out = Vec::with_capacity(2 + text.len() * 2);
@@ -203,7 +238,7 @@ pub fn encode_text(text: &str, encoding: TextEncoding) -> Vec<u8> {
             for unit in text.encode_utf16() {
                 out.extend_from_slice(&unit.to_le_bytes());
             }
-            out
+            (out, false)
         }
         TextEncoding::Utf16Be => {
             let mut out = Vec::with_capacity(2 + text.len() * 2);
@@ -211,28 +246,290 @@ pub fn encode_text(text: &str, encoding: TextEncoding) -> Vec<u8> {
             for unit in text.encode_utf16() {
                 out.extend_from_slice(&unit.to_be_bytes());
             }
-            out
+            (out, false)
         }
         TextEncoding::Ansi => {
             let wide: Vec<u16> = text.encode_utf16().collect();
             if wide.is_empty() {
-                return Vec::new();
+                return (Vec::new(), false);
             }
             unsafe {
                 let len = WideCharToMultiByte(CP_ACP, 0, &wide, None, None, None);
                 if len > 0 {
                     let mut buf = vec![0u8; len as usize];
-                    let len2 = WideCharToMultiByte(CP_ACP, 0, &wide, Some(&mut buf), None, None);
+                    let mut used_default = BOOL(0);
+                    let len2 = WideCharToMultiByte(
+                        CP_ACP,
+                        0,
+                        &wide,
+                        Some(&mut buf),
+                        None,
+                        Some(&mut used_default as *mut BOOL),
+                    );
                     if len2 > 0 {
                         buf.truncate(len2 as usize);
-                        return buf;
+                        return (buf, used_default.as_bool());
                     }
                 }
             }
-            let (encoded, _, _) = WINDOWS_1252.encode(text);
-            encoded.into_owned()
+            let (encoded, _, unmappable) = WINDOWS_1252.encode(text);
+            (encoded.into_owned(), unmappable)
+        }
+        TextEncoding::Iso8859_1 => {
+            let mut unmappable = false;
+            let bytes = text
+                .chars()
+                .map(|ch| {
+                    u8::try_from(ch as u32).unwrap_or_else(|_| {
+                        unmappable = true;
+                        b'?'
+                    })
+                })
+                .collect();
+            (bytes, unmappable)
+        }
+        other => match legacy_encoding(other) {
+            Some(encoding) => {
+                let (encoded, _, unmappable) = encoding.encode(text);
+                (encoded.into_owned(), unmappable)
+            }
+            None => (text.as_bytes().to_vec(), false),
+        },
+    }
+}
+
+// --- Legacy Encoding Detection ---
+
+const DETECTION_SAMPLE_BYTES: usize = 64 * 1024;
+const DETECTION_MIN_CONFIDENCE: i64 = 25;
+const DETECTION_MAX_HANGUL_RUN: usize = 5;
+
+/// Candidates tried when a file is neither UTF-8 nor UTF-16; on equal
+/// confidence the earlier entry wins. Caseless Hebrew and Arabic come before
+/// Cyrillic, whose lowercase letters share their byte range.
+const DETECTION_CANDIDATES: [TextEncoding; 16] = [
+    TextEncoding::Windows1252,
+    TextEncoding::Windows1250,
+    TextEncoding::Windows1255,
+    TextEncoding::Windows1256,
+    TextEncoding::Windows1251,
+    TextEncoding::Koi8R,
+    TextEncoding::Windows1253,
+    TextEncoding::Windows1254,
+    TextEncoding::Windows1257,
+    TextEncoding::Windows1258,
+    TextEncoding::Iso8859_2,
+    TextEncoding::ShiftJis,
+    TextEncoding::EucJp,
+    TextEncoding::Gb18030,
+    TextEncoding::Big5,
+    TextEncoding::EucKr,
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum DetectionScript {
+    Latin,
+    Greek,
+    Cyrillic,
+    Hebrew,
+    Arabic,
+    Han,
+    Kana,
+    HalfwidthKana,
+    Hangul,
+    Other,
+}
+
+fn legacy_encoding(encoding: TextEncoding) -> Option<&'static Encoding> {
+    let encoding = match encoding {
+        TextEncoding::Windows1250 => encoding_rs::WINDOWS_1250,
+        TextEncoding::Windows1251 => encoding_rs::WINDOWS_1251,
+        TextEncoding::Windows1252 => WINDOWS_1252,
+        TextEncoding::Windows1253 => encoding_rs::WINDOWS_1253,
+        // ISO-8859-9 differs from Windows-1254 only in the C1 range.
+        TextEncoding::Windows1254 | TextEncoding::Iso8859_9 => encoding_rs::WINDOWS_1254,
+        TextEncoding::Windows1255 => encoding_rs::WINDOWS_1255,
+        TextEncoding::Windows1256 => encoding_rs::WINDOWS_1256,
+        TextEncoding::Windows1257 => encoding_rs::WINDOWS_1257,
+        TextEncoding::Windows1258 => encoding_rs::WINDOWS_1258,
+        TextEncoding::Iso8859_2 => encoding_rs::ISO_8859_2,
+        TextEncoding::Iso8859_5 => encoding_rs::ISO_8859_5,
+        TextEncoding::Iso8859_7 => encoding_rs::ISO_8859_7,
+        TextEncoding::Iso8859_15 => encoding_rs::ISO_8859_15,
+        TextEncoding::Koi8R => encoding_rs::KOI8_R,
+        TextEncoding::Koi8U => encoding_rs::KOI8_U,
+        TextEncoding::ShiftJis => encoding_rs::SHIFT_JIS,
+        TextEncoding::EucJp => encoding_rs::EUC_JP,
+        TextEncoding::Gb18030 => encoding_rs::GB18030,
+        TextEncoding::Big5 => encoding_rs::BIG5,
+        TextEncoding::EucKr => encoding_rs::EUC_KR,
+        _ => return None,
+    };
+    Some(encoding)
+}
+
+/// Guesses the legacy code page of `bytes` that are not valid UTF-8.
+///
+/// Every candidate decodes a sample without replacement and is scored on how
+/// much the resulting non-ASCII text looks like natural language in that
+/// encoding's script. Returns `None` when no candidate is confident enough,
+/// leaving the caller to fall back to ANSI.
+pub fn detect_legacy_encoding(bytes: &[u8]) -> Option<TextEncoding> {
+    let sample = &bytes[..bytes.len().min(DETECTION_SAMPLE_BYTES)];
+    if sample.is_ascii() {
+        return None;
+    }
+    let is_complete = sample.len() == bytes.len();
+    let mut best: Option<(TextEncoding, i64)> = None;
+    for candidate in DETECTION_CANDIDATES {
+        let Some(encoding) = legacy_encoding(candidate) else {
+            continue;
+        };
+        let mut decoder = encoding.new_decoder_without_bom_handling();
+        let Some(capacity) = decoder.max_utf8_buffer_length_without_replacement(sample.len())
+        else {
+            continue;
+        };
+        let mut text = String::with_capacity(capacity);
+        let (result, _) =
+            decoder.decode_to_string_without_replacement(sample, &mut text, is_complete);
+        if !matches!(result, DecoderResult::InputEmpty) {
+            continue;
+        }
+        let confidence = detection_confidence(&text, candidate);
+        if best.is_none_or(|(_, score)| confidence > score) {
+            best = Some((candidate, confidence));
         }
     }
+    best.filter(|(_, confidence)| *confidence >= DETECTION_MIN_CONFIDENCE)
+        .map(|(encoding, _)| encoding)
+}
+
+/// Scores decoded text from 0 to 100; only non-ASCII characters count.
+fn detection_confidence(text: &str, encoding: TextEncoding) -> i64 {
+    let mut score = 0i64;
+    let mut considered = 0i64;
+    let mut prev = ' ';
+    let mut run = 0usize;
+    for ch in text.chars() {
+        if ch.is_ascii() {
+            prev = ch;
+            run = 0;
+            continue;
+        }
+        let script = detection_script(ch);
+        run = if !prev.is_ascii() && detection_script(prev) == script {
+            run + 1
+        } else {
+            1
+        };
+        considered += 1;
+        score += detection_char_score(ch, prev, script, run, encoding);
+        prev = ch;
+    }
+    if considered == 0 {
+        return 0;
+    }
+    (score * 100 / (considered * 3)).clamp(0, 100)
+}
+
+fn detection_char_score(
+    ch: char,
+    prev: char,
+    script: DetectionScript,
+    run: usize,
+    encoding: TextEncoding,
+) -> i64 {
+    let code = ch as u32;
+    if (0x80..=0x9F).contains(&code) || (0xE000..=0xF8FF).contains(&code) || ch == '\u{FFFD}' {
+        return -10;
+    }
+    let multibyte = matches!(
+        encoding,
+        TextEncoding::ShiftJis
+            | TextEncoding::EucJp
+            | TextEncoding::Gb18030
+            | TextEncoding::Big5
+            | TextEncoding::EucKr
+    );
+    let japanese = matches!(encoding, TextEncoding::ShiftJis | TextEncoding::EucJp);
+    let mut score = match script {
+        DetectionScript::Han if multibyte => 2,
+        DetectionScript::Kana if japanese => 3,
+        DetectionScript::Kana if multibyte => 1,
+        DetectionScript::Hangul if encoding == TextEncoding::EucKr => {
+            // Korean separates words with spaces; long runs of syllables
+            // usually mean Chinese bytes read as EUC-KR.
+            if run > DETECTION_MAX_HANGUL_RUN { 1 } else { 3 }
+        }
+        DetectionScript::Han | DetectionScript::Kana | DetectionScript::Hangul => -3,
+        DetectionScript::HalfwidthKana => -2,
+        _ if is_detection_typical_char(ch, script, encoding) => 3,
+        _ if ch.is_alphabetic() => 1,
+        _ if is_detection_common_symbol(ch) => 1,
+        _ => -2,
+    };
+    if ch.is_alphabetic() && prev.is_alphabetic() {
+        if detection_script(prev) != script {
+            score -= 3;
+        } else if script == DetectionScript::Latin && !prev.is_ascii() {
+            // Accented Latin letters rarely follow each other.
+            score -= 2;
+        }
+        if ch.is_uppercase() {
+            if prev.is_lowercase() {
+                score -= 3;
+            } else if prev.is_uppercase() {
+                score -= 1;
+            }
+        }
+    }
+    score
+}
+
+fn detection_script(ch: char) -> DetectionScript {
+    match ch as u32 {
+        0x0000..=0x024F | 0x1E00..=0x1EFF => DetectionScript::Latin,
+        0x0370..=0x03FF => DetectionScript::Greek,
+        0x0400..=0x04FF => DetectionScript::Cyrillic,
+        0x0590..=0x05FF => DetectionScript::Hebrew,
+        0x0600..=0x06FF => DetectionScript::Arabic,
+        0x3040..=0x30FF => DetectionScript::Kana,
+        0xFF61..=0xFF9F => DetectionScript::HalfwidthKana,
+        0x3000..=0x303F | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xFF01..=0xFF60 => {
+            DetectionScript::Han
+        }
+        0xAC00..=0xD7AF => DetectionScript::Hangul,
+        _ => DetectionScript::Other,
+    }
+}
+
+fn is_detection_typical_char(ch: char, script: DetectionScript, encoding: TextEncoding) -> bool {
+    match encoding {
+        TextEncoding::Windows1252 | TextEncoding::Iso8859_1 | TextEncoding::Iso8859_15 => {
+            "àáâãäåæçèéêëìíîïñòóôõöøùúûüýÿßœÀÁÂÄÅÆÇÈÉÊËÍÎÑÓÔÖØÚÜŒ".contains(ch)
+        }
+        TextEncoding::Windows1250 | TextEncoding::Iso8859_2 => {
+            "ąćęłńóśźżĄĆĘŁŃÓŚŹŻčďěňřšťůžČĎĚŇŘŠŤŮŽáéíúýőűŐŰăâîșțşţĂÂÎȘȚŞŢäöüÄÖÜß".contains(ch)
+        }
+        TextEncoding::Windows1251
+        | TextEncoding::Koi8R
+        | TextEncoding::Koi8U
+        | TextEncoding::Iso8859_5 => script == DetectionScript::Cyrillic,
+        TextEncoding::Windows1253 | TextEncoding::Iso8859_7 => script == DetectionScript::Greek,
+        TextEncoding::Windows1254 | TextEncoding::Iso8859_9 => "çğıöşüâîûÇĞİÖŞÜÂÎÛ".contains(ch),
+        TextEncoding::Windows1255 => script == DetectionScript::Hebrew,
+        TextEncoding::Windows1256 => script == DetectionScript::Arabic,
+        TextEncoding::Windows1257 => "āčēģīķļņšūžąęėįųõäöüĀČĒĢĪĶĻŅŠŪŽĄĘĖĮŲÕÄÖÜ".contains(ch),
+        TextEncoding::Windows1258 => {
+            "ăâđêôơưàáèéìíòóùúýĂÂĐÊÔƠƯ".contains(ch) || (0x0300..=0x0323).contains(&(ch as u32))
+        }
+        _ => false,
+    }
+}
+
+fn is_detection_common_symbol(ch: char) -> bool {
+    "–—‘’“”„«»…•°±×÷€£¥§©®·¿¡ ".contains(ch)
 }
 
 pub fn read_ppt_text(path: &Path, language: Language) -> Result<String, String> {
@@ -600,6 +897,435 @@ pub fn read_epub_text(path: &Path, language: Language) -> Result<String, String>
     Ok(full_text)
 }
 
+// --- MOBI/AZW3 Parsing ---
+
+const MOBI_COMPRESSION_NONE: u16 = 1;
+const MOBI_COMPRESSION_PALMDOC: u16 = 2;
+const MOBI_COMPRESSION_HUFF_CDIC: u16 = 17480;
+const MOBI_MAX_CHAPTER_LABEL_CHARS: usize = 120;
+const MOBI_HUFF_MAX_DEPTH: usize = 32;
+
+struct MobiBook {
+    title: String,
+    content: String,
+    is_html: bool,
+    chapters: Vec<String>,
+}
+
+pub fn read_mobi_text(path: &Path, language: Language) -> Result<String, String> {
+    let bytes = std::fs::read(path).map_err(|err| error_open_file_message(language, err))?;
+    let book = parse_mobi_book(&bytes, language)?;
+    let body = if book.is_html {
+        html_to_text(&book.content)
+    } else {
+        book.content
+    };
+
+    let mut full_text = String::new();
+    if !book.title.trim().is_empty() {
+        full_text.push_str(book.title.trim());
+        full_text.push_str("\n\n");
+    }
+    if !book.chapters.is_empty() {
+        full_text.push_str(&i18n::tr(language, "file_handler.mobi_contents"));
+        full_text.push('\n');
+        for (idx, chapter) in book.chapters.iter().enumerate() {
+            full_text.push_str(&format!("{}. {}\n", idx + 1, chapter));
+        }
+        full_text.push('\n');
+    }
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        full_text.push_str(trimmed);
+        full_text.push('\n');
+    }
+
+    if body.trim().is_empty() {
+        return Err(i18n::tr(language, "file_handler.mobi_no_text"));
+    }
+
+    Ok(full_text)
+}
+
+fn parse_mobi_book(data: &[u8], language: Language) -> Result<MobiBook, String> {
+    let invalid = || i18n::tr(language, "file_handler.mobi_invalid");
+    let kind = data.get(60..68).ok_or_else(invalid)?;
+    if kind != b"BOOKMOBI" && kind != b"TEXtREAd" {
+        return Err(invalid());
+    }
+    let record_count = read_be_u16(data, 76).ok_or_else(invalid)? as usize;
+    let mut offsets = Vec::with_capacity(record_count);
+    for i in 0..record_count {
+        offsets.push(read_be_u32(data, 78 + i * 8).ok_or_else(invalid)? as usize);
+    }
+    let records: Vec<&[u8]> = offsets
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| {
+            let end = offsets.get(i + 1).copied().unwrap_or(data.len());
+            data.get(start..end.max(start)).unwrap_or(&[])
+        })
+        .collect();
+    let header = *records.first().ok_or_else(invalid)?;
+
+    let compression = read_be_u16(header, 0).ok_or_else(invalid)?;
+    let text_record_count = read_be_u16(header, 8).ok_or_else(invalid)? as usize;
+    let encryption = read_be_u16(header, 12).ok_or_else(invalid)?;
+    if encryption != 0 {
+        return Err(i18n::tr(language, "file_handler.mobi_drm"));
+    }
+
+    let is_html = header.get(16..20) == Some(b"MOBI".as_slice());
+    let mut is_utf8 = false;
+    let mut extra_flags = 0u16;
+    let mut huff_range = 0..0;
+    let mut title_bytes: &[u8] = &[];
+    if is_html {
+        let header_len = read_be_u32(header, 20).unwrap_or(0) as usize;
+        is_utf8 = read_be_u32(header, 28) == Some(65001);
+        let title_offset = read_be_u32(header, 84).unwrap_or(0) as usize;
+        let title_len = read_be_u32(header, 88).unwrap_or(0) as usize;
+        title_bytes = header
+            .get(title_offset..title_offset.saturating_add(title_len))
+            .unwrap_or(&[]);
+        let huff_first = read_be_u32(header, 112).unwrap_or(0) as usize;
+        let huff_count = read_be_u32(header, 116).unwrap_or(0) as usize;
+        huff_range = huff_first..huff_first.saturating_add(huff_count);
+        // The trailing-entry flags only exist in headers that reach offset 0xF2.
+        if 16 + header_len >= 0xF4 {
+            extra_flags = read_be_u16(header, 0xF2).unwrap_or(0);
+        }
+    }
+
+    let mut huff_reader = if compression == MOBI_COMPRESSION_HUFF_CDIC {
+        let huff_records = records.get(huff_range).ok_or_else(invalid)?;
+        Some(HuffCdicReader::new(huff_records).ok_or_else(invalid)?)
+    } else {
+        None
+    };
+
+    let mut raw = Vec::new();
+    for record in records.iter().skip(1).take(text_record_count) {
+        let trailing = mobi_trailing_entries_size(record, extra_flags);
+        let body = &record[..record.len() - trailing];
+        match compression {
+            MOBI_COMPRESSION_NONE => raw.extend_from_slice(body),
+            MOBI_COMPRESSION_PALMDOC => raw.extend(palmdoc_decompress(body)),
+            MOBI_COMPRESSION_HUFF_CDIC => {
+                let reader = huff_reader.as_mut().ok_or_else(invalid)?;
+                raw.extend(reader.unpack(body, 0).ok_or_else(invalid)?);
+            }
+            other => {
+                return Err(i18n::tr_f(
+                    language,
+                    "file_handler.mobi_unsupported_compression",
+                    &[("kind", &other.to_string())],
+                ));
+            }
+        }
+    }
+
+    let decode = |bytes: &[u8]| {
+        if is_utf8 {
+            String::from_utf8_lossy(bytes).into_owned()
+        } else {
+            let (text, _, _) = WINDOWS_1252.decode(bytes);
+            text.into_owned()
+        }
+    };
+    let content = decode(&raw);
+    let chapters = if is_html {
+        mobi_chapter_titles(&content)
+    } else {
+        Vec::new()
+    };
+    Ok(MobiBook {
+        title: decode(title_bytes),
+        content,
+        is_html,
+        chapters,
+    })
+}
+
+fn read_be_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .and_then(|slice| slice.try_into().ok())
+        .map(u16::from_be_bytes)
+}
+
+fn read_be_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .and_then(|slice| slice.try_into().ok())
+        .map(u32::from_be_bytes)
+}
+
+/// Returns how many bytes at the end of a text record belong to trailing entries
+/// (multibyte overlap and indexing data) rather than to the compressed text.
+fn mobi_trailing_entries_size(record: &[u8], flags: u16) -> usize {
+    let mut size = 0usize;
+    let mut bits = flags >> 1;
+    while bits != 0 {
+        if bits & 1 != 0 {
+            size += mobi_backward_varint(&record[..record.len() - size.min(record.len())]);
+        }
+        bits >>= 1;
+    }
+    if flags & 1 != 0
+        && let Some(byte) = record
+            .len()
+            .checked_sub(size + 1)
+            .and_then(|idx| record.get(idx))
+    {
+        size += (*byte & 0x3) as usize + 1;
+    }
+    size.min(record.len())
+}
+
+fn mobi_backward_varint(data: &[u8]) -> usize {
+    let mut result = 0usize;
+    let mut shift = 0;
+    for &byte in data.iter().rev() {
+        result |= ((byte & 0x7F) as usize) << shift;
+        shift += 7;
+        if byte & 0x80 != 0 || shift >= 28 {
+            break;
+        }
+    }
+    result
+}
+
+fn palmdoc_decompress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() * 2);
+    let mut i = 0usize;
+    while i < data.len() {
+        let c = data[i];
+        i += 1;
+        match c {
+            0x01..=0x08 => {
+                let end = (i + c as usize).min(data.len());
+                out.extend_from_slice(&data[i..end]);
+                i = end;
+            }
+            0x00 | 0x09..=0x7F => out.push(c),
+            0x80..=0xBF => {
+                let Some(&next) = data.get(i) else {
+                    break;
+                };
+                i += 1;
+                let pair = (((c as usize) << 8) | next as usize) & 0x3FFF;
+                let distance = pair >> 3;
+                let length = (pair & 0x7) + 3;
+                if distance == 0 || distance > out.len() {
+                    continue;
+                }
+                let start = out.len() - distance;
+                for k in 0..length {
+                    out.push(out[start + k]);
+                }
+            }
+            _ => {
+                out.push(b' ');
+                out.push(c ^ 0x80);
+            }
+        }
+    }
+    out
+}
+
+struct HuffCdicReader {
+    dict1: Vec<(usize, bool, u64)>,
+    mincode: [u64; 33],
+    maxcode: [u64; 33],
+    dictionary: Vec<(Vec<u8>, bool)>,
+}
+
+impl HuffCdicReader {
+    fn new(records: &[&[u8]]) -> Option<Self> {
+        let (huff, cdics) = records.split_first()?;
+        if huff.get(0..4) != Some(b"HUFF".as_slice()) {
+            return None;
+        }
+        let off1 = read_be_u32(huff, 8)? as usize;
+        let off2 = read_be_u32(huff, 12)? as usize;
+
+        let mut dict1 = Vec::with_capacity(256);
+        for i in 0..256 {
+            let value = read_be_u32(huff, off1 + i * 4)?;
+            let codelen = (value & 0x1F) as usize;
+            let term = value & 0x80 != 0;
+            if codelen == 0 {
+                return None;
+            }
+            let maxcode = (((value >> 8) as u64 + 1) << (32 - codelen)) - 1;
+            dict1.push((codelen, term, maxcode));
+        }
+
+        let mut mincode = [0u64; 33];
+        let mut maxcode = [0u64; 33];
+        maxcode[0] = (1u64 << 32) - 1;
+        for codelen in 1..=32 {
+            let low = read_be_u32(huff, off2 + (codelen - 1) * 8)? as u64;
+            let high = read_be_u32(huff, off2 + (codelen - 1) * 8 + 4)? as u64;
+            mincode[codelen] = low << (32 - codelen);
+            maxcode[codelen] = ((high + 1) << (32 - codelen)) - 1;
+        }
+
+        let mut dictionary = Vec::new();
+        for cdic in cdics {
+            if cdic.get(0..4) != Some(b"CDIC".as_slice()) {
+                return None;
+            }
+            let phrases = read_be_u32(cdic, 8)? as usize;
+            let bits = read_be_u32(cdic, 12)?.min(31);
+            let count = (1usize << bits).min(phrases.saturating_sub(dictionary.len()));
+            for i in 0..count {
+                let offset = read_be_u16(cdic, 16 + i * 2)? as usize;
+                let blen = read_be_u16(cdic, 16 + offset)?;
+                let start = 18 + offset;
+                let slice = cdic.get(start..start + (blen & 0x7FFF) as usize)?;
+                dictionary.push((slice.to_vec(), blen & 0x8000 != 0));
+            }
+        }
+        Some(Self {
+            dict1,
+            mincode,
+            maxcode,
+            dictionary,
+        })
+    }
+
+    fn unpack(&mut self, data: &[u8], depth: usize) -> Option<Vec<u8>> {
+        if depth > MOBI_HUFF_MAX_DEPTH {
+            return None;
+        }
+        let mut padded = data.to_vec();
+        padded.extend_from_slice(&[0u8; 8]);
+        let read_window = |pos: usize| -> Option<u64> {
+            padded
+                .get(pos..pos + 8)
+                .and_then(|slice| slice.try_into().ok())
+                .map(u64::from_be_bytes)
+        };
+
+        let mut bits_left = data.len() as i64 * 8;
+        let mut pos = 0usize;
+        let mut x = read_window(pos)?;
+        let mut n: i64 = 32;
+        let mut out = Vec::new();
+        loop {
+            if n <= 0 {
+                pos += 4;
+                x = read_window(pos)?;
+                n += 32;
+            }
+            let code = (x >> n) & 0xFFFF_FFFF;
+            let (mut codelen, term, mut maxcode) = self.dict1[(code >> 24) as usize];
+            if !term {
+                while codelen < 32 && code < self.mincode[codelen] {
+                    codelen += 1;
+                }
+                maxcode = self.maxcode[codelen];
+            }
+            n -= codelen as i64;
+            bits_left -= codelen as i64;
+            if bits_left < 0 {
+                break;
+            }
+            let index = (maxcode.checked_sub(code)? >> (32 - codelen)) as usize;
+            let (slice, expanded) = self.dictionary.get(index)?.clone();
+            if expanded {
+                out.extend_from_slice(&slice);
+            } else {
+                let unpacked = self.unpack(&slice, depth + 1)?;
+                out.extend_from_slice(&unpacked);
+                self.dictionary[index] = (unpacked, true);
+            }
+        }
+        Some(out)
+    }
+}
+
+/// Builds the chapter index from the book's table of contents links
+/// (`<a filepos=...>`), falling back to top-level headings for KF8 books.
+fn mobi_chapter_titles(html: &str) -> Vec<String> {
+    let lower = html.to_ascii_lowercase();
+    let mut entries: Vec<(usize, String)> = Vec::new();
+    let mut search = 0usize;
+    while let Some(found) = lower[search..].find("<a ") {
+        let tag_start = search + found;
+        let Some(tag_len) = lower[tag_start..].find('>') else {
+            break;
+        };
+        let tag_end = tag_start + tag_len + 1;
+        let Some(close) = lower[tag_end..].find("</a>") else {
+            break;
+        };
+        let label_end = tag_end + close;
+        search = label_end + 4;
+        let Some(filepos) = mobi_filepos_attr(&lower[tag_start..tag_end]) else {
+            continue;
+        };
+        let label = collapse_whitespace(&html_to_text(&html[tag_end..label_end]));
+        if is_mobi_chapter_label(&label) && !entries.iter().any(|(pos, _)| *pos == filepos) {
+            entries.push((filepos, label));
+        }
+    }
+    if !entries.is_empty() {
+        entries.sort_by_key(|(pos, _)| *pos);
+        return entries.into_iter().map(|(_, label)| label).collect();
+    }
+
+    let mut headings = Vec::new();
+    let mut search = 0usize;
+    while let Some(found) = lower[search..].find("<h") {
+        let tag_start = search + found;
+        search = tag_start + 2;
+        let bytes = lower.as_bytes();
+        let level = bytes.get(tag_start + 2).copied();
+        let after = bytes.get(tag_start + 3).copied();
+        if !matches!(level, Some(b'1' | b'2')) || !matches!(after, Some(b'>' | b' ')) {
+            continue;
+        }
+        let Some(tag_len) = lower[tag_start..].find('>') else {
+            break;
+        };
+        let content_start = tag_start + tag_len + 1;
+        let Some(close) = lower[content_start..].find("</h") else {
+            break;
+        };
+        let content_end = content_start + close;
+        search = content_end;
+        let label = collapse_whitespace(&html_to_text(&html[content_start..content_end]));
+        if is_mobi_chapter_label(&label) {
+            headings.push(label);
+        }
+    }
+    headings
+}
+
+fn mobi_filepos_attr(tag: &str) -> Option<usize> {
+    let start = tag.find("filepos=")? + "filepos=".len();
+    let digits: String = tag[start..]
+        .trim_start_matches(['"', '\''])
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+fn is_mobi_chapter_label(label: &str) -> bool {
+    !label.is_empty()
+        && label.chars().count() <= MOBI_MAX_CHAPTER_LABEL_CHARS
+        && label.chars().any(|c| c.is_alphabetic())
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
 pub fn read_html_text(path: &Path, language: Language) -> Result<(String, TextEncoding), String> {
     let bytes = std::fs::read(path)
         .map_err(|err| crate::settings::error_open_file_message(language, err))?;
@@ -608,6 +1334,47 @@ pub fn read_html_text(path: &Path, language: Language) -> Result<(String, TextEn
     Ok((cleaned, encoding))
 }
 
+/// Reader-mode variant of [`read_html_text`]: keeps headings, lists and tables
+/// and appends the numbered list of links found in the page, with relative
+/// targets resolved against the file location.
+pub fn read_html_reader_text(
+    path: &Path,
+    language: Language,
+) -> Result<(String, TextEncoding), String> {
+    let bytes = std::fs::read(path)
+        .map_err(|err| crate::settings::error_open_file_message(language, err))?;
+    let (text, encoding) = decode_text(&bytes, language)?;
+    let document = crate::tools::reader::reader_mode_document(&text);
+    let mut out = document.text;
+    if !document.links.is_empty() {
+        let base = url::Url::from_file_path(path).ok();
+        out.push_str(&format!(
+            "\n\n## {}\n",
+            i18n::tr(language, "html.links_heading")
+        ));
+        for (index, link) in document.links.iter().enumerate() {
+            let target = resolve_html_link(base.as_ref(), &link.href);
+            out.push_str(&format!("[{}] {} <{}>\n", index + 1, link.text, target));
+        }
+    }
+    Ok((out, encoding))
+}
+
+fn resolve_html_link(base: Option<&url::Url>, href: &str) -> String {
+    let Some(resolved) = url::Url::parse(href)
+        .ok()
+        .or_else(|| base.and_then(|base| base.join(href).ok()))
+    else {
+        return href.to_string();
+    };
+    if resolved.scheme() == "file"
+        && let Ok(path) = resolved.to_file_path()
+    {
+        return path.display().to_string();
+    }
+    resolved.to_string()
+}
+
 fn html_to_text(html: &str) -> String {
     let mut out = String::new();
     let mut inside = false;
@@ -624,6 +1391,7 @@ fn html_to_text(html: &str) -> String {
                     .split_whitespace()
                     .next()
                     .unwrap_or("")
+                    .trim_end_matches('/')
                     .to_ascii_lowercase();
                 if matches!(
                     tag_name.as_str(),
@@ -641,6 +1409,7 @@ fn html_to_text(html: &str) -> String {
                         | "h4"
                         | "h5"
                         | "h6"
+                        | "mbp:pagebreak"
                 ) && !last_newline
                     && !out.is_empty()
                 {
@@ -1878,3 +2647,89 @@ fn error_invalid_utf16le_message(language: Language) -> String {
 fn error_invalid_utf16be_message(language: Language) -> String {
     i18n::tr(language, "file_handler.utf16be_invalid_length")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(text: &str, encoding: TextEncoding) -> Option<TextEncoding> {
+        let bytes = encode_text(text, encoding);
+        detect_legacy_encoding(&bytes)
+    }
+
+    #[test]
+    fn detects_single_byte_code_pages() {
+        let italian = "Perché è così difficile? Sì, è già tardi, però andrò là.";
+        assert_eq!(
+            detect(italian, TextEncoding::Windows1252),
+            Some(TextEncoding::Windows1252)
+        );
+        let czech = "Příliš žluťoučký kůň úpěl ďábelské ódy. Dobrý den, jak se máte?";
+        assert_eq!(
+            detect(czech, TextEncoding::Windows1250),
+            Some(TextEncoding::Windows1250)
+        );
+        let russian = "Привет, мир! Это простой текст на русском языке.";
+        assert_eq!(
+            detect(russian, TextEncoding::Windows1251),
+            Some(TextEncoding::Windows1251)
+        );
+        assert_eq!(
+            detect(russian, TextEncoding::Koi8R),
+            Some(TextEncoding::Koi8R)
+        );
+        let hebrew = "שלום עולם! זהו טקסט פשוט בעברית.";
+        assert_eq!(
+            detect(hebrew, TextEncoding::Windows1255),
+            Some(TextEncoding::Windows1255)
+        );
+    }
+
+    #[test]
+    fn detects_cjk_encodings() {
+        let japanese = "こんにちは世界。これは日本語のテキストです。";
+        assert_eq!(
+            detect(japanese, TextEncoding::ShiftJis),
+            Some(TextEncoding::ShiftJis)
+        );
+        assert_eq!(
+            detect(japanese, TextEncoding::EucJp),
+            Some(TextEncoding::EucJp)
+        );
+        let chinese = "你好，世界。这是一个简单的中文文本，用于测试编码检测。";
+        assert_eq!(
+            detect(chinese, TextEncoding::Gb18030),
+            Some(TextEncoding::Gb18030)
+        );
+        let korean = "안녕하세요 세계! 이것은 간단한 한국어 텍스트입니다.";
+        assert_eq!(
+            detect(korean, TextEncoding::EucKr),
+            Some(TextEncoding::EucKr)
+        );
+    }
+
+    #[test]
+    fn legacy_round_trip_and_ascii() {
+        assert_eq!(detect_legacy_encoding(b"plain ascii"), None);
+        let text = "Günaydın, şeker!";
+        let bytes = encode_text(text, TextEncoding::Iso8859_9);
+        let decoded =
+            decode_text_with_encoding(&bytes, TextEncoding::Iso8859_9, Language::English).unwrap();
+        assert_eq!(decoded, text);
+    }
+
+    #[test]
+    fn reports_unmappable_characters() {
+        let (bytes, unmappable) = encode_text_checked("Привет €", TextEncoding::Windows1252);
+        assert!(unmappable);
+        assert!(bytes.starts_with(b"&#1055;"));
+        assert!(bytes.ends_with(&[0x80]));
+        let (bytes, unmappable) = encode_text_checked("Привет", TextEncoding::Windows1251);
+        assert!(!unmappable);
+        assert_eq!(bytes.len(), 6);
+        assert_eq!(
+            encode_text_checked("a€", TextEncoding::Iso8859_1),
+            (b"a?".to_vec(), true)
+        );
+    }
+}
@@ -0,0 +1,414 @@
+use crate::file_handler::{decode_text_with_encoding, detect_legacy_encoding, encode_text};
+use crate::settings::{Language, TextEncoding};
+use memmap2::Mmap;
+use std::io::Read;
+use std::ops::Range;
+use std::path::Path;
+
+/// Text files at least this big are opened in large-file mode instead of being loaded whole.
+pub const LARGE_FILE_THRESHOLD: u64 = 64 * 1024 * 1024;
+/// Approximate size of the section of a large file shown in the editor at once.
+const WINDOW_BYTES: usize = 2 * 1024 * 1024;
+/// A line-start checkpoint is stored every this many lines.
+const LINE_INDEX_STRIDE: usize = 4096;
+const ENCODING_SAMPLE_BYTES: usize = 64 * 1024;
+
+/// A memory-mapped text file that is shown one section ("window") at a time.
+/// Lines are counted on `\n`; offsets are byte offsets past any BOM.
+pub struct LargeFile {
+    map: Mmap,
+    data_start: usize,
+    encoding: TextEncoding,
+    checkpoints: Vec<usize>,
+    line_count: usize,
+    window: Range<usize>,
+    window_first_line: usize,
+}
+
+/// True when `path` is big enough for large-file mode and not UTF-16, which is
+/// always loaded whole.
+pub fn is_large_file(path: &Path) -> bool {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+    };
+    if metadata.len() < LARGE_FILE_THRESHOLD {
+        return false;
+    }
+    let mut bom = [0u8; 2];
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return false;
+    };
+    if file.read_exact(&mut bom).is_err() {
+        return false;
+    }
+    bom != [0xFF, 0xFE] && bom != [0xFE, 0xFF]
+}
+
+/// Large-file mode only pages through encodings where `\n` is always a line break.
+pub fn supports_encoding(encoding: TextEncoding) -> bool {
+    !matches!(encoding, TextEncoding::Utf16Le | TextEncoding::Utf16Be)
+}
+
+impl LargeFile {
+    /// Maps `path` and indexes its lines; `encoding` overrides detection.
+    pub fn open(
+        path: &Path,
+        encoding: Option<TextEncoding>,
+        language: Language,
+    ) -> Result<Self, String> {
+        let file = std::fs::File::open(path)
+            .map_err(|err| crate::settings::error_open_file_message(language, err))?;
+        // SAFETY: the map is only read; a file truncated by another process while
+        // open is the usual memory-mapping caveat and is accepted for read-only viewing.
+        let map = unsafe { Mmap::map(&file) }
+            .map_err(|err| crate::settings::error_open_file_message(language, err))?;
+        let has_bom = map.starts_with(&[0xEF, 0xBB, 0xBF]);
+        let encoding = encoding.unwrap_or_else(|| {
+            if has_bom {
+                return TextEncoding::Utf8Bom;
+            }
+            let sample = &map[..map.len().min(ENCODING_SAMPLE_BYTES)];
+            match std::str::from_utf8(sample) {
+                Ok(_) => TextEncoding::Utf8,
+                // The sample may end in the middle of a character.
+                Err(err) if err.error_len().is_none() => TextEncoding::Utf8,
+                Err(_) => detect_legacy_encoding(sample).unwrap_or(TextEncoding::Ansi),
+            }
+        });
+        let data_start = if has_bom && matches!(encoding, TextEncoding::Utf8Bom) {
+            3
+        } else {
+            0
+        };
+        let (checkpoints, line_count) = build_line_index(&map[data_start..]);
+        let mut large = LargeFile {
+            map,
+            data_start,
+            encoding,
+            checkpoints,
+            line_count,
+            window: 0..0,
+            window_first_line: 0,
+        };
+        large.load_line(0);
+        Ok(large)
+    }
+
+    pub fn encoding(&self) -> TextEncoding {
+        self.encoding
+    }
+
+    /// Switches how the file is decoded; the caller reloads the window text.
+    pub fn set_encoding(&mut self, encoding: TextEncoding) {
+        self.encoding = encoding;
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_count
+    }
+
+    /// First and last line (1-based) of the section currently shown.
+    pub fn window_lines(&self) -> (usize, usize) {
+        let data = self.data();
+        let window = &data[self.window.clone()];
+        let mut breaks = count_newlines(window);
+        if window.last() == Some(&b'\n') {
+            breaks = breaks.saturating_sub(1);
+        }
+        (
+            self.window_first_line + 1,
+            self.window_first_line + breaks + 1,
+        )
+    }
+
+    pub fn window_text(&self) -> String {
+        self.decode(&self.data()[self.window.clone()])
+    }
+
+    pub fn next_window(&mut self) -> bool {
+        let len = self.data().len();
+        if self.window.end >= len {
+            return false;
+        }
+        let start = self.window.end;
+        self.window_first_line = self.line_of_offset(start);
+        self.window = start..window_end(self.data(), start, start);
+        true
+    }
+
+    pub fn previous_window(&mut self) -> bool {
+        if self.window.start == 0 {
+            return false;
+        }
+        let end = self.window.start;
+        let start = window_start(self.data(), end);
+        self.window_first_line = self.line_of_offset(start);
+        self.window = start..end;
+        true
+    }
+
+    /// Shows the section starting at `line` (0-based, clamped to the last line).
+    pub fn load_line(&mut self, line: usize) {
+        let line = line.min(self.line_count.saturating_sub(1));
+        let start = self.line_start(line);
+        self.window_first_line = line;
+        self.window = start..window_end(self.data(), start, start);
+    }
+
+    /// Shows a section containing `offset..offset + len`, starting at the line that holds it.
+    /// Returns false when the current section already contains it.
+    pub fn load_range(&mut self, offset: usize, len: usize) -> bool {
+        if self.window.start <= offset && offset + len <= self.window.end {
+            return false;
+        }
+        let line = self.line_of_offset(offset);
+        let mut start = self.line_start(line);
+        if offset - start > WINDOW_BYTES {
+            // A single huge line: start close to the match instead.
+            start = char_boundary(self.data(), offset - WINDOW_BYTES / 2);
+        }
+        self.window_first_line = self.line_of_offset(start);
+        self.window = start..window_end(self.data(), start, offset + len);
+        true
+    }
+
+    /// Searches the whole file, without decoding it, from byte `from`.
+    /// Case-insensitive matching only folds ASCII letters.
+    pub fn find(&self, needle: &str, from: usize, down: bool, match_case: bool) -> Option<usize> {
+        let needle = self.encode(needle);
+        find_bytes(self.data(), &needle, from, down, match_case)
+    }
+
+    /// Length in file bytes of `text` once encoded.
+    pub fn encoded_len(&self, text: &str) -> usize {
+        self.encode(text).len()
+    }
+
+    /// Caret position in the editor for a file offset inside the current window.
+    pub fn edit_position_of_offset(&self, offset: usize) -> i32 {
+        let offset = offset.clamp(self.window.start, self.window.end);
+        let prefix = self.decode(&self.data()[self.window.start..offset]);
+        edit_position(&prefix, prefix.len())
+    }
+
+    /// File offset of a caret position in the editor.
+    pub fn offset_of_edit_position(&self, position: i32) -> usize {
+        let text = self.window_text();
+        let index = byte_index_at_edit_position(&text, position);
+        self.window.start + self.encoded_len(&text[..index])
+    }
+
+    fn data(&self) -> &[u8] {
+        &self.map[self.data_start..]
+    }
+
+    fn decode(&self, bytes: &[u8]) -> String {
+        match self.encoding {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => {
+                String::from_utf8_lossy(bytes).into_owned()
+            }
+            // Only the error message depends on the language, and it is discarded.
+            encoding => decode_text_with_encoding(bytes, encoding, Language::default())
+                .unwrap_or_else(|_| String::from_utf8_lossy(bytes).into_owned()),
+        }
+    }
+
+    fn encode(&self, text: &str) -> Vec<u8> {
+        match self.encoding {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => text.as_bytes().to_vec(),
+            encoding => encode_text(text, encoding),
+        }
+    }
+
+    fn line_start(&self, line: usize) -> usize {
+        let data = self.data();
+        let checkpoint = (line / LINE_INDEX_STRIDE).min(self.checkpoints.len() - 1);
+        let mut pos = self.checkpoints[checkpoint];
+        let mut remaining = line - checkpoint * LINE_INDEX_STRIDE;
+        while remaining > 0 {
+            match data[pos..].iter().position(|&b| b == b'\n') {
+                Some(found) => pos += found + 1,
+                None => return pos,
+            }
+            remaining -= 1;
+        }
+        pos
+    }
+
+    fn line_of_offset(&self, offset: usize) -> usize {
+        let checkpoint = self.checkpoints.partition_point(|&c| c <= offset) - 1;
+        let start = self.checkpoints[checkpoint];
+        checkpoint * LINE_INDEX_STRIDE + count_newlines(&self.data()[start..offset])
+    }
+}
+
+/// Returns the offset of every `LINE_INDEX_STRIDE`th line start and the number of lines.
+fn build_line_index(data: &[u8]) -> (Vec<usize>, usize) {
+    let mut checkpoints = vec![0];
+    let mut line = 0usize;
+    for (idx, &b) in data.iter().enumerate() {
+        if b == b'\n' {
+            line += 1;
+            if line.is_multiple_of(LINE_INDEX_STRIDE) {
+                checkpoints.push(idx + 1);
+            }
+        }
+    }
+    (checkpoints, line + 1)
+}
+
+fn count_newlines(data: &[u8]) -> usize {
+    data.iter().filter(|&&b| b == b'\n').count()
+}
+
+/// End of a window starting at `start` that reaches at least `min_end`: the first line
+/// break after `WINDOW_BYTES`, or a character boundary if the line goes on much longer.
+fn window_end(data: &[u8], start: usize, min_end: usize) -> usize {
+    let target = (start + WINDOW_BYTES).max(min_end).min(data.len());
+    if target == data.len() {
+        return target;
+    }
+    let limit = (target + WINDOW_BYTES).min(data.len());
+    match data[target..limit].iter().position(|&b| b == b'\n') {
+        Some(found) => target + found + 1,
+        None if limit == data.len() => limit,
+        None => char_boundary(data, target).max(min_end),
+    }
+}
+
+/// Start of a window ending at `end`: the first line after the line break found at least
+/// `WINDOW_BYTES` back, or a character boundary if the line goes on much longer.
+fn window_start(data: &[u8], end: usize) -> usize {
+    let target = end.saturating_sub(WINDOW_BYTES);
+    if target == 0 {
+        return 0;
+    }
+    let limit = target.saturating_sub(WINDOW_BYTES);
+    match data[limit..target].iter().rposition(|&b| b == b'\n') {
+        Some(found) => limit + found + 1,
+        None if limit == 0 => 0,
+        None => char_boundary(data, target),
+    }
+}
+
+/// Moves `offset` back to the start of a UTF-8 sequence.
+fn char_boundary(data: &[u8], mut offset: usize) -> usize {
+    while offset > 0 && offset < data.len() && (data[offset] & 0xC0) == 0x80 {
+        offset -= 1;
+    }
+    offset
+}
+
+fn find_bytes(
+    hay: &[u8],
+    needle: &[u8],
+    from: usize,
+    down: bool,
+    match_case: bool,
+) -> Option<usize> {
+    if needle.is_empty() || needle.len() > hay.len() {
+        return None;
+    }
+    let last = hay.len() - needle.len();
+    let first = needle[0];
+    let matches_at = |pos: usize| {
+        let candidate = &hay[pos..pos + needle.len()];
+        if match_case {
+            candidate[0] == first && candidate == needle
+        } else {
+            candidate[0].eq_ignore_ascii_case(&first) && candidate.eq_ignore_ascii_case(needle)
+        }
+    };
+    if down {
+        if from > last {
+            return None;
+        }
+        (from..=last).find(|&pos| matches_at(pos))
+    } else {
+        let end = from.checked_sub(needle.len())?.min(last);
+        (0..=end).rev().find(|&pos| matches_at(pos))
+    }
+}
+
+/// Character position of `byte_index` in the editor, where every line break
+/// (`\r\n`, `\n` or `\r`) counts as one character.
+pub fn edit_position(text: &str, byte_index: usize) -> i32 {
+    let mut position = 0usize;
+    let mut prev_cr = false;
+    for ch in text[..byte_index].chars() {
+        if ch == '\n' && prev_cr {
+            prev_cr = false;
+            continue;
+        }
+        prev_cr = ch == '\r';
+        position += ch.len_utf16();
+    }
+    position as i32
+}
+
+/// Inverse of [`edit_position`].
+pub fn byte_index_at_edit_position(text: &str, position: i32) -> usize {
+    let target = position.max(0) as usize;
+    let mut current = 0usize;
+    let mut prev_cr = false;
+    for (idx, ch) in text.char_indices() {
+        if ch == '\n' && prev_cr {
+            prev_cr = false;
+            continue;
+        }
+        if current >= target {
+            return idx;
+        }
+        prev_cr = ch == '\r';
+        current += ch.len_utf16();
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_index_counts_lines_and_checkpoints() {
+        let text = "line\n".repeat(LINE_INDEX_STRIDE * 2 + 3);
+        let (checkpoints, lines) = build_line_index(text.as_bytes());
+        assert_eq!(lines, LINE_INDEX_STRIDE * 2 + 4);
+        assert_eq!(
+            checkpoints,
+            vec![0, LINE_INDEX_STRIDE * 5, LINE_INDEX_STRIDE * 10]
+        );
+    }
+
+    #[test]
+    fn find_bytes_searches_both_directions() {
+        let hay = b"Error one\nerror two\nERROR three";
+        assert_eq!(find_bytes(hay, b"error", 0, true, true), Some(10));
+        assert_eq!(find_bytes(hay, b"error", 1, true, false), Some(10));
+        assert_eq!(find_bytes(hay, b"error", 11, true, false), Some(20));
+        assert_eq!(find_bytes(hay, b"error", 20, false, false), Some(10));
+        assert_eq!(find_bytes(hay, b"error", 4, false, false), None);
+    }
+
+    #[test]
+    fn window_start_is_limited_without_line_breaks() {
+        let text = "é".repeat(WINDOW_BYTES * 2);
+        let data = text.as_bytes();
+        let start = window_start(data, data.len());
+        assert!(data.len() - start <= WINDOW_BYTES + 1);
+        assert!(text.is_char_boundary(start));
+
+        let mut data = vec![b'a'; WINDOW_BYTES * 3];
+        data[WINDOW_BYTES * 3 / 2] = b'\n';
+        assert_eq!(window_start(&data, data.len()), WINDOW_BYTES * 3 / 2 + 1);
+    }
+
+    #[test]
+    fn edit_positions_count_line_breaks_once() {
+        let text = "ab\r\ncd\ne";
+        assert_eq!(edit_position(text, 4), 3);
+        assert_eq!(edit_position(text, 7), 6);
+        assert_eq!(byte_index_at_edit_position(text, 3), 4);
+        assert_eq!(byte_index_at_edit_position(text, 6), 7);
+    }
+}
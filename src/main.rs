@@ -9,6 +9,7 @@ mod embedded_deps;
 mod macros;
 use accessibility::*;
 mod conpty;
+mod diff;
 mod settings;
 use editor_manager::Document;
 use settings::*;
@@ -25,6 +26,8 @@ mod sapi5_engine;
 use file_handler::*;
 mod menu;
 use menu::*;
+use text_codecs::TextCodec;
+use text_ops::CaseConversion;
 mod search;
 use search::*;
 mod audio_player;
@@ -32,13 +35,26 @@ use audio_player::*;
 mod editor_manager;
 use editor_manager::*;
 mod app_windows;
+mod archive;
 mod audio_utils;
+mod autocorrect;
+mod braille;
+mod encryption;
 mod i18n;
+mod large_file;
+mod outline;
 mod podcast;
 mod podcast_recorder;
+mod readability;
+mod snippets;
 mod spellcheck;
+mod subtitles;
+mod talking_book;
+mod text_codecs;
+mod text_macros;
 mod text_ops;
 mod tools;
+mod transliterate;
 mod updater;
 mod wikipedia;
 mod wiktionary;
@@ -67,8 +83,8 @@ use windows::Win32::System::LibraryLoader::{GetModuleHandleW, LoadLibraryW};
 use windows::Win32::System::Threading::{AttachThreadInput, GetCurrentThreadId};
 use windows::Win32::UI::Accessibility::NotifyWinEvent;
 use windows::Win32::UI::Controls::Dialogs::{
-    FINDREPLACE_FLAGS, FINDREPLACEW, GetSaveFileNameW, OFN_EXPLORER, OFN_HIDEREADONLY,
-    OFN_OVERWRITEPROMPT, OFN_PATHMUSTEXIST, OPENFILENAMEW,
+    FINDREPLACE_FLAGS, FINDREPLACEW, GetOpenFileNameW, GetSaveFileNameW, OFN_EXPLORER,
+    OFN_FILEMUSTEXIST, OFN_HIDEREADONLY, OFN_OVERWRITEPROMPT, OFN_PATHMUSTEXIST, OPENFILENAMEW,
 };
 use windows::Win32::UI::Controls::RichEdit::{
     CHARRANGE, EM_EXGETSEL, EM_EXSETSEL, EM_GETTEXTRANGE, EN_SELCHANGE, TEXTRANGEW,
@@ -78,15 +94,15 @@ use windows::Win32::UI::Controls::{
     TCN_SELCHANGE, WC_BUTTON, WC_COMBOBOXW, WC_STATIC, WC_TABCONTROLW,
 };
 use windows::Win32::UI::Input::KeyboardAndMouse::{
-    EnableWindow, GetFocus, GetKeyState, SetActiveWindow, SetFocus, VK_APPS, VK_CONTROL, VK_ESCAPE,
-    VK_F1, VK_F2, VK_F3, VK_F4, VK_F5, VK_F6, VK_F7, VK_F8, VK_F9, VK_F10, VK_MENU, VK_RETURN,
-    VK_SHIFT, VK_TAB,
+    EnableWindow, GetFocus, GetKeyState, SetActiveWindow, SetFocus, VK_APPS, VK_CONTROL, VK_DOWN,
+    VK_ESCAPE, VK_F1, VK_F2, VK_F3, VK_F4, VK_F5, VK_F6, VK_F7, VK_F8, VK_F9, VK_F10, VK_MENU,
+    VK_NEXT, VK_PRIOR, VK_RETURN, VK_SHIFT, VK_TAB, VK_UP,
 };
 use windows::Win32::UI::Shell::Common::COMDLG_FILTERSPEC;
 use windows::Win32::UI::Shell::{
     DragAcceptFiles, DragFinish, DragQueryFileW, FileSaveDialog, HDROP, IFileDialog,
     IFileDialogControlEvents, IFileDialogControlEvents_Impl, IFileDialogCustomize,
-    IFileDialogEvents, IFileDialogEvents_Impl, IFileSaveDialog, IShellItem,
+    IFileDialogEvents, IFileDialogEvents_Impl, IFileSaveDialog, IShellItem, ShellExecuteW,
 };
 use windows::Win32::UI::WindowsAndMessaging::{
     ACCEL, AllowSetForegroundWindow, AppendMenuW, BM_GETCHECK, BM_SETCHECK, BS_AUTOCHECKBOX,
@@ -1222,6 +1238,12 @@ pub(crate) struct AppState {
     recent_files: Vec<PathBuf>,
     settings: AppSettings,
     bookmarks: BookmarkStore,
+    snippets: snippets::SnippetStore,
+    /// Fields of the last inserted snippet that Tab still visits.
+    snippet_fields: Option<editor_manager::SnippetFields>,
+    autocorrect: autocorrect::AutocorrectStore,
+    /// The last autocorrection, which Ctrl+Z undoes right after it was made.
+    last_autocorrection: Option<editor_manager::AutocorrectUndo>,
     find_dialog: HWND,
     replace_dialog: HWND,
     options_dialog: HWND,
@@ -1229,6 +1251,7 @@ pub(crate) struct AppState {
     changelog_window: HWND,
     donations_window: HWND,
     bookmarks_window: HWND,
+    archive_window: HWND,
     dictionary_window: HWND,
     dictionary_entry_dialog: HWND,
     wiktionary_window: HWND,
@@ -1242,6 +1265,22 @@ pub(crate) struct AppState {
     rss_window: HWND,
     rss_add_dialog: HWND, // Input dialog for RSS
     go_to_time_dialog: HWND,
+    go_to_line_dialog: HWND,
+    subtitle_timing_dialog: HWND,
+    braille_export_dialog: HWND,
+    sort_lines_dialog: HWND,
+    invisible_chars_dialog: HWND,
+    filter_lines_dialog: HWND,
+    number_lines_dialog: HWND,
+    columns_dialog: HWND,
+    compare_window: HWND,
+    macros_window: HWND,
+    /// Steps of the macro being recorded, while recording.
+    macro_recording: Option<Vec<MacroStep>>,
+    snippets_window: HWND,
+    autocorrect_window: HWND,
+    outline_window: HWND,
+    find_all_window: HWND,
     playback_menu: HMENU,
     find_msg: u32,
     find_text: Vec<u16>,
@@ -1623,12 +1662,31 @@ fn main() -> windows::core::Result<()> {
                     && let Some(hwnd_edit) = get_active_edit(hwnd)
                     && GetFocus() == hwnd_edit
                 {
-                    if !editor_manager::try_normalize_undo(hwnd) {
+                    if !editor_manager::try_autocorrect_undo(hwnd)
+                        && !editor_manager::try_normalize_undo(hwnd)
+                    {
                         SendMessageW(hwnd_edit, WM_UNDO, WPARAM(0), LPARAM(0));
                     }
                     continue;
                 }
             }
+            // Ctrl+Enter follows a link only in HTML reader mode; elsewhere the editor
+            // gets the key.
+            if msg.message == WM_KEYDOWN && msg.wParam.0 as u32 == u32::from(VK_RETURN.0) {
+                let ctrl_down = (GetKeyState(VK_CONTROL.0 as i32) & (0x8000u16 as i16)) != 0;
+                let shift_down = (GetKeyState(VK_SHIFT.0 as i32) & (0x8000u16 as i16)) != 0;
+                let alt_down = (GetKeyState(VK_MENU.0 as i32) & (0x8000u16 as i16)) != 0;
+                if ctrl_down
+                    && !shift_down
+                    && !alt_down
+                    && let Some(hwnd_edit) = get_active_edit(hwnd)
+                    && GetFocus() == hwnd_edit
+                    && editor_manager::current_is_html_reader(hwnd)
+                {
+                    follow_link_at_caret(hwnd);
+                    continue;
+                }
+            }
             if msg.message == WM_KEYDOWN && msg.wParam.0 as u32 == u32::from(VK_F1.0) {
                 app_windows::help_window::open(hwnd);
                 continue;
@@ -1678,6 +1736,7 @@ fn main() -> windows::core::Result<()> {
                         .map(|d| matches!(d.format, FileFormat::Audiobook))
                         .unwrap_or(false);
                     let secondary_open = state.bookmarks_window.0 != 0
+                        || state.archive_window.0 != 0
                         || state.options_dialog.0 != 0
                         || state.help_window.0 != 0
                         || state.changelog_window.0 != 0
@@ -1687,6 +1746,20 @@ fn main() -> windows::core::Result<()> {
                     let secondary_open = secondary_open
                         || state.dictionary_entry_dialog.0 != 0
                         || state.go_to_time_dialog.0 != 0
+                        || state.go_to_line_dialog.0 != 0
+                        || state.subtitle_timing_dialog.0 != 0
+                        || state.braille_export_dialog.0 != 0
+                        || state.sort_lines_dialog.0 != 0
+                        || state.invisible_chars_dialog.0 != 0
+                        || state.filter_lines_dialog.0 != 0
+                        || state.number_lines_dialog.0 != 0
+                        || state.columns_dialog.0 != 0
+                        || state.compare_window.0 != 0
+                        || state.macros_window.0 != 0
+                        || state.snippets_window.0 != 0
+                        || state.autocorrect_window.0 != 0
+                        || state.outline_window.0 != 0
+                        || state.find_all_window.0 != 0
                         || state.podcasts_add_dialog.0 != 0;
 
                     let is_main_target = msg.hwnd == hwnd || IsChild(hwnd, msg.hwnd).as_bool();
@@ -1731,6 +1804,114 @@ fn main() -> windows::core::Result<()> {
                     handled = true;
                     return;
                 }
+                if state.go_to_line_dialog.0 != 0
+                    && app_windows::go_to_line_window::handle_navigation(
+                        state.go_to_line_dialog,
+                        &msg,
+                    )
+                {
+                    handled = true;
+                    return;
+                }
+                if state.subtitle_timing_dialog.0 != 0
+                    && app_windows::subtitle_timing_window::handle_navigation(
+                        state.subtitle_timing_dialog,
+                        &msg,
+                    )
+                {
+                    handled = true;
+                    return;
+                }
+                if state.braille_export_dialog.0 != 0
+                    && app_windows::braille_export_window::handle_navigation(
+                        state.braille_export_dialog,
+                        &msg,
+                    )
+                {
+                    handled = true;
+                    return;
+                }
+                if state.sort_lines_dialog.0 != 0
+                    && app_windows::sort_lines_window::handle_navigation(
+                        state.sort_lines_dialog,
+                        &msg,
+                    )
+                {
+                    handled = true;
+                    return;
+                }
+                if state.invisible_chars_dialog.0 != 0
+                    && app_windows::invisible_chars_window::handle_navigation(
+                        state.invisible_chars_dialog,
+                        &msg,
+                    )
+                {
+                    handled = true;
+                    return;
+                }
+                if state.number_lines_dialog.0 != 0
+                    && app_windows::number_lines_window::handle_navigation(
+                        state.number_lines_dialog,
+                        &msg,
+                    )
+                {
+                    handled = true;
+                    return;
+                }
+                if state.columns_dialog.0 != 0
+                    && app_windows::columns_window::handle_navigation(state.columns_dialog, &msg)
+                {
+                    handled = true;
+                    return;
+                }
+                if state.filter_lines_dialog.0 != 0
+                    && app_windows::filter_lines_window::handle_navigation(
+                        state.filter_lines_dialog,
+                        &msg,
+                    )
+                {
+                    handled = true;
+                    return;
+                }
+                if state.compare_window.0 != 0
+                    && app_windows::compare_window::handle_navigation(state.compare_window, &msg)
+                {
+                    handled = true;
+                    return;
+                }
+                if state.macros_window.0 != 0
+                    && app_windows::macros_window::handle_navigation(state.macros_window, &msg)
+                {
+                    handled = true;
+                    return;
+                }
+                if state.snippets_window.0 != 0
+                    && app_windows::snippets_window::handle_navigation(state.snippets_window, &msg)
+                {
+                    handled = true;
+                    return;
+                }
+                if state.autocorrect_window.0 != 0
+                    && app_windows::autocorrect_window::handle_navigation(
+                        state.autocorrect_window,
+                        &msg,
+                    )
+                {
+                    handled = true;
+                    return;
+                }
+                if state.outline_window.0 != 0
+                    && app_windows::outline_window::handle_navigation(state.outline_window, &msg)
+                {
+                    handled = true;
+                    return;
+                }
+                if state.find_all_window.0 != 0
+                    && app_windows::find_all_window::handle_navigation(state.find_all_window, &msg)
+                {
+                    handled = true;
+                    return;
+                }
 
                 if state.help_window.0 != 0 {
                     // Manual TAB handling for Help window
@@ -1814,6 +1995,13 @@ fn main() -> windows::core::Result<()> {
                     return;
                 }
 
+                if state.archive_window.0 != 0
+                    && app_windows::archive_window::handle_navigation(state.archive_window, &msg)
+                {
+                    handled = true;
+                    return;
+                }
+
                 if state.dictionary_window.0 != 0
                     && app_windows::dictionary_window::handle_navigation(
                         state.dictionary_window,
@@ -1946,6 +2134,8 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
             let find_msg = RegisterWindowMessageW(w!("commdlg_FindReplace"));
             let settings = load_settings();
             let bookmarks = load_bookmarks();
+            let snippets = snippets::load_snippets();
+            let autocorrect = autocorrect::load_autocorrect();
             let (_, recent_menu) = create_menus(hwnd, settings.language);
             let recent_files = load_recent_files();
             let panel_labels = voice_panel_labels(settings.language);
@@ -2222,6 +2412,10 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                 recent_files,
                 settings: settings.clone(),
                 bookmarks,
+                snippets,
+                snippet_fields: None,
+                autocorrect,
+                last_autocorrection: None,
                 find_dialog: HWND(0),
                 replace_dialog: HWND(0),
                 options_dialog: HWND(0),
@@ -2229,6 +2423,7 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                 changelog_window: HWND(0),
                 donations_window: HWND(0),
                 bookmarks_window: HWND(0),
+                archive_window: HWND(0),
                 dictionary_window: HWND(0),
                 dictionary_entry_dialog: HWND(0),
                 wiktionary_window: HWND(0),
@@ -2240,6 +2435,21 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                 podcasts_add_dialog: HWND(0),
                 rss_add_dialog: HWND(0),
                 go_to_time_dialog: HWND(0),
+                go_to_line_dialog: HWND(0),
+                subtitle_timing_dialog: HWND(0),
+                braille_export_dialog: HWND(0),
+                sort_lines_dialog: HWND(0),
+                invisible_chars_dialog: HWND(0),
+                filter_lines_dialog: HWND(0),
+                number_lines_dialog: HWND(0),
+                columns_dialog: HWND(0),
+                compare_window: HWND(0),
+                macros_window: HWND(0),
+                macro_recording: None,
+                snippets_window: HWND(0),
+                autocorrect_window: HWND(0),
+                outline_window: HWND(0),
+                find_all_window: HWND(0),
                 playback_menu: HMENU(0),
                 podcast_save_window: HWND(0),
                 batch_audiobooks_window: HWND(0),
@@ -2915,13 +3125,55 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                 }
                 return LRESULT(0);
             }
-
+            if (IDM_FILE_REOPEN_ENCODING_BASE
+                ..IDM_FILE_REOPEN_ENCODING_BASE + TextEncoding::ALL.len())
+                .contains(&cmd_id)
+            {
+                let encoding = TextEncoding::ALL[cmd_id - IDM_FILE_REOPEN_ENCODING_BASE];
+                editor_manager::reopen_current_document_with_encoding(hwnd, encoding);
+                return LRESULT(0);
+            }
+            if (IDM_EDIT_ENCODE_BASE..IDM_EDIT_ENCODE_BASE + TextCodec::ALL.len()).contains(&cmd_id)
+                || (IDM_EDIT_DECODE_BASE..IDM_EDIT_DECODE_BASE + TextCodec::ALL.len())
+                    .contains(&cmd_id)
+            {
+                let encode = cmd_id < IDM_EDIT_DECODE_BASE;
+                let (codec, key) = if encode {
+                    let codec = TextCodec::ALL[cmd_id - IDM_EDIT_ENCODE_BASE];
+                    (codec, codec.encode_label_key())
+                } else {
+                    let codec = TextCodec::ALL[cmd_id - IDM_EDIT_DECODE_BASE];
+                    (codec, codec.decode_label_key())
+                };
+                log_debug(&format!("Menu: {key}"));
+                match editor_manager::codec_active_edit(hwnd, codec, encode) {
+                    Some(true) => confirm_menu_action(hwnd, key),
+                    Some(false) => {}
+                    None => {
+                        let language =
+                            with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+                        show_error(hwnd, language, &i18n::tr(language, "codec.invalid"));
+                    }
+                }
+                return LRESULT(0);
+            }
+            if (IDM_MACRO_SHORTCUT_BASE + 1..=IDM_MACRO_SHORTCUT_BASE + 9).contains(&cmd_id) {
+                text_macros::run_shortcut(hwnd, (cmd_id - IDM_MACRO_SHORTCUT_BASE) as u8);
+                return LRESULT(0);
+            }
             match cmd_id {
                 IDM_FILE_NEW => {
                     log_debug("Menu: New document");
                     editor_manager::new_document(hwnd);
                     LRESULT(0)
                 }
+                IDM_FILE_NEW_FROM_TEMPLATE => {
+                    log_debug("Menu: New from template");
+                    if let Some(path) = open_template_dialog(hwnd) {
+                        editor_manager::new_document_from_template(hwnd, &path);
+                    }
+                    LRESULT(0)
+                }
                 IDM_FILE_OPEN => {
                     log_debug("Menu: Open document");
                     if let Some((path, encoding)) = open_file_dialog_with_encoding(hwnd) {
@@ -2942,6 +3194,11 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                     editor_manager::save_current_document_as(hwnd);
                     LRESULT(0)
                 }
+                IDM_FILE_SAVE_ENCRYPTED => {
+                    log_debug("Menu: Save document encrypted");
+                    editor_manager::save_current_document_encrypted(hwnd);
+                    LRESULT(0)
+                }
                 IDM_FILE_SAVE_ALL => {
                     log_debug("Menu: Save all documents");
                     editor_manager::save_all_documents(hwnd);
@@ -2984,6 +3241,16 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                     tts_engine::start_audiobook(hwnd);
                     LRESULT(0)
                 }
+                IDM_FILE_TALKING_BOOK => {
+                    log_debug("Menu: Export talking book");
+                    tts_engine::start_talking_book(hwnd);
+                    LRESULT(0)
+                }
+                IDM_FILE_BRAILLE_EXPORT => {
+                    log_debug("Menu: Export braille");
+                    app_windows::braille_export_window::open(hwnd);
+                    LRESULT(0)
+                }
                 IDM_FILE_BATCH_AUDIOBOOK => {
                     log_debug("Menu: Batch audiobooks");
                     app_windows::batch_audiobooks_window::open(hwnd);
@@ -2996,7 +3263,9 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                 }
                 IDM_EDIT_UNDO => {
                     log_debug("Menu: Undo");
-                    if !editor_manager::try_normalize_undo(hwnd) {
+                    if !editor_manager::try_autocorrect_undo(hwnd)
+                        && !editor_manager::try_normalize_undo(hwnd)
+                    {
                         editor_manager::send_to_active_edit(hwnd, WM_UNDO);
                     }
                     LRESULT(0)
@@ -3036,6 +3305,87 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                     search::find_next_from_state(hwnd);
                     LRESULT(0)
                 }
+                IDM_EDIT_FIND_ALL => {
+                    log_debug("Menu: Find all");
+                    search::find_all_from_state(hwnd);
+                    LRESULT(0)
+                }
+                IDM_EDIT_GO_TO_LINE => {
+                    log_debug("Menu: Go to line");
+                    app_windows::go_to_line_window::open(hwnd);
+                    LRESULT(0)
+                }
+                IDM_EDIT_OUTLINE => {
+                    log_debug("Menu: Outline");
+                    app_windows::outline_window::open(hwnd);
+                    LRESULT(0)
+                }
+                IDM_EDIT_NEXT_HEADING | IDM_EDIT_PREV_HEADING => {
+                    let forward = cmd_id == IDM_EDIT_NEXT_HEADING;
+                    log_debug(&format!("Menu: Heading (forward: {forward})"));
+                    go_to_adjacent_heading(hwnd, forward);
+                    LRESULT(0)
+                }
+                IDM_EDIT_LARGE_FILE_NEXT | IDM_EDIT_LARGE_FILE_PREV => {
+                    let forward = cmd_id == IDM_EDIT_LARGE_FILE_NEXT;
+                    log_debug(&format!("Menu: Large file section (forward: {forward})"));
+                    let language =
+                        with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+                    if !editor_manager::current_is_large_file(hwnd) {
+                        show_info(hwnd, language, &i18n::tr(language, "large_file.not_large"));
+                        return LRESULT(0);
+                    }
+                    let message = match editor_manager::step_large_file_section(hwnd, forward) {
+                        Some((first, last, total)) => i18n::tr_f(
+                            language,
+                            "large_file.section",
+                            &[
+                                ("first", &first.to_string()),
+                                ("last", &last.to_string()),
+                                ("total", &total.to_string()),
+                            ],
+                        ),
+                        None => i18n::tr(language, "large_file.no_more_sections"),
+                    };
+                    nvda_speak(&message);
+                    LRESULT(0)
+                }
+                IDM_EDIT_SUBTITLES_GO_TO_TIME
+                | IDM_EDIT_SUBTITLES_TIMESTAMPS
+                | IDM_EDIT_SUBTITLES_TIMING => {
+                    log_debug(&format!("Menu: Subtitles command {cmd_id}"));
+                    let language =
+                        with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+                    if !editor_manager::current_is_subtitles(hwnd) {
+                        show_info(
+                            hwnd,
+                            language,
+                            &i18n::tr(language, "subtitles.not_subtitles"),
+                        );
+                        return LRESULT(0);
+                    }
+                    if cmd_id == IDM_EDIT_SUBTITLES_GO_TO_TIME {
+                        app_windows::go_to_time_window::open(hwnd);
+                    } else if cmd_id == IDM_EDIT_SUBTITLES_TIMING {
+                        app_windows::subtitle_timing_window::open(hwnd);
+                    } else if let Some(show) = editor_manager::toggle_subtitle_timestamps(hwnd) {
+                        if let Some(settings) = with_state(hwnd, |state| state.settings.clone()) {
+                            save_settings(settings);
+                        }
+                        let key = if show {
+                            "subtitles.timestamps_shown"
+                        } else {
+                            "subtitles.timestamps_hidden"
+                        };
+                        nvda_speak(&i18n::tr(language, key));
+                    }
+                    LRESULT(0)
+                }
+                IDM_EDIT_FOLLOW_LINK => {
+                    log_debug("Menu: Follow link");
+                    follow_link_at_caret(hwnd);
+                    LRESULT(0)
+                }
                 IDM_EDIT_REPLACE => {
                     log_debug("Menu: Replace");
                     search::open_replace_dialog(hwnd);
@@ -3054,6 +3404,7 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                 IDM_EDIT_STRIP_MARKDOWN => {
                     log_debug("Menu: Strip Markdown");
                     if editor_manager::strip_markdown_active_edit(hwnd) {
+                        text_macros::record_command(hwnd, cmd_id);
                         confirm_menu_action(hwnd, "edit.strip_markdown");
                     }
                     LRESULT(0)
@@ -3061,6 +3412,7 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                 IDM_EDIT_NORMALIZE_WHITESPACE => {
                     log_debug("Menu: Normalize whitespace");
                     if editor_manager::normalize_whitespace_active_edit(hwnd) {
+                        text_macros::record_command(hwnd, cmd_id);
                         confirm_menu_action(hwnd, "edit.normalize_whitespace");
                     }
                     LRESULT(0)
@@ -3068,6 +3420,7 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                 IDM_EDIT_HARD_LINE_BREAK => {
                     log_debug("Menu: Hard line break");
                     if editor_manager::hard_line_break_active_edit(hwnd) {
+                        text_macros::record_command(hwnd, cmd_id);
                         confirm_menu_action(hwnd, "edit.hard_line_break");
                     }
                     LRESULT(0)
@@ -3075,13 +3428,106 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                 IDM_EDIT_ORDER_ITEMS => {
                     log_debug("Menu: Order items");
                     if editor_manager::order_items_active_edit(hwnd) {
+                        text_macros::record_command(hwnd, cmd_id);
                         confirm_menu_action(hwnd, "edit.order_items");
                     }
                     LRESULT(0)
                 }
+                IDM_EDIT_SORT_LINES => {
+                    log_debug("Menu: Sort lines");
+                    app_windows::sort_lines_window::open(hwnd);
+                    LRESULT(0)
+                }
+                IDM_EDIT_FILTER_LINES => {
+                    log_debug("Menu: Filter lines");
+                    if editor_manager::current_is_large_file(hwnd) {
+                        let language =
+                            with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+                        show_info(hwnd, language, &i18n::tr(language, "large_file.read_only"));
+                    } else {
+                        app_windows::filter_lines_window::open(hwnd);
+                    }
+                    LRESULT(0)
+                }
+                IDM_EDIT_CASE_UPPER
+                | IDM_EDIT_CASE_LOWER
+                | IDM_EDIT_CASE_SENTENCE
+                | IDM_EDIT_CASE_TITLE
+                | IDM_EDIT_CASE_TOGGLE
+                | IDM_EDIT_CASE_CAPITALIZE_SENTENCES => {
+                    log_debug(&format!("Menu: Change case {cmd_id}"));
+                    let (conversion, key) = match cmd_id {
+                        IDM_EDIT_CASE_UPPER => (CaseConversion::Upper, "edit.case_upper"),
+                        IDM_EDIT_CASE_LOWER => (CaseConversion::Lower, "edit.case_lower"),
+                        IDM_EDIT_CASE_SENTENCE => (CaseConversion::Sentence, "edit.case_sentence"),
+                        IDM_EDIT_CASE_TITLE => (CaseConversion::Title, "edit.case_title"),
+                        IDM_EDIT_CASE_TOGGLE => (CaseConversion::Toggle, "edit.case_toggle"),
+                        _ => (
+                            CaseConversion::CapitalizeSentences,
+                            "edit.case_capitalize_sentences",
+                        ),
+                    };
+                    if editor_manager::convert_case_active_edit(hwnd, conversion) {
+                        text_macros::record_command(hwnd, cmd_id);
+                        confirm_menu_action(hwnd, key);
+                    }
+                    LRESULT(0)
+                }
+                IDM_EDIT_NORMALIZE_NFC | IDM_EDIT_NORMALIZE_NFD | IDM_EDIT_NORMALIZE_NFKC => {
+                    log_debug(&format!("Menu: Unicode normalization {cmd_id}"));
+                    let (form, key) = match cmd_id {
+                        IDM_EDIT_NORMALIZE_NFC => (UnicodeForm::Nfc, "edit.normalize_nfc"),
+                        IDM_EDIT_NORMALIZE_NFD => (UnicodeForm::Nfd, "edit.normalize_nfd"),
+                        _ => (UnicodeForm::Nfkc, "edit.normalize_nfkc"),
+                    };
+                    if editor_manager::normalize_unicode_active_edit(hwnd, form) {
+                        confirm_menu_action(hwnd, key);
+                    } else {
+                        let language =
+                            with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+                        nvda_speak(&i18n::tr(language, "unicode.already_normalized"));
+                    }
+                    LRESULT(0)
+                }
+                IDM_EDIT_REVEAL_INVISIBLE => {
+                    log_debug("Menu: Reveal invisible characters");
+                    if editor_manager::invisible_chars_report_active_edit(hwnd) == 0 {
+                        let language =
+                            with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+                        show_info(hwnd, language, &i18n::tr(language, "unicode.none_found"));
+                    }
+                    LRESULT(0)
+                }
+                IDM_EDIT_CLEAN_INVISIBLE => {
+                    log_debug("Menu: Clean invisible characters");
+                    if editor_manager::current_is_large_file(hwnd) {
+                        let language =
+                            with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+                        show_info(hwnd, language, &i18n::tr(language, "large_file.read_only"));
+                    } else {
+                        app_windows::invisible_chars_window::open(hwnd);
+                    }
+                    LRESULT(0)
+                }
+                IDM_EDIT_MACRO_RECORD => {
+                    log_debug("Menu: Record macro");
+                    text_macros::start_recording(hwnd);
+                    LRESULT(0)
+                }
+                IDM_EDIT_MACRO_STOP => {
+                    log_debug("Menu: Stop macro recording");
+                    text_macros::stop_recording(hwnd);
+                    LRESULT(0)
+                }
+                IDM_EDIT_MACRO_MANAGE => {
+                    log_debug("Menu: Manage macros");
+                    app_windows::macros_window::open(hwnd, None);
+                    LRESULT(0)
+                }
                 IDM_EDIT_KEEP_UNIQUE_ITEMS => {
                     log_debug("Menu: Keep unique items");
                     if editor_manager::keep_unique_items_active_edit(hwnd) {
+                        text_macros::record_command(hwnd, cmd_id);
                         confirm_menu_action(hwnd, "edit.keep_unique_items");
                     }
                     LRESULT(0)
@@ -3089,6 +3535,7 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                 IDM_EDIT_REVERSE_ITEMS => {
                     log_debug("Menu: Reverse items");
                     if editor_manager::reverse_items_active_edit(hwnd) {
+                        text_macros::record_command(hwnd, cmd_id);
                         confirm_menu_action(hwnd, "edit.reverse_items");
                     }
                     LRESULT(0)
@@ -3096,6 +3543,7 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                 IDM_EDIT_QUOTE_LINES => {
                     log_debug("Menu: Quote lines");
                     if editor_manager::quote_lines_active_edit(hwnd) {
+                        text_macros::record_command(hwnd, cmd_id);
                         confirm_menu_action(hwnd, "edit.quote_lines");
                     }
                     LRESULT(0)
@@ -3103,10 +3551,44 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                 IDM_EDIT_UNQUOTE_LINES => {
                     log_debug("Menu: Unquote lines");
                     if editor_manager::unquote_lines_active_edit(hwnd) {
+                        text_macros::record_command(hwnd, cmd_id);
                         confirm_menu_action(hwnd, "edit.unquote_lines");
                     }
                     LRESULT(0)
                 }
+                IDM_EDIT_NUMBER_LINES | IDM_EDIT_COLUMNS => {
+                    log_debug(&format!("Menu: Line numbers or columns {cmd_id}"));
+                    if editor_manager::current_is_large_file(hwnd) {
+                        let language =
+                            with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+                        show_info(hwnd, language, &i18n::tr(language, "large_file.read_only"));
+                    } else if cmd_id == IDM_EDIT_NUMBER_LINES {
+                        app_windows::number_lines_window::open(hwnd);
+                    } else {
+                        app_windows::columns_window::open(hwnd);
+                    }
+                    LRESULT(0)
+                }
+                IDM_EDIT_REMOVE_LINE_NUMBERS => {
+                    log_debug("Menu: Remove line numbers");
+                    let removed = editor_manager::remove_line_numbers_active_edit(hwnd);
+                    if removed > 0 {
+                        text_macros::record_command(hwnd, cmd_id);
+                    }
+                    let language =
+                        with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+                    let message = if removed == 0 {
+                        i18n::tr(language, "number_lines.none_found")
+                    } else {
+                        i18n::tr_f(
+                            language,
+                            "number_lines.removed",
+                            &[("count", &removed.to_string())],
+                        )
+                    };
+                    nvda_speak(&message);
+                    LRESULT(0)
+                }
                 IDM_EDIT_TEXT_STATS => {
                     log_debug("Menu: Text stats");
                     editor_manager::text_stats_active_edit(hwnd);
@@ -3115,6 +3597,7 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                 IDM_EDIT_JOIN_LINES => {
                     log_debug("Menu: Join lines");
                     if editor_manager::join_lines_active_edit(hwnd) {
+                        text_macros::record_command(hwnd, cmd_id);
                         confirm_menu_action(hwnd, "edit.join_lines");
                     }
                     LRESULT(0)
@@ -3122,6 +3605,7 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                 IDM_EDIT_CLEAN_EOL_HYPHENS => {
                     log_debug("Menu: Clean EOL hyphens");
                     if editor_manager::clean_end_of_line_hyphens_active_edit(hwnd) {
+                        text_macros::record_command(hwnd, cmd_id);
                         confirm_menu_action(hwnd, "edit.clean_eol_hyphens");
                     }
                     LRESULT(0)
@@ -3129,6 +3613,7 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                 IDM_EDIT_REMOVE_DUPLICATE_LINES => {
                     log_debug("Menu: Remove duplicate lines");
                     if editor_manager::remove_duplicate_lines_active_edit(hwnd) {
+                        text_macros::record_command(hwnd, cmd_id);
                         confirm_menu_action(hwnd, "edit.remove_duplicate_lines");
                     }
                     LRESULT(0)
@@ -3136,10 +3621,69 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                 IDM_EDIT_REMOVE_DUPLICATE_CONSECUTIVE_LINES => {
                     log_debug("Menu: Remove duplicate consecutive lines");
                     if editor_manager::remove_duplicate_consecutive_lines_active_edit(hwnd) {
+                        text_macros::record_command(hwnd, cmd_id);
                         confirm_menu_action(hwnd, "edit.remove_duplicate_consecutive_lines");
                     }
                     LRESULT(0)
                 }
+                IDM_EDIT_FIX_MOJIBAKE => {
+                    log_debug("Menu: Fix garbled encoding");
+                    let repaired = editor_manager::fix_mojibake_active_edit(hwnd);
+                    if repaired > 0 {
+                        text_macros::record_command(hwnd, cmd_id);
+                    }
+                    let language =
+                        with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+                    let message = if repaired == 0 {
+                        i18n::tr(language, "edit.fix_mojibake_none")
+                    } else {
+                        i18n::tr_f(
+                            language,
+                            "edit.fix_mojibake_done",
+                            &[("count", &repaired.to_string())],
+                        )
+                    };
+                    show_info(hwnd, language, &message);
+                    LRESULT(0)
+                }
+                IDM_EDIT_TRANSLITERATE | IDM_EDIT_TO_ASCII => {
+                    log_debug(&format!("Menu: Transliterate {cmd_id}"));
+                    let (punctuation, key) = if cmd_id == IDM_EDIT_TO_ASCII {
+                        (true, "edit.to_ascii")
+                    } else {
+                        (false, "edit.transliterate")
+                    };
+                    if editor_manager::transliterate_active_edit(hwnd, punctuation) {
+                        text_macros::record_command(hwnd, cmd_id);
+                        confirm_menu_action(hwnd, key);
+                    }
+                    LRESULT(0)
+                }
+                IDM_EDIT_EOL_LF | IDM_EDIT_EOL_CRLF | IDM_EDIT_EOL_CR => {
+                    let line_ending = match cmd_id {
+                        IDM_EDIT_EOL_LF => LineEnding::Lf,
+                        IDM_EDIT_EOL_CR => LineEnding::Cr,
+                        _ => LineEnding::Crlf,
+                    };
+                    log_debug(&format!(
+                        "Menu: Convert line endings to {}",
+                        line_ending.label()
+                    ));
+                    let language =
+                        with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+                    let message = if editor_manager::current_is_large_file(hwnd) {
+                        i18n::tr(language, "large_file.read_only")
+                    } else {
+                        editor_manager::set_current_line_ending(hwnd, line_ending);
+                        i18n::tr_f(
+                            language,
+                            "text_stats.line_ending",
+                            &[("eol", line_ending.label())],
+                        )
+                    };
+                    show_info(hwnd, language, &message);
+                    LRESULT(0)
+                }
                 IDM_PLAYBACK_PLAY_PAUSE => {
                     handle_player_command(hwnd, PlayerCommand::TogglePause);
                     LRESULT(0)
@@ -3216,6 +3760,11 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                     toggle_favorites_panel(hwnd);
                     LRESULT(0)
                 }
+                IDM_VIEW_HTML_READER_MODE => {
+                    log_debug("Menu: Toggle HTML reader mode");
+                    toggle_html_reader_mode(hwnd);
+                    LRESULT(0)
+                }
                 cmd_id if text_color_from_menu_id(cmd_id).is_some() => {
                     let color = text_color_from_menu_id(cmd_id);
                     update_text_preferences(hwnd, color, None);
@@ -3243,6 +3792,16 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                     app_windows::bookmarks_window::open(hwnd);
                     LRESULT(0)
                 }
+                IDM_MANAGE_SNIPPETS => {
+                    log_debug("Menu: Manage snippets");
+                    app_windows::snippets_window::open(hwnd);
+                    LRESULT(0)
+                }
+                IDM_MANAGE_AUTOCORRECT => {
+                    log_debug("Menu: Autocorrect");
+                    app_windows::autocorrect_window::open(hwnd);
+                    LRESULT(0)
+                }
                 IDM_NEXT_TAB => {
                     next_tab_with_prompt(hwnd);
                     LRESULT(0)
@@ -3267,6 +3826,17 @@ unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
                     app_windows::wikipedia_window::open(hwnd);
                     LRESULT(0)
                 }
+                IDM_TOOLS_COMPARE => {
+                    log_debug("Menu: Compare documents");
+                    if editor_manager::current_is_large_file(hwnd) {
+                        let language =
+                            with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+                        show_info(hwnd, language, &i18n::tr(language, "large_file.read_only"));
+                    } else {
+                        app_windows::compare_window::open(hwnd);
+                    }
+                    LRESULT(0)
+                }
                 IDM_TOOLS_IMPORT_YOUTUBE => {
                     log_debug("Menu: Import YouTube transcript");
                     app_windows::youtube_transcript_window::import_youtube_transcript(hwnd);
@@ -3616,15 +4186,17 @@ unsafe fn update_text_preferences(hwnd: HWND, text_color: Option<u32>, text_size
 }
 
 unsafe fn update_voice_panel_menu_check(hwnd: HWND) {
-    let (visible, favorites_visible, text_color, text_size) = with_state(hwnd, |state| {
-        (
-            state.voice_panel_visible,
-            state.voice_favorites_visible,
-            state.settings.text_color,
-            state.settings.text_size,
-        )
-    })
-    .unwrap_or((false, false, 0x000000, 12));
+    let (visible, favorites_visible, html_reader_mode, text_color, text_size) =
+        with_state(hwnd, |state| {
+            (
+                state.voice_panel_visible,
+                state.voice_favorites_visible,
+                state.settings.html_reader_mode,
+                state.settings.text_color,
+                state.settings.text_size,
+            )
+        })
+        .unwrap_or((false, false, false, 0x000000, 12));
     let hmenu = GetMenu(hwnd);
     if hmenu.0 == 0 {
         return;
@@ -3643,6 +4215,16 @@ unsafe fn update_voice_panel_menu_check(hwnd: HWND) {
         IDM_VIEW_SHOW_FAVORITES as u32,
         (MF_BYCOMMAND | fav_flags).0,
     );
+    let reader_flags = if html_reader_mode {
+        MF_CHECKED
+    } else {
+        MF_UNCHECKED
+    };
+    CheckMenuItem(
+        hmenu,
+        IDM_VIEW_HTML_READER_MODE as u32,
+        (MF_BYCOMMAND | reader_flags).0,
+    );
 
     let color_items = [
         IDM_VIEW_TEXT_COLOR_BLACK,
@@ -3687,6 +4269,63 @@ unsafe fn update_voice_panel_menu_check(hwnd: HWND) {
     }
 }
 
+unsafe fn toggle_html_reader_mode(hwnd: HWND) {
+    let Some((enabled, settings)) = with_state(hwnd, |state| {
+        state.settings.html_reader_mode = !state.settings.html_reader_mode;
+        (state.settings.html_reader_mode, state.settings.clone())
+    }) else {
+        return;
+    };
+    save_settings(settings);
+    update_voice_panel_menu_check(hwnd);
+    let language = with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+    let key = if enabled {
+        "html.reader_mode_on"
+    } else {
+        "html.reader_mode_off"
+    };
+    nvda_speak(&i18n::tr(language, key));
+    editor_manager::reload_current_html_document(hwnd);
+}
+
+/// Opens the link whose `[n]` marker is at the caret: web and mail links go to
+/// the default handler, local files open in a new tab.
+unsafe fn follow_link_at_caret(hwnd: HWND) {
+    let language = with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+    let Some(target) = editor_manager::current_link_target(hwnd) else {
+        show_info(hwnd, language, &i18n::tr(language, "html.no_link"));
+        return;
+    };
+    let lower = target.to_ascii_lowercase();
+    let is_web = ["http://", "https://", "mailto:", "ftp://"]
+        .iter()
+        .any(|scheme| lower.starts_with(scheme));
+    if !is_web && Path::new(&target).is_file() {
+        editor_manager::open_document(hwnd, Path::new(&target));
+        return;
+    }
+    let opened = is_web && {
+        let target_w = to_wide(&target);
+        let verb = to_wide("open");
+        let result = ShellExecuteW(
+            HWND(0),
+            PCWSTR(verb.as_ptr()),
+            PCWSTR(target_w.as_ptr()),
+            PCWSTR::null(),
+            PCWSTR::null(),
+            SW_SHOW,
+        );
+        result.0 as isize > 32
+    };
+    if !opened {
+        show_error(
+            hwnd,
+            language,
+            &i18n::tr_f(language, "html.link_open_failed", &[("target", &target)]),
+        );
+    }
+}
+
 unsafe fn toggle_voice_panel(hwnd: HWND) {
     let visible = with_state(hwnd, |state| state.voice_panel_visible).unwrap_or(false);
     set_voice_panel_visible(hwnd, !visible);
@@ -5382,6 +6021,24 @@ unsafe fn handle_spellcheck_ignore_once(hwnd: HWND) {
     });
 }
 
+/// Moves the caret to the next or previous heading and announces it.
+unsafe fn go_to_adjacent_heading(hwnd: HWND, forward: bool) {
+    let language = with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+    let Some((headings, caret_line)) = editor_manager::current_outline(hwnd) else {
+        return;
+    };
+    let message = match outline::adjacent_heading(&headings, caret_line, forward) {
+        Some(heading) => {
+            editor_manager::go_to_line(hwnd, heading.line);
+            app_windows::outline_window::describe_heading(language, heading)
+        }
+        None if headings.is_empty() => i18n::tr(language, "outline.no_headings"),
+        None if forward => i18n::tr(language, "outline.no_next"),
+        None => i18n::tr(language, "outline.no_previous"),
+    };
+    nvda_speak(&message);
+}
+
 /// Navigate to next (forward=true) or previous (forward=false) spelling error
 unsafe fn go_to_spelling_error(hwnd: HWND, forward: bool) {
     use windows::Win32::UI::Controls::RichEdit::{CHARRANGE, EM_EXGETSEL, EM_EXSETSEL};
@@ -5707,7 +6364,7 @@ unsafe fn create_accelerators() -> HACCEL {
     let virt_shift = FCONTROL | FSHIFT | FVIRTKEY;
     let virt_alt = FALT | FVIRTKEY;
     let virt_alt_shift = FALT | FSHIFT | FVIRTKEY;
-    let accels = [
+    let mut accels = vec![
         ACCEL {
             fVirt: virt,
             key: 'N' as u16,
@@ -5783,6 +6440,46 @@ unsafe fn create_accelerators() -> HACCEL {
             key: VK_F3.0,
             cmd: IDM_EDIT_FIND_NEXT as u16,
         },
+        ACCEL {
+            fVirt: virt_alt,
+            key: VK_F3.0,
+            cmd: IDM_EDIT_FIND_ALL as u16,
+        },
+        ACCEL {
+            fVirt: virt,
+            key: 'G' as u16,
+            cmd: IDM_EDIT_GO_TO_LINE as u16,
+        },
+        ACCEL {
+            fVirt: virt_alt,
+            key: VK_NEXT.0,
+            cmd: IDM_EDIT_LARGE_FILE_NEXT as u16,
+        },
+        ACCEL {
+            fVirt: virt_alt,
+            key: VK_PRIOR.0,
+            cmd: IDM_EDIT_LARGE_FILE_PREV as u16,
+        },
+        ACCEL {
+            fVirt: virt_shift,
+            key: 'E' as u16,
+            cmd: IDM_EDIT_OUTLINE as u16,
+        },
+        ACCEL {
+            fVirt: virt_alt,
+            key: VK_DOWN.0,
+            cmd: IDM_EDIT_NEXT_HEADING as u16,
+        },
+        ACCEL {
+            fVirt: virt_alt,
+            key: VK_UP.0,
+            cmd: IDM_EDIT_PREV_HEADING as u16,
+        },
+        ACCEL {
+            fVirt: virt,
+            key: 'T' as u16,
+            cmd: IDM_EDIT_SUBTITLES_GO_TO_TIME as u16,
+        },
         ACCEL {
             fVirt: virt,
             key: 'H' as u16,
@@ -5919,6 +6616,11 @@ unsafe fn create_accelerators() -> HACCEL {
             cmd: IDM_INSERT_BOOKMARK as u16,
         },
     ];
+    accels.extend((1..=9u16).map(|digit| ACCEL {
+        fVirt: virt_shift,
+        key: '0' as u16 + digit,
+        cmd: (IDM_MACRO_SHORTCUT_BASE + digit as usize) as u16,
+    }));
     CreateAcceleratorTableW(&accels).unwrap_or(HACCEL(0))
 }
 
@@ -6070,6 +6772,11 @@ unsafe fn insert_bookmark(hwnd: HWND) {
         }
         snippet = snippet_pre;
     }
+    // bookmarks.json is plain text, so it gets no excerpt of an encrypted document.
+    if matches!(format, FileFormat::Encrypted(_)) {
+        let language = with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+        snippet = i18n::tr(language, "encryption.bookmark_snippet");
+    }
 
     let bookmark = Bookmark {
         position: pos,
@@ -6231,7 +6938,12 @@ pub(crate) unsafe fn open_pdf_document_async(hwnd: HWND, path: &Path) {
             format: FileFormat::Pdf,
             opened_text_encoding: None,
             current_save_text_encoding: None,
+            line_ending: LineEnding::default(),
+            archive_source: None,
+            large_file: None,
+            subtitles: None,
             from_rss: false,
+            html_reader: false,
         };
         state.docs.push(doc);
         insert_tab(state.hwnd_tab, &title, (state.docs.len() - 1) as i32);
@@ -6658,6 +7370,61 @@ fn is_reserved_filename(name: &str) -> bool {
 pub(crate) unsafe fn save_audio_dialog(
     hwnd: HWND,
     suggested_name: Option<&str>,
+) -> Option<PathBuf> {
+    save_export_dialog(
+        hwnd,
+        suggested_name,
+        "dialog.save_audio_filter",
+        "dialog.save_audio_title",
+        "mp3",
+    )
+}
+
+pub(crate) unsafe fn save_talking_book_dialog(
+    hwnd: HWND,
+    suggested_name: Option<&str>,
+) -> Option<PathBuf> {
+    save_export_dialog(
+        hwnd,
+        suggested_name,
+        "dialog.save_talking_book_filter",
+        "dialog.save_talking_book_title",
+        "epub",
+    )
+}
+
+pub(crate) unsafe fn save_braille_dialog(
+    hwnd: HWND,
+    suggested_name: Option<&str>,
+) -> Option<PathBuf> {
+    save_export_dialog(
+        hwnd,
+        suggested_name,
+        "dialog.save_braille_filter",
+        "dialog.save_braille_title",
+        "brf",
+    )
+}
+
+pub(crate) unsafe fn save_encrypted_dialog(
+    hwnd: HWND,
+    suggested_name: Option<&str>,
+) -> Option<PathBuf> {
+    save_export_dialog(
+        hwnd,
+        suggested_name,
+        "dialog.save_encrypted_filter",
+        "dialog.save_encrypted_title",
+        encryption::EXTENSION,
+    )
+}
+
+unsafe fn save_export_dialog(
+    hwnd: HWND,
+    suggested_name: Option<&str>,
+    filter_key: &str,
+    title_key: &str,
+    default_extension: &str,
 ) -> Option<PathBuf> {
     let mut file_buf = vec![0u16; 4096];
     if let Some(name) = suggested_name {
@@ -6669,9 +7436,9 @@ pub(crate) unsafe fn save_audio_dialog(
         file_buf[..copy_len].copy_from_slice(&name_wide[..copy_len]);
     }
     let language = with_state(hwnd, |state| state.settings.language).unwrap_or_default();
-    let filter_raw = i18n::tr(language, "dialog.save_audio_filter");
+    let filter_raw = i18n::tr(language, filter_key);
     let filter = to_wide(&filter_raw.replace("\\0", "\0"));
-    let title = to_wide(&i18n::tr(language, "dialog.save_audio_title"));
+    let title = to_wide(&i18n::tr(language, title_key));
     let mut ofn = OPENFILENAMEW {
         lStructSize: std::mem::size_of::<OPENFILENAMEW>() as u32,
         hwndOwner: hwnd,
@@ -6690,7 +7457,7 @@ pub(crate) unsafe fn save_audio_dialog(
         let path = PathBuf::from(String::from_utf16_lossy(&file_buf[..len]));
         let mut path = path;
         if path.extension().is_none() {
-            path.set_extension("mp3");
+            path.set_extension(default_extension);
         }
         Some(path)
     } else {
@@ -6698,6 +7465,38 @@ pub(crate) unsafe fn save_audio_dialog(
     }
 }
 
+/// Asks for a template file, starting in the templates folder of the settings dir.
+pub(crate) unsafe fn open_template_dialog(hwnd: HWND) -> Option<PathBuf> {
+    let language = with_state(hwnd, |state| state.settings.language).unwrap_or_default();
+    let templates_dir = snippets::templates_dir();
+    crate::log_if_err!(std::fs::create_dir_all(&templates_dir));
+    let initial_dir = to_wide(&templates_dir.to_string_lossy());
+    let filter_raw = i18n::tr(language, "snippets.template_filter");
+    let filter = to_wide(&filter_raw.replace("\\0", "\0"));
+    let mut file_buf = vec![0u16; 4096];
+    let mut ofn = OPENFILENAMEW {
+        lStructSize: std::mem::size_of::<OPENFILENAMEW>() as u32,
+        hwndOwner: hwnd,
+        lpstrFile: PWSTR(file_buf.as_mut_ptr()),
+        nMaxFile: file_buf.len() as u32,
+        lpstrFilter: PCWSTR(filter.as_ptr()),
+        lpstrInitialDir: PCWSTR(initial_dir.as_ptr()),
+        Flags: OFN_EXPLORER | OFN_FILEMUSTEXIST | OFN_PATHMUSTEXIST | OFN_HIDEREADONLY,
+        ..Default::default()
+    };
+    if !GetOpenFileNameW(&mut ofn).as_bool() {
+        return None;
+    }
+    let len = file_buf
+        .iter()
+        .position(|&c| c == 0)
+        .unwrap_or(file_buf.len());
+    if len == 0 {
+        return None;
+    }
+    Some(PathBuf::from(String::from_utf16_lossy(&file_buf[..len])))
+}
+
 pub(crate) unsafe fn show_error(hwnd: HWND, language: Language, message: &str) {
     log_debug(&format!("Error shown: {message}"));
     let wide = to_wide(message);
@@ -6870,24 +7669,17 @@ impl IFileDialogControlEvents_Impl for CustomFileDialogEventHandler {
 }
 
 fn encoding_to_index(enc: TextEncoding) -> u32 {
-    match enc {
-        TextEncoding::Ansi => 0,
-        TextEncoding::Utf8 => 1,
-        TextEncoding::Utf8Bom => 2,
-        TextEncoding::Utf16Le => 3,
-        TextEncoding::Utf16Be => 4,
-    }
+    TextEncoding::ALL
+        .iter()
+        .position(|candidate| *candidate == enc)
+        .unwrap_or(1) as u32
 }
 
 fn index_to_encoding(index: u32) -> TextEncoding {
-    match index {
-        0 => TextEncoding::Ansi,
-        1 => TextEncoding::Utf8,
-        2 => TextEncoding::Utf8Bom,
-        3 => TextEncoding::Utf16Le,
-        4 => TextEncoding::Utf16Be,
-        _ => TextEncoding::Utf8,
-    }
+    TextEncoding::ALL
+        .get(index as usize)
+        .copied()
+        .unwrap_or(TextEncoding::Utf8)
 }
 
 pub(crate) unsafe fn open_file_dialog_with_encoding(
@@ -6933,13 +7725,10 @@ pub(crate) unsafe fn open_file_dialog_with_encoding(
 
     let pfdc: IFileDialogCustomize = pfd.cast().ok()?;
     let encoding_label = i18n::tr(language, "dialog.encoding_label");
-    let encodings = vec![
-        i18n::tr(language, "encoding.ansi"),
-        i18n::tr(language, "encoding.utf8"),
-        i18n::tr(language, "encoding.utf8bom"),
-        i18n::tr(language, "encoding.utf16le"),
-        i18n::tr(language, "encoding.utf16be"),
-    ];
+    let encodings: Vec<String> = TextEncoding::ALL
+        .iter()
+        .map(|encoding| i18n::tr(language, encoding.label_key()))
+        .collect();
 
     log_debug("Adding encoding controls to open dialog");
 
@@ -7039,13 +7828,10 @@ pub(crate) unsafe fn save_file_dialog_with_encoding(
 
     let pfdc: IFileDialogCustomize = pfd.cast().ok()?;
     let encoding_label = i18n::tr(language, "dialog.encoding_label");
-    let encodings = vec![
-        i18n::tr(language, "encoding.ansi"),
-        i18n::tr(language, "encoding.utf8"),
-        i18n::tr(language, "encoding.utf8bom"),
-        i18n::tr(language, "encoding.utf16le"),
-        i18n::tr(language, "encoding.utf16be"),
-    ];
+    let encodings: Vec<String> = TextEncoding::ALL
+        .iter()
+        .map(|encoding| i18n::tr(language, encoding.label_key()))
+        .collect();
 
     // Use ComboBox with "Codifica: " prefix in each item for NVDA
     pfdc.AddComboBox(101).ok()?;
@@ -7102,6 +7888,9 @@ pub(crate) unsafe fn save_file_dialog_with_encoding(
                 7 => {
                     path.set_extension("html");
                 }
+                9 => {
+                    path.set_extension("srt");
+                }
                 _ => {}
             }
         }
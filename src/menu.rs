@@ -1,6 +1,7 @@
 use crate::accessibility::to_wide;
 use crate::i18n;
-use crate::settings::Language;
+use crate::settings::{Language, TextEncoding};
+use crate::text_codecs::TextCodec;
 use crate::with_state;
 use std::path::Path;
 use windows::Win32::Foundation::HWND;
@@ -25,6 +26,10 @@ pub const IDM_FILE_AUDIOBOOK: usize = 1011;
 pub const IDM_FILE_PODCAST: usize = 1012;
 pub const IDM_FILE_BATCH_AUDIOBOOK: usize = 1013;
 pub const IDM_FILE_CLOSE_OTHERS: usize = 1014;
+pub const IDM_FILE_TALKING_BOOK: usize = 1015;
+pub const IDM_FILE_BRAILLE_EXPORT: usize = 1016;
+pub const IDM_FILE_NEW_FROM_TEMPLATE: usize = 1017;
+pub const IDM_FILE_SAVE_ENCRYPTED: usize = 1018;
 pub const IDM_EDIT_UNDO: usize = 2001;
 pub const IDM_EDIT_CUT: usize = 2002;
 pub const IDM_EDIT_COPY: usize = 2003;
@@ -49,6 +54,42 @@ pub const IDM_EDIT_REMOVE_DUPLICATE_LINES: usize = 2021;
 pub const IDM_EDIT_REMOVE_DUPLICATE_CONSECUTIVE_LINES: usize = 2022;
 pub const IDM_EDIT_PREV_SPELLING_ERROR: usize = 2023;
 pub const IDM_EDIT_NEXT_SPELLING_ERROR: usize = 2024;
+pub const IDM_EDIT_EOL_LF: usize = 2025;
+pub const IDM_EDIT_EOL_CRLF: usize = 2026;
+pub const IDM_EDIT_EOL_CR: usize = 2027;
+pub const IDM_EDIT_FIX_MOJIBAKE: usize = 2028;
+pub const IDM_EDIT_GO_TO_LINE: usize = 2029;
+pub const IDM_EDIT_LARGE_FILE_NEXT: usize = 2030;
+pub const IDM_EDIT_LARGE_FILE_PREV: usize = 2031;
+pub const IDM_EDIT_FOLLOW_LINK: usize = 2032;
+pub const IDM_EDIT_SUBTITLES_GO_TO_TIME: usize = 2033;
+pub const IDM_EDIT_SUBTITLES_TIMESTAMPS: usize = 2034;
+pub const IDM_EDIT_SUBTITLES_TIMING: usize = 2035;
+pub const IDM_EDIT_SORT_LINES: usize = 2036;
+pub const IDM_EDIT_FILTER_LINES: usize = 2037;
+pub const IDM_EDIT_CASE_UPPER: usize = 2038;
+pub const IDM_EDIT_CASE_LOWER: usize = 2039;
+pub const IDM_EDIT_CASE_SENTENCE: usize = 2040;
+pub const IDM_EDIT_CASE_TITLE: usize = 2041;
+pub const IDM_EDIT_CASE_TOGGLE: usize = 2042;
+pub const IDM_EDIT_CASE_CAPITALIZE_SENTENCES: usize = 2043;
+pub const IDM_EDIT_MACRO_RECORD: usize = 2044;
+pub const IDM_EDIT_MACRO_STOP: usize = 2045;
+pub const IDM_EDIT_MACRO_MANAGE: usize = 2046;
+pub const IDM_EDIT_OUTLINE: usize = 2047;
+pub const IDM_EDIT_NEXT_HEADING: usize = 2048;
+pub const IDM_EDIT_PREV_HEADING: usize = 2049;
+pub const IDM_EDIT_NORMALIZE_NFC: usize = 2050;
+pub const IDM_EDIT_NORMALIZE_NFD: usize = 2051;
+pub const IDM_EDIT_NORMALIZE_NFKC: usize = 2052;
+pub const IDM_EDIT_REVEAL_INVISIBLE: usize = 2053;
+pub const IDM_EDIT_CLEAN_INVISIBLE: usize = 2054;
+pub const IDM_EDIT_TRANSLITERATE: usize = 2055;
+pub const IDM_EDIT_TO_ASCII: usize = 2056;
+pub const IDM_EDIT_NUMBER_LINES: usize = 2057;
+pub const IDM_EDIT_REMOVE_LINE_NUMBERS: usize = 2058;
+pub const IDM_EDIT_COLUMNS: usize = 2059;
+pub const IDM_EDIT_FIND_ALL: usize = 2060;
 pub const IDM_SPELLCHECK_SUGGESTION_BASE: usize = 12000;
 pub const IDM_SPELLCHECK_SUGGESTION_MAX: usize = 10;
 pub const IDM_SPELLCHECK_ADD_TO_DICTIONARY: usize = 12100;
@@ -71,9 +112,12 @@ pub const IDM_PLAYBACK_DOWNLOAD_EPISODE: usize = 8015;
 pub const IDM_INSERT_BOOKMARK: usize = 2101;
 pub const IDM_MANAGE_BOOKMARKS: usize = 2102;
 pub const IDM_INSERT_CLEAR_BOOKMARKS: usize = 2103;
+pub const IDM_MANAGE_SNIPPETS: usize = 2104;
+pub const IDM_MANAGE_AUTOCORRECT: usize = 2105;
 pub const IDM_NEXT_TAB: usize = 3001;
 pub const IDM_VIEW_SHOW_VOICES: usize = 6101;
 pub const IDM_VIEW_SHOW_FAVORITES: usize = 6102;
+pub const IDM_VIEW_HTML_READER_MODE: usize = 6103;
 pub const IDM_VIEW_TEXT_COLOR_BLACK: usize = 6201;
 pub const IDM_VIEW_TEXT_COLOR_DARK_BLUE: usize = 6202;
 pub const IDM_VIEW_TEXT_COLOR_DARK_GREEN: usize = 6203;
@@ -89,6 +133,12 @@ pub const IDM_VIEW_TEXT_SIZE_LARGE: usize = 6303;
 pub const IDM_VIEW_TEXT_SIZE_XLARGE: usize = 6304;
 pub const IDM_VIEW_TEXT_SIZE_XXLARGE: usize = 6305;
 pub const IDM_FILE_RECENT_BASE: usize = 4000;
+pub const IDM_FILE_REOPEN_ENCODING_BASE: usize = 4100;
+/// Ctrl+Shift+1 to Ctrl+Shift+9 send this base plus the digit to run a macro.
+pub const IDM_MACRO_SHORTCUT_BASE: usize = 4200;
+/// Edit > Text > Encode/Decode: these bases plus the index in `TextCodec::ALL`.
+pub const IDM_EDIT_ENCODE_BASE: usize = 4300;
+pub const IDM_EDIT_DECODE_BASE: usize = 4310;
 pub const IDM_TOOLS_OPTIONS: usize = 5001;
 pub const IDM_TOOLS_DICTIONARY: usize = 5002;
 pub const IDM_TOOLS_IMPORT_YOUTUBE: usize = 5003;
@@ -97,6 +147,7 @@ pub const IDM_TOOLS_RSS: usize = 5005;
 pub const IDM_TOOLS_PODCASTS: usize = 5006;
 pub const IDM_TOOLS_DICTIONARY_LOOKUP: usize = 5007;
 pub const IDM_TOOLS_WIKIPEDIA_IMPORT: usize = 5008;
+pub const IDM_TOOLS_COMPARE: usize = 5009;
 pub const IDM_HELP_GUIDE: usize = 7001;
 pub const IDM_HELP_ABOUT: usize = 7002;
 pub const IDM_HELP_CHECK_UPDATES: usize = 7003;
@@ -116,6 +167,7 @@ pub struct MenuLabels {
     pub menu_dictionary_lookup: String,
     pub menu_wikipedia_import: String,
     pub menu_import_youtube: String,
+    pub menu_compare: String,
     pub menu_prompt: String,
     pub menu_rss: String,
     pub menu_podcasts: String,
@@ -137,18 +189,24 @@ pub struct MenuLabels {
     pub view_text_size_xxlarge: String,
     pub view_show_voices: String,
     pub view_show_favorites: String,
+    pub view_html_reader_mode: String,
     pub file_new: String,
+    pub file_new_from_template: String,
     pub file_open: String,
     pub file_save: String,
     pub file_save_as: String,
+    pub file_save_encrypted: String,
     pub file_save_all: String,
     pub file_close: String,
     pub file_close_others: String,
     pub file_recent: String,
+    pub file_reopen_encoding: String,
     pub file_read_start: String,
     pub file_read_pause: String,
     pub file_read_stop: String,
     pub file_audiobook: String,
+    pub file_talking_book: String,
+    pub file_braille_export: String,
     pub file_podcast: String,
     pub file_batch_audiobooks: String,
     pub file_exit: String,
@@ -161,6 +219,7 @@ pub struct MenuLabels {
     pub edit_find_next: String,
     pub edit_replace: String,
     pub edit_find_in_files: String,
+    pub edit_find_all: String,
     pub edit_prev_spelling_error: String,
     pub edit_next_spelling_error: String,
     pub edit_text_menu: String,
@@ -168,18 +227,61 @@ pub struct MenuLabels {
     pub edit_normalize_whitespace: String,
     pub edit_hard_line_break: String,
     pub edit_order_items: String,
+    pub edit_sort_lines: String,
+    pub edit_filter_lines: String,
+    pub edit_case_menu: String,
+    pub edit_case_upper: String,
+    pub edit_case_lower: String,
+    pub edit_case_sentence: String,
+    pub edit_case_title: String,
+    pub edit_case_toggle: String,
+    pub edit_case_capitalize_sentences: String,
+    pub edit_unicode_menu: String,
+    pub edit_normalize_nfc: String,
+    pub edit_normalize_nfd: String,
+    pub edit_normalize_nfkc: String,
+    pub edit_reveal_invisible: String,
+    pub edit_clean_invisible: String,
+    pub edit_macros_menu: String,
+    pub edit_macro_record: String,
+    pub edit_macro_stop: String,
+    pub edit_macro_manage: String,
     pub edit_keep_unique_items: String,
     pub edit_reverse_items: String,
     pub edit_quote_lines: String,
     pub edit_unquote_lines: String,
+    pub edit_number_lines: String,
+    pub edit_remove_line_numbers: String,
+    pub edit_columns: String,
     pub edit_text_stats: String,
     pub edit_join_lines: String,
     pub edit_clean_eol_hyphens: String,
     pub edit_remove_duplicate_lines: String,
     pub edit_remove_duplicate_consecutive_lines: String,
+    pub edit_fix_mojibake: String,
+    pub edit_transliterate: String,
+    pub edit_to_ascii: String,
+    pub edit_codec_menu: String,
+    pub edit_go_to_line: String,
+    pub edit_outline: String,
+    pub edit_next_heading: String,
+    pub edit_prev_heading: String,
+    pub edit_large_file_next: String,
+    pub edit_large_file_prev: String,
+    pub edit_follow_link: String,
+    pub edit_line_endings_menu: String,
+    pub edit_eol_lf: String,
+    pub edit_eol_crlf: String,
+    pub edit_eol_cr: String,
+    pub edit_subtitles_menu: String,
+    pub edit_subtitles_go_to_time: String,
+    pub edit_subtitles_timestamps: String,
+    pub edit_subtitles_timing: String,
     pub insert_bookmark: String,
     pub insert_clear_bookmarks: String,
     pub manage_bookmarks: String,
+    pub manage_snippets: String,
+    pub manage_autocorrect: String,
     pub help_guide: String,
     pub help_changelog: String,
     pub help_donations: String,
@@ -201,6 +303,7 @@ pub fn menu_labels(language: Language) -> MenuLabels {
         menu_dictionary_lookup: i18n::tr(language, "menu.dictionary_lookup"),
         menu_wikipedia_import: i18n::tr(language, "menu.wikipedia_import"),
         menu_import_youtube: i18n::tr(language, "menu.import_youtube"),
+        menu_compare: i18n::tr(language, "menu.compare"),
         menu_prompt: i18n::tr(language, "menu.prompt"),
         menu_rss: i18n::tr(language, "menu.rss"),
         menu_podcasts: i18n::tr(language, "menu.podcasts"),
@@ -222,18 +325,24 @@ pub fn menu_labels(language: Language) -> MenuLabels {
         view_text_size_xxlarge: i18n::tr(language, "view.text_size.xxlarge"),
         view_show_voices: i18n::tr(language, "view.show_voices"),
         view_show_favorites: i18n::tr(language, "view.show_favorites"),
+        view_html_reader_mode: i18n::tr(language, "view.html_reader_mode"),
         file_new: i18n::tr(language, "file.new"),
+        file_new_from_template: i18n::tr(language, "file.new_from_template"),
         file_open: i18n::tr(language, "file.open"),
         file_save: i18n::tr(language, "file.save"),
         file_save_as: i18n::tr(language, "file.save_as"),
+        file_save_encrypted: i18n::tr(language, "file.save_encrypted"),
         file_save_all: i18n::tr(language, "file.save_all"),
         file_close: i18n::tr(language, "file.close"),
         file_close_others: i18n::tr(language, "file.close_others"),
         file_recent: i18n::tr(language, "file.recent"),
+        file_reopen_encoding: i18n::tr(language, "file.reopen_encoding"),
         file_read_start: i18n::tr(language, "file.read_start"),
         file_read_pause: i18n::tr(language, "file.read_pause"),
         file_read_stop: i18n::tr(language, "file.read_stop"),
         file_audiobook: i18n::tr(language, "file.audiobook"),
+        file_talking_book: i18n::tr(language, "file.talking_book"),
+        file_braille_export: i18n::tr(language, "file.braille_export"),
         file_podcast: i18n::tr(language, "file.podcast"),
         file_batch_audiobooks: i18n::tr(language, "file.batch_audiobooks"),
         file_exit: i18n::tr(language, "file.exit"),
@@ -246,6 +355,7 @@ pub fn menu_labels(language: Language) -> MenuLabels {
         edit_find_next: i18n::tr(language, "edit.find_next"),
         edit_replace: i18n::tr(language, "edit.replace"),
         edit_find_in_files: i18n::tr(language, "edit.find_in_files"),
+        edit_find_all: i18n::tr(language, "edit.find_all"),
         edit_prev_spelling_error: i18n::tr(language, "edit.prev_spelling_error"),
         edit_next_spelling_error: i18n::tr(language, "edit.next_spelling_error"),
         edit_text_menu: i18n::tr(language, "edit.text_menu"),
@@ -253,10 +363,32 @@ pub fn menu_labels(language: Language) -> MenuLabels {
         edit_normalize_whitespace: i18n::tr(language, "edit.normalize_whitespace"),
         edit_hard_line_break: i18n::tr(language, "edit.hard_line_break"),
         edit_order_items: i18n::tr(language, "edit.order_items"),
+        edit_sort_lines: i18n::tr(language, "edit.sort_lines"),
+        edit_filter_lines: i18n::tr(language, "edit.filter_lines"),
+        edit_case_menu: i18n::tr(language, "edit.case_menu"),
+        edit_case_upper: i18n::tr(language, "edit.case_upper"),
+        edit_case_lower: i18n::tr(language, "edit.case_lower"),
+        edit_case_sentence: i18n::tr(language, "edit.case_sentence"),
+        edit_case_title: i18n::tr(language, "edit.case_title"),
+        edit_case_toggle: i18n::tr(language, "edit.case_toggle"),
+        edit_case_capitalize_sentences: i18n::tr(language, "edit.case_capitalize_sentences"),
+        edit_unicode_menu: i18n::tr(language, "edit.unicode_menu"),
+        edit_normalize_nfc: i18n::tr(language, "edit.normalize_nfc"),
+        edit_normalize_nfd: i18n::tr(language, "edit.normalize_nfd"),
+        edit_normalize_nfkc: i18n::tr(language, "edit.normalize_nfkc"),
+        edit_reveal_invisible: i18n::tr(language, "edit.reveal_invisible"),
+        edit_clean_invisible: i18n::tr(language, "edit.clean_invisible"),
+        edit_macros_menu: i18n::tr(language, "edit.macros_menu"),
+        edit_macro_record: i18n::tr(language, "edit.macro_record"),
+        edit_macro_stop: i18n::tr(language, "edit.macro_stop"),
+        edit_macro_manage: i18n::tr(language, "edit.macro_manage"),
         edit_keep_unique_items: i18n::tr(language, "edit.keep_unique_items"),
         edit_reverse_items: i18n::tr(language, "edit.reverse_items"),
         edit_quote_lines: i18n::tr(language, "edit.quote_lines"),
         edit_unquote_lines: i18n::tr(language, "edit.unquote_lines"),
+        edit_number_lines: i18n::tr(language, "edit.number_lines"),
+        edit_remove_line_numbers: i18n::tr(language, "edit.remove_line_numbers"),
+        edit_columns: i18n::tr(language, "edit.columns"),
         edit_text_stats: i18n::tr(language, "edit.text_stats"),
         edit_join_lines: i18n::tr(language, "edit.join_lines"),
         edit_clean_eol_hyphens: i18n::tr(language, "edit.clean_eol_hyphens"),
@@ -265,9 +397,30 @@ pub fn menu_labels(language: Language) -> MenuLabels {
             language,
             "edit.remove_duplicate_consecutive_lines",
         ),
+        edit_fix_mojibake: i18n::tr(language, "edit.fix_mojibake"),
+        edit_transliterate: i18n::tr(language, "edit.transliterate"),
+        edit_to_ascii: i18n::tr(language, "edit.to_ascii"),
+        edit_codec_menu: i18n::tr(language, "edit.codec_menu"),
+        edit_go_to_line: i18n::tr(language, "edit.go_to_line"),
+        edit_outline: i18n::tr(language, "edit.outline"),
+        edit_next_heading: i18n::tr(language, "edit.next_heading"),
+        edit_prev_heading: i18n::tr(language, "edit.prev_heading"),
+        edit_large_file_next: i18n::tr(language, "edit.large_file_next"),
+        edit_large_file_prev: i18n::tr(language, "edit.large_file_prev"),
+        edit_follow_link: i18n::tr(language, "edit.follow_link"),
+        edit_line_endings_menu: i18n::tr(language, "edit.line_endings_menu"),
+        edit_eol_lf: i18n::tr(language, "edit.eol_lf"),
+        edit_eol_crlf: i18n::tr(language, "edit.eol_crlf"),
+        edit_eol_cr: i18n::tr(language, "edit.eol_cr"),
+        edit_subtitles_menu: i18n::tr(language, "edit.subtitles_menu"),
+        edit_subtitles_go_to_time: i18n::tr(language, "edit.subtitles_go_to_time"),
+        edit_subtitles_timestamps: i18n::tr(language, "edit.subtitles_timestamps"),
+        edit_subtitles_timing: i18n::tr(language, "edit.subtitles_timing"),
         insert_bookmark: i18n::tr(language, "insert.bookmark"),
         insert_clear_bookmarks: i18n::tr(language, "insert.clear_bookmarks"),
         manage_bookmarks: i18n::tr(language, "insert.manage_bookmarks"),
+        manage_snippets: i18n::tr(language, "insert.manage_snippets"),
+        manage_autocorrect: i18n::tr(language, "insert.manage_autocorrect"),
         help_guide: i18n::tr(language, "help.guide"),
         help_changelog: i18n::tr(language, "help.changelog"),
         help_donations: i18n::tr(language, "help.donations"),
@@ -422,6 +575,7 @@ pub unsafe fn create_menus(hwnd: HWND, language: Language) -> (HMENU, HMENU) {
     let hmenu = CreateMenu().unwrap_or(HMENU(0));
     let file_menu = CreateMenu().unwrap_or(HMENU(0));
     let recent_menu = CreateMenu().unwrap_or(HMENU(0));
+    let reopen_encoding_menu = CreateMenu().unwrap_or(HMENU(0));
     let edit_menu = CreateMenu().unwrap_or(HMENU(0));
     let view_menu = CreateMenu().unwrap_or(HMENU(0));
     let view_color_menu = CreateMenu().unwrap_or(HMENU(0));
@@ -433,9 +587,21 @@ pub unsafe fn create_menus(hwnd: HWND, language: Language) -> (HMENU, HMENU) {
     let labels = menu_labels(language);
 
     append_menu_string(file_menu, MF_STRING, IDM_FILE_NEW, &labels.file_new);
+    append_menu_string(
+        file_menu,
+        MF_STRING,
+        IDM_FILE_NEW_FROM_TEMPLATE,
+        &labels.file_new_from_template,
+    );
     append_menu_string(file_menu, MF_STRING, IDM_FILE_OPEN, &labels.file_open);
     append_menu_string(file_menu, MF_STRING, IDM_FILE_SAVE, &labels.file_save);
     append_menu_string(file_menu, MF_STRING, IDM_FILE_SAVE_AS, &labels.file_save_as);
+    append_menu_string(
+        file_menu,
+        MF_STRING,
+        IDM_FILE_SAVE_ENCRYPTED,
+        &labels.file_save_encrypted,
+    );
     append_menu_string(
         file_menu,
         MF_STRING,
@@ -449,6 +615,20 @@ pub unsafe fn create_menus(hwnd: HWND, language: Language) -> (HMENU, HMENU) {
         IDM_FILE_CLOSE_OTHERS,
         &labels.file_close_others,
     );
+    for (i, encoding) in TextEncoding::ALL.iter().enumerate() {
+        append_menu_string(
+            reopen_encoding_menu,
+            MF_STRING,
+            IDM_FILE_REOPEN_ENCODING_BASE + i,
+            &i18n::tr(language, encoding.label_key()),
+        );
+    }
+    append_menu_string(
+        file_menu,
+        MF_POPUP,
+        reopen_encoding_menu.0 as usize,
+        &labels.file_reopen_encoding,
+    );
     crate::log_if_err!(AppendMenuW(file_menu, MF_SEPARATOR, 0, PCWSTR::null()));
     append_menu_string(
         file_menu,
@@ -482,6 +662,18 @@ pub unsafe fn create_menus(hwnd: HWND, language: Language) -> (HMENU, HMENU) {
         IDM_FILE_AUDIOBOOK,
         &labels.file_audiobook,
     );
+    append_menu_string(
+        file_menu,
+        MF_STRING,
+        IDM_FILE_TALKING_BOOK,
+        &labels.file_talking_book,
+    );
+    append_menu_string(
+        file_menu,
+        MF_STRING,
+        IDM_FILE_BRAILLE_EXPORT,
+        &labels.file_braille_export,
+    );
     append_menu_string(
         file_menu,
         MF_STRING,
@@ -518,7 +710,50 @@ pub unsafe fn create_menus(hwnd: HWND, language: Language) -> (HMENU, HMENU) {
         IDM_EDIT_FIND_NEXT,
         &labels.edit_find_next,
     );
+    append_menu_string(
+        edit_menu,
+        MF_STRING,
+        IDM_EDIT_FIND_ALL,
+        &labels.edit_find_all,
+    );
     append_menu_string(edit_menu, MF_STRING, IDM_EDIT_REPLACE, &labels.edit_replace);
+    append_menu_string(
+        edit_menu,
+        MF_STRING,
+        IDM_EDIT_GO_TO_LINE,
+        &labels.edit_go_to_line,
+    );
+    append_menu_string(edit_menu, MF_STRING, IDM_EDIT_OUTLINE, &labels.edit_outline);
+    append_menu_string(
+        edit_menu,
+        MF_STRING,
+        IDM_EDIT_NEXT_HEADING,
+        &labels.edit_next_heading,
+    );
+    append_menu_string(
+        edit_menu,
+        MF_STRING,
+        IDM_EDIT_PREV_HEADING,
+        &labels.edit_prev_heading,
+    );
+    append_menu_string(
+        edit_menu,
+        MF_STRING,
+        IDM_EDIT_LARGE_FILE_NEXT,
+        &labels.edit_large_file_next,
+    );
+    append_menu_string(
+        edit_menu,
+        MF_STRING,
+        IDM_EDIT_LARGE_FILE_PREV,
+        &labels.edit_large_file_prev,
+    );
+    append_menu_string(
+        edit_menu,
+        MF_STRING,
+        IDM_EDIT_FOLLOW_LINK,
+        &labels.edit_follow_link,
+    );
     crate::log_if_err!(AppendMenuW(edit_menu, MF_SEPARATOR, 0, PCWSTR::null()));
     append_menu_string(
         edit_menu,
@@ -570,6 +805,18 @@ pub unsafe fn create_menus(hwnd: HWND, language: Language) -> (HMENU, HMENU) {
         IDM_EDIT_ORDER_ITEMS,
         &labels.edit_order_items,
     );
+    append_menu_string(
+        text_menu,
+        MF_STRING,
+        IDM_EDIT_SORT_LINES,
+        &labels.edit_sort_lines,
+    );
+    append_menu_string(
+        text_menu,
+        MF_STRING,
+        IDM_EDIT_FILTER_LINES,
+        &labels.edit_filter_lines,
+    );
     append_menu_string(
         text_menu,
         MF_STRING,
@@ -594,6 +841,19 @@ pub unsafe fn create_menus(hwnd: HWND, language: Language) -> (HMENU, HMENU) {
         IDM_EDIT_UNQUOTE_LINES,
         &labels.edit_unquote_lines,
     );
+    append_menu_string(
+        text_menu,
+        MF_STRING,
+        IDM_EDIT_NUMBER_LINES,
+        &labels.edit_number_lines,
+    );
+    append_menu_string(
+        text_menu,
+        MF_STRING,
+        IDM_EDIT_REMOVE_LINE_NUMBERS,
+        &labels.edit_remove_line_numbers,
+    );
+    append_menu_string(text_menu, MF_STRING, IDM_EDIT_COLUMNS, &labels.edit_columns);
     append_menu_string(
         text_menu,
         MF_STRING,
@@ -606,12 +866,210 @@ pub unsafe fn create_menus(hwnd: HWND, language: Language) -> (HMENU, HMENU) {
         IDM_EDIT_REMOVE_DUPLICATE_CONSECUTIVE_LINES,
         &labels.edit_remove_duplicate_consecutive_lines,
     );
+    append_menu_string(
+        text_menu,
+        MF_STRING,
+        IDM_EDIT_FIX_MOJIBAKE,
+        &labels.edit_fix_mojibake,
+    );
+    append_menu_string(
+        text_menu,
+        MF_STRING,
+        IDM_EDIT_TRANSLITERATE,
+        &labels.edit_transliterate,
+    );
+    append_menu_string(
+        text_menu,
+        MF_STRING,
+        IDM_EDIT_TO_ASCII,
+        &labels.edit_to_ascii,
+    );
+    let codec_menu = CreateMenu().unwrap_or(HMENU(0));
+    for (i, codec) in TextCodec::ALL.iter().enumerate() {
+        append_menu_string(
+            codec_menu,
+            MF_STRING,
+            IDM_EDIT_ENCODE_BASE + i,
+            &i18n::tr(language, codec.encode_label_key()),
+        );
+    }
+    crate::log_if_err!(AppendMenuW(codec_menu, MF_SEPARATOR, 0, PCWSTR::null()));
+    for (i, codec) in TextCodec::ALL.iter().enumerate() {
+        append_menu_string(
+            codec_menu,
+            MF_STRING,
+            IDM_EDIT_DECODE_BASE + i,
+            &i18n::tr(language, codec.decode_label_key()),
+        );
+    }
+    append_menu_string(
+        text_menu,
+        MF_POPUP,
+        codec_menu.0 as usize,
+        &labels.edit_codec_menu,
+    );
     append_menu_string(
         edit_menu,
         MF_POPUP,
         text_menu.0 as usize,
         &labels.edit_text_menu,
     );
+    let line_endings_menu = CreateMenu().unwrap_or(HMENU(0));
+    append_menu_string(
+        line_endings_menu,
+        MF_STRING,
+        IDM_EDIT_EOL_LF,
+        &labels.edit_eol_lf,
+    );
+    append_menu_string(
+        line_endings_menu,
+        MF_STRING,
+        IDM_EDIT_EOL_CRLF,
+        &labels.edit_eol_crlf,
+    );
+    append_menu_string(
+        line_endings_menu,
+        MF_STRING,
+        IDM_EDIT_EOL_CR,
+        &labels.edit_eol_cr,
+    );
+    append_menu_string(
+        edit_menu,
+        MF_POPUP,
+        line_endings_menu.0 as usize,
+        &labels.edit_line_endings_menu,
+    );
+    let case_menu = CreateMenu().unwrap_or(HMENU(0));
+    append_menu_string(
+        case_menu,
+        MF_STRING,
+        IDM_EDIT_CASE_UPPER,
+        &labels.edit_case_upper,
+    );
+    append_menu_string(
+        case_menu,
+        MF_STRING,
+        IDM_EDIT_CASE_LOWER,
+        &labels.edit_case_lower,
+    );
+    append_menu_string(
+        case_menu,
+        MF_STRING,
+        IDM_EDIT_CASE_SENTENCE,
+        &labels.edit_case_sentence,
+    );
+    append_menu_string(
+        case_menu,
+        MF_STRING,
+        IDM_EDIT_CASE_TITLE,
+        &labels.edit_case_title,
+    );
+    append_menu_string(
+        case_menu,
+        MF_STRING,
+        IDM_EDIT_CASE_TOGGLE,
+        &labels.edit_case_toggle,
+    );
+    append_menu_string(
+        case_menu,
+        MF_STRING,
+        IDM_EDIT_CASE_CAPITALIZE_SENTENCES,
+        &labels.edit_case_capitalize_sentences,
+    );
+    append_menu_string(
+        edit_menu,
+        MF_POPUP,
+        case_menu.0 as usize,
+        &labels.edit_case_menu,
+    );
+    let unicode_menu = CreateMenu().unwrap_or(HMENU(0));
+    append_menu_string(
+        unicode_menu,
+        MF_STRING,
+        IDM_EDIT_NORMALIZE_NFC,
+        &labels.edit_normalize_nfc,
+    );
+    append_menu_string(
+        unicode_menu,
+        MF_STRING,
+        IDM_EDIT_NORMALIZE_NFD,
+        &labels.edit_normalize_nfd,
+    );
+    append_menu_string(
+        unicode_menu,
+        MF_STRING,
+        IDM_EDIT_NORMALIZE_NFKC,
+        &labels.edit_normalize_nfkc,
+    );
+    crate::log_if_err!(AppendMenuW(unicode_menu, MF_SEPARATOR, 0, PCWSTR::null()));
+    append_menu_string(
+        unicode_menu,
+        MF_STRING,
+        IDM_EDIT_REVEAL_INVISIBLE,
+        &labels.edit_reveal_invisible,
+    );
+    append_menu_string(
+        unicode_menu,
+        MF_STRING,
+        IDM_EDIT_CLEAN_INVISIBLE,
+        &labels.edit_clean_invisible,
+    );
+    append_menu_string(
+        edit_menu,
+        MF_POPUP,
+        unicode_menu.0 as usize,
+        &labels.edit_unicode_menu,
+    );
+    let macros_menu = CreateMenu().unwrap_or(HMENU(0));
+    append_menu_string(
+        macros_menu,
+        MF_STRING,
+        IDM_EDIT_MACRO_RECORD,
+        &labels.edit_macro_record,
+    );
+    append_menu_string(
+        macros_menu,
+        MF_STRING,
+        IDM_EDIT_MACRO_STOP,
+        &labels.edit_macro_stop,
+    );
+    append_menu_string(
+        macros_menu,
+        MF_STRING,
+        IDM_EDIT_MACRO_MANAGE,
+        &labels.edit_macro_manage,
+    );
+    append_menu_string(
+        edit_menu,
+        MF_POPUP,
+        macros_menu.0 as usize,
+        &labels.edit_macros_menu,
+    );
+    let subtitles_menu = CreateMenu().unwrap_or(HMENU(0));
+    append_menu_string(
+        subtitles_menu,
+        MF_STRING,
+        IDM_EDIT_SUBTITLES_GO_TO_TIME,
+        &labels.edit_subtitles_go_to_time,
+    );
+    append_menu_string(
+        subtitles_menu,
+        MF_STRING,
+        IDM_EDIT_SUBTITLES_TIMESTAMPS,
+        &labels.edit_subtitles_timestamps,
+    );
+    append_menu_string(
+        subtitles_menu,
+        MF_STRING,
+        IDM_EDIT_SUBTITLES_TIMING,
+        &labels.edit_subtitles_timing,
+    );
+    append_menu_string(
+        edit_menu,
+        MF_POPUP,
+        subtitles_menu.0 as usize,
+        &labels.edit_subtitles_menu,
+    );
     crate::log_if_err!(AppendMenuW(edit_menu, MF_SEPARATOR, 0, PCWSTR::null()));
     append_menu_string(
         edit_menu,
@@ -633,6 +1091,12 @@ pub unsafe fn create_menus(hwnd: HWND, language: Language) -> (HMENU, HMENU) {
         IDM_VIEW_SHOW_FAVORITES,
         &labels.view_show_favorites,
     );
+    append_menu_string(
+        view_menu,
+        MF_STRING,
+        IDM_VIEW_HTML_READER_MODE,
+        &labels.view_html_reader_mode,
+    );
     crate::log_if_err!(AppendMenuW(view_menu, MF_SEPARATOR, 0, PCWSTR::null()));
     append_menu_string(
         view_color_menu,
@@ -750,6 +1214,18 @@ pub unsafe fn create_menus(hwnd: HWND, language: Language) -> (HMENU, HMENU) {
         IDM_MANAGE_BOOKMARKS,
         &labels.manage_bookmarks,
     );
+    append_menu_string(
+        insert_menu,
+        MF_STRING,
+        IDM_MANAGE_SNIPPETS,
+        &labels.manage_snippets,
+    );
+    append_menu_string(
+        insert_menu,
+        MF_STRING,
+        IDM_MANAGE_AUTOCORRECT,
+        &labels.manage_autocorrect,
+    );
     append_menu_string(hmenu, MF_POPUP, insert_menu.0 as usize, &labels.menu_insert);
 
     append_menu_string(tools_menu, MF_STRING, IDM_TOOLS_PROMPT, &labels.menu_prompt);
@@ -790,6 +1266,12 @@ pub unsafe fn create_menus(hwnd: HWND, language: Language) -> (HMENU, HMENU) {
         IDM_TOOLS_IMPORT_YOUTUBE,
         &labels.menu_import_youtube,
     );
+    append_menu_string(
+        tools_menu,
+        MF_STRING,
+        IDM_TOOLS_COMPARE,
+        &labels.menu_compare,
+    );
     append_menu_string(hmenu, MF_POPUP, tools_menu.0 as usize, &labels.menu_tools);
 
     append_menu_string(help_menu, MF_STRING, IDM_HELP_GUIDE, &labels.help_guide);
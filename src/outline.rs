@@ -0,0 +1,208 @@
+/// Longest line still taken for a numbered or all-caps heading.
+const MAX_HEADING_CHARS: usize = 80;
+/// Shortest `=` or `-` underline accepted as a setext heading.
+const MIN_SETEXT_UNDERLINE: usize = 3;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Heading {
+    /// 1 for the top level.
+    pub level: usize,
+    pub title: String,
+    /// Line of the heading, 0-based in the text it was found in.
+    pub line: usize,
+}
+
+/// Finds markdown `#` headings, setext headings underlined with `===` or `---`,
+/// numbered section headings ("1.2.3 Scope") and all-caps chapter lines. Fenced
+/// code blocks are skipped. CRLF, LF and CR all end a line.
+pub fn detect_headings(text: &str) -> Vec<Heading> {
+    let normalized = text.replace("\r\n", "\n").replace('\r', "\n");
+    let lines: Vec<&str> = normalized.split('\n').collect();
+    let mut headings = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let trimmed = lines[i].trim();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            i += 1;
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(&trimmed[..3]);
+            i += 1;
+            continue;
+        }
+        let previous_blank = i == 0 || lines[i - 1].trim().is_empty();
+        let underline = lines
+            .get(i + 1)
+            .and_then(|next| setext_level(next))
+            .filter(|_| !trimmed.is_empty() && !trimmed.starts_with(['-', '*', '>']));
+        if let Some((level, title)) = atx_heading(trimmed) {
+            headings.push(Heading {
+                level,
+                title,
+                line: i,
+            });
+        } else if let Some(level) = underline {
+            let title = trimmed.to_string();
+            headings.push(Heading {
+                level,
+                title,
+                line: i,
+            });
+            // The underline belongs to the heading.
+            i += 1;
+        } else if let Some((level, title)) = numbered_heading(trimmed, previous_blank)
+            .or_else(|| caps_heading(trimmed, previous_blank))
+        {
+            headings.push(Heading {
+                level,
+                title,
+                line: i,
+            });
+        }
+        i += 1;
+    }
+    headings
+}
+
+fn atx_heading(line: &str) -> Option<(usize, String)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let title = rest.trim();
+    // A closing run of `#` is decoration, unless it is glued to the title.
+    let without_closing = title.trim_end_matches('#');
+    let title = if without_closing.is_empty() || without_closing.ends_with([' ', '\t']) {
+        without_closing.trim_end()
+    } else {
+        title
+    };
+    (!title.is_empty()).then(|| (level, title.to_string()))
+}
+
+fn setext_level(line: &str) -> Option<usize> {
+    let trimmed = line.trim();
+    let marker = trimmed.chars().next()?;
+    let level = match marker {
+        '=' => 1,
+        '-' => 2,
+        _ => return None,
+    };
+    (trimmed.len() >= MIN_SETEXT_UNDERLINE && trimmed.chars().all(|c| c == marker)).then_some(level)
+}
+
+/// "1.2.3 Title" is a level 3 heading. A single number ("4. Results") could just
+/// as well start a list item, so it needs a blank line before it and a capital
+/// letter after it.
+fn numbered_heading(line: &str, previous_blank: bool) -> Option<(usize, String)> {
+    let (number, rest) = line.split_once([' ', '\t'])?;
+    let number = number.strip_suffix('.').unwrap_or(number);
+    let parts: Vec<&str> = number.split('.').collect();
+    let numeric = parts
+        .iter()
+        .all(|part| (1..=3).contains(&part.len()) && part.chars().all(|c| c.is_ascii_digit()));
+    let rest = rest.trim();
+    let first = rest.chars().next()?;
+    if !numeric
+        || !first.is_alphabetic()
+        || line.chars().count() > MAX_HEADING_CHARS
+        || rest.ends_with(['.', ',', ';', ':'])
+    {
+        return None;
+    }
+    if parts.len() == 1 && !(previous_blank && first.is_uppercase()) {
+        return None;
+    }
+    Some((parts.len().min(6), line.to_string()))
+}
+
+/// A short line whose letters are all capitals, after a blank line: "CHAPTER ONE".
+fn caps_heading(line: &str, previous_blank: bool) -> Option<(usize, String)> {
+    if !previous_blank || line.chars().count() > MAX_HEADING_CHARS || line.ends_with([',', ';']) {
+        return None;
+    }
+    let mut letters = line.chars().filter(|c| c.is_alphabetic()).peekable();
+    letters.peek()?;
+    let mut count = 0;
+    for letter in letters {
+        if !letter.is_uppercase() {
+            return None;
+        }
+        count += 1;
+    }
+    (count >= 3).then(|| (1, line.to_string()))
+}
+
+/// The first heading after `line`, or the last one before it when going back.
+pub fn adjacent_heading(headings: &[Heading], line: usize, forward: bool) -> Option<&Heading> {
+    if forward {
+        headings.iter().find(|heading| heading.line > line)
+    } else {
+        headings.iter().rev().find(|heading| heading.line < line)
+    }
+}
+
+/// Index of the heading whose section contains `line`.
+pub fn heading_at(headings: &[Heading], line: usize) -> Option<usize> {
+    headings.iter().rposition(|heading| heading.line <= line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heading(level: usize, title: &str, line: usize) -> Heading {
+        Heading {
+            level,
+            title: title.to_string(),
+            line,
+        }
+    }
+
+    #[test]
+    fn test_markdown_headings() {
+        let text = "# Title #\r\n\r\nIntro\r\n\r\nSetext\r\n======\r\n```\r\n# not a heading\r\n```\r\n## Part ##\r\n#tag\r\nSub\r\n---";
+        assert_eq!(
+            detect_headings(text),
+            vec![
+                heading(1, "Title", 0),
+                heading(1, "Setext", 4),
+                heading(2, "Part", 9),
+                heading(2, "Sub", 11)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plain_text_headings() {
+        let text = "CHAPTER ONE\n\n1 Introduction\n\n1.2.3 Scope of work\nText follows here.\n2. buy milk\nNOT a HEADING\n\nTotal: 42.\n\n1.5 is a number.";
+        let headings = detect_headings(text);
+        assert_eq!(
+            headings,
+            vec![
+                heading(1, "CHAPTER ONE", 0),
+                heading(1, "1 Introduction", 2),
+                heading(3, "1.2.3 Scope of work", 4)
+            ]
+        );
+        assert_eq!(
+            adjacent_heading(&headings, 2, true).map(|h| h.line),
+            Some(4)
+        );
+        assert_eq!(
+            adjacent_heading(&headings, 2, false).map(|h| h.line),
+            Some(0)
+        );
+        assert_eq!(adjacent_heading(&headings, 4, true), None);
+        assert_eq!(heading_at(&headings, 3), Some(1));
+    }
+}
@@ -0,0 +1,277 @@
+use crate::settings::Language;
+use std::collections::HashMap;
+
+/// Silent reading speed assumed for the reading time, in words per minute.
+const READING_WORDS_PER_MINUTE: f64 = 230.0;
+/// Speaking speed of a voice at its default rate; `tts_rate` is a percentage on top.
+const SPEECH_WORDS_PER_MINUTE: f64 = 160.0;
+
+const VOWELS: &str = "aeiouyàáâãäåèéêëìíîïòóôõöùúûüýÿ";
+
+const STOP_WORDS_EN: &[&str] = &[
+    "a", "about", "after", "all", "also", "an", "and", "any", "are", "as", "at", "be", "been",
+    "but", "by", "can", "could", "did", "do", "does", "for", "from", "had", "has", "have", "he",
+    "her", "him", "his", "how", "i", "if", "in", "into", "is", "it", "its", "just", "me", "more",
+    "my", "no", "not", "of", "on", "one", "only", "or", "other", "our", "out", "over", "she", "so",
+    "some", "than", "that", "the", "their", "them", "then", "there", "these", "they", "this",
+    "those", "to", "up", "us", "very", "was", "we", "were", "what", "when", "which", "while",
+    "who", "will", "with", "would", "you", "your",
+];
+
+const STOP_WORDS_IT: &[&str] = &[
+    "a", "ad", "agli", "ai", "al", "alla", "alle", "allo", "anche", "che", "chi", "ci", "come",
+    "con", "da", "dagli", "dai", "dal", "dalla", "dalle", "dallo", "degli", "dei", "del", "della",
+    "delle", "dello", "di", "dove", "e", "ed", "era", "essere", "gli", "ha", "hanno", "ho", "i",
+    "il", "in", "io", "la", "le", "lei", "lo", "loro", "lui", "ma", "mi", "ne", "negli", "nei",
+    "nel", "nella", "nelle", "non", "o", "per", "più", "quando", "quella", "quello", "questa",
+    "questo", "se", "si", "sia", "sono", "su", "sua", "sue", "sugli", "sui", "sul", "sulla", "suo",
+    "suoi", "ti", "tra", "tu", "un", "una", "uno", "è",
+];
+
+const STOP_WORDS_ES: &[&str] = &[
+    "a", "al", "algo", "como", "con", "cuando", "de", "del", "donde", "e", "el", "ella", "ellos",
+    "en", "entre", "era", "es", "esa", "ese", "eso", "esta", "este", "esto", "fue", "ha", "han",
+    "hay", "la", "las", "le", "les", "lo", "los", "me", "mi", "muy", "más", "no", "nos", "o",
+    "para", "pero", "por", "que", "qué", "se", "ser", "si", "sin", "sobre", "son", "su", "sus",
+    "también", "te", "tu", "un", "una", "uno", "unos", "y", "ya", "yo", "él",
+];
+
+const STOP_WORDS_PT: &[&str] = &[
+    "a", "ao", "aos", "as", "com", "como", "da", "das", "de", "do", "dos", "e", "ela", "ele",
+    "eles", "em", "entre", "era", "essa", "esse", "esta", "este", "eu", "foi", "há", "isso",
+    "isto", "já", "lhe", "mais", "mas", "me", "muito", "na", "nas", "no", "nos", "não", "o", "os",
+    "ou", "para", "pela", "pelo", "por", "que", "se", "sem", "ser", "seu", "seus", "sua", "suas",
+    "são", "também", "te", "um", "uma", "umas", "uns", "à", "é",
+];
+
+const STOP_WORDS_VI: &[&str] = &[
+    "các", "cho", "chỉ", "có", "cũng", "của", "đã", "đang", "để", "đó", "được", "khi", "không",
+    "là", "lại", "mà", "một", "này", "nên", "những", "nhưng", "nó", "ra", "rằng", "rất", "sẽ",
+    "thì", "trong", "từ", "và", "vào", "về", "với",
+];
+
+/// Counts behind the readability indices.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct TextMetrics {
+    pub sentences: usize,
+    pub paragraphs: usize,
+    pub words: usize,
+    pub letters: usize,
+    pub syllables: usize,
+}
+
+/// The readability index used for a language, with its score.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ReadabilityIndex {
+    /// Italian, 0 (hardest) to 100 (easiest).
+    Gulpease(f64),
+    /// English, as a US school grade level.
+    FleschKincaid(f64),
+    /// Spanish, 0 (hardest) to 100 (easiest).
+    FernandezHuerta(f64),
+}
+
+/// Words are runs of letters and digits; apostrophes and hyphens split them.
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+}
+
+pub fn measure(text: &str, language: Language) -> TextMetrics {
+    let mut metrics = TextMetrics::default();
+    for word in words(text) {
+        metrics.words += 1;
+        metrics.letters += word.chars().count();
+        metrics.syllables += syllables(word, language);
+    }
+    metrics.sentences = count_sentences(text);
+    metrics.paragraphs = count_paragraphs(text);
+    metrics
+}
+
+/// Sentences end at runs of `.`, `!`, `?` or `…` followed by a space or the end of
+/// the text; a final fragment without a terminator counts too.
+fn count_sentences(text: &str) -> usize {
+    let mut sentences = 0;
+    let mut has_words = false;
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch.is_alphanumeric() {
+            has_words = true;
+        } else if matches!(ch, '.' | '!' | '?' | '…') && has_words {
+            while chars.peek().is_some_and(|c| {
+                matches!(
+                    c,
+                    '.' | '!' | '?' | '…' | '"' | '\'' | '”' | '’' | ')' | '»'
+                )
+            }) {
+                chars.next();
+            }
+            if chars.peek().is_none_or(|c| c.is_whitespace()) {
+                sentences += 1;
+                has_words = false;
+            }
+        }
+    }
+    sentences + usize::from(has_words)
+}
+
+/// Paragraphs are blocks of text separated by blank lines.
+fn count_paragraphs(text: &str) -> usize {
+    let mut paragraphs = 0;
+    let mut in_paragraph = false;
+    for line in text.lines() {
+        let blank = line.trim().is_empty();
+        if !blank && !in_paragraph {
+            paragraphs += 1;
+        }
+        in_paragraph = !blank;
+    }
+    paragraphs
+}
+
+/// Estimates syllables as groups of vowels; English drops a silent final "e".
+fn syllables(word: &str, language: Language) -> usize {
+    let lower = word.to_lowercase();
+    let mut count = 0;
+    let mut previous_vowel = false;
+    for ch in lower.chars() {
+        let vowel = VOWELS.contains(ch) && (ch != 'y' || language == Language::English);
+        if vowel && !previous_vowel {
+            count += 1;
+        }
+        previous_vowel = vowel;
+    }
+    if language == Language::English
+        && count > 1
+        && lower.ends_with('e')
+        && !lower.ends_with("le")
+        && !lower.ends_with("ee")
+    {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+pub fn readability_index(metrics: &TextMetrics, language: Language) -> Option<ReadabilityIndex> {
+    if metrics.words == 0 || metrics.sentences == 0 {
+        return None;
+    }
+    let words = metrics.words as f64;
+    let sentences = metrics.sentences as f64;
+    let syllables = metrics.syllables as f64;
+    match language {
+        Language::Italian => {
+            let letters = metrics.letters as f64;
+            let score = 89.0 + (300.0 * sentences - 10.0 * letters) / words;
+            Some(ReadabilityIndex::Gulpease(score.clamp(0.0, 100.0)))
+        }
+        Language::English => {
+            let grade = 0.39 * words / sentences + 11.8 * syllables / words - 15.59;
+            Some(ReadabilityIndex::FleschKincaid(grade.max(0.0)))
+        }
+        Language::Spanish => {
+            let score = 206.84 - 60.0 * syllables / words - 102.0 * sentences / words;
+            Some(ReadabilityIndex::FernandezHuerta(score.clamp(0.0, 100.0)))
+        }
+        Language::Portuguese | Language::Vietnamese => None,
+    }
+}
+
+fn stop_words(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::Italian => STOP_WORDS_IT,
+        Language::English => STOP_WORDS_EN,
+        Language::Spanish => STOP_WORDS_ES,
+        Language::Portuguese => STOP_WORDS_PT,
+        Language::Vietnamese => STOP_WORDS_VI,
+    }
+}
+
+/// The `limit` most frequent words, lowercased, leaving out stop words of `language`,
+/// single letters and numbers. Ties are listed alphabetically.
+pub fn top_words(text: &str, language: Language, limit: usize) -> Vec<(String, usize)> {
+    let stop = stop_words(language);
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for word in words(text) {
+        if word.chars().count() < 2 || word.chars().all(|c| c.is_numeric()) {
+            continue;
+        }
+        let lower = word.to_lowercase();
+        if stop.contains(&lower.as_str()) {
+            continue;
+        }
+        *counts.entry(lower).or_default() += 1;
+    }
+    let mut ranked: Vec<(String, usize)> = counts.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked.truncate(limit);
+    ranked
+}
+
+/// Estimated seconds to read `words` silently.
+pub fn reading_seconds(words: usize) -> u64 {
+    (words as f64 * 60.0 / READING_WORDS_PER_MINUTE).round() as u64
+}
+
+/// Estimated seconds to listen to `words` read aloud at `tts_rate` percent.
+pub fn listening_seconds(words: usize, tts_rate: i32) -> u64 {
+    let factor = f64::from(100 + tts_rate.max(-90)) / 100.0;
+    (words as f64 * 60.0 / (SPEECH_WORDS_PER_MINUTE * factor)).round() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure() {
+        let text = "Hello there. How are you?\nFine!\n\nA new paragraph...\nwith no end";
+        let metrics = measure(text, Language::English);
+        assert_eq!(metrics.sentences, 5);
+        assert_eq!(metrics.paragraphs, 2);
+        assert_eq!(metrics.words, 12);
+        assert_eq!(count_sentences("Version 1.5 is out. Really."), 2);
+        assert_eq!(syllables("table", Language::English), 2);
+        assert_eq!(syllables("make", Language::English), 1);
+        assert_eq!(syllables("paura", Language::Italian), 2);
+    }
+
+    #[test]
+    fn test_readability_index() {
+        let metrics = TextMetrics {
+            sentences: 2,
+            paragraphs: 1,
+            words: 20,
+            letters: 100,
+            syllables: 30,
+        };
+        assert_eq!(
+            readability_index(&metrics, Language::Italian),
+            Some(ReadabilityIndex::Gulpease(69.0))
+        );
+        let Some(ReadabilityIndex::FleschKincaid(grade)) =
+            readability_index(&metrics, Language::English)
+        else {
+            panic!("expected Flesch-Kincaid");
+        };
+        assert!((grade - 6.01).abs() < 1e-9);
+        assert_eq!(readability_index(&metrics, Language::Vietnamese), None);
+        assert_eq!(
+            readability_index(&TextMetrics::default(), Language::Italian),
+            None
+        );
+    }
+
+    #[test]
+    fn test_top_words() {
+        let text = "The cat and the dog. The CAT sleeps; a dog barks, the cat runs 42 times.";
+        assert_eq!(
+            top_words(text, Language::English, 2),
+            vec![("cat".to_string(), 3), ("dog".to_string(), 2)]
+        );
+        assert_eq!(listening_seconds(160, 0), 60);
+        assert_eq!(listening_seconds(160, 100), 30);
+        assert_eq!(reading_seconds(230), 60);
+    }
+}
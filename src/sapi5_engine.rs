@@ -16,7 +16,7 @@ use windows::Win32::Media::Speech::{
     SPF_IS_XML, SPF_PURGEBEFORESPEAK, SPFM_CREATE_ALWAYS, SPRS_DONE, SPVOICESTATUS, SpFileStream,
     SpObjectTokenCategory, SpVoice,
 };
-use windows::Win32::System::Com::{CLSCTX_ALL, CoCreateInstance, CoTaskMemFree};
+use windows::Win32::System::Com::{CLSCTX_ALL, CoCreateInstance, CoTaskMemFree, STREAM_SEEK_CUR};
 use windows::core::{GUID, PCWSTR, w};
 
 // SPDFID_WaveFormatEx: {C31ADBAE-527F-4ff5-A230-F62BB61FF70C}
@@ -298,9 +298,11 @@ pub struct SapiExportOptions<'a> {
     pub cancel: Arc<AtomicBool>,
 }
 
+/// Speaks each chunk into the output file. After every chunk the callback receives the
+/// chunk count and the audio position reached so far, in milliseconds.
 pub fn speak_sapi_to_file(
     options: SapiExportOptions,
-    mut progress_callback: impl FnMut(usize),
+    mut progress_callback: impl FnMut(usize, u64),
 ) -> Result<(), String> {
     let _com = ComGuard::new_sta().map_err(|e| format!("CoInitializeEx failed: {}", e))?;
 
@@ -381,7 +383,12 @@ pub fn speak_sapi_to_file(
                     .Speak(PCWSTR(chunk_wide.as_ptr()), SPF_IS_XML.0 as u32, None)
                     .map_err(|e| format!("Speak failed: {}", e))?;
 
-                progress_callback(i + 1);
+                // Speak is synchronous here, so the stream already holds this chunk.
+                let mut position = 0u64;
+                if let Err(e) = stream.Seek(0, STREAM_SEEK_CUR, Some(&mut position)) {
+                    crate::log_debug(&format!("Failed to read SAPI5 stream position: {}", e));
+                }
+                progress_callback(i + 1, position * 1000 / wfx.nAvgBytesPerSec as u64);
             }
 
             if let Err(e) = voice.WaitUntilDone(u32::MAX) {
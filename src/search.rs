@@ -1,7 +1,7 @@
 use crate::accessibility::{EM_REPLACESEL, EM_SCROLLCARET, to_wide};
 use crate::editor_manager::get_edit_text;
 use crate::i18n;
-use crate::settings::{Language, find_title, text_not_found_message};
+use crate::settings::{Language, MacroStep, find_title, text_not_found_message};
 use crate::{get_active_edit, show_error, show_info, with_state};
 use fancy_regex::Regex;
 use windows::Win32::Foundation::HINSTANCE;
@@ -30,6 +30,8 @@ const FIND_ID_DOT_MATCHES_NEWLINE: isize = 5102;
 const FIND_ID_WRAP_AROUND: isize = 5103;
 const REPLACE_ID_IN_SELECTION: isize = 5104;
 const REPLACE_ID_IN_ALL_DOCS: isize = 5105;
+/// Characters of context shown around each Find all match.
+const FIND_ALL_CONTEXT_CHARS: usize = 60;
 
 #[derive(Copy, Clone)]
 pub struct FindOptions {
@@ -143,23 +145,38 @@ pub unsafe fn handle_find_message(hwnd: HWND, lparam: LPARAM) {
     });
 
     if (fr.Flags & FR_REPLACEALL) != FINDREPLACE_FLAGS(0) {
-        replace_all(
-            hwnd,
-            hwnd_edit,
-            &search,
-            &{
-                let len = fr.wReplaceWithLen as usize;
-                let slice = std::slice::from_raw_parts(fr.lpstrReplaceWith.0, len);
-                let len = if len > 0 && slice[len - 1] == 0 {
-                    len - 1
-                } else {
-                    len
-                };
-                String::from_utf16_lossy(&slice[..len])
-            },
-            find_flags,
-            &options,
-        );
+        let replace = {
+            let len = fr.wReplaceWithLen as usize;
+            let slice = std::slice::from_raw_parts(fr.lpstrReplaceWith.0, len);
+            let len = if len > 0 && slice[len - 1] == 0 {
+                len - 1
+            } else {
+                len
+            };
+            String::from_utf16_lossy(&slice[..len])
+        };
+        let count = replace_all(hwnd, hwnd_edit, &search, &replace, find_flags, &options);
+        // A macro step is always replayed on the whole document.
+        let whole_document = !options.replace_in_selection && !options.replace_in_all_docs;
+        if count > 0 && whole_document {
+            crate::text_macros::record(
+                hwnd,
+                MacroStep::ReplaceAll {
+                    find: search,
+                    replace,
+                    match_case: (find_flags & FR_MATCHCASE) != FINDREPLACE_FLAGS(0),
+                    whole_word: (find_flags & FR_WHOLEWORD) != FINDREPLACE_FLAGS(0),
+                    use_regex: options.use_regex,
+                    dot_matches_newline: options.dot_matches_newline,
+                },
+            );
+        } else if count > 0 && crate::text_macros::is_recording(hwnd) {
+            show_info(
+                hwnd,
+                language,
+                &i18n::tr(language, "macro.replace_scope_not_recorded"),
+            );
+        }
         return;
     }
 
@@ -257,6 +274,76 @@ pub unsafe fn find_next_from_state(hwnd: HWND) {
     }
 }
 
+/// Lists every match of the last search in the current document, announcing how many
+/// there are. In a large file only the loaded section is searched.
+pub unsafe fn find_all_from_state(hwnd: HWND) {
+    let (search, flags, language): (String, FINDREPLACE_FLAGS, Language) =
+        with_state(hwnd, |state| {
+            let len = state.find_text.len();
+            let len = if len > 0 && state.find_text[len - 1] == 0 {
+                len - 1
+            } else {
+                len
+            };
+            let search = String::from_utf16_lossy(&state.find_text[..len]);
+            (search, state.last_find_flags, state.settings.language)
+        })
+        .unwrap_or((String::new(), FINDREPLACE_FLAGS(0), Language::default()));
+    if search.is_empty() {
+        open_find_dialog(hwnd);
+        return;
+    }
+    let Some(hwnd_edit) = get_active_edit(hwnd) else {
+        return;
+    };
+    let options = get_find_options(hwnd);
+    let text = get_edit_text(hwnd_edit);
+    let found = match find_all_in_text(
+        &text,
+        &search,
+        (flags & FR_MATCHCASE) != FINDREPLACE_FLAGS(0),
+        (flags & FR_WHOLEWORD) != FINDREPLACE_FLAGS(0),
+        options.use_regex,
+        options.dot_matches_newline,
+    ) {
+        Ok(found) => found,
+        Err(err) => {
+            let message = i18n::tr_f(language, "find.regex_error", &[("err", &err)]);
+            show_error(hwnd, language, &message);
+            return;
+        }
+    };
+    if found.is_empty() {
+        let message = to_wide(&text_not_found_message(language));
+        let title = to_wide(&find_title(language));
+        MessageBoxW(
+            hwnd,
+            PCWSTR(message.as_ptr()),
+            PCWSTR(title.as_ptr()),
+            MB_OK | MB_ICONWARNING,
+        );
+        return;
+    }
+    let first_line = crate::editor_manager::current_first_line(hwnd);
+    // Matches come in order, so editor positions are counted from the previous one.
+    let (mut scanned, mut position) = (0usize, 0i32);
+    let mut edit_position = |index: usize| {
+        position += crate::large_file::edit_position(&text[scanned..], index - scanned);
+        scanned = index;
+        position
+    };
+    let matches = found
+        .into_iter()
+        .map(|found| crate::app_windows::find_all_window::FindAllItem {
+            start: edit_position(found.start),
+            end: edit_position(found.end),
+            line: first_line + found.line,
+            context: found.context,
+        })
+        .collect();
+    crate::app_windows::find_all_window::open(hwnd, hwnd_edit, matches);
+}
+
 fn extract_find_flags(flags: FINDREPLACE_FLAGS) -> FINDREPLACE_FLAGS {
     let mut out = FINDREPLACE_FLAGS(0);
     if (flags & FR_MATCHCASE) != FINDREPLACE_FLAGS(0) {
@@ -322,6 +409,12 @@ pub unsafe fn find_next(
         return true;
     }
 
+    // Only one section of a large file is loaded; scan the rest of the file on disk.
+    if crate::editor_manager::current_is_large_file(hwnd) {
+        let match_case = (flags & FR_MATCHCASE) != FINDREPLACE_FLAGS(0);
+        return crate::editor_manager::find_in_large_file(hwnd, search, down, match_case, wrap);
+    }
+
     if wrap {
         ft.chrg.cpMin = if down { 0 } else { -1 };
         ft.chrg.cpMax = if down { -1 } else { 0 };
@@ -399,6 +492,7 @@ unsafe fn replace_selection_if_match(
     }
 }
 
+/// Replaces every match in the scope chosen in the dialog; returns how many were replaced.
 pub unsafe fn replace_all(
     hwnd: HWND,
     hwnd_edit: HWND,
@@ -406,21 +500,18 @@ pub unsafe fn replace_all(
     replace: &str,
     flags: FINDREPLACE_FLAGS,
     options: &FindOptions,
-) {
+) -> usize {
     if options.use_regex {
-        replace_all_regex(hwnd, hwnd_edit, search, replace, flags, options);
-        return;
+        return replace_all_regex(hwnd, hwnd_edit, search, replace, flags, options);
     }
     if options.replace_in_all_docs {
-        replace_all_in_all_docs(hwnd, search, replace, flags);
-        return;
+        return replace_all_in_all_docs(hwnd, search, replace, flags);
     }
     if options.replace_in_selection {
-        replace_all_in_selection(hwnd, hwnd_edit, search, replace, flags);
-        return;
+        return replace_all_in_selection(hwnd, hwnd_edit, search, replace, flags);
     }
     if search.is_empty() {
-        return;
+        return 0;
     }
     let mut start = 0i32;
     let mut count = 0usize;
@@ -489,6 +580,7 @@ pub unsafe fn replace_all(
         );
         show_info(hwnd, language, &message);
     }
+    count
 }
 
 unsafe fn get_find_options(hwnd: HWND) -> FindOptions {
@@ -766,6 +858,154 @@ fn build_regex(
     Regex::new(&final_pattern).map_err(|err| err.to_string())
 }
 
+/// Builds the matcher of Filter lines with the same case and whole-word handling as Find;
+/// a literal pattern is escaped first.
+pub(crate) fn build_line_filter(
+    pattern: &str,
+    use_regex: bool,
+    match_case: bool,
+    whole_word: bool,
+) -> Result<Regex, String> {
+    build_text_regex(pattern, match_case, whole_word, use_regex, false)
+}
+
+/// Replaces every match in `text` as Replace All does, for macro playback; returns the
+/// new text and the number of replacements.
+pub(crate) fn replace_all_in_text(
+    text: &str,
+    search: &str,
+    replace: &str,
+    match_case: bool,
+    whole_word: bool,
+    use_regex: bool,
+    dot_matches_newline: bool,
+) -> Result<(String, usize), String> {
+    if search.is_empty() {
+        return Ok((text.to_string(), 0));
+    }
+    let regex = build_text_regex(
+        search,
+        match_case,
+        whole_word,
+        use_regex,
+        dot_matches_newline,
+    )?;
+    let count = regex.find_iter(text).count();
+    if count == 0 {
+        return Ok((text.to_string(), 0));
+    }
+    let replaced = if use_regex {
+        let normalized = normalize_regex_replacement(replace);
+        regex.replace_all(text, normalized.as_str()).to_string()
+    } else {
+        regex
+            .replace_all(text, |_: &fancy_regex::Captures| replace.to_string())
+            .to_string()
+    };
+    Ok((replaced, count))
+}
+
+/// Builds the matcher of a search outside the Find dialog; a literal search is escaped
+/// first.
+fn build_text_regex(
+    search: &str,
+    match_case: bool,
+    whole_word: bool,
+    use_regex: bool,
+    dot_matches_newline: bool,
+) -> Result<Regex, String> {
+    let mut flags = FINDREPLACE_FLAGS(0);
+    if match_case {
+        flags |= FR_MATCHCASE;
+    }
+    if whole_word {
+        flags |= FR_WHOLEWORD;
+    }
+    let options = FindOptions {
+        use_regex,
+        dot_matches_newline,
+        wrap_around: false,
+        replace_in_selection: false,
+        replace_in_all_docs: false,
+    };
+    if use_regex {
+        build_regex(search, flags, &options)
+    } else {
+        build_regex(&fancy_regex::escape(search), flags, &options)
+    }
+}
+
+/// A match listed by Find all.
+struct FoundMatch {
+    /// Byte range of the match in the searched text.
+    start: usize,
+    end: usize,
+    /// Line of the match start, 0-based.
+    line: usize,
+    /// The text around the match on its line.
+    context: String,
+}
+
+/// Every match of `search` in `text`, in order. Empty matches, such as those of `^`,
+/// are skipped since there is nothing to select. A regex that fails while matching,
+/// for instance on the backtracking limit, is an error rather than a shorter list.
+fn find_all_in_text(
+    text: &str,
+    search: &str,
+    match_case: bool,
+    whole_word: bool,
+    use_regex: bool,
+    dot_matches_newline: bool,
+) -> Result<Vec<FoundMatch>, String> {
+    if search.is_empty() {
+        return Ok(Vec::new());
+    }
+    let regex = build_text_regex(
+        search,
+        match_case,
+        whole_word,
+        use_regex,
+        dot_matches_newline,
+    )?;
+    let mut found = Vec::new();
+    let mut line = 0usize;
+    let mut line_start = 0usize;
+    let mut scanned = 0usize;
+    for m in regex.find_iter(text) {
+        let m = m.map_err(|err| err.to_string())?;
+        if m.start() == m.end() {
+            continue;
+        }
+        for (idx, ch) in text[scanned..m.start()].char_indices() {
+            let at = scanned + idx;
+            if ch == '\r' || (ch == '\n' && !text[..at].ends_with('\r')) {
+                line += 1;
+            }
+            if ch == '\r' || ch == '\n' {
+                line_start = at + 1;
+            }
+        }
+        scanned = m.start();
+        let line_end = text[line_start..]
+            .find(['\r', '\n'])
+            .map_or(text.len(), |index| line_start + index);
+        let match_len = m.end().min(line_end).saturating_sub(m.start());
+        let context = crate::app_windows::find_in_files_window::snippet_for_match(
+            &text[line_start..line_end],
+            m.start() - line_start,
+            match_len,
+            FIND_ALL_CONTEXT_CHARS,
+        );
+        found.push(FoundMatch {
+            start: m.start(),
+            end: m.end(),
+            line,
+            context,
+        });
+    }
+    Ok(found)
+}
+
 unsafe fn find_next_regex(
     hwnd: HWND,
     hwnd_edit: HWND,
@@ -907,9 +1147,9 @@ unsafe fn replace_all_regex(
     replace: &str,
     flags: FINDREPLACE_FLAGS,
     options: &FindOptions,
-) {
+) -> usize {
     if search.is_empty() {
-        return;
+        return 0;
     }
     let language = with_state(hwnd, |state| state.settings.language).unwrap_or_default();
     let regex = match build_regex(search, flags, options) {
@@ -917,7 +1157,7 @@ unsafe fn replace_all_regex(
         Err(err) => {
             let message = i18n::tr_f(language, "find.regex_error", &[("err", &err)]);
             show_error(hwnd, language, &message);
-            return;
+            return 0;
         }
     };
     let normalized = normalize_regex_replacement(replace);
@@ -954,7 +1194,7 @@ unsafe fn replace_all_regex(
             LPARAM(&mut cr as *mut _ as isize),
         );
         if cr.cpMin == cr.cpMax {
-            return;
+            return 0;
         }
         let text = get_edit_text(hwnd_edit);
         let start = utf16_index_to_byte(&text, cr.cpMin);
@@ -993,6 +1233,7 @@ unsafe fn replace_all_regex(
         );
         show_info(hwnd, language, &message);
     }
+    total_count
 }
 
 unsafe fn replace_all_in_all_docs(
@@ -1000,7 +1241,7 @@ unsafe fn replace_all_in_all_docs(
     search: &str,
     replace: &str,
     flags: FINDREPLACE_FLAGS,
-) {
+) -> usize {
     let edits = with_state(hwnd, |state| {
         state
             .docs
@@ -1031,6 +1272,7 @@ unsafe fn replace_all_in_all_docs(
         );
         show_info(hwnd, language, &message);
     }
+    total_count
 }
 
 unsafe fn replace_all_in_selection(
@@ -1039,7 +1281,7 @@ unsafe fn replace_all_in_selection(
     search: &str,
     replace: &str,
     flags: FINDREPLACE_FLAGS,
-) {
+) -> usize {
     let mut cr = CHARRANGE { cpMin: 0, cpMax: 0 };
     SendMessageW(
         hwnd_edit,
@@ -1048,7 +1290,7 @@ unsafe fn replace_all_in_selection(
         LPARAM(&mut cr as *mut _ as isize),
     );
     if cr.cpMin == cr.cpMax {
-        return;
+        return 0;
     }
     let count = replace_all_in_range(hwnd_edit, search, replace, flags, cr.cpMin, cr.cpMax);
     let language = with_state(hwnd, |state| state.settings.language).unwrap_or_default();
@@ -1069,6 +1311,7 @@ unsafe fn replace_all_in_selection(
         );
         show_info(hwnd, language, &message);
     }
+    count
 }
 
 unsafe fn replace_all_in_range(
@@ -1186,3 +1429,44 @@ fn byte_index_to_utf16(text: &str, byte_idx: usize) -> i32 {
     }
     utf16_count as i32
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines_and_ranges(found: &[FoundMatch]) -> Vec<(usize, usize, usize)> {
+        found.iter().map(|m| (m.line, m.start, m.end)).collect()
+    }
+
+    #[test]
+    fn find_all_literal_counts_lines_across_line_breaks() {
+        let text = "cat\r\ndog cat\rbird\ncat";
+        let found = find_all_in_text(text, "CAT", false, false, false, false).unwrap();
+        assert_eq!(
+            lines_and_ranges(&found),
+            vec![(0, 0, 3), (1, 9, 12), (3, 18, 21)]
+        );
+        assert_eq!(found[1].context, "dog cat");
+        assert!(
+            find_all_in_text(text, "CAT", true, false, false, false)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn find_all_regex_skips_empty_matches() {
+        let text = "a1\r\nb22\r\n";
+        let found = find_all_in_text(text, r"\d*", false, false, true, false).unwrap();
+        assert_eq!(lines_and_ranges(&found), vec![(0, 1, 2), (1, 5, 7)]);
+        assert!(find_all_in_text(text, "(", false, false, true, false).is_err());
+    }
+
+    #[test]
+    fn find_all_multi_line_match_starts_on_its_first_line() {
+        let text = "one\r\ntwo\r\nthree";
+        let found = find_all_in_text(text, "two.+thr", false, false, true, true).unwrap();
+        assert_eq!(lines_and_ranges(&found), vec![(1, 5, 13)]);
+        assert_eq!(found[0].context, "two");
+    }
+}
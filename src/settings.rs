@@ -63,6 +63,143 @@ pub enum TextEncoding {
     Utf16Le,
     #[serde(rename = "utf16be")]
     Utf16Be,
+    #[serde(rename = "windows1250")]
+    Windows1250,
+    #[serde(rename = "windows1251")]
+    Windows1251,
+    #[serde(rename = "windows1252")]
+    Windows1252,
+    #[serde(rename = "windows1253")]
+    Windows1253,
+    #[serde(rename = "windows1254")]
+    Windows1254,
+    #[serde(rename = "windows1255")]
+    Windows1255,
+    #[serde(rename = "windows1256")]
+    Windows1256,
+    #[serde(rename = "windows1257")]
+    Windows1257,
+    #[serde(rename = "windows1258")]
+    Windows1258,
+    #[serde(rename = "iso88591")]
+    Iso8859_1,
+    #[serde(rename = "iso88592")]
+    Iso8859_2,
+    #[serde(rename = "iso88595")]
+    Iso8859_5,
+    #[serde(rename = "iso88597")]
+    Iso8859_7,
+    #[serde(rename = "iso88599")]
+    Iso8859_9,
+    #[serde(rename = "iso885915")]
+    Iso8859_15,
+    #[serde(rename = "koi8r")]
+    Koi8R,
+    #[serde(rename = "koi8u")]
+    Koi8U,
+    #[serde(rename = "shiftjis")]
+    ShiftJis,
+    #[serde(rename = "eucjp")]
+    EucJp,
+    #[serde(rename = "gb18030")]
+    Gb18030,
+    #[serde(rename = "big5")]
+    Big5,
+    #[serde(rename = "euckr")]
+    EucKr,
+}
+
+impl TextEncoding {
+    /// Every encoding offered in the open/save dialogs and in "Reopen with encoding",
+    /// in menu order. The first five keep their historical dialog indexes.
+    pub const ALL: [TextEncoding; 27] = [
+        TextEncoding::Ansi,
+        TextEncoding::Utf8,
+        TextEncoding::Utf8Bom,
+        TextEncoding::Utf16Le,
+        TextEncoding::Utf16Be,
+        TextEncoding::Windows1250,
+        TextEncoding::Windows1251,
+        TextEncoding::Windows1252,
+        TextEncoding::Windows1253,
+        TextEncoding::Windows1254,
+        TextEncoding::Windows1255,
+        TextEncoding::Windows1256,
+        TextEncoding::Windows1257,
+        TextEncoding::Windows1258,
+        TextEncoding::Iso8859_1,
+        TextEncoding::Iso8859_2,
+        TextEncoding::Iso8859_5,
+        TextEncoding::Iso8859_7,
+        TextEncoding::Iso8859_9,
+        TextEncoding::Iso8859_15,
+        TextEncoding::Koi8R,
+        TextEncoding::Koi8U,
+        TextEncoding::ShiftJis,
+        TextEncoding::EucJp,
+        TextEncoding::Gb18030,
+        TextEncoding::Big5,
+        TextEncoding::EucKr,
+    ];
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            TextEncoding::Ansi => "encoding.ansi",
+            TextEncoding::Utf8 => "encoding.utf8",
+            TextEncoding::Utf8Bom => "encoding.utf8bom",
+            TextEncoding::Utf16Le => "encoding.utf16le",
+            TextEncoding::Utf16Be => "encoding.utf16be",
+            TextEncoding::Windows1250 => "encoding.windows1250",
+            TextEncoding::Windows1251 => "encoding.windows1251",
+            TextEncoding::Windows1252 => "encoding.windows1252",
+            TextEncoding::Windows1253 => "encoding.windows1253",
+            TextEncoding::Windows1254 => "encoding.windows1254",
+            TextEncoding::Windows1255 => "encoding.windows1255",
+            TextEncoding::Windows1256 => "encoding.windows1256",
+            TextEncoding::Windows1257 => "encoding.windows1257",
+            TextEncoding::Windows1258 => "encoding.windows1258",
+            TextEncoding::Iso8859_1 => "encoding.iso88591",
+            TextEncoding::Iso8859_2 => "encoding.iso88592",
+            TextEncoding::Iso8859_5 => "encoding.iso88595",
+            TextEncoding::Iso8859_7 => "encoding.iso88597",
+            TextEncoding::Iso8859_9 => "encoding.iso88599",
+            TextEncoding::Iso8859_15 => "encoding.iso885915",
+            TextEncoding::Koi8R => "encoding.koi8r",
+            TextEncoding::Koi8U => "encoding.koi8u",
+            TextEncoding::ShiftJis => "encoding.shiftjis",
+            TextEncoding::EucJp => "encoding.eucjp",
+            TextEncoding::Gb18030 => "encoding.gb18030",
+            TextEncoding::Big5 => "encoding.big5",
+            TextEncoding::EucKr => "encoding.euckr",
+        }
+    }
+}
+
+/// Line terminator a text document uses on disk; the editor itself always works with CRLF.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum LineEnding {
+    #[default]
+    Crlf,
+    Lf,
+    Cr,
+}
+
+impl LineEnding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Lf => "\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LineEnding::Crlf => "CRLF",
+            LineEnding::Lf => "LF",
+            LineEnding::Cr => "CR",
+        }
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -73,10 +210,13 @@ pub enum FileFormat {
     Pdf,
     Spreadsheet,
     Epub,
+    Mobi,
     Html,
     Ppt,
     Pptx,
     Audiobook,
+    /// Password-protected UTF-8 text; the key is kept so saving does not ask again.
+    Encrypted(crate::encryption::DocumentKey),
 }
 
 impl Default for FileFormat {
@@ -149,6 +289,85 @@ pub enum PodcastFormat {
     Wav,
 }
 
+/// Comparison used by the Sort lines dialog.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, Debug)]
+pub enum SortMode {
+    #[serde(rename = "alphabetical")]
+    #[default]
+    Alphabetical,
+    #[serde(rename = "natural")]
+    Natural,
+    #[serde(rename = "numeric")]
+    Numeric,
+}
+
+/// What the Filter lines dialog does with the lines matching its pattern.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum FilterAction {
+    #[serde(rename = "keep")]
+    #[default]
+    Keep,
+    #[serde(rename = "remove")]
+    Remove,
+    #[serde(rename = "copy_to_new_tab")]
+    CopyToNewTab,
+}
+
+/// What the Columns dialog does with the chosen field range.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum ColumnAction {
+    #[serde(rename = "extract")]
+    #[default]
+    Extract,
+    #[serde(rename = "delete")]
+    Delete,
+}
+
+/// One recorded step of a text macro. Played back, every step works on the whole document.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum MacroStep {
+    StripMarkdown,
+    NormalizeWhitespace,
+    HardLineBreak,
+    OrderItems,
+    KeepUniqueItems,
+    ReverseItems,
+    QuoteLines,
+    UnquoteLines,
+    JoinLines,
+    CleanEolHyphens,
+    RemoveDuplicateLines,
+    RemoveDuplicateConsecutiveLines,
+    FixMojibake,
+    UpperCase,
+    LowerCase,
+    SentenceCase,
+    TitleCase,
+    ToggleCase,
+    CapitalizeSentences,
+    Transliterate,
+    TransliterateToAscii,
+    RemoveLineNumbers,
+    ReplaceAll {
+        find: String,
+        replace: String,
+        match_case: bool,
+        whole_word: bool,
+        use_regex: bool,
+        dot_matches_newline: bool,
+    },
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TextMacro {
+    pub name: String,
+    /// Digit of its Ctrl+Shift shortcut, 1 to 9; 0 when it has none.
+    #[serde(default)]
+    pub shortcut: u8,
+    pub steps: Vec<MacroStep>,
+}
+
 pub const PODCAST_DEVICE_DEFAULT: &str = "default";
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -165,8 +384,13 @@ pub struct AppSettings {
     pub word_wrap: bool,
     pub wrap_width: u32,
     pub smart_quotes: bool,
+    /// Corrects the word before the caret when a space or punctuation is typed.
+    pub autocorrect: bool,
     #[serde(default)]
     pub strip_markdown_keep_bullets: bool,
+    #[serde(default)]
+    pub html_reader_mode: bool,
+    pub subtitles_show_timestamps: bool,
     pub quote_prefix: String,
     pub move_cursor_during_reading: bool,
     pub audiobook_skip_seconds: u32,
@@ -191,6 +415,31 @@ pub struct AppSettings {
     pub podcast_index_api_key: String,
     pub podcast_index_api_secret: String,
     pub youtube_include_timestamps: bool,
+    #[serde(default)]
+    pub youtube_as_subtitles: bool,
+    pub braille_table: String,
+    pub braille_cells_per_line: u32,
+    pub braille_lines_per_page: u32,
+    pub braille_page_numbers: bool,
+    pub sort_mode: SortMode,
+    pub sort_descending: bool,
+    pub sort_case_sensitive: bool,
+    pub sort_ignore_accents: bool,
+    pub sort_field: u32,
+    pub sort_delimiter: String,
+    pub sort_remove_duplicates: bool,
+    pub filter_action: FilterAction,
+    pub filter_use_regex: bool,
+    pub filter_match_case: bool,
+    pub filter_whole_word: bool,
+    pub number_start: i64,
+    pub number_step: i64,
+    pub number_width: u32,
+    pub number_separator: String,
+    pub column_action: ColumnAction,
+    pub column_fields: String,
+    pub column_delimiter: String,
+    pub macros: Vec<TextMacro>,
     pub last_seen_changelog_version: String,
     pub favorite_voices: Vec<FavoriteVoice>,
     pub dictionary: Vec<DictionaryEntry>,
@@ -276,7 +525,10 @@ impl Default for AppSettings {
             word_wrap: true,
             wrap_width: 80,
             smart_quotes: false,
+            autocorrect: false,
             strip_markdown_keep_bullets: false,
+            html_reader_mode: false,
+            subtitles_show_timestamps: true,
             quote_prefix: "> ".to_string(),
             move_cursor_during_reading: false,
             audiobook_skip_seconds: 60,
@@ -301,6 +553,30 @@ impl Default for AppSettings {
             podcast_index_api_key: String::new(),
             podcast_index_api_secret: String::new(),
             youtube_include_timestamps: true,
+            youtube_as_subtitles: false,
+            braille_table: String::new(),
+            braille_cells_per_line: 40,
+            braille_lines_per_page: 25,
+            braille_page_numbers: true,
+            sort_mode: SortMode::Alphabetical,
+            sort_descending: false,
+            sort_case_sensitive: false,
+            sort_ignore_accents: false,
+            sort_field: 0,
+            sort_delimiter: String::new(),
+            sort_remove_duplicates: false,
+            filter_action: FilterAction::Keep,
+            filter_use_regex: false,
+            filter_match_case: false,
+            filter_whole_word: false,
+            number_start: 1,
+            number_step: 1,
+            number_width: 0,
+            number_separator: ". ".to_string(),
+            column_action: ColumnAction::Extract,
+            column_fields: "1".to_string(),
+            column_delimiter: "\t".to_string(),
+            macros: Vec::new(),
             last_seen_changelog_version: String::new(),
             favorite_voices: Vec::new(),
             dictionary: Vec::new(),
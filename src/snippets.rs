@@ -0,0 +1,207 @@
+use crate::settings::Language;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// Longest abbreviation looked up before the caret when expanding a snippet.
+pub const MAX_ABBREVIATION_CHARS: usize = 32;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snippet {
+    pub abbreviation: String,
+    pub text: String,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct SnippetStore {
+    pub snippets: Vec<Snippet>,
+}
+
+impl SnippetStore {
+    pub fn find(&self, abbreviation: &str) -> Option<&Snippet> {
+        self.snippets
+            .iter()
+            .find(|snippet| snippet.abbreviation == abbreviation)
+    }
+}
+
+fn snippet_store_path() -> PathBuf {
+    crate::settings::settings_dir().join("snippets.json")
+}
+
+pub fn load_snippets() -> SnippetStore {
+    std::fs::read_to_string(snippet_store_path())
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_snippets(store: &SnippetStore) {
+    let path = snippet_store_path();
+    if let Some(parent) = path.parent() {
+        crate::log_if_err!(std::fs::create_dir_all(parent));
+    }
+    if let Ok(json) = serde_json::to_string_pretty(store) {
+        crate::log_if_err!(std::fs::write(path, json));
+    }
+}
+
+/// Folder the "New from template" dialog opens in.
+pub fn templates_dir() -> PathBuf {
+    crate::settings::settings_dir().join("templates")
+}
+
+/// Date format of the `{date}` placeholder for the interface language.
+pub fn date_format(language: Language) -> &'static str {
+    match language {
+        Language::English => "%m/%d/%Y",
+        _ => "%d/%m/%Y",
+    }
+}
+
+/// What the named placeholders expand to.
+pub struct PlaceholderValues {
+    pub date: String,
+    pub time: String,
+    pub filename: String,
+    pub clipboard: String,
+}
+
+pub struct Expansion {
+    /// The expanded text, with `\n` line breaks.
+    pub text: String,
+    /// Tab-stop fields in the order Tab visits them, as UTF-16 offsets into `text`.
+    pub fields: Vec<Range<usize>>,
+}
+
+/// The abbreviation typed just before the caret: the trailing run of
+/// non-whitespace characters of `before`.
+pub fn abbreviation_before(before: &str) -> &str {
+    let start = before
+        .char_indices()
+        .rev()
+        .find(|(_, ch)| ch.is_whitespace())
+        .map(|(idx, ch)| idx + ch.len_utf8())
+        .unwrap_or(0);
+    &before[start..]
+}
+
+/// Expands `{date}`, `{time}`, `{filename}` and `{clipboard}`, and turns `{1}`,
+/// `{2:default text}`... into tab-stop fields visited in number order, `{0}` last.
+/// `{{` and `}}` stand for literal braces; other text in braces is kept as is.
+pub fn expand(template: &str, values: &PlaceholderValues) -> Expansion {
+    let template = template.replace("\r\n", "\n");
+    let mut text = String::new();
+    let mut utf16_len = 0usize;
+    let mut fields: Vec<(usize, Range<usize>)> = Vec::new();
+    let mut push = |text: &mut String, s: &str| {
+        let s = s.replace("\r\n", "\n");
+        utf16_len += s.encode_utf16().count();
+        text.push_str(&s);
+        utf16_len
+    };
+    let mut rest = template.as_str();
+    while let Some(pos) = rest.find(['{', '}']) {
+        push(&mut text, &rest[..pos]);
+        let tail = &rest[pos..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            push(&mut text, &tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        // A brace that does not open a known placeholder is text, and scanning
+        // goes on right after it.
+        let placeholder = tail
+            .find('}')
+            .filter(|_| tail.starts_with('{'))
+            .map(|close| (close, &tail[1..close]))
+            .filter(|(_, inner)| is_placeholder(inner));
+        let Some((close, inner)) = placeholder else {
+            push(&mut text, &tail[..1]);
+            rest = &tail[1..];
+            continue;
+        };
+        let (name, default) = inner.split_once(':').unwrap_or((inner, ""));
+        match inner {
+            "date" => {
+                push(&mut text, &values.date);
+            }
+            "time" => {
+                push(&mut text, &values.time);
+            }
+            "filename" => {
+                push(&mut text, &values.filename);
+            }
+            "clipboard" => {
+                push(&mut text, &values.clipboard);
+            }
+            _ => {
+                let number = name.parse::<usize>().unwrap_or(0);
+                let start = push(&mut text, "");
+                let end = push(&mut text, default);
+                fields.push((number, start..end));
+            }
+        }
+        rest = &tail[close + 1..];
+    }
+    push(&mut text, rest);
+    // Number 0 is where the caret ends up, after every other field.
+    fields.sort_by_key(|(number, _)| if *number == 0 { usize::MAX } else { *number });
+    Expansion {
+        text,
+        fields: fields.into_iter().map(|(_, range)| range).collect(),
+    }
+}
+
+/// True for the text between braces of a named placeholder or a numbered field.
+fn is_placeholder(inner: &str) -> bool {
+    let name = inner.split_once(':').map_or(inner, |(name, _)| name);
+    matches!(inner, "date" | "time" | "filename" | "clipboard")
+        || (!name.is_empty() && name.chars().all(|c| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values() -> PlaceholderValues {
+        PlaceholderValues {
+            date: "18/10/2026".to_string(),
+            time: "09:30".to_string(),
+            filename: "letter.txt".to_string(),
+            clipboard: "line one\r\nline two".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_expand_placeholders() {
+        let expansion = expand(
+            "{date} {time} {filename}\r\n{clipboard} {other} {{x}}",
+            &values(),
+        );
+        assert_eq!(
+            expansion.text,
+            "18/10/2026 09:30 letter.txt\nline one\nline two {other} {x}"
+        );
+        assert!(expansion.fields.is_empty());
+        assert_eq!(expand("a { b", &values()).text, "a { b");
+        assert_eq!(expand("a { b {date}", &values()).text, "a { b 18/10/2026");
+        let expansion = expand("f() { {1:body} }", &values());
+        assert_eq!(expansion.text, "f() { body }");
+        assert_eq!(expansion.fields, vec![6..10]);
+    }
+
+    #[test]
+    fn test_expand_fields() {
+        let expansion = expand("Dear {2:name},\n{0}\nè {1}!", &values());
+        assert_eq!(expansion.text, "Dear name,\n\nè !");
+        assert_eq!(expansion.fields, vec![14..14, 5..9, 11..11]);
+    }
+
+    #[test]
+    fn test_abbreviation_before() {
+        assert_eq!(abbreviation_before("Hello ;sig"), ";sig");
+        assert_eq!(abbreviation_before("sig"), "sig");
+        assert_eq!(abbreviation_before("line\r"), "");
+    }
+}
@@ -0,0 +1,542 @@
+use crate::i18n;
+use crate::settings::Language;
+use std::path::Path;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SubtitleFormat {
+    Srt,
+    Vtt,
+}
+
+impl SubtitleFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?;
+        if ext.eq_ignore_ascii_case("srt") {
+            Some(SubtitleFormat::Srt)
+        } else if ext.eq_ignore_ascii_case("vtt") {
+            Some(SubtitleFormat::Vtt)
+        } else {
+            None
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "srt",
+            SubtitleFormat::Vtt => "vtt",
+        }
+    }
+
+    fn millis_separator(self) -> char {
+        match self {
+            SubtitleFormat::Srt => ',',
+            SubtitleFormat::Vtt => '.',
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cue {
+    /// SRT sequence number or WebVTT cue identifier, kept as written.
+    pub id: Option<String>,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// WebVTT cue settings following the end time (`align:start`, ...).
+    pub settings: String,
+    pub text: String,
+    /// WebVTT blocks (NOTE, STYLE, ...) written just before this cue.
+    pub preamble: Vec<String>,
+}
+
+impl Cue {
+    pub fn new(start_ms: u64, end_ms: u64, text: &str) -> Self {
+        Cue {
+            id: None,
+            start_ms,
+            end_ms,
+            settings: String::new(),
+            text: text.to_string(),
+            preamble: Vec::new(),
+        }
+    }
+}
+
+/// Shown in place of an empty cue when timestamps are hidden, so that every cue
+/// still has a block of its own.
+const EMPTY_CUE_PLACEHOLDER: &str = "[...]";
+
+#[derive(Clone, Debug)]
+pub struct SubtitleTrack {
+    pub format: SubtitleFormat,
+    /// The `WEBVTT` line with any header text; empty for SRT.
+    header: String,
+    pub cues: Vec<Cue>,
+    /// WebVTT blocks following the last cue.
+    trailer: Vec<String>,
+}
+
+/// A subtitle document as shown in the editor: only the cue text, optionally
+/// preceded by a `[start --> end]` line, one blank-line separated block per cue.
+#[derive(Clone, Debug)]
+pub struct SubtitleView {
+    pub track: SubtitleTrack,
+    pub show_timestamps: bool,
+}
+
+impl SubtitleView {
+    pub fn display_text(&self) -> String {
+        self.track.display_text(self.show_timestamps)
+    }
+
+    /// Reads the edited cue blocks back into the track.
+    pub fn sync_from_text(&mut self, text: &str, language: Language) -> Result<(), String> {
+        self.track
+            .apply_display_text(text, self.show_timestamps, language)
+    }
+}
+
+impl SubtitleTrack {
+    pub fn from_cues(format: SubtitleFormat, cues: Vec<Cue>) -> Self {
+        SubtitleTrack {
+            format,
+            header: String::new(),
+            cues,
+            trailer: Vec::new(),
+        }
+    }
+
+    pub fn parse(text: &str, format: SubtitleFormat, language: Language) -> Result<Self, String> {
+        let text = text
+            .trim_start_matches('\u{feff}')
+            .replace("\r\n", "\n")
+            .replace('\r', "\n");
+        let mut track = SubtitleTrack::from_cues(format, Vec::new());
+        let mut preamble = Vec::new();
+        for (first_line, block) in blocks(&text) {
+            let lines: Vec<&str> = block.lines().collect();
+            if format == SubtitleFormat::Vtt
+                && track.header.is_empty()
+                && track.cues.is_empty()
+                && lines[0].starts_with("WEBVTT")
+            {
+                track.header = block.to_string();
+                continue;
+            }
+            let Some(timing) = lines.iter().position(|line| line.contains("-->")) else {
+                if format == SubtitleFormat::Vtt {
+                    preamble.push(block.to_string());
+                    continue;
+                }
+                return Err(parse_error(language, first_line));
+            };
+            if timing > 1 {
+                return Err(parse_error(language, first_line));
+            }
+            let (start_ms, end_ms, settings) = parse_timing_line(lines[timing])
+                .ok_or_else(|| parse_error(language, first_line + timing))?;
+            track.cues.push(Cue {
+                id: (timing == 1).then(|| lines[0].trim().to_string()),
+                start_ms,
+                end_ms,
+                settings,
+                text: lines[timing + 1..].join("\n"),
+                preamble: std::mem::take(&mut preamble),
+            });
+        }
+        track.trailer = preamble;
+        if format == SubtitleFormat::Vtt && track.header.is_empty() {
+            return Err(i18n::tr(language, "subtitles.missing_header"));
+        }
+        Ok(track)
+    }
+
+    /// Serializes the track, converting it when `format` differs from the one it
+    /// was read from. SRT numbering is kept when every cue still has a number.
+    pub fn serialize(&self, format: SubtitleFormat) -> String {
+        let mut out = String::new();
+        if format == SubtitleFormat::Vtt {
+            if self.header.is_empty() {
+                out.push_str("WEBVTT");
+            } else {
+                out.push_str(&self.header);
+            }
+            out.push_str("\n\n");
+        }
+        let keep_numbers = self.cues.iter().all(|cue| {
+            cue.id
+                .as_deref()
+                .is_some_and(|id| id.parse::<u64>().is_ok())
+        });
+        for (index, cue) in self.cues.iter().enumerate() {
+            if format == SubtitleFormat::Vtt {
+                for block in &cue.preamble {
+                    out.push_str(block);
+                    out.push_str("\n\n");
+                }
+            }
+            match format {
+                SubtitleFormat::Srt if keep_numbers => {
+                    out.push_str(cue.id.as_deref().unwrap_or_default());
+                    out.push('\n');
+                }
+                SubtitleFormat::Srt => out.push_str(&format!("{}\n", index + 1)),
+                SubtitleFormat::Vtt => {
+                    if let Some(id) = &cue.id {
+                        out.push_str(id);
+                        out.push('\n');
+                    }
+                }
+            }
+            out.push_str(&format_time(cue.start_ms, format));
+            out.push_str(" --> ");
+            out.push_str(&format_time(cue.end_ms, format));
+            if format == SubtitleFormat::Vtt && !cue.settings.is_empty() {
+                out.push(' ');
+                out.push_str(&cue.settings);
+            }
+            out.push('\n');
+            if !cue.text.is_empty() {
+                out.push_str(&cue.text);
+                out.push('\n');
+            }
+            out.push('\n');
+        }
+        if format == SubtitleFormat::Vtt {
+            for block in &self.trailer {
+                out.push_str(block);
+                out.push_str("\n\n");
+            }
+        }
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+        out
+    }
+
+    pub fn display_text(&self, with_timestamps: bool) -> String {
+        let blocks: Vec<String> = self
+            .cues
+            .iter()
+            .map(|cue| {
+                if with_timestamps {
+                    format!(
+                        "[{} --> {}]\n{}",
+                        format_time(cue.start_ms, self.format),
+                        format_time(cue.end_ms, self.format),
+                        cue.text
+                    )
+                } else if cue.text.trim().is_empty() {
+                    EMPTY_CUE_PLACEHOLDER.to_string()
+                } else {
+                    cue.text.clone()
+                }
+            })
+            .collect();
+        blocks.join("\n\n")
+    }
+
+    /// Replaces the cues with the blocks of an edited [`Self::display_text`].
+    /// Without timestamps the number of blocks must match the number of cues; an
+    /// empty cue is shown as a placeholder block.
+    pub fn apply_display_text(
+        &mut self,
+        text: &str,
+        with_timestamps: bool,
+        language: Language,
+    ) -> Result<(), String> {
+        let blocks: Vec<(usize, &str)> = blocks(text).collect();
+        if !with_timestamps {
+            if blocks.len() != self.cues.len() {
+                return Err(i18n::tr_f(
+                    language,
+                    "subtitles.cue_count_mismatch",
+                    &[
+                        ("expected", &self.cues.len().to_string()),
+                        ("found", &blocks.len().to_string()),
+                    ],
+                ));
+            }
+            for (cue, (_, block)) in self.cues.iter_mut().zip(&blocks) {
+                cue.text = if *block == EMPTY_CUE_PLACEHOLDER {
+                    String::new()
+                } else {
+                    block.to_string()
+                };
+            }
+            return Ok(());
+        }
+        let renumber = blocks.len() != self.cues.len();
+        let mut cues = Vec::with_capacity(blocks.len());
+        for (index, (first_line, block)) in blocks.into_iter().enumerate() {
+            let (timing, body) = block.split_once('\n').unwrap_or((block, ""));
+            let (start_ms, end_ms, _) = timing
+                .trim()
+                .strip_prefix('[')
+                .and_then(|rest| rest.strip_suffix(']'))
+                .and_then(parse_timing_line)
+                .ok_or_else(|| parse_error(language, first_line))?;
+            let mut cue = self
+                .cues
+                .get(index)
+                .cloned()
+                .unwrap_or_else(|| Cue::new(0, 0, ""));
+            if renumber && self.format == SubtitleFormat::Srt {
+                cue.id = Some((index + 1).to_string());
+            }
+            cue.start_ms = start_ms;
+            cue.end_ms = end_ms;
+            cue.text = body.to_string();
+            cues.push(cue);
+        }
+        self.cues = cues;
+        Ok(())
+    }
+
+    /// Multiplies every time by `factor` and then moves it by `shift_ms`,
+    /// clamping at zero.
+    pub fn adjust_timing(&mut self, factor: f64, shift_ms: i64) {
+        let adjust = |ms: u64| -> u64 {
+            let scaled = (ms as f64 * factor).round() as i64;
+            scaled.saturating_add(shift_ms).max(0) as u64
+        };
+        for cue in &mut self.cues {
+            cue.start_ms = adjust(cue.start_ms);
+            cue.end_ms = adjust(cue.end_ms);
+        }
+    }
+
+    /// Index of the cue playing at `ms`, or of the next one when `ms` falls
+    /// between cues.
+    pub fn cue_index_at(&self, ms: u64) -> Option<usize> {
+        if self.cues.is_empty() {
+            return None;
+        }
+        let playing = self
+            .cues
+            .iter()
+            .position(|cue| cue.start_ms <= ms && ms < cue.end_ms);
+        let next = self.cues.iter().position(|cue| cue.start_ms >= ms);
+        Some(playing.or(next).unwrap_or(self.cues.len() - 1))
+    }
+}
+
+/// Byte offset of the `index`-th cue block of a displayed subtitle document.
+pub fn cue_offset(text: &str, index: usize) -> Option<usize> {
+    let (_, block) = blocks(text).nth(index)?;
+    Some(block.as_ptr() as usize - text.as_ptr() as usize)
+}
+
+/// Index of the cue block containing (or preceding) byte `offset`.
+pub fn cue_at_offset(text: &str, offset: usize) -> Option<usize> {
+    let base = text.as_ptr() as usize;
+    blocks(text)
+        .take_while(|(_, block)| block.as_ptr() as usize - base <= offset)
+        .count()
+        .checked_sub(1)
+}
+
+/// Splits `text` into blocks separated by blank lines, yielding each block
+/// with the 1-based line number it starts on.
+fn blocks(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut result = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+    let mut end = 0;
+    let mut offset = 0;
+    for (number, raw) in text.split('\n').enumerate() {
+        let line = raw.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            if let Some((line_number, block_start)) = start.take() {
+                result.push((line_number, &text[block_start..end]));
+            }
+        } else {
+            if start.is_none() {
+                start = Some((number + 1, offset));
+            }
+            end = offset + line.len();
+        }
+        offset += raw.len() + 1;
+    }
+    if let Some((line_number, block_start)) = start {
+        result.push((line_number, &text[block_start..end]));
+    }
+    result.into_iter()
+}
+
+fn parse_error(language: Language, line: usize) -> String {
+    i18n::tr_f(
+        language,
+        "subtitles.invalid_timing",
+        &[("line", &line.to_string())],
+    )
+}
+
+fn parse_timing_line(line: &str) -> Option<(u64, u64, String)> {
+    let (start, rest) = line.split_once("-->")?;
+    let rest = rest.trim();
+    let (end, settings) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    Some((
+        parse_time(start.trim())?,
+        parse_time(end)?,
+        settings.trim().to_string(),
+    ))
+}
+
+/// Parses `hh:mm:ss,mmm`, `hh:mm:ss.mmm` or `mm:ss.mmm` into milliseconds.
+pub fn parse_time(value: &str) -> Option<u64> {
+    let (clock, millis) = value.rsplit_once([',', '.']).unwrap_or((value, "0"));
+    if millis.is_empty() || millis.len() > 3 || !millis.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let millis = millis.parse::<u64>().ok()? * 10u64.pow(3 - millis.len() as u32);
+    let parts: Vec<&str> = clock.split(':').collect();
+    if !(2..=3).contains(&parts.len()) {
+        return None;
+    }
+    let mut seconds = 0u64;
+    for (index, part) in parts.iter().enumerate() {
+        if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let value = part.parse::<u64>().ok()?;
+        if index > 0 && value >= 60 {
+            return None;
+        }
+        seconds = seconds * 60 + value;
+    }
+    Some(seconds * 1000 + millis)
+}
+
+/// Parses a timing shift in seconds (`-1.5`) or as a signed time
+/// (`-00:00:02,500`) into milliseconds. An empty value means no shift.
+pub fn parse_shift(value: &str) -> Option<i64> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Some(0);
+    }
+    let (sign, rest) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest.trim()),
+        None => (1, value.strip_prefix('+').unwrap_or(value).trim()),
+    };
+    let ms = if rest.contains(':') {
+        parse_time(rest)? as i64
+    } else {
+        let seconds = rest.replace(',', ".").parse::<f64>().ok()?;
+        if !seconds.is_finite() || seconds < 0.0 {
+            return None;
+        }
+        (seconds * 1000.0).round() as i64
+    };
+    Some(sign * ms)
+}
+
+/// Parses a stretch factor given as a number (`1.001`) or as a ratio of frame
+/// rates (`25/23.976`). An empty value leaves the timing unchanged.
+pub fn parse_factor(value: &str) -> Option<f64> {
+    let number = |text: &str| text.trim().replace(',', ".").parse::<f64>().ok();
+    let value = value.trim();
+    let factor = if value.is_empty() {
+        1.0
+    } else if let Some((numerator, denominator)) = value.split_once('/') {
+        number(numerator)? / number(denominator)?
+    } else {
+        number(value)?
+    };
+    (factor.is_finite() && factor > 0.0).then_some(factor)
+}
+
+fn format_time(ms: u64, format: SubtitleFormat) -> String {
+    let hours = ms / 3_600_000;
+    let minutes = (ms % 3_600_000) / 60_000;
+    let seconds = (ms % 60_000) / 1000;
+    format!(
+        "{hours:02}:{minutes:02}:{seconds:02}{}{:03}",
+        format.millis_separator(),
+        ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRT: &str = "1\r\n00:00:01,000 --> 00:00:02,500\r\nHello\r\nthere\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000\r\nBye\r\n";
+
+    #[test]
+    fn srt_round_trip_keeps_numbering() {
+        let track = SubtitleTrack::parse(SRT, SubtitleFormat::Srt, Language::English).unwrap();
+        assert_eq!(track.cues.len(), 2);
+        assert_eq!(track.cues[0].text, "Hello\nthere");
+        assert_eq!(track.cues[1].start_ms, 3000);
+        assert_eq!(
+            track.serialize(SubtitleFormat::Srt),
+            SRT.replace("\r\n", "\n")
+        );
+    }
+
+    #[test]
+    fn display_text_edits_are_applied() {
+        let mut track = SubtitleTrack::parse(SRT, SubtitleFormat::Srt, Language::English).unwrap();
+        let shown = track.display_text(true);
+        assert_eq!(
+            shown,
+            "[00:00:01,000 --> 00:00:02,500]\nHello\nthere\n\n[00:00:03,000 --> 00:00:04,000]\nBye"
+        );
+        let edited = shown
+            .replace("Bye", "Goodbye")
+            .replace("00:00:03,000", "00:00:03,200");
+        track
+            .apply_display_text(&edited, true, Language::English)
+            .unwrap();
+        assert_eq!(track.cues[1].text, "Goodbye");
+        assert_eq!(track.cues[1].start_ms, 3200);
+        assert!(
+            track
+                .apply_display_text("only one", false, Language::English)
+                .is_err()
+        );
+        assert_eq!(cue_offset(&shown, 1), shown.find("[00:00:03"));
+    }
+
+    #[test]
+    fn empty_cues_survive_hidden_timestamps() {
+        let srt = "1\n00:00:01,000 --> 00:00:02,000\nHi\n\n2\n00:00:03,000 --> 00:00:04,000\n\n3\n00:00:05,000 --> 00:00:06,000\nBye\n";
+        let mut track = SubtitleTrack::parse(srt, SubtitleFormat::Srt, Language::English).unwrap();
+        assert_eq!(track.cues.len(), 3);
+        let shown = track.display_text(false);
+        assert_eq!(shown, "Hi\n\n[...]\n\nBye");
+        track
+            .apply_display_text(&shown, false, Language::English)
+            .unwrap();
+        assert_eq!(track.serialize(SubtitleFormat::Srt), srt);
+        assert_eq!(cue_at_offset(&shown, shown.find("Bye").unwrap()), Some(2));
+    }
+
+    #[test]
+    fn vtt_keeps_header_notes_and_settings() {
+        let vtt =
+            "WEBVTT - demo\n\nNOTE a comment\n\nintro\n00:01.000 --> 00:02.000 align:start\nHi\n";
+        let mut track = SubtitleTrack::parse(vtt, SubtitleFormat::Vtt, Language::English).unwrap();
+        assert_eq!(track.cues[0].id.as_deref(), Some("intro"));
+        track.adjust_timing(2.0, -500);
+        assert_eq!(
+            track.serialize(SubtitleFormat::Vtt),
+            "WEBVTT - demo\n\nNOTE a comment\n\nintro\n00:00:01.500 --> 00:00:03.500 align:start\nHi\n"
+        );
+        assert_eq!(
+            track.serialize(SubtitleFormat::Srt),
+            "1\n00:00:01,500 --> 00:00:03,500\nHi\n"
+        );
+        assert_eq!(track.cue_index_at(0), Some(0));
+        assert_eq!(track.cue_index_at(9_000), Some(0));
+    }
+
+    #[test]
+    fn shift_and_factor_inputs() {
+        assert_eq!(parse_shift("-1,5"), Some(-1500));
+        assert_eq!(parse_shift("+00:00:02.250"), Some(2250));
+        assert_eq!(parse_shift(""), Some(0));
+        assert_eq!(parse_shift("abc"), None);
+        assert_eq!(parse_factor("2"), Some(2.0));
+        assert_eq!(parse_factor("25/25"), Some(1.0));
+        assert_eq!(parse_factor("0"), None);
+    }
+}
@@ -0,0 +1,362 @@
+//! EPUB 3 talking books: the document split into sentences, one narration MP3 and a
+//! SMIL media overlay that lets reading systems highlight each sentence while it plays.
+
+use crate::settings::Language;
+use std::io::Write;
+use std::path::Path;
+use zip::CompressionMethod;
+use zip::write::{FileOptions, ZipWriter};
+
+/// Sentences longer than this are cut at the next space so highlights stay short.
+const MAX_SENTENCE_CHARS: usize = 400;
+const AUDIO_HREF: &str = "audio/narration.mp3";
+const ACTIVE_CLASS: &str = "-epub-media-overlay-active";
+
+/// A paragraph of the book, already split into the sentences that get their own clip.
+pub type Paragraph = Vec<String>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Clip {
+    pub begin_ms: u64,
+    pub end_ms: u64,
+}
+
+pub struct TalkingBook<'a> {
+    pub identifier: &'a str,
+    pub title: &'a str,
+    pub language: &'a str,
+    pub paragraphs: &'a [Paragraph],
+    /// One clip per spoken sentence, in reading order.
+    pub clips: &'a [Clip],
+}
+
+/// Splits text into paragraphs (non-empty lines) of sentences.
+pub fn paragraphs(text: &str) -> Vec<Paragraph> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(sentences)
+        .collect()
+}
+
+/// Sentences without letters or digits (separators, lone punctuation) are shown but not read.
+pub fn is_spoken(sentence: &str) -> bool {
+    sentence.chars().any(char::is_alphanumeric)
+}
+
+/// Turns the audio offsets reached after each sentence into consecutive clips.
+pub fn clips_from_ends(ends: &[u64]) -> Vec<Clip> {
+    let mut begin_ms = 0;
+    ends.iter()
+        .map(|&end| {
+            let end_ms = end.max(begin_ms);
+            let clip = Clip { begin_ms, end_ms };
+            begin_ms = end_ms;
+            clip
+        })
+        .collect()
+}
+
+/// BCP 47 tag for the package: the locale prefix of Edge voices ("it-IT-IsabellaNeural"),
+/// otherwise the interface language.
+pub fn language_tag(voice: &str, fallback: Language) -> String {
+    let mut parts = voice.split('-');
+    if let (Some(lang), Some(region)) = (parts.next(), parts.next())
+        && lang.len() == 2
+        && lang.chars().all(|c| c.is_ascii_lowercase())
+        && region.len() == 2
+        && region.chars().all(|c| c.is_ascii_uppercase())
+    {
+        return format!("{lang}-{region}");
+    }
+    match fallback {
+        Language::Italian => "it",
+        Language::English => "en",
+        Language::Spanish => "es",
+        Language::Portuguese => "pt",
+        Language::Vietnamese => "vi",
+    }
+    .to_string()
+}
+
+fn sentences(line: &str) -> Paragraph {
+    let mut out = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0usize;
+    let chars: Vec<char> = line.chars().collect();
+    for (idx, &ch) in chars.iter().enumerate() {
+        current.push(ch);
+        current_chars += 1;
+        let next = chars.get(idx + 1).copied();
+        if !next.is_none_or(char::is_whitespace) {
+            continue;
+        }
+        let ends_sentence = current
+            .trim_end_matches(['"', '\'', ')', ']', '»', '”', '’'])
+            .ends_with(['.', '!', '?', '…']);
+        if ends_sentence || (ch.is_whitespace() && current_chars >= MAX_SENTENCE_CHARS) {
+            push_sentence(&mut out, &current);
+            current.clear();
+            current_chars = 0;
+        }
+    }
+    push_sentence(&mut out, &current);
+    out
+}
+
+fn push_sentence(out: &mut Paragraph, sentence: &str) {
+    let sentence = sentence.trim();
+    if !sentence.is_empty() {
+        out.push(sentence.to_string());
+    }
+}
+
+/// SMIL clock value, e.g. `0:01:02.345`.
+fn clock_value(ms: u64) -> String {
+    format!(
+        "{}:{:02}:{:02}.{:03}",
+        ms / 3_600_000,
+        (ms / 60_000) % 60,
+        (ms / 1000) % 60,
+        ms % 1000
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+impl TalkingBook<'_> {
+    fn duration_ms(&self) -> u64 {
+        self.clips.last().map(|clip| clip.end_ms).unwrap_or(0)
+    }
+
+    fn content_xhtml(&self) -> String {
+        let title = escape_xml(self.title);
+        let lang = escape_xml(self.language);
+        let mut out = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n\
+             <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" \
+             xml:lang=\"{lang}\" lang=\"{lang}\">\n<head>\n<meta charset=\"UTF-8\"/>\n\
+             <title>{title}</title>\n<link rel=\"stylesheet\" type=\"text/css\" href=\"style.css\"/>\n\
+             </head>\n<body epub:type=\"bodymatter\">\n<h1>{title}</h1>\n"
+        );
+        let mut spoken = 0usize;
+        for paragraph in self.paragraphs {
+            out.push_str("<p>");
+            for (idx, sentence) in paragraph.iter().enumerate() {
+                if idx > 0 {
+                    out.push(' ');
+                }
+                if is_spoken(sentence) {
+                    spoken += 1;
+                    out.push_str(&format!(
+                        "<span id=\"s{spoken}\">{}</span>",
+                        escape_xml(sentence)
+                    ));
+                } else {
+                    out.push_str(&escape_xml(sentence));
+                }
+            }
+            out.push_str("</p>\n");
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    fn overlay_smil(&self) -> String {
+        let mut out = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <smil xmlns=\"http://www.w3.org/ns/SMIL\" xmlns:epub=\"http://www.idpf.org/2007/ops\" \
+             version=\"3.0\">\n<body>\n\
+             <seq id=\"seq1\" epub:textref=\"content.xhtml\" epub:type=\"bodymatter\">\n",
+        );
+        for (idx, clip) in self.clips.iter().enumerate() {
+            let n = idx + 1;
+            out.push_str(&format!(
+                "<par id=\"p{n}\"><text src=\"content.xhtml#s{n}\"/>\
+                 <audio src=\"{AUDIO_HREF}\" clipBegin=\"{}\" clipEnd=\"{}\"/></par>\n",
+                clock_value(clip.begin_ms),
+                clock_value(clip.end_ms)
+            ));
+        }
+        out.push_str("</seq>\n</body>\n</smil>\n");
+        out
+    }
+
+    fn nav_xhtml(&self) -> String {
+        let title = escape_xml(self.title);
+        let lang = escape_xml(self.language);
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n\
+             <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" \
+             xml:lang=\"{lang}\" lang=\"{lang}\">\n<head>\n<meta charset=\"UTF-8\"/>\n\
+             <title>{title}</title>\n</head>\n<body>\n<nav epub:type=\"toc\" id=\"toc\">\n\
+             <h1>{title}</h1>\n<ol>\n<li><a href=\"content.xhtml\">{title}</a></li>\n</ol>\n\
+             </nav>\n</body>\n</html>\n"
+        )
+    }
+
+    fn package_opf(&self, modified: &str) -> String {
+        let duration = clock_value(self.duration_ms());
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" \
+             unique-identifier=\"book-id\" xml:lang=\"{lang}\">\n\
+             <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n\
+             <dc:identifier id=\"book-id\">{identifier}</dc:identifier>\n\
+             <dc:title>{title}</dc:title>\n\
+             <dc:language>{lang}</dc:language>\n\
+             <meta property=\"dcterms:modified\">{modified}</meta>\n\
+             <meta property=\"media:duration\" refines=\"#overlay\">{duration}</meta>\n\
+             <meta property=\"media:duration\">{duration}</meta>\n\
+             <meta property=\"media:active-class\">{ACTIVE_CLASS}</meta>\n\
+             </metadata>\n<manifest>\n\
+             <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n\
+             <item id=\"style\" href=\"style.css\" media-type=\"text/css\"/>\n\
+             <item id=\"content\" href=\"content.xhtml\" media-type=\"application/xhtml+xml\" \
+             media-overlay=\"overlay\"/>\n\
+             <item id=\"overlay\" href=\"content.smil\" media-type=\"application/smil+xml\"/>\n\
+             <item id=\"audio\" href=\"{AUDIO_HREF}\" media-type=\"audio/mpeg\"/>\n\
+             </manifest>\n<spine>\n<itemref idref=\"content\"/>\n</spine>\n</package>\n",
+            lang = escape_xml(self.language),
+            identifier = escape_xml(self.identifier),
+            title = escape_xml(self.title),
+        )
+    }
+
+    /// Writes the package to `output`, embedding the narration found at `audio`.
+    pub fn write_epub(&self, output: &Path, audio: &Path) -> Result<(), String> {
+        let modified = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let file = std::fs::File::create(output).map_err(|err| err.to_string())?;
+        let mut zip = ZipWriter::new(file);
+        let stored = FileOptions::default().compression_method(CompressionMethod::Stored);
+        let deflated = FileOptions::default();
+        let result = (|| -> Result<(), String> {
+            // The mimetype entry must come first and be stored uncompressed.
+            zip.start_file("mimetype", stored)
+                .map_err(|err| err.to_string())?;
+            zip.write_all(b"application/epub+zip")
+                .map_err(|err| err.to_string())?;
+            let entries = [
+                (
+                    "META-INF/container.xml",
+                    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                     <container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">\n\
+                     <rootfiles>\n<rootfile full-path=\"EPUB/package.opf\" \
+                     media-type=\"application/oebps-package+xml\"/>\n</rootfiles>\n</container>\n"
+                        .to_string(),
+                ),
+                ("EPUB/package.opf", self.package_opf(&modified)),
+                ("EPUB/nav.xhtml", self.nav_xhtml()),
+                (
+                    "EPUB/style.css",
+                    format!(
+                        "body {{ line-height: 1.5; }}\n\
+                         .{ACTIVE_CLASS} {{ background-color: #ffff00; color: #000000; }}\n"
+                    ),
+                ),
+                ("EPUB/content.xhtml", self.content_xhtml()),
+                ("EPUB/content.smil", self.overlay_smil()),
+            ];
+            for (name, content) in entries {
+                zip.start_file(name, deflated)
+                    .map_err(|err| err.to_string())?;
+                zip.write_all(content.as_bytes())
+                    .map_err(|err| err.to_string())?;
+            }
+            zip.start_file(format!("EPUB/{AUDIO_HREF}"), stored)
+                .map_err(|err| err.to_string())?;
+            let mut narration = std::fs::File::open(audio).map_err(|err| err.to_string())?;
+            std::io::copy(&mut narration, &mut zip).map_err(|err| err.to_string())?;
+            zip.finish().map_err(|err| err.to_string())?;
+            Ok(())
+        })();
+        drop(zip);
+        if result.is_err() {
+            let _ = std::fs::remove_file(output);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_paragraphs_into_sentences() {
+        let text = "Hello there. \"How are you?\" Fine!\n\n  ***  \nLast line without stop";
+        let paragraphs = paragraphs(text);
+        assert_eq!(
+            paragraphs,
+            vec![
+                vec![
+                    "Hello there.".to_string(),
+                    "\"How are you?\"".to_string(),
+                    "Fine!".to_string()
+                ],
+                vec!["***".to_string()],
+                vec!["Last line without stop".to_string()],
+            ]
+        );
+        assert!(!is_spoken("***"));
+        assert_eq!(sentences("v1.2 is out."), vec!["v1.2 is out.".to_string()]);
+    }
+
+    #[test]
+    fn overlay_points_each_spoken_sentence_at_its_clip() {
+        let paragraphs = vec![
+            vec!["One.".to_string(), "Two & three.".to_string()],
+            vec!["---".to_string()],
+        ];
+        let clips = clips_from_ends(&[1500, 61_250]);
+        assert_eq!(
+            clips[1],
+            Clip {
+                begin_ms: 1500,
+                end_ms: 61_250
+            }
+        );
+        let book = TalkingBook {
+            identifier: "urn:uuid:test",
+            title: "Book",
+            language: "en-US",
+            paragraphs: &paragraphs,
+            clips: &clips,
+        };
+        let content = book.content_xhtml();
+        assert!(content.contains(
+            "<p><span id=\"s1\">One.</span> <span id=\"s2\">Two &amp; three.</span></p>"
+        ));
+        assert!(content.contains("<p>---</p>"));
+        let smil = book.overlay_smil();
+        assert!(smil.contains(
+            "<text src=\"content.xhtml#s2\"/><audio src=\"audio/narration.mp3\" \
+             clipBegin=\"0:00:01.500\" clipEnd=\"0:01:01.250\"/>"
+        ));
+        assert!(
+            book.package_opf("2024-01-01T00:00:00Z")
+                .contains("<meta property=\"media:duration\">0:01:01.250</meta>")
+        );
+        assert_eq!(
+            language_tag("it-IT-IsabellaNeural", Language::English),
+            "it-IT"
+        );
+        assert_eq!(
+            language_tag("Microsoft Zira Desktop", Language::Spanish),
+            "es"
+        );
+    }
+}
@@ -0,0 +1,425 @@
+use encoding_rs::WINDOWS_1252;
+
+const BASE64_STANDARD: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_URL_SAFE: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+/// Longest quoted-printable line, soft line break included (RFC 2045).
+const QUOTED_PRINTABLE_LINE: usize = 76;
+
+/// Encodings of Edit > Text > Encode/Decode.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TextCodec {
+    Base64,
+    /// Base64 with `-` and `_` and no padding, as used in URLs and tokens.
+    Base64Url,
+    Percent,
+    HtmlEntities,
+    QuotedPrintable,
+    UnicodeEscape,
+}
+
+impl TextCodec {
+    pub const ALL: [TextCodec; 6] = [
+        TextCodec::Base64,
+        TextCodec::Base64Url,
+        TextCodec::Percent,
+        TextCodec::HtmlEntities,
+        TextCodec::QuotedPrintable,
+        TextCodec::UnicodeEscape,
+    ];
+
+    pub fn encode_label_key(self) -> &'static str {
+        match self {
+            TextCodec::Base64 => "codec.encode_base64",
+            TextCodec::Base64Url => "codec.encode_base64_url",
+            TextCodec::Percent => "codec.encode_percent",
+            TextCodec::HtmlEntities => "codec.encode_html",
+            TextCodec::QuotedPrintable => "codec.encode_quoted_printable",
+            TextCodec::UnicodeEscape => "codec.encode_unicode_escape",
+        }
+    }
+
+    pub fn decode_label_key(self) -> &'static str {
+        match self {
+            TextCodec::Base64 => "codec.decode_base64",
+            TextCodec::Base64Url => "codec.decode_base64_url",
+            TextCodec::Percent => "codec.decode_percent",
+            TextCodec::HtmlEntities => "codec.decode_html",
+            TextCodec::QuotedPrintable => "codec.decode_quoted_printable",
+            TextCodec::UnicodeEscape => "codec.decode_unicode_escape",
+        }
+    }
+
+    pub fn encode(self, text: &str) -> String {
+        match self {
+            TextCodec::Base64 => encode_base64(text.as_bytes(), BASE64_STANDARD, true),
+            TextCodec::Base64Url => encode_base64(text.as_bytes(), BASE64_URL_SAFE, false),
+            TextCodec::Percent => encode_percent(text),
+            TextCodec::HtmlEntities => encode_html_entities(text),
+            TextCodec::QuotedPrintable => encode_quoted_printable(text),
+            TextCodec::UnicodeEscape => encode_unicode_escapes(text),
+        }
+    }
+
+    /// None when `text` is not valid for the encoding, or Base64 that does not hold
+    /// UTF-8 text. Percent and quoted-printable bytes that are not UTF-8 are read as
+    /// Windows-1252, like old web pages and mail.
+    pub fn decode(self, text: &str) -> Option<String> {
+        match self {
+            TextCodec::Base64 | TextCodec::Base64Url => {
+                String::from_utf8(decode_base64(text)?).ok()
+            }
+            TextCodec::Percent => Some(legacy_text(decode_percent(text))),
+            TextCodec::HtmlEntities => Some(decode_html_entities(text)),
+            TextCodec::QuotedPrintable => Some(legacy_text(decode_quoted_printable(text))),
+            TextCodec::UnicodeEscape => Some(crate::tools::reader::decode_unicode(text)),
+        }
+    }
+}
+
+fn legacy_text(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes)
+        .unwrap_or_else(|err| WINDOWS_1252.decode(err.as_bytes()).0.into_owned())
+}
+
+fn encode_base64(bytes: &[u8], alphabet: &[u8; 64], pad: bool) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |acc, (idx, byte)| {
+            acc | ((*byte as u32) << (16 - 8 * idx))
+        });
+        for idx in 0..=chunk.len() {
+            out.push(alphabet[((group >> (18 - 6 * idx)) & 0x3f) as usize] as char);
+        }
+        if pad {
+            for _ in chunk.len()..3 {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Accepts both alphabets, with or without padding; whitespace is skipped.
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u8> = text
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .collect::<String>()
+        .trim_end_matches('=')
+        .chars()
+        .map(|ch| match ch {
+            'A'..='Z' => Some(ch as u8 - b'A'),
+            'a'..='z' => Some(ch as u8 - b'a' + 26),
+            '0'..='9' => Some(ch as u8 - b'0' + 52),
+            '+' | '-' => Some(62),
+            '/' | '_' => Some(63),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+    if digits.len() % 4 == 1 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(digits.len() * 3 / 4);
+    for chunk in digits.chunks(4) {
+        let group = chunk.iter().enumerate().fold(0u32, |acc, (idx, digit)| {
+            acc | ((*digit as u32) << (18 - 6 * idx))
+        });
+        for idx in 0..chunk.len() - 1 {
+            out.push((group >> (16 - 8 * idx)) as u8);
+        }
+    }
+    Some(out)
+}
+
+/// Percent-encodes everything but the unreserved characters of RFC 3986.
+fn encode_percent(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
+
+/// A `%` not followed by two hex digits is kept as it is.
+fn decode_percent(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%'
+            && let Some(byte) = hex_byte(bytes.get(idx + 1..idx + 3))
+        {
+            out.push(byte);
+            idx += 3;
+        } else {
+            out.push(bytes[idx]);
+            idx += 1;
+        }
+    }
+    out
+}
+
+fn hex_byte(digits: Option<&[u8]>) -> Option<u8> {
+    let digits = std::str::from_utf8(digits?).ok()?;
+    if !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    u8::from_str_radix(digits, 16).ok()
+}
+
+fn encode_html_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            '\u{00A0}' => out.push_str("&nbsp;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Named entities commonly found in mail and web pages.
+const HTML_ENTITIES: &[(&str, char)] = &[
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("apos", '\''),
+    ("nbsp", '\u{00A0}'),
+    ("copy", '©'),
+    ("reg", '®'),
+    ("trade", '™'),
+    ("euro", '€'),
+    ("pound", '£'),
+    ("deg", '°'),
+    ("hellip", '…'),
+    ("ndash", '–'),
+    ("mdash", '—'),
+    ("lsquo", '‘'),
+    ("rsquo", '’'),
+    ("ldquo", '“'),
+    ("rdquo", '”'),
+    ("laquo", '«'),
+    ("raquo", '»'),
+    ("agrave", 'à'),
+    ("aacute", 'á'),
+    ("acirc", 'â'),
+    ("atilde", 'ã'),
+    ("ccedil", 'ç'),
+    ("egrave", 'è'),
+    ("eacute", 'é'),
+    ("ecirc", 'ê'),
+    ("igrave", 'ì'),
+    ("iacute", 'í'),
+    ("ntilde", 'ñ'),
+    ("ograve", 'ò'),
+    ("oacute", 'ó'),
+    ("ocirc", 'ô'),
+    ("otilde", 'õ'),
+    ("ugrave", 'ù'),
+    ("uacute", 'ú'),
+    ("uuml", 'ü'),
+    ("Agrave", 'À'),
+    ("Aacute", 'Á'),
+    ("Egrave", 'È'),
+    ("Eacute", 'É'),
+    ("Ograve", 'Ò'),
+    ("Oacute", 'Ó'),
+    ("Ugrave", 'Ù'),
+    ("Uacute", 'Ú'),
+    ("Ccedil", 'Ç'),
+    ("Ntilde", 'Ñ'),
+];
+
+/// Decodes named and numeric entities; unknown ones are kept as they are.
+fn decode_html_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| Some((html_entity(&rest[1..=end])?, end + 2)));
+        match decoded {
+            Some((ch, len)) => {
+                out.push(ch);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn html_entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    HTML_ENTITIES
+        .iter()
+        .find(|(entity, _)| *entity == name)
+        .map(|(_, ch)| *ch)
+}
+
+/// Encodes the UTF-8 bytes of each line, keeping the line breaks and wrapping long
+/// lines with soft breaks.
+fn encode_quoted_printable(text: &str) -> String {
+    let eol = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut out = String::with_capacity(text.len());
+    for (line_idx, line) in text.split('\n').enumerate() {
+        if line_idx > 0 {
+            out.push_str(eol);
+        }
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let bytes = line.as_bytes();
+        let mut width = 0;
+        for (idx, byte) in bytes.iter().enumerate() {
+            let last = idx + 1 == bytes.len();
+            let literal =
+                matches!(byte, 33..=60 | 62..=126) || (!last && matches!(byte, b' ' | b'\t'));
+            let token = if literal {
+                (*byte as char).to_string()
+            } else {
+                format!("={byte:02X}")
+            };
+            // Room is left for the "=" of a soft break, unless this ends the line.
+            let limit = if last {
+                QUOTED_PRINTABLE_LINE
+            } else {
+                QUOTED_PRINTABLE_LINE - 1
+            };
+            if width + token.len() > limit {
+                out.push('=');
+                out.push_str(eol);
+                width = 0;
+            }
+            width += token.len();
+            out.push_str(&token);
+        }
+    }
+    out
+}
+
+/// Joins soft line breaks and decodes `=XX`; a stray `=` is kept.
+fn decode_quoted_printable(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] != b'=' {
+            out.push(bytes[idx]);
+            idx += 1;
+        } else if bytes[idx + 1..].starts_with(b"\r\n") {
+            idx += 3;
+        } else if bytes[idx + 1..].starts_with(b"\n") {
+            idx += 2;
+        } else if let Some(byte) = hex_byte(bytes.get(idx + 1..idx + 3)) {
+            out.push(byte);
+            idx += 3;
+        } else {
+            out.push(b'=');
+            idx += 1;
+        }
+    }
+    out
+}
+
+/// Writes every non-ASCII character as `\uXXXX`, with surrogate pairs above U+FFFF.
+fn encode_unicode_escapes(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if ch.is_ascii() {
+            out.push(ch);
+            continue;
+        }
+        let mut units = [0u16; 2];
+        for unit in ch.encode_utf16(&mut units) {
+            out.push_str(&format!("\\u{unit:04X}"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_and_percent() {
+        for text in ["", "a", "ab", "abc", "perché? ~ok/"] {
+            for codec in [TextCodec::Base64, TextCodec::Base64Url, TextCodec::Percent] {
+                assert_eq!(codec.decode(&codec.encode(text)).as_deref(), Some(text));
+            }
+        }
+        assert_eq!(TextCodec::Base64.encode("perché?"), "cGVyY2jDqT8=");
+        assert_eq!(TextCodec::Base64Url.encode("perché?"), "cGVyY2jDqT8");
+        assert_eq!(
+            TextCodec::Base64.decode("cGVy\r\nY2jDqT8=").as_deref(),
+            Some("perché?")
+        );
+        assert_eq!(TextCodec::Base64.decode("not base64!"), None);
+        assert_eq!(TextCodec::Percent.encode("a b&é"), "a%20b%26%C3%A9");
+        assert_eq!(
+            TextCodec::Percent.decode("caf%E9 100%").as_deref(),
+            Some("café 100%")
+        );
+    }
+
+    #[test]
+    fn test_html_quoted_printable_and_escapes() {
+        assert_eq!(
+            TextCodec::HtmlEntities.encode("<a href=\"x\">Tom & Jerry</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&lt;/a&gt;"
+        );
+        assert_eq!(
+            TextCodec::HtmlEntities
+                .decode("caf&eacute; &#233;&#xE9; &unknown; AT&T")
+                .as_deref(),
+            Some("café éé &unknown; AT&T")
+        );
+        let long = "é".repeat(30);
+        let encoded = TextCodec::QuotedPrintable.encode(&format!("a = b \r\n{long}"));
+        assert!(encoded.starts_with("a =3D b=20\r\n=C3=A9"));
+        assert!(
+            encoded
+                .lines()
+                .all(|line| line.len() <= QUOTED_PRINTABLE_LINE)
+        );
+        assert_eq!(
+            TextCodec::QuotedPrintable.decode(&encoded).as_deref(),
+            Some(format!("a = b \r\n{long}").as_str())
+        );
+        assert_eq!(
+            TextCodec::QuotedPrintable.decode("caf=E9 =\nok").as_deref(),
+            Some("café ok")
+        );
+        let text = "città 😀";
+        let escaped = TextCodec::UnicodeEscape.encode(text);
+        assert_eq!(escaped, "citt\\u00E0 \\uD83D\\uDE00");
+        assert_eq!(
+            TextCodec::UnicodeEscape.decode(&escaped).as_deref(),
+            Some(text)
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use crate::editor_manager::{apply_macro_steps, apply_macro_to_edit, macro_target_edits};
+use crate::file_handler::{
+    PdfTextResult, decode_text, encode_text_checked, is_doc_path, is_docx_path, is_epub_path,
+    is_html_path, is_mobi_path, is_mp3_path, is_pdf_path, is_ppt_path, is_pptx_path,
+    is_spreadsheet_path, read_pdf_text_with_status,
+};
+use crate::i18n;
+use crate::menu::*;
+use crate::settings::{AppSettings, MacroStep, TextMacro, save_settings};
+use crate::{show_error, show_info, with_state};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use windows::Win32::Foundation::HWND;
+
+/// The macro step an Edit menu command records, if it is one of the recordable
+/// text transforms.
+fn step_for_command(cmd_id: usize) -> Option<MacroStep> {
+    let step = match cmd_id {
+        IDM_EDIT_STRIP_MARKDOWN => MacroStep::StripMarkdown,
+        IDM_EDIT_NORMALIZE_WHITESPACE => MacroStep::NormalizeWhitespace,
+        IDM_EDIT_HARD_LINE_BREAK => MacroStep::HardLineBreak,
+        IDM_EDIT_ORDER_ITEMS => MacroStep::OrderItems,
+        IDM_EDIT_KEEP_UNIQUE_ITEMS => MacroStep::KeepUniqueItems,
+        IDM_EDIT_REVERSE_ITEMS => MacroStep::ReverseItems,
+        IDM_EDIT_QUOTE_LINES => MacroStep::QuoteLines,
+        IDM_EDIT_UNQUOTE_LINES => MacroStep::UnquoteLines,
+        IDM_EDIT_JOIN_LINES => MacroStep::JoinLines,
+        IDM_EDIT_CLEAN_EOL_HYPHENS => MacroStep::CleanEolHyphens,
+        IDM_EDIT_REMOVE_DUPLICATE_LINES => MacroStep::RemoveDuplicateLines,
+        IDM_EDIT_REMOVE_DUPLICATE_CONSECUTIVE_LINES => MacroStep::RemoveDuplicateConsecutiveLines,
+        IDM_EDIT_FIX_MOJIBAKE => MacroStep::FixMojibake,
+        IDM_EDIT_CASE_UPPER => MacroStep::UpperCase,
+        IDM_EDIT_CASE_LOWER => MacroStep::LowerCase,
+        IDM_EDIT_CASE_SENTENCE => MacroStep::SentenceCase,
+        IDM_EDIT_CASE_TITLE => MacroStep::TitleCase,
+        IDM_EDIT_CASE_TOGGLE => MacroStep::ToggleCase,
+        IDM_EDIT_CASE_CAPITALIZE_SENTENCES => MacroStep::CapitalizeSentences,
+        IDM_EDIT_TRANSLITERATE => MacroStep::Transliterate,
+        IDM_EDIT_TO_ASCII => MacroStep::TransliterateToAscii,
+        IDM_EDIT_REMOVE_LINE_NUMBERS => MacroStep::RemoveLineNumbers,
+        _ => return None,
+    };
+    Some(step)
+}
+
+/// Records the step of a menu command once it has changed the document, so refused
+/// or no-op commands are left out of the macro.
+pub unsafe fn record_command(hwnd: HWND, cmd_id: usize) {
+    if let Some(step) = step_for_command(cmd_id) {
+        record(hwnd, step);
+    }
+}
+
+/// Adds `step` to the macro being recorded; does nothing when not recording.
+pub unsafe fn record(hwnd: HWND, step: MacroStep) {
+    with_state(hwnd, |state| {
+        if let Some(steps) = state.macro_recording.as_mut() {
+            steps.push(step);
+        }
+    });
+}
+
+pub unsafe fn is_recording(hwnd: HWND) -> bool {
+    with_state(hwnd, |state| state.macro_recording.is_some()).unwrap_or(false)
+}
+
+pub unsafe fn start_recording(hwnd: HWND) {
+    let language = with_state(hwnd, |state| {
+        state.macro_recording = Some(Vec::new());
+        state.settings.language
+    })
+    .unwrap_or_default();
+    show_info(
+        hwnd,
+        language,
+        &i18n::tr(language, "macro.recording_started"),
+    );
+}
+
+/// Ends recording and saves the steps as a new macro, then opens the macro
+/// manager on it so it can be named and given a shortcut.
+pub unsafe fn stop_recording(hwnd: HWND) {
+    let Some((language, recorded)) = with_state(hwnd, |state| {
+        (state.settings.language, state.macro_recording.take())
+    }) else {
+        return;
+    };
+    let steps = match recorded {
+        None => {
+            show_info(hwnd, language, &i18n::tr(language, "macro.not_recording"));
+            return;
+        }
+        Some(steps) if steps.is_empty() => {
+            show_info(
+                hwnd,
+                language,
+                &i18n::tr(language, "macro.nothing_recorded"),
+            );
+            return;
+        }
+        Some(steps) => steps,
+    };
+    let count = steps.len();
+    let Some(index) = with_state(hwnd, |state| {
+        let number = (state.settings.macros.len() + 1).to_string();
+        state.settings.macros.push(TextMacro {
+            name: i18n::tr_f(language, "macro.default_name", &[("number", &number)]),
+            shortcut: 0,
+            steps,
+        });
+        save_settings(state.settings.clone());
+        state.settings.macros.len() - 1
+    }) else {
+        return;
+    };
+    crate::app_windows::macros_window::open(hwnd, Some(index));
+    crate::accessibility::nvda_speak(&i18n::tr_f(
+        language,
+        "macro.recorded",
+        &[("count", &count.to_string())],
+    ));
+}
+
+/// Runs the macro assigned to Ctrl+Shift+`digit` on the current document.
+pub unsafe fn run_shortcut(hwnd: HWND, digit: u8) {
+    let Some((language, found)) = with_state(hwnd, |state| {
+        let found = state
+            .settings
+            .macros
+            .iter()
+            .find(|m| m.shortcut == digit)
+            .cloned();
+        (state.settings.language, found)
+    }) else {
+        return;
+    };
+    let Some(text_macro) = found else {
+        let message = i18n::tr_f(
+            language,
+            "macro.no_shortcut",
+            &[("digit", &digit.to_string())],
+        );
+        show_info(hwnd, language, &message);
+        return;
+    };
+    match run_on_tabs(hwnd, &text_macro.steps, false) {
+        Ok(changed) => {
+            let key = if changed > 0 {
+                "macro.applied"
+            } else {
+                "macro.unchanged"
+            };
+            let message = i18n::tr_f(language, key, &[("name", &text_macro.name)]);
+            show_info(hwnd, language, &message);
+        }
+        Err(err) => {
+            let message = i18n::tr_f(language, "macro.error", &[("error", &err)]);
+            show_error(hwnd, language, &message);
+        }
+    }
+}
+
+/// Plays back `steps` on the current tab, or on every editable tab when `all_tabs`
+/// is set; returns how many documents changed.
+pub unsafe fn run_on_tabs(
+    hwnd: HWND,
+    steps: &[MacroStep],
+    all_tabs: bool,
+) -> Result<usize, String> {
+    let Some(settings) = with_state(hwnd, |state| state.settings.clone()) else {
+        return Ok(0);
+    };
+    let mut changed = 0;
+    for hwnd_edit in macro_target_edits(hwnd, all_tabs) {
+        if apply_macro_to_edit(hwnd, hwnd_edit, steps, &settings)? {
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+/// Plays back `steps` on files on disk. Plain text files are rewritten in their own
+/// encoding, unless the result no longer fits in it; documents that can only be read
+/// as text (PDF, Word, e-books...) get a UTF-8 copy beside them named `name (macro).txt`,
+/// numbered if needed so that no file is overwritten. Audio files, archives and
+/// encrypted documents are skipped. Returns the files that could not be processed.
+pub fn run_on_files(
+    paths: &[PathBuf],
+    steps: &[MacroStep],
+    settings: &AppSettings,
+) -> Vec<PathBuf> {
+    paths
+        .iter()
+        .filter(|path| run_on_file(path, steps, settings).is_none())
+        .cloned()
+        .collect()
+}
+
+fn run_on_file(path: &Path, steps: &[MacroStep], settings: &AppSettings) -> Option<()> {
+    let language = settings.language;
+    // Rewriting an archive or an encrypted document as text would destroy it.
+    if is_mp3_path(path)
+        || crate::archive::is_archive_path(path)
+        || crate::encryption::is_encrypted_file(path)
+    {
+        return None;
+    }
+    let converted = is_pdf_path(path)
+        || is_docx_path(path)
+        || is_doc_path(path)
+        || is_epub_path(path)
+        || is_mobi_path(path)
+        || is_html_path(path)
+        || is_spreadsheet_path(path)
+        || is_pptx_path(path)
+        || is_ppt_path(path);
+    if !converted {
+        let bytes = std::fs::read(path).ok()?;
+        let (text, encoding) = decode_text(&bytes, language).ok()?;
+        let processed = apply_macro_steps(&text, steps, settings).ok()?;
+        if processed != text {
+            // Leave the file alone rather than fill it with replacement characters.
+            let (bytes, unmappable) = encode_text_checked(&processed, encoding);
+            if unmappable {
+                return None;
+            }
+            std::fs::write(path, bytes).ok()?;
+        }
+        return Some(());
+    }
+    let text = if is_pdf_path(path) {
+        // A scanned PDF without a text layer has nothing to process.
+        match read_pdf_text_with_status(path, language).ok()? {
+            PdfTextResult::Text(text) => text,
+            PdfTextResult::NoText => return None,
+        }
+    } else {
+        crate::app_windows::find_in_files_window::read_text_for_search(path, language)?
+    };
+    let processed = apply_macro_steps(&text, steps, settings).ok()?;
+    write_converted_copy(path, &processed)
+}
+
+fn write_converted_copy(path: &Path, text: &str) -> Option<()> {
+    let stem = path.file_stem()?.to_string_lossy();
+    for number in 1.. {
+        let name = if number == 1 {
+            format!("{stem} (macro).txt")
+        } else {
+            format!("{stem} (macro {number}).txt")
+        };
+        match std::fs::File::create_new(path.with_file_name(name)) {
+            Ok(mut file) => return file.write_all(text.as_bytes()).ok(),
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(_) => return None,
+        }
+    }
+    None
+}
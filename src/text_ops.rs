@@ -1,3 +1,4 @@
+use crate::settings::{Language, LineEnding, SortMode};
 use std::collections::HashSet;
 
 /// Detects the end-of-line style: "\r\n" or "\n".
@@ -106,6 +107,873 @@ pub fn remove_duplicate_consecutive_lines(scope: &str) -> String {
     out
 }
 
+/// Splits the lines of `scope` into those accepted by `is_match` and the others, in their
+/// original order. Returns both groups, each keeping the scope's line ending and trailing
+/// line break, and the number of matching lines.
+pub fn partition_lines<F>(scope: &str, is_match: F) -> (String, String, usize)
+where
+    F: Fn(&str) -> bool,
+{
+    if scope.is_empty() {
+        return (String::new(), String::new(), 0);
+    }
+
+    let eol = detect_eol(scope);
+    let (content, trailing) = split_trailing_newline(scope);
+    let (matched, unmatched): (Vec<&str>, Vec<&str>) = split_lines_keep_empty(content)
+        .into_iter()
+        .partition(|line| is_match(line));
+
+    let join = |lines: &[&str]| {
+        let mut out = lines.join(eol);
+        if trailing && !lines.is_empty() {
+            out.push_str(eol);
+        }
+        out
+    };
+    (join(&matched), join(&unmatched), matched.len())
+}
+
+/// Detects the dominant line ending of freshly decoded file content.
+/// Text without line breaks, or with no clear majority, defaults to CRLF.
+pub fn detect_line_ending(text: &str) -> LineEnding {
+    let bytes = text.as_bytes();
+    let (mut crlf, mut lf, mut cr) = (0usize, 0usize, 0usize);
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\r' if bytes.get(i + 1) == Some(&b'\n') => {
+                crlf += 1;
+                i += 1;
+            }
+            b'\r' => cr += 1,
+            b'\n' => lf += 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    if lf > crlf && lf > cr {
+        LineEnding::Lf
+    } else if cr > crlf && cr > lf {
+        LineEnding::Cr
+    } else {
+        LineEnding::Crlf
+    }
+}
+
+/// Rewrites every line break (CRLF, LF or CR) as `line_ending`.
+pub fn convert_line_endings(text: &str, line_ending: LineEnding) -> String {
+    let normalized = text.replace("\r\n", "\n").replace('\r', "\n");
+    match line_ending {
+        LineEnding::Lf => normalized,
+        _ => normalized.replace('\n', line_ending.as_str()),
+    }
+}
+
+/// Byte index where line `line` (0-based) starts; CRLF, LF and CR all end a line.
+pub fn line_start_index(text: &str, line: usize) -> Option<usize> {
+    if line == 0 {
+        return Some(0);
+    }
+    let bytes = text.as_bytes();
+    let mut current = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        let is_break = match bytes[i] {
+            b'\r' if bytes.get(i + 1) == Some(&b'\n') => {
+                i += 1;
+                true
+            }
+            b'\r' | b'\n' => true,
+            _ => false,
+        };
+        i += 1;
+        if is_break {
+            current += 1;
+            if current == line {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+/// Number of lines in `text`, counting the (possibly empty) one after the last break.
+pub fn line_count(text: &str) -> usize {
+    let breaks = text.matches("\r\n").count();
+    let lone = text.bytes().filter(|&b| b == b'\r' || b == b'\n').count() - 2 * breaks;
+    breaks + lone + 1
+}
+
+/// Windows-1252 characters in the 0x80..=0x9F range, indexed by `byte - 0x80`.
+/// Bytes the code page leaves undefined decode to the matching C1 control.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+/// Maximum number of repair passes, so text encoded twice ("ÃƒÂ©") is also restored.
+const MOJIBAKE_MAX_PASSES: usize = 3;
+
+fn windows_1252_byte(ch: char) -> Option<u8> {
+    let code = ch as u32;
+    if code < 0x80 || (0xA0..=0xFF).contains(&code) {
+        return Some(code as u8);
+    }
+    WINDOWS_1252_HIGH
+        .iter()
+        .position(|candidate| *candidate == ch)
+        .map(|index| 0x80 + index as u8)
+}
+
+/// Reverses UTF-8 text that was decoded as Windows-1252 ("perchÃ©" becomes "perché").
+/// Only complete, valid UTF-8 sequences are rewritten; returns the text and the
+/// number of garbled sequences found in the original text.
+pub fn fix_mojibake(text: &str) -> (String, usize) {
+    let (mut current, repaired) = fix_mojibake_pass(text);
+    for _ in 1..MOJIBAKE_MAX_PASSES {
+        let (fixed, count) = fix_mojibake_pass(&current);
+        if count == 0 {
+            break;
+        }
+        current = fixed;
+    }
+    (current, repaired)
+}
+
+fn fix_mojibake_pass(text: &str) -> (String, usize) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut count = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        if let Some((decoded, consumed)) = decode_mojibake_sequence(&chars[i..]) {
+            out.push(decoded);
+            count += 1;
+            i += consumed;
+        } else {
+            out.push(chars[i]);
+            i += 1;
+        }
+    }
+    (out, count)
+}
+
+fn decode_mojibake_sequence(chars: &[char]) -> Option<(char, usize)> {
+    let lead = windows_1252_byte(*chars.first()?)?;
+    let len = match lead {
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => return None,
+    };
+    if chars.len() < len {
+        return None;
+    }
+    let mut bytes = [0u8; 4];
+    bytes[0] = lead;
+    for (slot, ch) in bytes[1..len].iter_mut().zip(&chars[1..len]) {
+        let byte = windows_1252_byte(*ch)?;
+        if !(0x80..=0xBF).contains(&byte) {
+            return None;
+        }
+        *slot = byte;
+    }
+    let decoded = std::str::from_utf8(&bytes[..len]).ok()?;
+    decoded.chars().next().map(|ch| (ch, len))
+}
+
+/// Options of the configurable line sort (Edit > Text > Sort lines).
+#[derive(Clone)]
+pub struct SortOptions {
+    pub mode: SortMode,
+    pub descending: bool,
+    pub case_sensitive: bool,
+    pub ignore_accents: bool,
+    /// 1-based field used as sort key; 0 sorts by the whole line.
+    pub field: usize,
+    /// Field separator; empty splits fields on runs of whitespace.
+    pub delimiter: String,
+    pub remove_duplicates: bool,
+    /// Alphabet used for collation, so "ñ" follows "n" in Spanish and "đ" follows "d" in Vietnamese.
+    pub language: Language,
+}
+
+/// Mark that turns a base letter into an accented or derived letter.
+/// Derived-letter marks come first, in Vietnamese alphabet order (a, ă, â; o, ô, ơ),
+/// followed by the accents in Vietnamese tone order.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Mark {
+    None,
+    Breve,
+    Circumflex,
+    Horn,
+    Stroke,
+    Grave,
+    Acute,
+    Hook,
+    Tilde,
+    DotBelow,
+    Diaeresis,
+    Ring,
+    Cedilla,
+}
+
+/// Lowercase letters that several alphabets treat as letters of their own.
+const DERIVED_LETTERS: &[(char, char, Mark)] = &[
+    ('ă', 'a', Mark::Breve),
+    ('â', 'a', Mark::Circumflex),
+    ('ê', 'e', Mark::Circumflex),
+    ('î', 'i', Mark::Circumflex),
+    ('ô', 'o', Mark::Circumflex),
+    ('û', 'u', Mark::Circumflex),
+    ('ơ', 'o', Mark::Horn),
+    ('ư', 'u', Mark::Horn),
+    ('đ', 'd', Mark::Stroke),
+    ('ø', 'o', Mark::Stroke),
+    ('ñ', 'n', Mark::Tilde),
+];
+
+/// Accented lowercase letters: each character of the first string is the matching
+/// letter of the second string (plain or derived) with the given accent.
+const ACCENTED_LETTERS: &[(Mark, &str, &str)] = &[
+    (Mark::Grave, "àèìòùỳằầềồờừ", "aeiouyăâêôơư"),
+    (Mark::Acute, "áéíóúýắấếốớứ", "aeiouyăâêôơư"),
+    (Mark::Hook, "ảẻỉỏủỷẳẩểổởử", "aeiouyăâêôơư"),
+    (Mark::Tilde, "ãẽĩõũỹẵẫễỗỡữ", "aeiouyăâêôơư"),
+    (Mark::DotBelow, "ạẹịọụỵặậệộợự", "aeiouyăâêôơư"),
+    (Mark::Diaeresis, "äëïöüÿ", "aeiouy"),
+    (Mark::Ring, "å", "a"),
+    (Mark::Cedilla, "ç", "c"),
+];
+
+/// Marks that make a separate letter of the alphabet in `language` rather than an accent.
+fn is_letter_mark(language: Language, mark: Mark) -> bool {
+    match language {
+        Language::Spanish => mark == Mark::Tilde,
+        Language::Vietnamese => matches!(
+            mark,
+            Mark::Breve | Mark::Circumflex | Mark::Horn | Mark::Stroke
+        ),
+        Language::Italian | Language::English | Language::Portuguese => false,
+    }
+}
+
+/// Splits a lowercase character into base letter, derived-letter mark and accent.
+fn decompose(ch: char) -> (char, Mark, Mark) {
+    let (letter, accent) = ACCENTED_LETTERS
+        .iter()
+        .find_map(|(mark, accented, plain)| {
+            accented
+                .chars()
+                .position(|candidate| candidate == ch)
+                .and_then(|idx| plain.chars().nth(idx))
+                .map(|letter| (letter, *mark))
+        })
+        .unwrap_or((ch, Mark::None));
+    match DERIVED_LETTERS
+        .iter()
+        .find(|(derived, _, _)| *derived == letter)
+    {
+        Some((_, base, mark)) => (*base, *mark, accent),
+        None => (letter, Mark::None, accent),
+    }
+}
+
+/// One primary collation element: digit runs only appear in natural order.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Element {
+    /// Significant digits of a number: shorter means smaller, then digit by digit.
+    Number(usize, String),
+    Char(u32, u8),
+}
+
+/// Multi-level collation key: letters first, then accents, then case.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct CollationKey {
+    primary: Vec<Element>,
+    secondary: Vec<(Mark, Mark)>,
+    tertiary: Vec<bool>,
+}
+
+fn collation_key(text: &str, options: &SortOptions, natural: bool) -> CollationKey {
+    let mut key = CollationKey {
+        primary: Vec::new(),
+        secondary: Vec::new(),
+        tertiary: Vec::new(),
+    };
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if natural && ch.is_ascii_digit() {
+            let mut digits = String::from(ch);
+            while let Some(next) = chars.next_if(char::is_ascii_digit) {
+                digits.push(next);
+            }
+            let significant = digits.trim_start_matches('0').to_string();
+            key.primary
+                .push(Element::Number(significant.len(), significant));
+            continue;
+        }
+        for lower in ch.to_lowercase() {
+            let (base, letter_mark, accent) = decompose(lower);
+            let (rank, letter_mark) = if is_letter_mark(options.language, letter_mark) {
+                (letter_mark as u8, Mark::None)
+            } else {
+                (0, letter_mark)
+            };
+            key.primary.push(Element::Char(base as u32, rank));
+            if !options.ignore_accents {
+                key.secondary.push((letter_mark, accent));
+            }
+            if options.case_sensitive {
+                key.tertiary.push(ch.is_uppercase());
+            }
+        }
+    }
+    key
+}
+
+/// Leading number of `text` (sign and either decimal separator allowed), if any.
+fn parse_number(text: &str) -> Option<f64> {
+    let start = text.find(|ch: char| ch.is_ascii_digit())?;
+    let negative = text[..start].ends_with('-');
+    let mut number = String::new();
+    let mut seen_separator = false;
+    for ch in text[start..].chars() {
+        if ch.is_ascii_digit() {
+            number.push(ch);
+        } else if (ch == '.' || ch == ',') && !seen_separator {
+            seen_separator = true;
+            number.push('.');
+        } else {
+            break;
+        }
+    }
+    let value: f64 = number.trim_end_matches('.').parse().ok()?;
+    Some(if negative { -value } else { value })
+}
+
+/// Text of the line the sort compares: the whole line or one trimmed field.
+fn sort_field<'a>(line: &'a str, options: &SortOptions) -> &'a str {
+    if options.field == 0 {
+        return line;
+    }
+    let field = if options.delimiter.is_empty() {
+        line.split_whitespace().nth(options.field - 1)
+    } else {
+        line.split(options.delimiter.as_str())
+            .nth(options.field - 1)
+    };
+    field.unwrap_or("").trim()
+}
+
+/// Sort key of a line, computed once before sorting; only numeric sorts fill `number`.
+struct LineKey {
+    number: Option<f64>,
+    collation: CollationKey,
+}
+
+fn line_key(line: &str, options: &SortOptions) -> LineKey {
+    let text = sort_field(line, options);
+    LineKey {
+        number: match options.mode {
+            SortMode::Numeric => parse_number(text),
+            SortMode::Alphabetical | SortMode::Natural => None,
+        },
+        collation: collation_key(text, options, options.mode != SortMode::Alphabetical),
+    }
+}
+
+fn compare_keys(a: &LineKey, b: &LineKey) -> std::cmp::Ordering {
+    match (a.number, b.number) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        // Lines without a number follow the numbered ones (precede them when descending).
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => a.collation.cmp(&b.collation),
+    }
+}
+
+/// Sorts the lines of `scope` according to `options`. The sort is stable, blank lines
+/// keep their position and, when duplicates are removed, the first occurrence of
+/// lines that compare equal as a whole is kept.
+pub fn sort_lines(scope: &str, options: &SortOptions) -> String {
+    if scope.is_empty() {
+        return String::new();
+    }
+
+    let eol = detect_eol(scope);
+    let (content, trailing) = split_trailing_newline(scope);
+    let lines = split_lines_keep_empty(content);
+
+    let mut sorted: Vec<&str> = lines
+        .iter()
+        .copied()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    if options.remove_duplicates {
+        let mut seen = HashSet::new();
+        sorted.retain(|line| seen.insert(collation_key(line, options, false)));
+    }
+    let mut keyed: Vec<(LineKey, &str)> = sorted
+        .into_iter()
+        .map(|line| (line_key(line, options), line))
+        .collect();
+    keyed.sort_by(|(a, _), (b, _)| {
+        let ordering = compare_keys(a, b);
+        if options.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+
+    let mut sorted = keyed.into_iter().map(|(_, line)| line);
+    let out_lines: Vec<&str> = lines
+        .into_iter()
+        .filter_map(|line| {
+            if line.trim().is_empty() {
+                Some(line)
+            } else {
+                sorted.next()
+            }
+        })
+        .collect();
+
+    let mut out = out_lines.join(eol);
+    if trailing {
+        out.push_str(eol);
+    }
+    out
+}
+
+/// Case operations of Edit > Change case.
+#[derive(Clone, Copy)]
+pub enum CaseConversion {
+    Upper,
+    Lower,
+    Sentence,
+    Title,
+    Toggle,
+    CapitalizeSentences,
+}
+
+/// Words left lowercase inside a title unless they open or close it.
+fn title_small_words(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::English => &[
+            "a", "an", "and", "as", "at", "but", "by", "for", "from", "in", "into", "nor", "of",
+            "off", "on", "or", "per", "so", "the", "to", "up", "via", "vs", "with", "yet",
+        ],
+        Language::Italian => &[
+            "a", "ad", "agli", "ai", "al", "alla", "alle", "allo", "che", "col", "con", "da",
+            "dagli", "dai", "dal", "dalla", "dalle", "dallo", "degli", "dei", "del", "della",
+            "delle", "dello", "di", "e", "ed", "fra", "gli", "i", "il", "in", "la", "le", "lo",
+            "ma", "negli", "nei", "nel", "nella", "nelle", "nello", "o", "od", "per", "su",
+            "sugli", "sui", "sul", "sulla", "sulle", "sullo", "tra", "un", "una", "uno",
+        ],
+        Language::Spanish => &[
+            "a", "al", "con", "de", "del", "e", "el", "en", "entre", "la", "las", "lo", "los",
+            "ni", "o", "para", "pero", "por", "que", "sin", "sobre", "u", "un", "una", "unas",
+            "unos", "y",
+        ],
+        Language::Portuguese => &[
+            "a", "à", "ao", "aos", "as", "às", "com", "da", "das", "de", "do", "dos", "e", "em",
+            "mas", "na", "nas", "nem", "no", "nos", "o", "os", "ou", "para", "pela", "pelas",
+            "pelo", "pelos", "por", "que", "sem", "um", "uma", "umas", "uns",
+        ],
+        Language::Vietnamese => &[],
+    }
+}
+
+/// Converts the case of `text`; `language` selects the small words of title case.
+pub fn convert_case(text: &str, conversion: CaseConversion, language: Language) -> String {
+    match conversion {
+        CaseConversion::Upper => text.to_uppercase(),
+        CaseConversion::Lower => text.to_lowercase(),
+        CaseConversion::Sentence => capitalize_sentences(text, true),
+        CaseConversion::Title => text
+            .split_inclusive('\n')
+            .map(|line| title_case_line(line, title_small_words(language)))
+            .collect(),
+        CaseConversion::Toggle => {
+            let mut out = String::with_capacity(text.len());
+            for ch in text.chars() {
+                if ch.is_uppercase() {
+                    out.extend(ch.to_lowercase());
+                } else {
+                    out.extend(ch.to_uppercase());
+                }
+            }
+            out
+        }
+        CaseConversion::CapitalizeSentences => capitalize_sentences(text, false),
+    }
+}
+
+/// Uppercases the first letter of every sentence: at the start of the text, after
+/// ".", "!", "?" or "…" followed by whitespace, and after a blank line. With
+/// `lowercase_rest` every other letter is lowercased (sentence case).
+fn capitalize_sentences(text: &str, lowercase_rest: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut capitalize = true;
+    let mut after_terminal = false;
+    let mut line_breaks = 0;
+    for ch in text.chars() {
+        if ch.is_alphabetic() {
+            if capitalize {
+                out.extend(ch.to_uppercase());
+            } else if lowercase_rest {
+                out.extend(ch.to_lowercase());
+            } else {
+                out.push(ch);
+            }
+            capitalize = false;
+            after_terminal = false;
+            line_breaks = 0;
+            continue;
+        }
+        match ch {
+            '.' | '!' | '?' | '…' => after_terminal = true,
+            '\n' => {
+                line_breaks += 1;
+                if after_terminal || line_breaks > 1 {
+                    capitalize = true;
+                }
+            }
+            _ if ch.is_whitespace() => {
+                if after_terminal {
+                    capitalize = true;
+                }
+            }
+            // Closing quotes and brackets may follow the final punctuation.
+            '"' | '\'' | ')' | ']' | '»' | '”' | '’' => {}
+            _ => {
+                after_terminal = false;
+                line_breaks = 0;
+                if ch.is_numeric() {
+                    capitalize = false;
+                }
+            }
+        }
+        out.push(ch);
+    }
+    out
+}
+
+/// Byte ranges of the words of `line`; apostrophes between letters stay inside a word.
+fn word_ranges(line: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    let mut chars = line.char_indices().peekable();
+    while let Some((idx, ch)) = chars.next() {
+        let inside = ch.is_alphanumeric()
+            || (start.is_some()
+                && matches!(ch, '\'' | '’')
+                && chars.peek().is_some_and(|(_, next)| next.is_alphanumeric()));
+        match (inside, start) {
+            (true, None) => start = Some(idx),
+            (false, Some(begin)) => {
+                ranges.push((begin, idx));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(begin) = start {
+        ranges.push((begin, line.len()));
+    }
+    ranges
+}
+
+fn title_case_line(line: &str, small_words: &[&str]) -> String {
+    let words = word_ranges(line);
+    let mut out = String::with_capacity(line.len());
+    let mut prev_end = 0;
+    for (idx, (start, end)) in words.iter().copied().enumerate() {
+        let gap = &line[prev_end..start];
+        out.push_str(gap);
+        let lower = line[start..end].to_lowercase();
+        // Words opening the line or a new clause, and the last word, are always capitalized.
+        let opens_clause = idx == 0 || gap.contains([':', '.', '!', '?', '—']);
+        if !opens_clause && idx + 1 != words.len() && small_words.contains(&lower.as_str()) {
+            out.push_str(&lower);
+        } else {
+            let mut chars = lower.chars();
+            if let Some(first) = chars.next() {
+                out.extend(first.to_uppercase());
+                out.push_str(chars.as_str());
+            }
+        }
+        prev_end = end;
+    }
+    out.push_str(&line[prev_end..]);
+    out
+}
+
+/// Characters that cannot be seen in the editor, or pass for an ordinary space,
+/// grouped by what the cleanup does with them.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InvisibleKind {
+    ZeroWidth,
+    SoftHyphen,
+    /// Replaced by a plain space rather than removed.
+    SpecialSpace,
+    Bidi,
+    Control,
+    /// Only reported: composing the text with NFC fixes decomposed accents.
+    CombiningMark,
+}
+
+pub fn invisible_kind(ch: char) -> Option<InvisibleKind> {
+    match ch {
+        '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}' | '\u{180E}' => {
+            Some(InvisibleKind::ZeroWidth)
+        }
+        '\u{00AD}' => Some(InvisibleKind::SoftHyphen),
+        '\u{00A0}' | '\u{2000}'..='\u{200A}' | '\u{202F}' | '\u{205F}' | '\u{3000}' => {
+            Some(InvisibleKind::SpecialSpace)
+        }
+        '\u{200E}'
+        | '\u{200F}'
+        | '\u{061C}'
+        | '\u{202A}'..='\u{202E}'
+        | '\u{2066}'..='\u{2069}' => Some(InvisibleKind::Bidi),
+        '\t' | '\n' | '\r' => None,
+        _ if ch.is_control() => Some(InvisibleKind::Control),
+        '\u{0300}'..='\u{036F}' => Some(InvisibleKind::CombiningMark),
+        _ => None,
+    }
+}
+
+/// The Unicode name of the characters `invisible_kind` knows by code point.
+pub fn invisible_char_name(ch: char) -> &'static str {
+    match ch {
+        '\u{200B}' => "ZERO WIDTH SPACE",
+        '\u{200C}' => "ZERO WIDTH NON-JOINER",
+        '\u{200D}' => "ZERO WIDTH JOINER",
+        '\u{2060}' => "WORD JOINER",
+        '\u{FEFF}' => "ZERO WIDTH NO-BREAK SPACE",
+        '\u{180E}' => "MONGOLIAN VOWEL SEPARATOR",
+        '\u{00AD}' => "SOFT HYPHEN",
+        '\u{00A0}' => "NO-BREAK SPACE",
+        '\u{2000}' => "EN QUAD",
+        '\u{2001}' => "EM QUAD",
+        '\u{2002}' => "EN SPACE",
+        '\u{2003}' => "EM SPACE",
+        '\u{2004}' => "THREE-PER-EM SPACE",
+        '\u{2005}' => "FOUR-PER-EM SPACE",
+        '\u{2006}' => "SIX-PER-EM SPACE",
+        '\u{2007}' => "FIGURE SPACE",
+        '\u{2008}' => "PUNCTUATION SPACE",
+        '\u{2009}' => "THIN SPACE",
+        '\u{200A}' => "HAIR SPACE",
+        '\u{202F}' => "NARROW NO-BREAK SPACE",
+        '\u{205F}' => "MEDIUM MATHEMATICAL SPACE",
+        '\u{3000}' => "IDEOGRAPHIC SPACE",
+        '\u{200E}' => "LEFT-TO-RIGHT MARK",
+        '\u{200F}' => "RIGHT-TO-LEFT MARK",
+        '\u{061C}' => "ARABIC LETTER MARK",
+        '\u{202A}' => "LEFT-TO-RIGHT EMBEDDING",
+        '\u{202B}' => "RIGHT-TO-LEFT EMBEDDING",
+        '\u{202C}' => "POP DIRECTIONAL FORMATTING",
+        '\u{202D}' => "LEFT-TO-RIGHT OVERRIDE",
+        '\u{202E}' => "RIGHT-TO-LEFT OVERRIDE",
+        '\u{2066}' => "LEFT-TO-RIGHT ISOLATE",
+        '\u{2067}' => "RIGHT-TO-LEFT ISOLATE",
+        '\u{2068}' => "FIRST STRONG ISOLATE",
+        '\u{2069}' => "POP DIRECTIONAL ISOLATE",
+        _ => match invisible_kind(ch) {
+            Some(InvisibleKind::Control) => "CONTROL CHARACTER",
+            Some(InvisibleKind::CombiningMark) => "COMBINING MARK",
+            _ => "",
+        },
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct InvisibleChar {
+    /// 1-based, like the status bar.
+    pub line: usize,
+    /// 1-based, counted in characters.
+    pub column: usize,
+    pub ch: char,
+    pub kind: InvisibleKind,
+}
+
+/// Lists the invisible characters of `text` with their line and column. CRLF, LF
+/// and CR all end a line.
+pub fn find_invisible_chars(text: &str) -> Vec<InvisibleChar> {
+    let mut found = Vec::new();
+    let (mut line, mut column) = (1, 0);
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\n' || ch == '\r' {
+            if ch == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            line += 1;
+            column = 0;
+            continue;
+        }
+        column += 1;
+        if let Some(kind) = invisible_kind(ch) {
+            found.push(InvisibleChar {
+                line,
+                column,
+                ch,
+                kind,
+            });
+        }
+    }
+    found
+}
+
+/// Removes the invisible characters of the given kinds; special spaces become a
+/// plain space instead. Returns the cleaned text and how many characters changed.
+pub fn remove_invisible_chars(text: &str, kinds: &[InvisibleKind]) -> (String, usize) {
+    let mut out = String::with_capacity(text.len());
+    let mut changed = 0;
+    for ch in text.chars() {
+        match invisible_kind(ch).filter(|kind| kinds.contains(kind)) {
+            Some(InvisibleKind::SpecialSpace) => {
+                out.push(' ');
+                changed += 1;
+            }
+            Some(_) => changed += 1,
+            None => out.push(ch),
+        }
+    }
+    (out, changed)
+}
+
+/// Rebuilds `scope` line by line, keeping its line ending and trailing line break.
+fn map_lines<F>(scope: &str, mut f: F) -> String
+where
+    F: FnMut(&str) -> String,
+{
+    if scope.is_empty() {
+        return String::new();
+    }
+    let eol = detect_eol(scope);
+    let (content, trailing) = split_trailing_newline(scope);
+    let lines: Vec<String> = split_lines_keep_empty(content)
+        .into_iter()
+        .map(&mut f)
+        .collect();
+    let mut out = lines.join(eol);
+    if trailing {
+        out.push_str(eol);
+    }
+    out
+}
+
+/// Options of Edit > Text > Number lines.
+#[derive(Clone)]
+pub struct NumberingOptions {
+    pub start: i64,
+    pub step: i64,
+    /// Minimum number of digits; shorter numbers are padded with zeros.
+    pub width: usize,
+    /// Text between the number and the line, such as ". " or a tab.
+    pub separator: String,
+}
+
+/// Prefixes every line of `scope`, blank ones included, with its number.
+pub fn number_lines(scope: &str, options: &NumberingOptions) -> String {
+    let mut number = options.start;
+    map_lines(scope, |line| {
+        let prefixed = format!(
+            "{number:0width$}{}{line}",
+            options.separator,
+            width = options.width
+        );
+        number = number.saturating_add(options.step);
+        prefixed
+    })
+}
+
+/// The line without its leading number: "12. ", "3) ", "(4) ", "[5] ", "6: ", "7 - "
+/// or "8\t". Punctuation must be followed by a space, a tab or the end of the line,
+/// and a bare number by a tab or " - ", so "3.14 is pi", "2024 was a good year" and
+/// "3 apples" are left alone.
+fn strip_line_number(line: &str) -> Option<&str> {
+    let rest = line.trim_start_matches([' ', '\t']);
+    let (rest, close) = match rest.chars().next() {
+        Some('(') => (&rest[1..], Some(')')),
+        Some('[') => (&rest[1..], Some(']')),
+        _ => (rest, None),
+    };
+    let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits == 0 {
+        return None;
+    }
+    let rest = &rest[digits..];
+    let (rest, punctuated) = match close {
+        Some(close) => (rest.strip_prefix(close)?, true),
+        None => match rest.strip_prefix(['.', ')', ':', ']']) {
+            Some(after) => (after, true),
+            None => (rest, false),
+        },
+    };
+    let trimmed = rest.trim_start_matches([' ', '\t']);
+    if punctuated {
+        return (trimmed.len() < rest.len() || rest.is_empty()).then_some(trimmed);
+    }
+    if rest.starts_with('\t') {
+        return Some(trimmed);
+    }
+    // "7 - item": without punctuation only a dash marks the number as numbering.
+    let after = trimmed.strip_prefix('-')?;
+    (rest.starts_with(' ') && after.starts_with([' ', '\t']))
+        .then(|| after.trim_start_matches([' ', '\t']))
+}
+
+/// Removes the leading number of every numbered line of `scope`. Returns the text and
+/// how many lines lost their number.
+pub fn remove_line_numbers(scope: &str) -> (String, usize) {
+    let mut removed = 0;
+    let text = map_lines(scope, |line| match strip_line_number(line) {
+        Some(rest) => {
+            removed += 1;
+            rest.to_string()
+        }
+        None => line.to_string(),
+    });
+    (text, removed)
+}
+
+/// Keeps (`keep`) or deletes fields `first` to `last`, 1-based and inclusive, of every
+/// line of `scope`. Fields are separated by `delimiter`; an empty delimiter splits on
+/// runs of whitespace and joins the remaining fields with single spaces. Lines with
+/// fewer fields keep what they have, or become blank when extracting.
+pub fn select_fields(
+    scope: &str,
+    delimiter: &str,
+    first: usize,
+    last: usize,
+    keep: bool,
+) -> String {
+    let in_range = |index: usize| index + 1 >= first && index < last;
+    map_lines(scope, |line| {
+        if line.is_empty() {
+            return String::new();
+        }
+        let (fields, joiner): (Vec<&str>, &str) = if delimiter.is_empty() {
+            (line.split_whitespace().collect(), " ")
+        } else {
+            (line.split(delimiter).collect(), delimiter)
+        };
+        fields
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| in_range(*index) == keep)
+            .map(|(_, field)| field)
+            .collect::<Vec<_>>()
+            .join(joiner)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -192,4 +1060,223 @@ mod tests {
         // Out: ["", "a"]. Join("\n") -> "\na". Trailing=true -> "\na\n".
         assert_eq!(remove_duplicate_lines("\n\na\n\n"), "\na\n");
     }
+
+    #[test]
+    fn test_detect_line_ending() {
+        assert_eq!(detect_line_ending("a\nb\nc"), LineEnding::Lf);
+        assert_eq!(detect_line_ending("a\r\nb\r\n"), LineEnding::Crlf);
+        assert_eq!(detect_line_ending("a\rb\rc\r\n"), LineEnding::Cr);
+        assert_eq!(detect_line_ending("single line"), LineEnding::Crlf);
+        assert_eq!(detect_line_ending("a\nb\r\n"), LineEnding::Crlf);
+    }
+
+    #[test]
+    fn test_convert_line_endings() {
+        let input = "a\r\nb\nc\rd";
+        assert_eq!(convert_line_endings(input, LineEnding::Lf), "a\nb\nc\nd");
+        assert_eq!(
+            convert_line_endings(input, LineEnding::Crlf),
+            "a\r\nb\r\nc\r\nd"
+        );
+        assert_eq!(convert_line_endings(input, LineEnding::Cr), "a\rb\rc\rd");
+    }
+
+    #[test]
+    fn test_fix_mojibake() {
+        assert_eq!(
+            fix_mojibake("perchÃ© cosÃ¬ â€œcitazioneâ€\u{9d}"),
+            ("perché così “citazione”".to_string(), 4)
+        );
+        assert_eq!(fix_mojibake("ÃƒÂ©tÃƒÂ©"), ("été".to_string(), 4));
+        assert_eq!(fix_mojibake("già, Ãxyz"), ("già, Ãxyz".to_string(), 0));
+    }
+
+    #[test]
+    fn test_line_start_index_and_count() {
+        let text = "one\r\ntwo\nthree\rfour";
+        assert_eq!(line_count(text), 4);
+        assert_eq!(line_start_index(text, 0), Some(0));
+        assert_eq!(line_start_index(text, 1), Some(5));
+        assert_eq!(line_start_index(text, 2), Some(9));
+        assert_eq!(line_start_index(text, 3), Some(15));
+        assert_eq!(line_start_index(text, 4), None);
+    }
+
+    fn sort_options(mode: SortMode) -> SortOptions {
+        SortOptions {
+            mode,
+            descending: false,
+            case_sensitive: false,
+            ignore_accents: false,
+            field: 0,
+            delimiter: String::new(),
+            remove_duplicates: false,
+            language: Language::English,
+        }
+    }
+
+    #[test]
+    fn test_sort_lines_natural_and_numeric() {
+        let natural = sort_options(SortMode::Natural);
+        assert_eq!(
+            sort_lines("file10\nfile2\nFile1\n", &natural),
+            "File1\nfile2\nfile10\n"
+        );
+        let mut numeric = sort_options(SortMode::Numeric);
+        numeric.descending = true;
+        assert_eq!(
+            sort_lines("2,5 kg\nnone\n-3\n10\n", &numeric),
+            "none\n10\n2,5 kg\n-3\n"
+        );
+    }
+
+    #[test]
+    fn test_sort_lines_collation() {
+        let mut options = sort_options(SortMode::Alphabetical);
+        assert_eq!(
+            sort_lines("zeta\néte\nete\nfin", &options),
+            "ete\néte\nfin\nzeta"
+        );
+        options.language = Language::Spanish;
+        assert_eq!(sort_lines("ñu\nnz\no", &options), "nz\nñu\no");
+        options.language = Language::Vietnamese;
+        assert_eq!(
+            sort_lines("đá\ndz\nâm\năn\nam", &options),
+            "am\năn\nâm\ndz\nđá"
+        );
+        options.language = Language::English;
+        options.case_sensitive = true;
+        assert_eq!(sort_lines("B\nb\na", &options), "a\nb\nB");
+    }
+
+    #[test]
+    fn test_sort_lines_field_and_duplicates() {
+        let mut options = sort_options(SortMode::Numeric);
+        options.field = 2;
+        options.delimiter = ";".to_string();
+        assert_eq!(
+            sort_lines("b;3\r\na;1\r\n\r\nc;2\r\n", &options),
+            "a;1\r\nc;2\r\n\r\nb;3\r\n"
+        );
+        let mut options = sort_options(SortMode::Alphabetical);
+        options.remove_duplicates = true;
+        options.ignore_accents = true;
+        assert_eq!(sort_lines("Perché\nb\nperche\na", &options), "a\nb\nPerché");
+    }
+
+    #[test]
+    fn test_partition_lines() {
+        let (matched, unmatched, count) =
+            partition_lines("apple\r\nbanana\r\navocado\r\n", |line| {
+                line.starts_with('a')
+            });
+        assert_eq!(matched, "apple\r\navocado\r\n");
+        assert_eq!(unmatched, "banana\r\n");
+        assert_eq!(count, 2);
+        assert_eq!(
+            partition_lines("x\ny", |line| line == "z"),
+            (String::new(), "x\ny".to_string(), 0)
+        );
+    }
+
+    #[test]
+    fn test_convert_case() {
+        let text = "the lord of the rings. a NEW hope!\nciao";
+        assert_eq!(
+            convert_case(text, CaseConversion::Sentence, Language::English),
+            "The lord of the rings. A new hope!\nCiao"
+        );
+        assert_eq!(
+            convert_case(text, CaseConversion::CapitalizeSentences, Language::English),
+            "The lord of the rings. A NEW hope!\nCiao"
+        );
+        assert_eq!(
+            convert_case(text, CaseConversion::Title, Language::English),
+            "The Lord of the Rings. A New Hope!\nCiao"
+        );
+        assert_eq!(
+            convert_case(
+                "il nome della rosa: un'indagine",
+                CaseConversion::Title,
+                Language::Italian
+            ),
+            "Il Nome della Rosa: Un'indagine"
+        );
+        assert_eq!(
+            convert_case("Straße aBc", CaseConversion::Toggle, Language::English),
+            "sTRASSE AbC"
+        );
+    }
+
+    #[test]
+    fn test_invisible_chars() {
+        let text = "a\u{200B}b\u{00A0}c\r\n\rsoft\u{00AD}ly e\u{0301}\u{202E}\u{7}";
+        let found = find_invisible_chars(text);
+        let positions: Vec<(usize, usize, InvisibleKind)> = found
+            .iter()
+            .map(|item| (item.line, item.column, item.kind))
+            .collect();
+        assert_eq!(
+            positions,
+            vec![
+                (1, 2, InvisibleKind::ZeroWidth),
+                (1, 4, InvisibleKind::SpecialSpace),
+                (3, 5, InvisibleKind::SoftHyphen),
+                (3, 10, InvisibleKind::CombiningMark),
+                (3, 11, InvisibleKind::Bidi),
+                (3, 12, InvisibleKind::Control)
+            ]
+        );
+        assert_eq!(invisible_char_name('\u{202E}'), "RIGHT-TO-LEFT OVERRIDE");
+        let (cleaned, changed) = remove_invisible_chars(
+            text,
+            &[
+                InvisibleKind::ZeroWidth,
+                InvisibleKind::SpecialSpace,
+                InvisibleKind::SoftHyphen,
+            ],
+        );
+        assert_eq!(cleaned, "ab c\r\n\rsoftly e\u{0301}\u{202E}\u{7}");
+        assert_eq!(changed, 3);
+    }
+
+    #[test]
+    fn test_number_lines() {
+        let options = NumberingOptions {
+            start: 9,
+            step: 1,
+            width: 2,
+            separator: ". ".to_string(),
+        };
+        let numbered = number_lines("alpha\r\n\r\nbeta\r\n", &options);
+        assert_eq!(numbered, "09. alpha\r\n10. \r\n11. beta\r\n");
+        assert_eq!(
+            remove_line_numbers(&numbered),
+            ("alpha\r\n\r\nbeta\r\n".to_string(), 3)
+        );
+        let (text, removed) =
+            remove_line_numbers("(1) a\n[2] b\n3)\tc\n4 - d\n5\te\n3.14 is pi\n2024\nplain");
+        assert_eq!(text, "a\nb\nc\nd\ne\n3.14 is pi\n2024\nplain");
+        assert_eq!(removed, 5);
+        // A line that merely starts with a number keeps it.
+        assert_eq!(
+            remove_line_numbers("2024 was a good year\n3 apples\n12 -3 degrees"),
+            (
+                "2024 was a good year\n3 apples\n12 -3 degrees".to_string(),
+                0
+            )
+        );
+    }
+
+    #[test]
+    fn test_select_fields() {
+        let csv = "a,b,c,d\nshort\n\n1,2,3,4\n";
+        assert_eq!(select_fields(csv, ",", 2, 3, true), "b,c\n\n\n2,3\n");
+        assert_eq!(select_fields(csv, ",", 2, 3, false), "a,d\nshort\n\n1,4\n");
+        assert_eq!(select_fields("x\ty\tz", "\t", 2, usize::MAX, true), "y\tz");
+        assert_eq!(
+            select_fields("  one   two three", "", 2, 2, false),
+            "one three"
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
 
 #[derive(Debug, Clone)]
 pub struct ArticleContent {
@@ -6,7 +6,8 @@ pub struct ArticleContent {
     pub content: String,
 }
 
-fn decode_unicode(input: &str) -> String {
+/// Decodes `\uXXXX` escapes, joining surrogate pairs; malformed escapes are kept.
+pub(crate) fn decode_unicode(input: &str) -> String {
     let mut result = String::new();
     let mut chars = input.chars().peekable();
     while let Some(c) = chars.next() {
@@ -18,11 +19,17 @@ fn decode_unicode(input: &str) -> String {
                     hex.push(h);
                 }
             }
-            if let Ok(code) = u32::from_str_radix(&hex, 16)
-                && let Some(decoded_char) = std::char::from_u32(code)
-            {
-                result.push(decoded_char);
-                continue;
+            if let Ok(code) = u32::from_str_radix(&hex, 16) {
+                if let Some(decoded_char) = std::char::from_u32(code) {
+                    result.push(decoded_char);
+                    continue;
+                }
+                if (0xD800..0xDC00).contains(&code)
+                    && let Some(decoded_char) = decode_low_surrogate(&mut chars, code)
+                {
+                    result.push(decoded_char);
+                    continue;
+                }
             }
             result.push_str("\\u");
             result.push_str(&hex);
@@ -33,6 +40,23 @@ fn decode_unicode(input: &str) -> String {
     result
 }
 
+/// Consumes the `\uXXXX` low surrogate that completes `high`, if it follows.
+fn decode_low_surrogate(
+    chars: &mut std::iter::Peekable<std::str::Chars<'_>>,
+    high: u32,
+) -> Option<char> {
+    let mut ahead = chars.clone();
+    if ahead.next() != Some('\\') || ahead.next() != Some('u') {
+        return None;
+    }
+    let hex: String = ahead.by_ref().take(4).collect();
+    let low = u32::from_str_radix(&hex, 16)
+        .ok()
+        .filter(|low| (0xDC00..0xE000).contains(low))?;
+    *chars = ahead;
+    char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+}
+
 /// Estrae una stringa JSON gestendo correttamente gli escape (\" \\ \n ecc.)
 /// Ritorna la stringa decodificata e la posizione dopo la virgoletta di chiusura
 fn extract_json_string(s: &str) -> Option<(String, usize)> {
@@ -253,35 +277,10 @@ pub fn reader_mode_extract(html_content: &str) -> Option<ArticleContent> {
         }
     }
 
-    // 3. FALLBACK CSS
+    // 3. FALLBACK HTML
     if !found_anything || body_acc.len() < 300 {
-        let content_selectors = [
-            "p[data-type='paragraph']", // WSJ modern
-            ".wsj-article-body p",
-            "article p",
-            ".atext",
-            ".art-text",
-            ".story-content p",
-            ".article-body p",
-            "#col-sx-interna p",
-        ];
-        for sel_str in content_selectors {
-            if let Ok(selector) = Selector::parse(sel_str) {
-                let mut sel_acc = String::new();
-                for element in document.select(&selector) {
-                    let text = element.text().collect::<Vec<_>>().join(" ");
-                    if text.to_lowercase().contains("enable js") {
-                        continue;
-                    }
-                    sel_acc.push_str(&text);
-                    sel_acc.push_str("\n\n");
-                }
-                if sel_acc.len() > 200 {
-                    body_acc.push_str(&sel_acc);
-                    break;
-                }
-            }
-        }
+        let article = ReaderWriter::default().render(&article_elements(&document));
+        body_acc.push_str(&article.text);
     }
 
     let mut final_text = String::new();
@@ -336,3 +335,455 @@ pub fn collapse_blank_lines(s: &str) -> String {
     }
     out.trim_end_matches('\n').to_string()
 }
+
+/// A link collected while rendering a page in reader mode, numbered by its
+/// position in [`ReaderDocument::links`] (1-based in the text).
+#[derive(Debug, Clone)]
+pub struct ReaderLink {
+    pub text: String,
+    pub href: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReaderDocument {
+    pub text: String,
+    pub links: Vec<ReaderLink>,
+}
+
+/// Elements known to hold the article body on news sites, tried before the
+/// generic containers in [`pick_reader_root`].
+const ARTICLE_SELECTORS: &[&str] = &[
+    "p[data-type='paragraph']", // WSJ modern
+    ".wsj-article-body",
+    ".atext",
+    ".art-text",
+    ".story-content",
+    ".article-body",
+    "#col-sx-interna",
+];
+
+const READER_SKIPPED_TAGS: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "nav", "header", "footer", "aside", "form",
+    "button", "select", "iframe", "svg", "canvas",
+];
+
+const READER_BLOCK_TAGS: &[&str] = &[
+    "p",
+    "div",
+    "section",
+    "article",
+    "main",
+    "blockquote",
+    "figure",
+    "figcaption",
+    "dl",
+    "dt",
+    "dd",
+    "address",
+    "hr",
+    "caption",
+];
+
+/// Renders a saved web page as plain text, keeping the structure that matters
+/// when reading: headings as `#` lines by level, list items, tables row by row
+/// with cells separated by ` | `, and a `[n]` marker after every link.
+pub fn reader_mode_document(html_content: &str) -> ReaderDocument {
+    let document = Html::parse_document(html_content);
+    let elements = article_elements(&document);
+    let mut writer = ReaderWriter {
+        link_markers: true,
+        ..Default::default()
+    };
+
+    let has_h1 = Selector::parse("h1").ok().is_some_and(|s| {
+        elements
+            .iter()
+            .any(|el| el.value().name() == "h1" || el.select(&s).next().is_some())
+    });
+    if !has_h1
+        && let Ok(s) = Selector::parse("title")
+        && let Some(el) = document.select(&s).next()
+    {
+        let title = el.text().collect::<Vec<_>>().join(" ");
+        let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !title.is_empty() {
+            writer.push_block(&format!("# {title}"));
+        }
+    }
+
+    writer.render(&elements)
+}
+
+/// The elements holding the article: the first of [`ARTICLE_SELECTORS`] with
+/// enough text, otherwise the best generic container.
+fn article_elements(document: &Html) -> Vec<ElementRef<'_>> {
+    for sel in ARTICLE_SELECTORS {
+        let Ok(selector) = Selector::parse(sel) else {
+            continue;
+        };
+        let elements: Vec<ElementRef<'_>> = document
+            .select(&selector)
+            .filter(|el| {
+                !el.text()
+                    .collect::<String>()
+                    .to_lowercase()
+                    .contains("enable js")
+            })
+            .collect();
+        let len: usize = elements
+            .iter()
+            .map(|el| el.text().map(str::len).sum::<usize>())
+            .sum();
+        if len > 200 {
+            return elements;
+        }
+    }
+    vec![pick_reader_root(document)]
+}
+
+fn pick_reader_root(document: &Html) -> ElementRef<'_> {
+    for sel in ["article", "main", "[role='main']", "body"] {
+        let Ok(selector) = Selector::parse(sel) else {
+            continue;
+        };
+        let best = document
+            .select(&selector)
+            .max_by_key(|el| el.text().map(str::len).sum::<usize>());
+        if let Some(el) = best
+            && el.text().any(|t| !t.trim().is_empty())
+        {
+            return el;
+        }
+    }
+    document.root_element()
+}
+
+#[derive(Default)]
+struct ReaderWriter {
+    out: String,
+    line: String,
+    pending_space: bool,
+    list_depth: usize,
+    preformatted: bool,
+    /// Appends a `[n]` marker after links and collects them in `links`.
+    link_markers: bool,
+    links: Vec<ReaderLink>,
+}
+
+impl ReaderWriter {
+    fn render(mut self, elements: &[ElementRef<'_>]) -> ReaderDocument {
+        for el in elements {
+            self.element(*el);
+            self.flush();
+        }
+        let mut text = String::with_capacity(self.out.len());
+        let mut blank = true;
+        for line in self.out.lines().map(str::trim_end) {
+            if line.is_empty() && blank {
+                continue;
+            }
+            blank = line.is_empty();
+            text.push_str(line);
+            text.push('\n');
+        }
+        ReaderDocument {
+            text: text.trim_end().to_string(),
+            links: self.links,
+        }
+    }
+
+    fn walk(&mut self, element: ElementRef<'_>) {
+        for child in element.children() {
+            if let Some(text) = child.value().as_text() {
+                self.push_text(text);
+            } else if let Some(el) = ElementRef::wrap(child) {
+                self.element(el);
+            }
+        }
+    }
+
+    fn element(&mut self, el: ElementRef<'_>) {
+        let name = el.value().name();
+        if READER_SKIPPED_TAGS.contains(&name) || el.value().attr("hidden").is_some() {
+            return;
+        }
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                let text = self.inline_text(el);
+                if !text.is_empty() {
+                    self.push_block(&format!("{} {text}", "#".repeat(level)));
+                }
+            }
+            "ul" | "ol" => {
+                self.flush();
+                self.list_depth += 1;
+                let mut number = 0usize;
+                for item in el.children().filter_map(ElementRef::wrap) {
+                    if item.value().name() != "li" {
+                        self.element(item);
+                        continue;
+                    }
+                    number += 1;
+                    let marker = if name == "ol" {
+                        format!("{number}.")
+                    } else {
+                        "-".to_string()
+                    };
+                    self.flush();
+                    self.line = format!("{}{marker} ", "  ".repeat(self.list_depth - 1));
+                    self.pending_space = false;
+                    self.walk(item);
+                    self.flush_line();
+                }
+                self.list_depth -= 1;
+                if self.list_depth == 0 {
+                    self.out.push('\n');
+                }
+            }
+            "table" => {
+                self.flush();
+                self.table(el);
+                self.out.push('\n');
+            }
+            "br" => {
+                self.line.push('\n');
+                self.pending_space = false;
+            }
+            "pre" => {
+                self.flush();
+                self.preformatted = true;
+                self.walk(el);
+                self.preformatted = false;
+                self.flush();
+            }
+            "a" => {
+                self.walk(el);
+                if !self.link_markers {
+                    return;
+                }
+                let href = el.value().attr("href").unwrap_or("").trim();
+                if href.is_empty() || href.starts_with('#') || href.starts_with("javascript:") {
+                    return;
+                }
+                let number = match self.links.iter().position(|link| link.href == href) {
+                    Some(index) => index + 1,
+                    None => {
+                        let mut text = el.text().collect::<Vec<_>>().join(" ");
+                        text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                        if text.is_empty() {
+                            text = href.to_string();
+                        }
+                        self.links.push(ReaderLink {
+                            text,
+                            href: href.to_string(),
+                        });
+                        self.links.len()
+                    }
+                };
+                self.push_text(&format!(" [{number}]"));
+            }
+            "img" => {
+                if let Some(alt) = el.value().attr("alt").filter(|a| !a.trim().is_empty()) {
+                    self.push_text(alt);
+                }
+            }
+            "li" => {
+                self.flush();
+                self.line = "- ".to_string();
+                self.walk(el);
+                self.flush_line();
+            }
+            _ if READER_BLOCK_TAGS.contains(&name) && self.list_depth > 0 => {
+                self.pending_space = !self.line.is_empty() && !self.line.ends_with(' ');
+                self.walk(el);
+            }
+            _ if READER_BLOCK_TAGS.contains(&name) => {
+                self.flush();
+                self.walk(el);
+                self.flush();
+            }
+            _ => self.walk(el),
+        }
+    }
+
+    fn table(&mut self, table: ElementRef<'_>) {
+        for child in table.children().filter_map(ElementRef::wrap) {
+            match child.value().name() {
+                "thead" | "tbody" | "tfoot" => self.table(child),
+                "caption" => {
+                    let text = self.inline_text(child);
+                    if !text.is_empty() {
+                        self.out.push_str(&text);
+                        self.out.push('\n');
+                    }
+                }
+                "tr" => {
+                    let cells: Vec<String> = child
+                        .children()
+                        .filter_map(ElementRef::wrap)
+                        .filter(|cell| matches!(cell.value().name(), "td" | "th"))
+                        .map(|cell| self.inline_text(cell).replace('\n', " "))
+                        .collect();
+                    if cells.iter().any(|cell| !cell.is_empty()) {
+                        self.out.push_str(&cells.join(" | "));
+                        self.out.push('\n');
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Renders `el` on its own, returning its text without touching the
+    /// current line; links found inside are still collected.
+    fn inline_text(&mut self, el: ElementRef<'_>) -> String {
+        self.flush();
+        let line = std::mem::take(&mut self.line);
+        self.walk(el);
+        let text = std::mem::replace(&mut self.line, line);
+        self.pending_space = false;
+        text.trim().to_string()
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if self.preformatted {
+            self.line.push_str(text);
+            return;
+        }
+        for ch in text.chars() {
+            if ch.is_whitespace() {
+                self.pending_space = !self.line.is_empty() && !self.line.ends_with(['\n', ' ']);
+                continue;
+            }
+            if self.pending_space {
+                self.line.push(' ');
+                self.pending_space = false;
+            }
+            self.line.push(ch);
+        }
+    }
+
+    fn push_block(&mut self, text: &str) {
+        self.flush();
+        self.out.push_str(text);
+        self.out.push_str("\n\n");
+    }
+
+    fn flush_line(&mut self) {
+        let line = self.line.trim_end();
+        if !line.trim().is_empty() {
+            self.out.push_str(line);
+            self.out.push('\n');
+        }
+        self.line.clear();
+        self.pending_space = false;
+    }
+
+    fn flush(&mut self) {
+        if self.line.trim().is_empty() {
+            self.line.clear();
+            self.pending_space = false;
+            return;
+        }
+        self.flush_line();
+        if self.list_depth == 0 {
+            self.out.push('\n');
+        }
+    }
+}
+
+/// Finds the target of the `[n]` link marker under (or just before) the caret
+/// at `index`, looking it up in the numbered link list at the end of `text`.
+pub fn link_target_at(text: &str, index: usize) -> Option<String> {
+    let index = index.min(text.len());
+    let line_start = text[..index].rfind('\n').map_or(0, |pos| pos + 1);
+    let line_end = text[index..]
+        .find('\n')
+        .map_or(text.len(), |pos| index + pos);
+    let line = &text[line_start..line_end];
+    let caret = index - line_start;
+
+    let mut markers = Vec::new();
+    let mut search = 0;
+    while let Some(open) = line[search..].find('[') {
+        let start = search + open;
+        let Some(close) = line[start..].find(']') else {
+            break;
+        };
+        let end = start + close + 1;
+        if let Ok(number) = line[start + 1..end - 1].parse::<usize>() {
+            markers.push((start, end, number));
+        }
+        search = end;
+    }
+    let number = markers
+        .iter()
+        .find(|(start, end, _)| (*start..=*end).contains(&caret))
+        .or_else(|| markers.iter().rev().find(|(_, end, _)| *end <= caret))
+        .or_else(|| markers.first())
+        .map(|(_, _, number)| *number)?;
+
+    let prefix = format!("[{number}] ");
+    text.lines().rev().find_map(|line| {
+        let line = line.trim_end_matches('\r').trim_end();
+        if !line.starts_with(&prefix) || !line.ends_with('>') {
+            return None;
+        }
+        let open = line.rfind('<')?;
+        let target = line[open + 1..line.len() - 1].trim();
+        (!target.is_empty()).then(|| target.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reader_document_keeps_headings_tables_and_links() {
+        let html = "<html><head><title>Page</title></head><body><nav>Menu</nav>\
+            <article><h1>Hello</h1><p>See <a href=\"a.html\">this page</a>.</p>\
+            <table><tr><th>A</th><th>B</th></tr><tr><td>1</td><td>2</td></tr></table>\
+            <ul><li>One</li><li>Two</li></ul></article></body></html>";
+        let doc = reader_mode_document(html);
+        assert_eq!(
+            doc.text,
+            "# Hello\n\nSee this page [1].\n\nA | B\n1 | 2\n\n- One\n- Two"
+        );
+        assert_eq!(doc.links.len(), 1);
+        assert_eq!(doc.links[0].text, "this page");
+        assert_eq!(doc.links[0].href, "a.html");
+    }
+
+    #[test]
+    fn article_extract_and_reader_document_share_the_walker() {
+        let html = "<html><body><nav>Menu</nav><article><h1>Hello</h1>\
+            <p>See <a href=\"a.html\">this page</a>.</p><ul><li>One</li></ul></article></body></html>";
+        let article = reader_mode_extract(html).unwrap();
+        assert_eq!(article.content, "# Hello\n\nSee this page.\n\n- One");
+        assert_eq!(
+            reader_mode_document(html).text,
+            "# Hello\n\nSee this page [1].\n\n- One"
+        );
+    }
+
+    #[test]
+    fn link_target_follows_marker_to_link_list() {
+        let text = "See this page [1] and [2].\n\n## Links\n[1] this page <https://a.example/>\n[2] other <C:\\docs\\b.html>";
+        assert_eq!(
+            link_target_at(text, 15).as_deref(),
+            Some("https://a.example/")
+        );
+        assert_eq!(
+            link_target_at(text, 24).as_deref(),
+            Some("C:\\docs\\b.html")
+        );
+        let list_line = text.find("[2] other").unwrap();
+        assert_eq!(
+            link_target_at(text, list_line + 5).as_deref(),
+            Some("C:\\docs\\b.html")
+        );
+        assert_eq!(link_target_at("no links here", 3), None);
+    }
+}
@@ -0,0 +1,192 @@
+/// Latin letters with accents, hooks or strokes, grouped by the plain letter they
+/// lose them to. Vietnamese letters are covered with every tone.
+const LATIN_LETTERS: &[(&str, &str)] = &[
+    ("àáâãäåāăąǎạảấầẩẫậắằẳẵặ", "a"),
+    ("çćĉċč", "c"),
+    ("ďđð", "d"),
+    ("èéêëēĕėęěẹẻẽếềểễệ", "e"),
+    ("ĝğġģ", "g"),
+    ("ĥħ", "h"),
+    ("ìíîïĩīĭįıǐỉị", "i"),
+    ("ĵ", "j"),
+    ("ķ", "k"),
+    ("ĺļľŀł", "l"),
+    ("ñńņňŉ", "n"),
+    ("òóôõöøōŏőơǒọỏốồổỗộớờởỡợ", "o"),
+    ("ŕŗř", "r"),
+    ("śŝşšș", "s"),
+    ("ţťŧț", "t"),
+    ("ùúûüũūŭůűųưǔụủứừửữự", "u"),
+    ("ŵ", "w"),
+    ("ýÿŷỳỵỷỹ", "y"),
+    ("źżž", "z"),
+    ("ß", "ss"),
+    ("æ", "ae"),
+    ("œ", "oe"),
+    ("þ", "th"),
+];
+
+/// Greek letters, accented forms included, as in ELOT 743.
+const GREEK_LETTERS: &[(&str, &str)] = &[
+    ("αά", "a"),
+    ("β", "v"),
+    ("γ", "g"),
+    ("δ", "d"),
+    ("εέ", "e"),
+    ("ζ", "z"),
+    ("ηή", "i"),
+    ("θ", "th"),
+    ("ιίϊΐ", "i"),
+    ("κ", "k"),
+    ("λ", "l"),
+    ("μ", "m"),
+    ("ν", "n"),
+    ("ξ", "x"),
+    ("οό", "o"),
+    ("π", "p"),
+    ("ρ", "r"),
+    ("σς", "s"),
+    ("τ", "t"),
+    ("υύϋΰ", "y"),
+    ("φ", "f"),
+    ("χ", "ch"),
+    ("ψ", "ps"),
+    ("ωώ", "o"),
+];
+
+/// Russian, Ukrainian, Belarusian and Serbian Cyrillic, romanized the way
+/// passports spell names.
+const CYRILLIC_LETTERS: &[(&str, &str)] = &[
+    ("а", "a"),
+    ("б", "b"),
+    ("в", "v"),
+    ("гґ", "g"),
+    ("д", "d"),
+    ("ђ", "dj"),
+    ("еёэ", "e"),
+    ("є", "ye"),
+    ("ж", "zh"),
+    ("з", "z"),
+    ("иіы", "i"),
+    ("ї", "yi"),
+    ("й", "y"),
+    ("ј", "j"),
+    ("к", "k"),
+    ("л", "l"),
+    ("љ", "lj"),
+    ("м", "m"),
+    ("н", "n"),
+    ("њ", "nj"),
+    ("о", "o"),
+    ("п", "p"),
+    ("р", "r"),
+    ("с", "s"),
+    ("т", "t"),
+    ("ћ", "c"),
+    ("уў", "u"),
+    ("ф", "f"),
+    ("х", "kh"),
+    ("ц", "ts"),
+    ("ч", "ch"),
+    ("џ", "dz"),
+    ("ш", "sh"),
+    ("щ", "shch"),
+    ("ъь", ""),
+    ("ю", "yu"),
+    ("я", "ya"),
+];
+
+/// Typographic punctuation and its ASCII counterpart.
+const PUNCTUATION: &[(&str, &str)] = &[
+    ("‘’‚‛′‹›", "'"),
+    ("“”„‟″«»", "\""),
+    ("‐‑‒–—―−", "-"),
+    ("…", "..."),
+    ("•·", "*"),
+];
+
+fn lookup(table: &[(&str, &'static str)], ch: char) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(letters, _)| letters.contains(ch))
+        .map(|(_, ascii)| *ascii)
+}
+
+/// The ASCII spelling of a lowercase letter, if it has one.
+fn ascii_letter(ch: char) -> Option<&'static str> {
+    lookup(LATIN_LETTERS, ch)
+        .or_else(|| lookup(GREEK_LETTERS, ch))
+        .or_else(|| lookup(CYRILLIC_LETTERS, ch))
+}
+
+/// Removes accents and spells Vietnamese, Greek and Cyrillic letters with plain
+/// Latin letters: "perché" becomes "perche", "Жуков" becomes "Zhukov". With
+/// `punctuation`, typographic quotes, dashes and ellipses become ASCII too.
+/// Characters with no ASCII form, such as CJK text, are kept.
+pub fn to_ascii(text: &str, punctuation: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        // Combining accents of decomposed text.
+        if ('\u{0300}'..='\u{036F}').contains(&ch) {
+            continue;
+        }
+        if punctuation && let Some(ascii) = lookup(PUNCTUATION, ch) {
+            out.push_str(ascii);
+            continue;
+        }
+        if ch.is_ascii() {
+            out.push(ch);
+            continue;
+        }
+        let mut lower = ch.to_lowercase();
+        let (Some(lower_ch), None) = (lower.next(), lower.next()) else {
+            out.push(ch);
+            continue;
+        };
+        let Some(ascii) = ascii_letter(lower_ch) else {
+            out.push(ch);
+            continue;
+        };
+        if lower_ch == ch {
+            out.push_str(ascii);
+        } else if chars.peek().is_some_and(|next| next.is_uppercase()) {
+            // "ЖУКОВ" stays all capitals: "ZHUKOV".
+            out.push_str(&ascii.to_uppercase());
+        } else {
+            let mut letters = ascii.chars();
+            if let Some(first) = letters.next() {
+                out.push(first.to_ascii_uppercase());
+                out.push_str(letters.as_str());
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_ascii_letters() {
+        assert_eq!(to_ascii("perché Così ÀNCORA", false), "perche Cosi ANCORA");
+        assert_eq!(
+            to_ascii("Tiếng Việt đẹp Đà Nẵng", false),
+            "Tieng Viet dep Da Nang"
+        );
+        assert_eq!(to_ascii("Жуков ЖУКОВ Щука", false), "Zhukov ZHUKOV Shchuka");
+        assert_eq!(
+            to_ascii("Αθήνα Straße Œuvre", false),
+            "Athina Strasse Oeuvre"
+        );
+        assert_eq!(to_ascii("e\u{0301}te\u{0301} 東京", false), "ete 東京");
+    }
+
+    #[test]
+    fn test_to_ascii_punctuation() {
+        let text = "«Sì» — “quoted” l’uno…";
+        assert_eq!(to_ascii(text, false), "«Si» — “quoted” l’uno…");
+        assert_eq!(to_ascii(text, true), "\"Si\" - \"quoted\" l'uno...");
+    }
+}
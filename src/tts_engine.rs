@@ -4,7 +4,10 @@ use crate::settings;
 use crate::settings::{
     AudiobookResult, DictionaryEntry, Language, TRUSTED_CLIENT_TOKEN, TtsEngine,
 };
-use crate::{get_active_edit, log_debug, save_audio_dialog, show_error, with_state};
+use crate::talking_book;
+use crate::{
+    get_active_edit, log_debug, save_audio_dialog, save_talking_book_dialog, show_error, with_state,
+};
 use chrono::Local;
 use futures_util::{SinkExt, StreamExt};
 use rand::Rng;
@@ -1447,6 +1450,221 @@ pub fn start_audiobook(hwnd: HWND) {
     });
 }
 
+/// Records the document as an EPUB 3 talking book whose text highlights follow the speech.
+pub fn start_talking_book(hwnd: HWND) {
+    let Some(hwnd_edit) = (unsafe { get_active_edit(hwnd) }) else {
+        return;
+    };
+    let (
+        language,
+        tts_engine,
+        voice,
+        split_on_newline,
+        dictionary,
+        tts_rate,
+        tts_pitch,
+        tts_volume,
+    ) = unsafe {
+        with_state(hwnd, |state| {
+            (
+                state.settings.language,
+                state.settings.tts_engine,
+                state.settings.tts_voice.clone(),
+                state.settings.split_on_newline,
+                state.settings.dictionary.clone(),
+                state.settings.tts_rate,
+                state.settings.tts_pitch,
+                state.settings.tts_volume,
+            )
+        })
+    }
+    .unwrap_or((
+        Language::Italian,
+        TtsEngine::Edge,
+        "it-IT-IsabellaNeural".to_string(),
+        true,
+        Vec::new(),
+        0,
+        0,
+        100,
+    ));
+    if tts_engine == TtsEngine::Sapi4 {
+        unsafe {
+            show_error(
+                hwnd,
+                language,
+                &i18n::tr(language, "talking_book.sapi4_unsupported"),
+            );
+        }
+        return;
+    }
+    let text = unsafe { get_edit_text(hwnd_edit) };
+    let paragraphs = talking_book::paragraphs(&strip_dashed_lines(&text));
+    let chunks: Vec<String> = paragraphs
+        .iter()
+        .flatten()
+        .filter(|sentence| talking_book::is_spoken(sentence))
+        .map(|sentence| prepare_tts_text(sentence, split_on_newline, &dictionary))
+        .collect();
+    if chunks.is_empty() {
+        unsafe {
+            show_error(hwnd, language, &settings::tts_no_text_message(language));
+        }
+        return;
+    }
+    let suggested_name = unsafe {
+        with_state(hwnd, |state| {
+            state.docs.get(state.current).map(|doc| {
+                let p = Path::new(&doc.title);
+                p.file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or(&doc.title)
+                    .to_string()
+            })
+        })
+    }
+    .flatten();
+
+    let Some(output) = (unsafe { save_talking_book_dialog(hwnd, suggested_name.as_deref()) })
+    else {
+        return;
+    };
+    let title = suggested_name.unwrap_or_else(|| {
+        output
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string()
+    });
+
+    let cancel_token = Arc::new(AtomicBool::new(false));
+    let progress_hwnd = unsafe {
+        let h = crate::app_windows::audiobook_window::open(hwnd, chunks.len());
+        if with_state(hwnd, |state| {
+            state.audiobook_progress = h;
+            state.audiobook_cancel = Some(cancel_token.clone());
+        })
+        .is_none()
+        {
+            crate::log_debug("Failed to update audiobook progress state");
+        }
+        h
+    };
+
+    let cancel_clone = cancel_token.clone();
+    std::thread::spawn(move || {
+        let audio = std::env::temp_dir().join(format!(
+            "novapad_talking_book_{}.mp3",
+            Uuid::new_v4().simple()
+        ));
+        let options = AudiobookCommonOptions {
+            voice: &voice,
+            output: &audio,
+            progress_hwnd,
+            cancel: cancel_clone,
+            language,
+            rate: tts_rate,
+            pitch: tts_pitch,
+            volume: tts_volume,
+        };
+        let ends: Result<Vec<u64>, String> = match tts_engine {
+            TtsEngine::Edge => {
+                let mut progress = 0;
+                run_tts_audiobook_part_timed(&chunks, &mut progress, &options).map(|durations| {
+                    durations
+                        .iter()
+                        .scan(0u64, |end, duration| {
+                            *end += duration;
+                            Some(*end)
+                        })
+                        .collect()
+                })
+            }
+            TtsEngine::Sapi5 => run_sapi_talking_book_audio(&chunks, &options),
+            TtsEngine::Sapi4 => Err(i18n::tr(language, "talking_book.sapi4_unsupported")),
+        };
+        let result = ends.and_then(|ends| {
+            let clips = talking_book::clips_from_ends(&ends);
+            let identifier = format!("urn:uuid:{}", Uuid::new_v4());
+            talking_book::TalkingBook {
+                identifier: &identifier,
+                title: &title,
+                language: &talking_book::language_tag(&voice, language),
+                paragraphs: &paragraphs,
+                clips: &clips,
+            }
+            .write_epub(&output, &audio)
+            .map_err(|err| i18n::tr_f(language, "talking_book.write_error", &[("err", &err)]))
+        });
+        if audio.exists()
+            && let Err(e) = std::fs::remove_file(&audio)
+        {
+            crate::log_debug(&format!("Failed to remove talking book audio: {}", e));
+        }
+        let success = result.is_ok();
+        let message = match result {
+            Ok(()) => i18n::tr(language, "talking_book.saved"),
+            Err(err) => err,
+        };
+        let payload = Box::new(AudiobookResult { success, message });
+        unsafe {
+            if let Err(e) = PostMessageW(
+                hwnd,
+                crate::WM_TTS_AUDIOBOOK_DONE,
+                WPARAM(0),
+                LPARAM(Box::into_raw(payload) as isize),
+            ) {
+                crate::log_debug(&format!("Failed to post WM_TTS_AUDIOBOOK_DONE: {}", e));
+            }
+        }
+    });
+}
+
+/// Speaks every chunk with SAPI 5 into `options.output`, returning the audio offset
+/// in milliseconds reached after each chunk.
+fn run_sapi_talking_book_audio(
+    chunks: &[String],
+    options: &AudiobookCommonOptions,
+) -> Result<Vec<u64>, String> {
+    let mut ends = Vec::with_capacity(chunks.len());
+    let progress_hwnd = options.progress_hwnd;
+    crate::sapi5_engine::speak_sapi_to_file(
+        crate::sapi5_engine::SapiExportOptions {
+            chunks,
+            voice_name: options.voice,
+            output_path: options.output,
+            language: options.language,
+            rate: options.rate,
+            pitch: options.pitch,
+            volume: options.volume,
+            cancel: options.cancel.clone(),
+        },
+        |chunk_idx, audio_ms| {
+            ends.push(audio_ms);
+            if progress_hwnd.0 != 0 {
+                unsafe {
+                    if let Err(e) = PostMessageW(
+                        progress_hwnd,
+                        crate::WM_UPDATE_PROGRESS,
+                        WPARAM(chunk_idx),
+                        LPARAM(0),
+                    ) {
+                        crate::log_debug(&format!("Failed to post WM_UPDATE_PROGRESS: {}", e));
+                    }
+                }
+            }
+        },
+    )
+    .map_err(|e| {
+        if e == "Cancelled" {
+            cancelled_message(options.language)
+        } else {
+            e
+        }
+    })?;
+    Ok(ends)
+}
+
 fn parse_sapi4_voice_index(voice: &str) -> i32 {
     if let Some(hash_pos) = voice.find('#') {
         let rest = &voice[hash_pos + 1..];
@@ -1799,7 +2017,7 @@ fn run_split_sapi_audiobook(
                 volume: options.volume,
                 cancel: cancel_clone,
             },
-            |_chunk_idx| {
+            |_chunk_idx, _audio_ms| {
                 current_global_progress += 1;
                 if progress_hwnd_clone.0 != 0 {
                     unsafe {
@@ -1871,7 +2089,7 @@ fn run_marker_split_sapi_audiobook(
                 volume: options.volume,
                 cancel: cancel_clone,
             },
-            |_chunk_idx| {
+            |_chunk_idx, _audio_ms| {
                 current_global_progress += 1;
                 if progress_hwnd_clone.0 != 0 {
                     unsafe {
@@ -1905,6 +2123,15 @@ pub(crate) fn run_tts_audiobook_part(
     current_global_progress: &mut usize,
     options: &AudiobookCommonOptions,
 ) -> Result<(), String> {
+    run_tts_audiobook_part_timed(chunks, current_global_progress, options).map(|_| ())
+}
+
+/// Like `run_tts_audiobook_part`, also returning the duration in milliseconds of each chunk.
+fn run_tts_audiobook_part_timed(
+    chunks: &[String],
+    current_global_progress: &mut usize,
+    options: &AudiobookCommonOptions,
+) -> Result<Vec<u64>, String> {
     let file = std::fs::File::create(options.output).map_err(|err| err.to_string())?;
     let mut writer = BufWriter::new(file);
     let rt = tokio::runtime::Builder::new_multi_thread()
@@ -1968,6 +2195,7 @@ pub(crate) fn run_tts_audiobook_part(
         });
 
         let mut stream = futures_util::stream::iter(tasks).buffered(30);
+        let mut durations = Vec::with_capacity(chunks.len());
 
         while let Some(result) = stream.next().await {
             if options.cancel.load(Ordering::Relaxed) {
@@ -1979,6 +2207,11 @@ pub(crate) fn run_tts_audiobook_part(
                 Err(e) => return Err(e),
             };
 
+            // Edge streams constant 48 kbit/s MP3, so the size is a safe fallback.
+            let duration_ms = mp3_duration::from_read(&mut std::io::Cursor::new(&audio))
+                .map(|duration| duration.as_millis() as u64)
+                .unwrap_or(audio.len() as u64 / 6);
+            durations.push(duration_ms);
             writer.write_all(&audio).map_err(|err| err.to_string())?;
             *current_global_progress += 1;
             if options.progress_hwnd.0 != 0 {
@@ -1998,7 +2231,7 @@ pub(crate) fn run_tts_audiobook_part(
             }
         }
         writer.flush().map_err(|err| err.to_string())?;
-        Ok(())
+        Ok(durations)
     })
     .map_err(|e| {
         if let Err(rem_err) = std::fs::remove_file(options.output) {